@@ -60,7 +60,7 @@ fn main() {
 
         // Creates a button entitled Exit
         let btn_exit = Button::with_title("Exit")
-                                .set_action(|_| CallbackReturn::Close);
+                                .set_action(|_| ActionReturn::Close);
 
         // Creates a button entitled 'Activate'
         let btn_on_off = Button::with_title("Activate")
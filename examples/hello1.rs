@@ -11,7 +11,7 @@ fn main () {
         let button = Button::with_title("Ok")
                             .set_attrib("EXPAND", "YES")
                             .set_attrib("TIP", "Exit button")
-                            .set_action(|_| CallbackReturn::Close);
+                            .set_action(|_| ActionReturn::Close);
 
         let label = Label::with_title("Hello, world!");
 
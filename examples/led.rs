@@ -25,7 +25,7 @@ fn main () {
 
         let mut dialog = Dialog::from_handle(Handle::from_named("dlg").unwrap()).unwrap();
         let mut button = Button::from_handle(Handle::from_named("btn").unwrap()).unwrap();
-        button.set_action(|_| CallbackReturn::Close);
+        button.set_action(|_| ActionReturn::Close);
 
         dialog.show()
 
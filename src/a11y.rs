@@ -0,0 +1,274 @@
+//! Accessibility helpers, for screen readers and keyboard users.
+//!
+//! IUP has no accessibility attributes of its own. Screen readers read the title of the native
+//! controls, and for controls without a meaningful title (e.g. image buttons) they fall back to
+//! the tooltip, both in Windows and GTK. Thus the accessible name and description are given
+//! through TIP, and also kept in the A11Y_NAME and A11Y_DESCRIPTION attributes to be queried.
+//!
+//! ```ignore
+//! let save = Button::new().set_attrib("IMAGE", "IUP_FileSave");
+//! a11y::set_name(save, "Save");
+//! a11y::set_focus_order(&[name_text.into(), email_text.into(), save.into()]);
+//! ```
+//!
+//! # Mnemonics
+//!
+//! A `&` in the title of a label, button, toggle or menu item underlines the next character, e.g.
+//! "&File", and `&&` shows a `&`. Buttons, toggles and menu items are activated by Alt and
+//! their mnemonic natively, while labels need `enable_mnemonics` to move the focus to the
+//! control they name:
+//!
+//! ```ignore
+//! let name_label = Label::with_title("&Name:");
+//! a11y::set_label_for(name_label, name_text);
+//! a11y::enable_mnemonics(dialog);
+//! ```
+use iup_sys;
+
+use Element;
+use Handle;
+use dialog::Dialog;
+use control::Label;
+use element::{Node, Container, Widget};
+use callback::{KAnyCb, KeyReturn};
+
+/// Key code of Tab, with the Shift modifier flag for Shift+Tab.
+const K_TAB: i32 = 9;
+const K_STAB: i32 = K_TAB | 0x10000000;
+
+/// Modifier flags of the key codes.
+const K_SHIFT: i32 = 0x10000000;
+const K_CTRL: i32 = 0x20000000;
+const K_ALT: i32 = 0x40000000;
+const K_SYS: i32 = 0x80000000u32 as i32;
+
+/// Classes whose mnemonics are handled, natively or by `enable_mnemonics`.
+const MNEMONIC_CLASSES: &'static [&'static str] = &["label", "button", "toggle"];
+
+/// Classes the user can interact with, which must have an accessible name.
+const INTERACTIVE_CLASSES: &'static [&'static str] = &[
+    "button", "toggle", "text", "multiline", "list", "tree", "val", "canvas",
+];
+
+/// Sets the name a screen reader announces for `elem`.
+///
+/// It's shown as the tooltip unless the element already has one.
+pub fn set_name<E: Element, S: Into<String>>(mut elem: E, name: S) {
+    let name = name.into();
+    if elem.attrib("TIP").is_none() {
+        elem.set_attrib("TIP", &name[..]);
+    }
+    elem.set_attrib("A11Y_NAME", name);
+}
+
+/// Sets a longer description of `elem`, shown as its tooltip.
+pub fn set_description<E: Element, S: Into<String>>(mut elem: E, description: S) {
+    let description = description.into();
+    elem.set_attrib("TIP", &description[..]);
+    elem.set_attrib("A11Y_DESCRIPTION", description);
+}
+
+/// Gets the name a screen reader most likely announces for `elem`: the name set with `set_name`,
+/// its title or its tooltip.
+pub fn name<E: Element>(elem: E) -> Option<String> {
+    elem.attrib("A11Y_NAME")
+        .or_else(|| elem.attrib("TITLE").filter(|title| !title.is_empty())
+                                        .map(|title| strip_mnemonic(&title)))
+        .or_else(|| elem.attrib("TIP"))
+}
+
+/// Gets the description set with `set_description`.
+pub fn description<E: Element>(elem: E) -> Option<String> {
+    elem.attrib("A11Y_DESCRIPTION")
+}
+
+/// Sets whether `elem` receives the keyboard focus with Tab, see CANFOCUS.
+///
+/// Must be set before the element is mapped.
+pub fn set_focusable<E: Element>(mut elem: E, focusable: bool) {
+    elem.set_attrib("CANFOCUS", if focusable { "YES" } else { "NO" });
+}
+
+/// Makes Tab and Shift+Tab move the focus through `elems` in the given order, wrapping around,
+/// instead of the order they appear in the dialog.
+///
+/// This replaces the `KAnyCb` of the elements.
+pub fn set_focus_order(elems: &[Handle]) {
+    let n = elems.len();
+    for (i, elem) in elems.iter().enumerate() {
+        let next = elems[(i + 1) % n];
+        let prev = elems[(i + n - 1) % n];
+        elem.clone().set_k_any(move |(_, c): (Handle, i32)| {
+            match c {
+                K_TAB => { unsafe { iup_sys::IupSetFocus(next.raw()) }; KeyReturn::Ignore },
+                K_STAB => { unsafe { iup_sys::IupSetFocus(prev.raw()) }; KeyReturn::Ignore },
+                _ => KeyReturn::Default,
+            }
+        });
+    }
+}
+
+/// Gets the mnemonic of a title, the uppercase character after the first single `&`.
+///
+/// ```
+/// assert_eq!(iup::a11y::mnemonic("&File"), Some('F'));
+/// assert_eq!(iup::a11y::mnemonic("Save &as..."), Some('A'));
+/// assert_eq!(iup::a11y::mnemonic("Fish && Chips"), None);
+/// ```
+pub fn mnemonic(title: &str) -> Option<char> {
+    let mut chars = title.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.next() {
+                Some('&') => continue,
+                Some(c) => return c.to_uppercase().next(),
+                None => return None,
+            }
+        }
+    }
+    None
+}
+
+/// Removes the mnemonic marker of a title, giving the text shown to the user.
+///
+/// ```
+/// assert_eq!(iup::a11y::strip_mnemonic("Fish && &Chips"), "Fish & Chips");
+/// ```
+pub fn strip_mnemonic(title: &str) -> String {
+    let mut stripped = String::with_capacity(title.len());
+    let mut chars = title.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.next() {
+                Some(next) => stripped.push(next),
+                None => break,
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Links `label` to the control it names, which receives the focus when the mnemonic of the
+/// label is pressed (see `enable_mnemonics`) and is announced by the title of the label unless
+/// it has a name already.
+///
+/// Without a link the focus goes to the first interactive element after the label.
+pub fn set_label_for<E: Element>(mut label: Label, mut target: E) {
+    label.set_attrib_handle("A11Y_LABEL_FOR", target);
+    if target.attrib("A11Y_NAME").is_none() {
+        if let Some(title) = label.attrib("TITLE") {
+            target.set_attrib("A11Y_NAME", strip_mnemonic(&title));
+        }
+    }
+}
+
+/// Gets the control named by `label`, the one set with `set_label_for` or else the first
+/// interactive element after the label.
+pub fn label_target(mut label: Label) -> Option<Handle> {
+    label.attrib_handle("A11Y_LABEL_FOR").or_else(|| {
+        let mut next = label.brother();
+        while let Some(elem) = next {
+            if let Some(found) = first_interactive(elem) {
+                return Some(found);
+            }
+            next = elem.brother();
+        }
+        None
+    })
+}
+
+/// Makes Alt and the mnemonic of a label in `dialog` move the focus to the control it names,
+/// see `label_target`.
+///
+/// Labels which are hidden or inactive are skipped. This replaces the `KAnyCb` of the dialog,
+/// which receives the keys not handled by the control with the focus.
+pub fn enable_mnemonics(mut dialog: Dialog) {
+    dialog.set_k_any(|(dialog, c): (Dialog, i32)| {
+        if c & K_ALT == 0 || c & (K_CTRL | K_SYS) != 0 {
+            return KeyReturn::Default;
+        }
+        let key = match ::std::char::from_u32((c & !(K_SHIFT | K_ALT)) as u32) {
+            Some(key) => key.to_uppercase().next(),
+            None => return KeyReturn::Default,
+        };
+        let labels = with_mnemonics(dialog).into_iter().filter(|&(elem, mnemonic)| {
+            Some(mnemonic) == key && class_of(elem) == "label"
+                && elem.is_visible() && elem.is_enabled()
+        });
+        for (label, _) in labels {
+            let label = unsafe { Label::from_raw_unchecked(label.raw()) };
+            if let Some(target) = label_target(label) {
+                unsafe { iup_sys::IupSetFocus(target.raw()) };
+                return KeyReturn::Ignore;
+            }
+        }
+        KeyReturn::Default
+    });
+}
+
+/// Finds the mnemonics used by more than one label, button or toggle inside `elem`, with the
+/// elements using each of them.
+///
+/// Useful in tests to make sure every mnemonic of a dialog is unique.
+pub fn mnemonic_conflicts<E: Node>(elem: E) -> Vec<(char, Vec<Handle>)> {
+    let mut conflicts: Vec<(char, Vec<Handle>)> = Vec::new();
+    for (elem, mnemonic) in with_mnemonics(elem) {
+        match conflicts.iter().position(|&(c, _)| c == mnemonic) {
+            Some(i) => conflicts[i].1.push(elem),
+            None => conflicts.push((mnemonic, vec![elem])),
+        }
+    }
+    conflicts.retain(|&(_, ref elems)| elems.len() > 1);
+    conflicts
+}
+
+/// Finds the labels, buttons and toggles inside `elem` having a mnemonic, in dialog order.
+fn with_mnemonics<E: Node>(elem: E) -> Vec<(Handle, char)> {
+    let mut found = Vec::new();
+    find_mnemonics(unsafe { Handle::from_raw_unchecked(elem.raw()) }, &mut found);
+    found
+}
+
+fn find_mnemonics(handle: Handle, found: &mut Vec<(Handle, char)>) {
+    if MNEMONIC_CLASSES.contains(&&class_of(handle)[..]) {
+        if let Some(mnemonic) = handle.attrib("TITLE").and_then(|title| mnemonic(&title)) {
+            found.push((handle, mnemonic));
+        }
+    }
+    for child in handle.children() {
+        find_mnemonics(child, found);
+    }
+}
+
+/// Finds the first element inside `handle` (itself included) which can receive the focus.
+fn first_interactive(handle: Handle) -> Option<Handle> {
+    if INTERACTIVE_CLASSES.contains(&&class_of(handle)[..])
+        && handle.attrib("CANFOCUS").map_or(true, |canfocus| canfocus != "NO") {
+        return Some(handle);
+    }
+    handle.children().into_iter().filter_map(first_interactive).next()
+}
+
+fn class_of(handle: Handle) -> String {
+    unsafe { handle.classname() }.to_string_lossy().into_owned()
+}
+
+/// Finds the interactive elements inside `elem` which have no accessible name, see `name`.
+///
+/// Useful in tests to make sure every control can be identified by screen reader users.
+pub fn unnamed<E: Node>(elem: E) -> Vec<Handle> {
+    let mut found = Vec::new();
+    find_unnamed(unsafe { Handle::from_raw_unchecked(elem.raw()) }, &mut found);
+    found
+}
+
+fn find_unnamed(handle: Handle, found: &mut Vec<Handle>) {
+    if INTERACTIVE_CLASSES.contains(&&class_of(handle)[..]) && name(handle).is_none() {
+        found.push(handle);
+    }
+    for child in handle.children() {
+        find_unnamed(child, found);
+    }
+}
@@ -0,0 +1,416 @@
+//! Application wide shutdown.
+//!
+//! `exit` ends the application from anywhere in the main loop, even from a nested popup, and
+//! the closures registered with `on_exit` run right before IUP is closed, while the elements
+//! are still alive (e.g. to save the window placement).
+//!
+//! ```ignore
+//! iup::with_iup(|| {
+//!     app::on_exit(|| config.save().unwrap());
+//!     quit_item.set_action(|_| app::exit(0));
+//!     ...
+//! }).unwrap();
+//! std::process::exit(app::exit_code().unwrap_or(0));
+//! ```
+//!
+//! With the `ctrlc` feature, `exit_on_ctrlc` makes a GUI launched from a console shut down
+//! cleanly when interrupted.
+//!
+//! `Application` is the canonical entry point of a program, owning the initialization, the
+//! common settings and the main dialog:
+//!
+//! ```ignore
+//! fn main() {
+//!     Application::new()
+//!         .image_lib()
+//!         .language(Language::Portuguese)
+//!         .main(|_| build_dialog())
+//!         .run()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! `AppBuilder` is an alternative to `with_iup` when IUP must receive the command line
+//! arguments, when the user initialization must run from the ENTRY_POINT callback, or when the
+//! main loop is driven by the application with `IupLoopStep`.
+use iup_sys;
+use libc::c_int;
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::mem;
+use std::result::Result;
+use std::time::Duration;
+#[cfg(feature = "ctrlc")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use InitError;
+use element;
+use dialog::Dialog;
+use element::Widget;
+use i18n::{self, Language};
+use mainloop;
+use timer;
+#[cfg(feature = "ctrlc")]
+use util;
+#[cfg(feature = "ctrlc")]
+use ctrlc;
+
+/// Interval between the attempts to leave the nested levels of the main loop.
+const UNWIND_INTERVAL: u64 = 10;
+/// Conventional exit code of a process interrupted by Ctrl+C.
+#[cfg(feature = "ctrlc")]
+const INTERRUPTED: i32 = 130;
+
+thread_local!(static EXIT_HOOKS: RefCell<Vec<Box<FnOnce()>>> = RefCell::new(Vec::new()));
+thread_local!(static EXIT_CODE: Cell<Option<i32>> = Cell::new(None));
+thread_local!(static ARGS: RefCell<Vec<String>> = RefCell::new(Vec::new()));
+thread_local!(static ENTRY_POINT: RefCell<Option<Box<FnOnce() -> Result<(), String>>>> =
+              RefCell::new(None));
+thread_local!(static ENTRY_RESULT: RefCell<Option<Result<(), String>>> = RefCell::new(None));
+
+/// Registers `f` to be called when the main loop ends, before IUP is closed.
+///
+/// The closures are called in the reverse order they were registered.
+pub fn on_exit<F: FnOnce() + 'static>(f: F) {
+    EXIT_HOOKS.with(|hooks| hooks.borrow_mut().push(Box::new(f)));
+}
+
+/// Ends the main loop with the exit `code`, leaving any nested level (such as popups) first.
+///
+/// The main loop ends once the current callback returns, so `with_iup` returns and the code
+/// is available from `exit_code`.
+pub fn exit(code: i32) {
+    EXIT_CODE.with(|exit_code| exit_code.set(Some(code)));
+    unwind();
+}
+
+/// Gets the code given to `exit`, or `None` if it wasn't called.
+pub fn exit_code() -> Option<i32> {
+    EXIT_CODE.with(|exit_code| exit_code.get())
+}
+
+/// Leaves the current level of the main loop, and again on the outer levels until the
+/// main loop ends.
+fn unwind() {
+    if mainloop::level() > 1 {
+        timer::delay(Duration::from_millis(UNWIND_INTERVAL), unwind);
+    }
+    mainloop::exit_loop();
+}
+
+/// Calls the exit hooks, IUP must still be open.
+#[doc(hidden)]
+pub fn run_exit_hooks() {
+    // Taken out so hooks are free to register more, which are then ignored.
+    let hooks = EXIT_HOOKS.with(|hooks| hooks.replace(Vec::new()));
+    for hook in hooks.into_iter().rev() {
+        hook();
+    }
+}
+
+/// Calls `exit` when the process receives Ctrl+C (SIGINT, or SIGTERM with the `termination`
+/// feature of ctrlc), must be called from the main loop thread after IUP is initialized.
+///
+/// The exit code is 130. Posting is enabled, see `util::post`. Ctrl+C is ignored while IUP is
+/// closed, and calling this again (e.g. in a second `with_iup`) keeps the same handler. Fails if
+/// another Ctrl+C handler was already set.
+#[cfg(feature = "ctrlc")]
+pub fn exit_on_ctrlc() -> Result<(), String> {
+    util::enable_posting();
+    if CTRLC_HANDLER_SET.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    ctrlc::set_handler(|| {
+        // Posting fails once IUP is closed, then there's no loop to end.
+        let _ = util::post(|| exit(INTERRUPTED));
+    }).map_err(|e| {
+        CTRLC_HANDLER_SET.store(false, Ordering::SeqCst);
+        e.to_string()
+    })
+}
+
+/// Whether `exit_on_ctrlc` set the Ctrl+C handler, which can only be set once per process.
+#[cfg(feature = "ctrlc")]
+static CTRLC_HANDLER_SET: AtomicBool = AtomicBool::new(false);
+
+/// Gets the command line arguments given to `AppBuilder::args`, without the ones handled by
+/// the IUP driver.
+pub fn args() -> Vec<String> {
+    ARGS.with(|args| args.borrow().clone())
+}
+
+/// How the main loop is run after the user initialization.
+enum LoopStyle {
+    MainLoop,
+    EntryPoint,
+    Steps(Box<FnMut() -> bool>),
+}
+
+/// Initializes IUP, the user interface and runs the main loop, in this order:
+///
+///  1. `IupOpen`, with the command line arguments if any was given.
+///  2. The binding specific global settings (UTF-8 mode).
+///  3. The globals set with `global`, in the order they were given.
+///  4. The user initialization closure, now or from the ENTRY_POINT callback.
+///  5. The main loop, until it ends.
+///  6. The `on_exit` hooks and `IupClose`.
+///
+/// ```ignore
+/// AppBuilder::new()
+///     .env_args()
+///     .global("LOCKLOOP", "YES")
+///     .run(|| {
+///         Dialog::new(Label::with_title("Hello")).show()
+///     }).unwrap();
+/// ```
+pub struct AppBuilder {
+    args: Vec<String>,
+    globals: Vec<(String, String)>,
+    style: LoopStyle,
+}
+
+impl AppBuilder {
+    /// Creates a builder which behaves like `with_iup`.
+    pub fn new() -> AppBuilder {
+        AppBuilder { args: Vec::new(), globals: Vec::new(), style: LoopStyle::MainLoop }
+    }
+
+    /// Gives `args` to `IupOpen`, the first being the program name.
+    ///
+    /// The driver handles and removes its own switches, the others are available from `args`.
+    pub fn args<I: IntoIterator<Item=String>>(mut self, args: I) -> AppBuilder {
+        self.args = args.into_iter().collect();
+        self
+    }
+
+    /// Gives the arguments of the process to `IupOpen`, see `args`.
+    pub fn env_args(self) -> AppBuilder {
+        self.args(env::args())
+    }
+
+    /// Sets a global attribute after IUP is initialized but before the user initialization.
+    pub fn global<S1, S2>(mut self, name: S1, value: S2) -> AppBuilder
+                                        where S1: Into<String>, S2: Into<String> {
+        self.globals.push((name.into(), value.into()));
+        self
+    }
+
+    /// Runs the user initialization from the ENTRY_POINT callback, called once by IUP when the
+    /// main loop starts, as required by drivers whose main loop must run before any dialog is
+    /// created.
+    pub fn entry_point(mut self) -> AppBuilder {
+        self.style = LoopStyle::EntryPoint;
+        self
+    }
+
+    /// Drives the main loop with `IupLoopStep`, calling `step` between the processed events,
+    /// e.g. to integrate an external event loop.
+    ///
+    /// The loop ends when `step` returns false, or when IUP closes the loop as usual. Since
+    /// `IupLoopStep` does not wait for events, `step` should block or sleep for a while.
+    pub fn steps<F: FnMut() -> bool + 'static>(mut self, step: F) -> AppBuilder {
+        self.style = LoopStyle::Steps(Box::new(step));
+        self
+    }
+
+    /// Initializes IUP, calls `f` for user initialization and runs the application.
+    ///
+    /// See `with_iup` for the details, this function will also return only after the GUI
+    /// application is closed.
+    pub fn run<F>(self, f: F) -> Result<(), InitError>
+                                where F: FnOnce() -> Result<(), String> + 'static {
+        let remaining = try!(::open_with_args(&self.args));
+        ARGS.with(|args| *args.borrow_mut() = remaining);
+        for &(ref name, ref value) in self.globals.iter() {
+            element::set_global(&name[..], &value[..]);
+        }
+
+        let user_result = match self.style {
+            LoopStyle::MainLoop => {
+                let user_result = f();
+                if user_result.is_ok() {
+                    unsafe { iup_sys::IupMainLoop(); }
+                }
+                user_result
+            },
+            LoopStyle::EntryPoint => unsafe {
+                ENTRY_POINT.with(|entry| *entry.borrow_mut() = Some(Box::new(f)));
+                let on_entry_point = on_entry_point as extern fn() -> c_int;
+                iup_sys::IupSetFunction(cstr!("ENTRY_POINT"), mem::transmute(on_entry_point));
+                iup_sys::IupMainLoop();
+                // If the main loop ended before calling it, the initialization never ran.
+                ENTRY_POINT.with(|entry| entry.borrow_mut().take());
+                ENTRY_RESULT.with(|result| result.borrow_mut().take())
+                            .unwrap_or(Err("ENTRY_POINT was not called".into()))
+            },
+            LoopStyle::Steps(mut step) => {
+                let user_result = f();
+                if user_result.is_ok() {
+                    while unsafe { iup_sys::IupLoopStep() } != iup_sys::IUP_CLOSE && step() {}
+                }
+                user_result
+            },
+        };
+
+        ::close();
+
+        user_result.map_err(|e| InitError::UserError(e))
+    }
+}
+
+/// Runs the user initialization given to `AppBuilder::run`, ending the loop on errors.
+extern fn on_entry_point() -> c_int {
+    let f = ENTRY_POINT.with(|entry| entry.borrow_mut().take());
+    if let Some(f) = f {
+        let result = f();
+        if result.is_err() {
+            mainloop::exit_loop();
+        }
+        ENTRY_RESULT.with(|entry_result| *entry_result.borrow_mut() = Some(result));
+    }
+    iup_sys::IUP_DEFAULT
+}
+
+/// The application, owning the initialization and finalization of IUP, the global settings,
+/// the main dialog and the main loop.
+///
+/// The main dialog is created by the factory given to `main` once IUP is initialized and the
+/// settings are applied, then it's shown and the main loop runs until the application ends.
+pub struct Application {
+    builder: AppBuilder,
+    utf8: bool,
+    image_lib: bool,
+    controls: bool,
+    gl: bool,
+    language: Option<Language>,
+    main: Option<Box<FnOnce(&[String]) -> Dialog>>,
+}
+
+impl Application {
+    /// Creates an application with the default settings and no main dialog.
+    pub fn new() -> Application {
+        Application {
+            builder: AppBuilder::new(),
+            utf8: true,
+            image_lib: false,
+            controls: false,
+            gl: false,
+            language: None,
+            main: None,
+        }
+    }
+
+    /// Gives the process arguments to IUP, see `AppBuilder::args`.
+    pub fn env_args(mut self) -> Application {
+        self.builder = self.builder.env_args();
+        self
+    }
+
+    /// Sets a global attribute before the main dialog is created.
+    pub fn global<S1, S2>(mut self, name: S1, value: S2) -> Application
+                                        where S1: Into<String>, S2: Into<String> {
+        self.builder = self.builder.global(name, value);
+        self
+    }
+
+    /// Sets whether strings are UTF-8 encoded (the default), or in the current locale.
+    pub fn utf8(mut self, utf8: bool) -> Application {
+        self.utf8 = utf8;
+        self
+    }
+
+    /// Registers the images of the IUP image library by name, e.g. "IUP_FileOpen".
+    pub fn image_lib(mut self) -> Application {
+        self.image_lib = true;
+        self
+    }
+
+    /// Initializes the additional controls of the `iupcontrols` library.
+    #[cfg(feature = "controls")]
+    pub fn controls(mut self) -> Application {
+        self.controls = true;
+        self
+    }
+
+    /// Initializes the OpenGL canvas of the `iupgl` library.
+    #[cfg(feature = "gl")]
+    pub fn gl(mut self) -> Application {
+        self.gl = true;
+        self
+    }
+
+    /// Sets the language of the predefined dialogs and messages.
+    pub fn language(mut self, language: Language) -> Application {
+        self.language = Some(language);
+        self
+    }
+
+    /// Sets the factory of the main dialog, which receives the arguments left by the driver.
+    pub fn main<F: FnOnce(&[String]) -> Dialog + 'static>(mut self, factory: F) -> Application {
+        self.main = Some(Box::new(factory));
+        self
+    }
+
+    /// Initializes IUP, applies the settings, shows the main dialog and runs the main loop.
+    ///
+    /// Returns only after the application ends, see `with_iup`.
+    pub fn run(self) -> Result<(), InitError> {
+        let Application { builder, utf8, image_lib, controls, gl, language, main } = self;
+        builder.run(move || {
+            if !utf8 {
+                element::set_global("UTF8MODE", "NO");
+                element::set_global("UTF8MODE_FILE", "NO");
+            }
+            if image_lib {
+                unsafe { iup_sys::IupImageLibOpen() };
+            }
+            if controls {
+                try!(open_controls());
+            }
+            if gl {
+                open_gl();
+            }
+            if let Some(language) = language {
+                i18n::set_language(language);
+            }
+            match main {
+                Some(factory) => factory(&args()).show(),
+                None => Err("The application has no main dialog".into()),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "controls")]
+fn open_controls() -> Result<(), String> {
+    match unsafe { IupControlsOpen() } {
+        iup_sys::IUP_ERROR => Err("Failed to initialize the iupcontrols library".into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "controls"))]
+fn open_controls() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(feature = "gl")]
+fn open_gl() {
+    unsafe { IupGLCanvasOpen() };
+}
+
+#[cfg(not(feature = "gl"))]
+fn open_gl() {}
+
+// Not yet bound by iup-sys.
+#[cfg(feature = "controls")]
+#[link(name = "iupcontrols")]
+extern {
+    fn IupControlsOpen() -> c_int;
+}
+
+#[cfg(feature = "gl")]
+#[link(name = "iupgl")]
+extern {
+    fn IupGLCanvasOpen();
+}
@@ -0,0 +1,158 @@
+//! Recording and playback of user input.
+//!
+//! IUP is able to record all mouse and keyboard input to a file and reproduce it later.
+//! This is useful to automate user interface tests: record a session once by hand and replay it
+//! every time the test runs.
+//!
+//! ```ignore
+//! automation::play_input_then("session.rec", |_| {
+//!     println!("Playback finished!");
+//!     CallbackReturn::Close
+//! }).unwrap();
+//! ```
+use iup_sys;
+use libc::{c_char, c_int};
+use std::path::Path;
+use std::ptr;
+use std::result::Result;
+use std::cell::Cell;
+use std::ffi::CString;
+
+use Element;
+use element::WeakHandle;
+use timer::{self, Timer};
+use callback::{Callback, ActionCb};
+
+/// The file format used to record input.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RecordMode {
+    /// A compact binary file.
+    Binary,
+    /// A human readable text file.
+    Text,
+}
+
+/// Changes the behaviour of an ongoing playback.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlayControl {
+    /// Pauses the playback.
+    Pause,
+    /// Continues a paused playback.
+    Continue,
+    /// Reproduces the events faster.
+    Faster,
+    /// Reproduces the events slower.
+    Slower,
+}
+
+/// Starts recording all mouse and keyboard input into the file at `path`.
+///
+/// If a recording is already in progress it is stopped and a new one starts.
+pub fn record_input<P: AsRef<Path>>(path: P, mode: RecordMode) -> Result<(), String> {
+    let cpath = try!(path_to_cstring(path.as_ref()));
+    let mode = match mode {
+        RecordMode::Binary => iup_sys::IUP_RECBINARY,
+        RecordMode::Text => iup_sys::IUP_RECTEXT,
+    };
+    match unsafe { iup_sys::IupRecordInput(cpath.as_ptr(), mode) } {
+        iup_sys::IUP_NOERROR => Ok(()),
+        _ => Err(format!("Failed to record input into {}", path.as_ref().display())),
+    }
+}
+
+/// Stops the current recording, if any, and closes its file.
+pub fn stop_recording() {
+    unsafe { iup_sys::IupRecordInput(ptr::null(), 0) };
+}
+
+/// Reproduces all mouse and keyboard input from a file saved with `record_input`.
+///
+/// The record mode is automatically detected. This function returns immediately after the file
+/// is opened, the input is reproduced from inside the main loop through the IDLE_ACTION callback.
+/// The playback stops when the file ends, when `stop_playing` is called or when the user presses
+/// any mouse or keyboard button.
+pub fn play_input<P: AsRef<Path>>(path: P) -> Result<(), String> {
+    let cpath = try!(path_to_cstring(path.as_ref()));
+    match unsafe { iup_sys::IupPlayInput(cpath.as_ptr()) } {
+        iup_sys::IUP_NOERROR => Ok(()),
+        _ => Err(format!("Failed to play input from {}", path.as_ref().display())),
+    }
+}
+
+/// Same as `play_input` but calls `on_done` once the playback stops for any reason.
+///
+/// Playback end is detected by polling when IUP restores the previous IDLE_ACTION callback,
+/// so `callback::set_idle` must not be called while playing.
+///
+/// `CallbackReturn::Close` returned by `on_done` will be processed.
+pub fn play_input_then<P, F>(path: P, on_done: F) -> Result<(), String>
+                                        where P: AsRef<Path>, F: Callback<()> {
+    try!(play_input(path));
+    monitor().set_action_cb(PlaybackMonitor { player: current_idle(), on_done: on_done }).run();
+    Ok(())
+}
+
+/// Stops the current playback, if any.
+pub fn stop_playing() {
+    unsafe { iup_sys::IupPlayInput(ptr::null()) };
+}
+
+/// Pauses, continues or changes the speed of the current playback.
+pub fn control_playback(control: PlayControl) {
+    let value = match control {
+        PlayControl::Pause => cstr!("PAUSE"),
+        PlayControl::Continue => cstr!("CONTINUE"),
+        PlayControl::Faster => cstr!("FAST"),
+        PlayControl::Slower => cstr!("SLOW"),
+    };
+    unsafe { iup_sys::IupSetGlobal(cstr!("PLAYINPUT"), value) };
+}
+
+/// How often, in milliseconds, the playback end is checked.
+const MONITOR_INTERVAL: u32 = 100;
+
+thread_local!(static MONITOR: Cell<Option<WeakHandle<Timer>>> = Cell::new(None));
+
+/// Gets (or lazily creates) the timer used to detect the end of a playback.
+fn monitor() -> Timer {
+    match MONITOR.with(|monitor| monitor.get()).and_then(|monitor| monitor.upgrade()) {
+        Some(timer) => timer,
+        None => {
+            let timer = timer::owned_timer().set_time(MONITOR_INTERVAL);
+            MONITOR.with(|monitor| monitor.set(Some(timer.downgrade())));
+            timer
+        },
+    }
+}
+
+struct PlaybackMonitor<F> {
+    player: usize,
+    on_done: F,
+}
+
+impl<F: Callback<()>> Callback<(Timer,)> for PlaybackMonitor<F> {
+    fn on_callback(&mut self, (mut timer,): (Timer,)) -> c_int {
+        if current_idle() == self.player {
+            iup_sys::IUP_DEFAULT
+        } else {
+            timer.stop();
+            self.on_done.on_callback(())
+        }
+    }
+}
+
+extern {
+    // Redeclared so a missing idle function comes back as `None` instead of a null fn pointer.
+    #[link_name = "IupGetFunction"]
+    fn iup_get_function(name: *const c_char) -> Option<iup_sys::Icallback>;
+}
+
+/// Gets the address of the current IDLE_ACTION callback (zero if none).
+fn current_idle() -> usize {
+    unsafe { iup_get_function(cstr!("IDLE_ACTION")).map_or(0, |f| f as usize) }
+}
+
+fn path_to_cstring(path: &Path) -> Result<CString, String> {
+    let str = try!(path.to_str().ok_or_else(|| "Failed to convert Path to string".to_string()));
+    Ok(CString::new(str).unwrap())
+}
@@ -1,7 +1,7 @@
-//! Mouse button presses callback.
+//! Mouse button, motion and wheel callbacks.
 use iup_sys;
-use std::fmt;
-use libc::{c_char, c_int};
+use std::ffi::CStr;
+use libc::{c_char, c_int, c_float};
 use callback::IntoRust;
 
 /// Mouse buttons.
@@ -40,80 +40,108 @@ pub enum MouseButtonState {
     Pressed,
 }
 
-// TODO a way to unallow the user to move/copy instances of this type as the char buffer will
-// be invalid after the callback exits.
-/// The state of mouse buttons and some keyboard buttons.
-pub struct KeyStates(*const c_char);
+/// The state of mouse buttons and some keyboard keys at the moment a pointer event is generated.
+///
+/// This is parsed from the IUP status string, so unlike the raw buffer it can be freely copied
+/// and stored after the callback returns.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct MouseState {
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+    sys: bool,
+    double: bool,
+    buttons: [bool; 5],
+}
+
+impl MouseState {
+    /// Parses an IUP status string such as `"S 1  D   "`.
+    ///
+    /// Each flag has a fixed position in the string, a space meaning the flag is not set.
+    pub fn from_status(status: &[u8]) -> MouseState {
+        let at = |i: usize, c: u8| status.get(i) == Some(&c);
+        MouseState {
+            shift: at(0, b'S'),
+            ctrl: at(1, b'C'),
+            buttons: [at(2, b'1'), at(3, b'2'), at(4, b'3'), at(8, b'4'), at(9, b'5')],
+            double: at(5, b'D'),
+            alt: at(6, b'A'),
+            sys: at(7, b'Y'),
+        }
+    }
 
-impl KeyStates {
     /// Whether this state have a SHIFT key pressed.
     #[inline(always)]
     pub fn is_shift(&self) -> bool {
-        unsafe { iup_sys::iup_isshift(self.0) }
+        self.shift
     }
     /// Whether this state have a CONTROL key pressed.
     #[inline(always)]
-    pub fn is_control(&self) -> bool {
-        unsafe { iup_sys::iup_iscontrol(self.0) }
+    pub fn is_ctrl(&self) -> bool {
+        self.ctrl
     }
     /// Whether this state have a ALT key pressed.
     #[inline(always)]
     pub fn is_alt(&self) -> bool {
-        unsafe { iup_sys::iup_isalt(self.0) }
+        self.alt
     }
     /// Whether this state have the system key pressed.
     ///
     /// The system key in Windows is the *Windows key* and in Mac is the *Apple key*.
     #[inline(always)]
     pub fn is_sys(&self) -> bool {
-        unsafe { iup_sys::iup_issys(self.0) }
+        self.sys
     }
     /// Whether this state have the specified button in the callback doubly pressed.
     #[inline(always)]
     pub fn is_double(&self) -> bool {
-        unsafe { iup_sys::iup_isdouble(self.0) }
+        self.double
+    }
+    /// Whether this state have the specified mouse button pressed.
+    #[inline(always)]
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.buttons[button as usize]
     }
     /// Whether this state have the left mouse button pressed.
     #[inline(always)]
     pub fn is_button1(&self) -> bool {
-        unsafe { iup_sys::iup_isbutton1(self.0) }
+        self.is_pressed(MouseButton::Button1)
     }
     /// Whether this state have the middle mouse button pressed.
     #[inline(always)]
     pub fn is_button2(&self) -> bool {
-        unsafe { iup_sys::iup_isbutton2(self.0) }
+        self.is_pressed(MouseButton::Button2)
     }
     /// Whether this state have the right mouse button pressed.
     #[inline(always)]
     pub fn is_button3(&self) -> bool {
-        unsafe { iup_sys::iup_isbutton3(self.0) }
+        self.is_pressed(MouseButton::Button3)
     }
     /// Whether this state have the additional mouse button 1 pressed.
     #[inline(always)]
     pub fn is_button4(&self) -> bool {
-        unsafe { iup_sys::iup_isbutton4(self.0) }
+        self.is_pressed(MouseButton::Button4)
     }
     /// Whether this state have the additional mouse button 2 pressed.
     #[inline(always)]
     pub fn is_button5(&self) -> bool {
-        unsafe { iup_sys::iup_isbutton5(self.0) }
+        self.is_pressed(MouseButton::Button5)
     }
 }
 
-impl fmt::Debug for KeyStates {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.write_fmt(format_args!(
-            "KeyStates(Shift={}, Control={}, Alt={}, Sys={}, Double={},\
-             Button1={}, Button2={}, Button3={}, Button4={}, Button5={})",
-            self.is_shift(), self.is_control(), self.is_alt(), self.is_sys(), self.is_double(),
-            self.is_button1(), self.is_button2(), self.is_button3(), self.is_button4(), self.is_button5()
-        ))
+impl IntoRust<MouseState> for *mut c_char {
+    fn into_rust(self) -> MouseState {
+        if self.is_null() {
+            MouseState::default()
+        } else {
+            MouseState::from_status(unsafe { CStr::from_ptr(self).to_bytes() })
+        }
     }
 }
 
-impl IntoRust<KeyStates> for *mut c_char {
-    fn into_rust(self) -> KeyStates {
-        KeyStates(self)
+impl IntoRust<f32> for c_float {
+    fn into_rust(self) -> f32 {
+        self as f32
     }
 }
 
@@ -145,7 +173,7 @@ impl_callback! {
     /// The `i32` parameters are the x,y position in the canvas where the event has occurred,
     /// in pixels.
     ///
-    /// The `KeyStates` parameter is the state of the mouse buttons and some keyboard keys at
+    /// The `MouseState` parameter is the state of the mouse buttons and some keyboard keys at
     //// the moment the event is generated.
     ///
     /// `CallbackReturn::Close` will be processed. On some controls if `CallbackReturn::Ignore`
@@ -159,7 +187,7 @@ impl_callback! {
         let name = "BUTTON_CB";
         extern fn listener(ih: *mut iup_sys::Ihandle, button: c_int, pressed: c_int,
                            x: c_int, y: c_int, status: *mut c_char) -> CallbackReturn;
-        fn set_button_cb<F: Callback(Self, MouseButton, MouseButtonState, i32, i32, KeyStates)>(&mut self, cb: F) -> Self;
+        fn set_button_cb<F: Callback(Self, MouseButton, MouseButtonState, i32, i32, MouseState)>(&mut self, cb: F) -> Self;
         fn remove_button_cb(&mut self) -> Option<Box<_>>;
     }
 }
@@ -170,13 +198,75 @@ impl_callback! {
     /// The `i32` parameters are the x,y position in the canvas where the event has occurred,
     /// in pixels.
     ///
-    /// The `KeyStates` parameter is the state of the mouse buttons and some keyboard keys at
+    /// The `MouseState` parameter is the state of the mouse buttons and some keyboard keys at
     //// the moment the event is generated.
     pub trait MotionCb where Self: Element {
         let name = "MOTION_CB";
         extern fn listener(ih: *mut iup_sys::Ihandle, x: c_int, y: c_int, status: *mut c_char) -> CallbackReturn;
-        fn set_motion_cb<F: Callback(Self, i32, i32, KeyStates)>(&mut self, cb: F) -> Self;
+        fn set_motion_cb<F: Callback(Self, i32, i32, MouseState)>(&mut self, cb: F) -> Self;
         fn remove_motion_cb(&mut self) -> Option<Box<_>>;
     }
 }
 
+
+impl_callback! {
+    /// Action generated when the mouse wheel is rotated.
+    ///
+    /// The `f32` parameter is the amount the wheel was rotated in notches, positive when rotated
+    /// forward (away from the user) and negative when rotated backwards.
+    ///
+    /// The `i32` parameters are the x,y position in the canvas where the event has occurred,
+    /// in pixels.
+    ///
+    /// The `MouseState` parameter is the state of the mouse buttons and some keyboard keys at
+    /// the moment the event is generated.
+    ///
+    /// If this callback is not defined the wheel will automatically scroll the canvas in the
+    /// vertical direction by some lines, the SCROLL_CB callback if defined will be called.
+    pub trait WheelCb where Self: Element {
+        let name = "WHEEL_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, delta: c_float, x: c_int, y: c_int, status: *mut c_char) -> CallbackReturn;
+        fn set_wheel_cb<F: Callback(Self, f32, i32, i32, MouseState)>(&mut self, cb: F) -> Self;
+        fn remove_wheel_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MouseButton, MouseState};
+
+    #[test]
+    fn empty_status_has_nothing_set() {
+        for status in &[&b""[..], &b"          "[..]] {
+            let state = MouseState::from_status(status);
+            assert_eq!(state, MouseState::default());
+            assert!(!state.is_shift() && !state.is_ctrl() && !state.is_double());
+        }
+    }
+
+    #[test]
+    fn modifiers_at_their_position() {
+        let state = MouseState::from_status(b"SC   DAY  ");
+        assert!(state.is_shift() && state.is_ctrl() && state.is_alt() && state.is_sys());
+        assert!(state.is_double());
+        assert!(!state.is_button1());
+    }
+
+    #[test]
+    fn buttons_at_their_position() {
+        let state = MouseState::from_status(b"  1 3   4 ");
+        assert!(state.is_button1() && state.is_button3() && state.is_button4());
+        assert!(!state.is_button2() && !state.is_button5());
+        assert!(state.is_pressed(MouseButton::Button1));
+        assert!(!state.is_shift() && !state.is_double());
+    }
+
+    #[test]
+    fn flags_out_of_place_are_ignored() {
+        let state = MouseState::from_status(b"1S");
+        assert!(!state.is_shift() && !state.is_button1());
+        // A short string only sets the flags it reaches.
+        let state = MouseState::from_status(b" C2");
+        assert!(state.is_ctrl() && !state.is_button2());
+    }
+}
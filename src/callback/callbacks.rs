@@ -1,11 +1,13 @@
 use libc::c_char;
 use std::path::PathBuf;
 
+use callback::{ActionReturn, KeyReturn, RefuseReturn};
+
 //
 // The following regex can be used to convert from doc comments to attrib comments:
 //     ([\t ]*)\/\/\/[ ]?(.*)
 //     $1#[doc="$2"]
-///
+//
 // See also PR #26
 //
 
@@ -38,11 +40,12 @@ impl_callback! {
 impl_callback! {
     #[doc="Action generated when the element is activated. Affects each element differently."]
     #[doc=""]
-    #[doc="See the documentation of the `Self` object for the effect of this callback on it."]
+    #[doc="See the documentation of the `Self` object for the effect of this callback on it. The"]
+    #[doc="callback returns an `ActionReturn`."]
     pub trait Action where Self: Element {
         let name = "ACTION";
         extern fn listener(ih: *mut iup_sys::Ihandle) -> CallbackReturn;
-        fn set_action<F: Callback(Self)>(&mut self, cb: F) -> Self;
+        fn set_action<F: Callback(Self) -> ActionReturn>(&mut self, cb: F) -> Self;
         fn remove_action(&mut self) -> Option<Box<_>>;
     }
 }
@@ -171,6 +174,25 @@ impl_callback! {
     }
 }
 
+impl_callback! {
+    #[doc="Action generated when a keyboard event occurs."]
+    #[doc=""]
+    #[doc="The `i32` parameter is the IUP code of the key pressed, which for printable characters is"]
+    #[doc="their ASCII value combined with the modifier flags (e.g. Ctrl is `0x20000000`)."]
+    #[doc=""]
+    #[doc="The callback returns a `KeyReturn`:"]
+    #[doc=" + `KeyReturn::Default` or `()` for the default reaction."]
+    #[doc=" + `KeyReturn::Close` will be processed."]
+    #[doc=" + `KeyReturn::Ignore` ignores the key, it is not processed by the control."]
+    #[doc=" + `KeyReturn::Continue` propagates the key to the parent of the element."]
+    pub trait KAnyCb where Self: Element {
+        let name = "K_ANY";
+        extern fn listener(ih: *mut iup_sys::Ihandle, c: c_int) -> CallbackReturn;
+        fn set_k_any<F: Callback(Self, i32) -> KeyReturn>(&mut self, cb: F) -> Self;
+        fn remove_k_any(&mut self) -> Option<Box<_>>;
+    }
+}
+
 // Other Callbacks
 // ----------------------------
 
@@ -242,13 +264,17 @@ impl_callback! {
     #[doc="Called just before a dialog is closed when the user clicks the close button of the title bar"]
     #[doc="or an equivalent action."]
     #[doc=""]
-    #[doc="`CallbackReturn::Close` will be processed. If `CallbackReturn::Ignore`, it prevents the dialog"]
-    #[doc="from being closed. If you destroy the dialog in this callback, you must"]
-    #[doc="return `CallbackReturn::Ignore`."]
+    #[doc="`RefuseReturn::Close` will be processed. If `RefuseReturn::Ignore`, it prevents the dialog"]
+    #[doc="from being closed. The dialog may be destroyed from this callback only with"]
+    #[doc="`Element::destroy_later`."]
     pub trait CloseCb where Self: Element {
         let name = "CLOSE_CB";
         extern fn listener(ih: *mut iup_sys::Ihandle) -> CallbackReturn;
-        fn set_move_cb<F: Callback(Self)>(&mut self, cb: F) -> Self;
+        fn set_close_cb<F: Callback(Self) -> RefuseReturn>(&mut self, cb: F) -> Self;
+        fn remove_close_cb(&mut self) -> Option<Box<_>>;
+        #[deprecated(note = "renamed to `set_close_cb`, the old name clashes with `MoveCb`")]
+        fn set_move_cb<F: Callback(Self) -> RefuseReturn>(&mut self, cb: F) -> Self;
+        #[deprecated(note = "renamed to `remove_close_cb`, the old name clashes with `MoveCb`")]
         fn remove_move_cb(&mut self) -> Option<Box<_>>;
     }
 }
@@ -269,7 +295,11 @@ impl_callback! {
     pub trait ResizeCb where Self: Element {
         let name = "RESIZE_CB";
         extern fn listener(ih: *mut iup_sys::Ihandle, w: c_int, h: c_int) -> CallbackReturn;
+        fn set_resize_cb<F: Callback(Self, i32, i32)>(&mut self, cb: F) -> Self;
+        fn remove_resize_cb(&mut self) -> Option<Box<_>>;
+        #[deprecated(note = "renamed to `set_resize_cb`, the old name clashes with `MoveCb`")]
         fn set_move_cb<F: Callback(Self, i32, i32)>(&mut self, cb: F) -> Self;
+        #[deprecated(note = "renamed to `remove_resize_cb`, the old name clashes with `MoveCb`")]
         fn remove_move_cb(&mut self) -> Option<Box<_>>;
     }
 }
@@ -49,6 +49,14 @@ macro_rules! clear_fbox_callback {
         let ih = $ih;
         let capsule_box = iup_sys::IupGetAttribute(ih, fbox_c_str!($cb_name))
                                                 as *mut Box<$crate::callback::Callback<$($rargs),*>>;
+
+        // Unset even without a closure box, the callback may have been set by `set_zst_callback!`.
+        if ih.is_null() {
+            iup_sys::IupSetFunction(cstr!($cb_name), transmute(ptr::null::<u8>()));
+        } else {
+            iup_sys::IupSetCallback(ih, cstr!($cb_name), transmute(ptr::null::<u8>()));
+        }
+
         if capsule_box.is_null() {
             None 
         } else {
@@ -58,23 +66,53 @@ macro_rules! clear_fbox_callback {
 
             iup_sys::IupSetAttribute(ih, fbox_c_str!($cb_name), ptr::null());
 
-            if ih.is_null() {
-                iup_sys::IupSetFunction(cstr!($cb_name), transmute(ptr::null::<u8>()));
-            } else {
-                iup_sys::IupSetCallback(ih, cstr!($cb_name), transmute(ptr::null::<u8>()));
-            }
-            
             Some(*inner_box)
             // inner_box itself gets freed now
         }
     }}
 }
 
+/// Sets a zero-sized closure (e.g. a plain `fn` or a closure capturing nothing) as a callback to
+/// IUP, without allocating.
+///
+/// Such a closure holds no data, so it isn't stored at all: `$clistener` must be specialized for
+/// the closure type and make the closure out of nothing, see `zst_callback!`.
+///
+/// Note: `$ih` can be a `ptr::null_mut` to set a callback in the global enviroment.
+macro_rules! set_zst_callback {
+    ($ih:expr, $cb_name:expr, $clistener:expr, $rcb:expr, Callback<$($rargs:ty),*>) => {{
+
+        use $crate::iup_sys;
+
+        clear_fbox_callback!($ih, $cb_name, Callback<$($rargs),*>);
+
+        let ih: *mut iup_sys::Ihandle = $ih;
+        ::std::mem::forget($rcb);
+        if ih.is_null() {
+            iup_sys::IupSetFunction(cstr!($cb_name), transmute($clistener));
+        } else {
+            iup_sys::IupSetCallback(ih, cstr!($cb_name), transmute($clistener));
+        }
+    }}
+}
+
+/// Makes a value of the zero-sized closure type `$f` and calls it with `$args`.
+macro_rules! zst_callback {
+    ($f:ty, $args:expr) => {{
+        // A zero-sized type has no bytes, so any value of it is as good as the one given to
+        // `set_zst_callback!`. The value is forgotten just like that one was.
+        let mut cb: $f = unsafe { ::std::mem::zeroed() };
+        let r = cb.on_callback($args);
+        ::std::mem::forget(cb);
+        r
+    }}
+}
+
 macro_rules! get_fbox_callback {
     ($ih:expr, $cb_name:expr, Callback<$($rargs:ty),*>) => {{
         let fbox_ptr  = unsafe {
                         iup_sys::IupGetAttribute($ih, fbox_c_str!($cb_name))
-                                as *mut Box<$crate::callback::Callback<($($rargs),*)>>
+                                as *mut Box<$crate::callback::Callback<$($rargs),*>>
         };
         assert!(fbox_ptr.is_null() == false);
         let fbox: &mut Box<_> = unsafe { &mut (*(fbox_ptr)) };
@@ -90,6 +128,8 @@ macro_rules! get_fbox_callback {
 ///   + `$set_method` to set associate a closure with the callback `$cb_name`.
 ///     The `F` (macro captured) constraint defines the type of high-level callback.
 ///   + `$remove_method` to remove a previosly associated callback `$cb_name`.
+///   + Optionally, old names of `$set_method` and `$remove_method` forwarding to them, which
+///     must have attributes (i.e. `#[deprecated]`).
 ///   + `listener` is **not** defined. It is the native C callback signature (macro captured).
 ///   + `resolve_args` is optional should have a code body, and is also not defined.
 ///      It is responsible for translating the C arguments into Rust arguments. By default it just
@@ -108,8 +148,14 @@ macro_rules! impl_callback {
             let name = $cb_name:expr;
             extern fn listener(ih: *mut iup_sys::Ihandle $(, $ls_arg:ident: $ls_arg_ty:ty)*) -> CallbackReturn;
 
-            fn $set_method:ident<F: Callback(Self $(, $fn_arg_ty:ty)*)>(&mut self, cb: F) -> Self;
+            fn $set_method:ident<F: Callback(Self $(, $fn_arg_ty:ty)*) $(-> $ret:ty)?>(&mut self, cb: F) -> Self;
             fn $remove_method:ident(&mut self) -> Option<Box<_>>;
+            $(
+                $(#[$old_set_attr:meta])+
+                fn $old_set_method:ident<F: Callback(Self $(, $old_arg_ty:ty)*) $(-> $old_ret:ty)?>(&mut self, cb: F) -> Self;
+                $(#[$old_remove_attr:meta])+
+                fn $old_remove_method:ident(&mut self) -> Option<Box<_>>;
+            )?
         }
         
     ) => {
@@ -119,8 +165,14 @@ macro_rules! impl_callback {
                 let name = $cb_name;
                 extern fn listener(ih: *mut iup_sys::Ihandle $(, $ls_arg: $ls_arg_ty)*) -> CallbackReturn;
 
-                fn $set_method<F: Callback(Self $(, $fn_arg_ty)*)>(&mut self, cb: F) -> Self;
+                fn $set_method<F: Callback(Self $(, $fn_arg_ty)*) $(-> $ret)?>(&mut self, cb: F) -> Self;
                 fn $remove_method(&mut self) -> Option<Box<_>>;
+                $(
+                    $(#[$old_set_attr])+
+                    fn $old_set_method<F: Callback(Self $(, $old_arg_ty)*) $(-> $old_ret)?>(&mut self, cb: F) -> Self;
+                    $(#[$old_remove_attr])+
+                    fn $old_remove_method(&mut self) -> Option<Box<_>>;
+                )?
 
                 fn resolve_args(elem: Self, $($ls_arg: $ls_arg_ty),*) -> (Self, $($fn_arg_ty),*) {
                     (elem, $($ls_arg.into_rust()),*)
@@ -137,8 +189,14 @@ macro_rules! impl_callback {
             let name = $cb_name:expr;
             extern fn listener(ih: *mut iup_sys::Ihandle $(, $ls_arg:ident: $ls_arg_ty:ty)*) -> CallbackReturn;
 
-            fn $set_method:ident<F: Callback(Self $(, $fn_arg_ty:ty)*)>(&mut self, cb: F) -> Self;
+            fn $set_method:ident<F: Callback(Self $(, $fn_arg_ty:ty)*) $(-> $ret:ty)?>(&mut self, cb: F) -> Self;
             fn $remove_method:ident(&mut self) -> Option<Box<_>>;
+            $(
+                $(#[$old_set_attr:meta])+
+                fn $old_set_method:ident<F: Callback(Self $(, $old_arg_ty:ty)*) $(-> $old_ret:ty)?>(&mut self, cb: F) -> Self;
+                $(#[$old_remove_attr:meta])+
+                fn $old_remove_method:ident(&mut self) -> Option<Box<_>>;
+            )?
 
             fn resolve_args($aa_argself:ident: Self, $($aa_arg:ident: $aa_arg_ty:ty),*)
                             -> (Self, $($aa_ret_ty:ty),*)
@@ -151,7 +209,7 @@ macro_rules! impl_callback {
         pub trait $trait_name where Self: $crate::Element {
 
             fn $set_method<F>(&mut self, cb: F) -> Self
-                    where F: $crate::callback::Callback<(Self, $($fn_arg_ty),*)> {
+                    where F: $crate::callback::Callback<(Self, $($fn_arg_ty),*) $(, $ret)?> {
 
                 use std::mem::transmute;
                 use libc::c_int;
@@ -165,27 +223,63 @@ macro_rules! impl_callback {
                 }
 
                 extern fn listener<Self0: $trait_name>(ih: *mut iup_sys::Ihandle, $($ls_arg: $ls_arg_ty),*) -> c_int {
-                    let fbox: &mut Box<_> = get_fbox_callback!(ih, $cb_name, Callback<(Self0, $($fn_arg_ty),*)>);
+                    let _running = match $crate::debug::enter_callback(ih, $cb_name) {
+                        Some(running) => running,
+                        None => return iup_sys::IUP_DEFAULT,
+                    };
+                    let fbox: &mut Box<_> = get_fbox_callback!(ih, $cb_name, Callback<(Self0, $($fn_arg_ty),*) $(, $ret)?>);
                     let element = unsafe { <Self0 as $crate::Element>::from_raw_unchecked(ih) };
+                    let _span = trace_span!("callback", name = $cb_name, ih = ?ih);
                     fbox.on_callback(resolve_args::<Self0>(element, $($ls_arg),*))
                 }
 
+                // Listener of zero-sized closures, see `set_zst_callback!`.
+                extern fn zst_listener<Self0: $trait_name, F0>(ih: *mut iup_sys::Ihandle, $($ls_arg: $ls_arg_ty),*) -> c_int
+                                        where F0: $crate::callback::Callback<(Self0, $($fn_arg_ty),*) $(, $ret)?> {
+                    let _running = match $crate::debug::enter_callback(ih, $cb_name) {
+                        Some(running) => running,
+                        None => return iup_sys::IUP_DEFAULT,
+                    };
+                    let element = unsafe { <Self0 as $crate::Element>::from_raw_unchecked(ih) };
+                    let _span = trace_span!("callback", name = $cb_name, ih = ?ih);
+                    zst_callback!(F0, resolve_args::<Self0>(element, $($ls_arg),*))
+                }
+
                 unsafe {
-                    set_fbox_callback!(self.raw(), $cb_name, listener::<Self>, cb, 
-                                       Callback<(Self, $($fn_arg_ty),*)>);
+                    if ::std::mem::size_of::<F>() == 0 {
+                        set_zst_callback!(self.raw(), $cb_name, zst_listener::<Self, F>, cb,
+                                          Callback<(Self, $($fn_arg_ty),*) $(, $ret)?>);
+                    } else {
+                        set_fbox_callback!(self.raw(), $cb_name, listener::<Self>, cb, 
+                                           Callback<(Self, $($fn_arg_ty),*) $(, $ret)?>);
+                    }
                 }
 
                 self.clone()
             }
 
             fn $remove_method(&mut self)
-                                -> Option<Box<$crate::callback::Callback<(Self, $($fn_arg_ty),*)>>> {
+                                -> Option<Box<$crate::callback::Callback<(Self, $($fn_arg_ty),*) $(, $ret)?>>> {
                 unsafe {
                     let old_cb = clear_fbox_callback!(self.raw(), $cb_name,
-                                                      Callback<(Self, $($fn_arg_ty),*)>);
+                                                      Callback<(Self, $($fn_arg_ty),*) $(, $ret)?>);
                     old_cb
                 }
             }
+
+            $(
+                $(#[$old_set_attr])+
+                fn $old_set_method<F>(&mut self, cb: F) -> Self
+                        where F: $crate::callback::Callback<(Self, $($old_arg_ty),*) $(, $old_ret)?> {
+                    self.$set_method(cb)
+                }
+
+                $(#[$old_remove_attr])+
+                fn $old_remove_method(&mut self)
+                        -> Option<Box<$crate::callback::Callback<(Self, $($old_arg_ty),*) $(, $old_ret)?>>> {
+                    self.$remove_method()
+                }
+            )?
         }
     };
 
@@ -211,13 +305,34 @@ macro_rules! impl_callback {
                 use $crate::callback::IntoRust;
 
                 extern fn listener($($ls_arg: $ls_arg_ty),*) -> c_int {
+                    let _running = match $crate::debug::enter_callback(ptr::null_mut(), $cb_name) {
+                        Some(running) => running,
+                        None => return iup_sys::IUP_DEFAULT,
+                    };
                     let fbox: &mut Box<_> = get_fbox_callback!(ptr::null_mut(), $cb_name, Callback<($($fn_arg_ty),*)>);
+                    let _span = trace_span!("callback", name = $cb_name);
                     fbox.on_callback(($($ls_arg.into_rust()),*))
                 }
 
+                // Listener of zero-sized closures, see `set_zst_callback!`.
+                extern fn zst_listener<F0>($($ls_arg: $ls_arg_ty),*) -> c_int
+                                        where F0: $crate::callback::Callback<($($fn_arg_ty),*)> {
+                    let _running = match $crate::debug::enter_callback(ptr::null_mut(), $cb_name) {
+                        Some(running) => running,
+                        None => return iup_sys::IUP_DEFAULT,
+                    };
+                    let _span = trace_span!("callback", name = $cb_name);
+                    zst_callback!(F0, ($($ls_arg.into_rust()),*))
+                }
+
                 unsafe {
-                    set_fbox_callback!(ptr::null_mut(), $cb_name, listener, cb, 
-                                       Callback<($($fn_arg_ty),*)>);
+                    if ::std::mem::size_of::<F>() == 0 {
+                        set_zst_callback!(ptr::null_mut(), $cb_name, zst_listener::<F>, cb,
+                                          Callback<($($fn_arg_ty),*)>);
+                    } else {
+                        set_fbox_callback!(ptr::null_mut(), $cb_name, listener, cb, 
+                                           Callback<($($fn_arg_ty),*)>);
+                    }
                 }
             }
 
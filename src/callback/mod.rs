@@ -21,6 +21,7 @@ pub unsafe fn drop_callbacks(ih: *mut iup_sys::Ihandle) {
     // button.rs
     drop_callback!(ih, "BUTTON_CB");
     drop_callback!(ih, "MOTION_CB");
+    drop_callback!(ih, "WHEEL_CB");
 
     // callbacks.rs
     drop_callback!(ih, "ACTION");
@@ -33,6 +34,7 @@ pub unsafe fn drop_callbacks(ih: *mut iup_sys::Ihandle) {
     drop_callback!(ih, "ENTERWINDOW_CB");
     drop_callback!(ih, "LEAVEWINDOW_CB");
     drop_callback!(ih, "HELP_CB");
+    drop_callback!(ih, "K_ANY");
     drop_callback!(ih, "CARET_CB");
     drop_callback!(ih, "SPIN_CB");
     drop_callback!(ih, "VALUECHANGED_CB");
@@ -41,11 +43,40 @@ pub unsafe fn drop_callbacks(ih: *mut iup_sys::Ihandle) {
     drop_callback!(ih, "MOVE_CB");
     drop_callback!(ih, "RESIZE_CB");
 
+    // list.rs
+    drop_callback!(ih, "EDIT_CB");
+
+    // tree.rs
+    drop_callback!(ih, "SELECTION_CB");
+    drop_callback!(ih, "BRANCHOPEN_CB");
+    drop_callback!(ih, "BRANCHCLOSE_CB");
+    drop_callback!(ih, "EXECUTELEAF_CB");
+    drop_callback!(ih, "MULTISELECTION_CB");
+    drop_callback!(ih, "MULTIUNSELECTION_CB");
+    drop_callback!(ih, "SHOWRENAME_CB");
+    drop_callback!(ih, "TOGGLEVALUE_CB");
+    drop_callback!(ih, "RENAME_CB");
+
+    // matrix.rs
+    #[cfg(feature = "controls")]
+    drop_callback!(ih, "CLICK_CB");
+
+    // flattabs.rs
+    drop_callback!(ih, "TABCHANGEPOS_CB");
+    drop_callback!(ih, "TABCLOSE_CB");
+
+    // touch.rs
+    #[cfg(feature = "tuio")]
+    drop_callback!(ih, "TOUCH_CB");
+    #[cfg(feature = "tuio")]
+    drop_callback!(ih, "MULTITOUCH_CB");
+
     // dialog.rs
     drop_callback!(ih, "COPYDATA_CB");
     drop_callback!(ih, "MDIACTIVATE_CB");
     drop_callback!(ih, "SHOW_CB");
     drop_callback!(ih, "TRAYCLICK_CB");
+    drop_callback!(ih, "CUSTOMFRAMEACTIVATE_CB");
 }
 
 
@@ -54,7 +85,12 @@ pub unsafe fn drop_callbacks(ih: *mut iup_sys::Ihandle) {
 /// Return this from a callback to tell the framework a non-default action to be performed.
 ///
 /// Not all callbacks accepts `Close`, `Ignore` or `Continue`, check their respective docs.
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// Callbacks giving meaning to only some of the values return a type having only those instead
+/// (`KeyReturn`, `ActionReturn`, `RefuseReturn` or `EditReturn`), as pointed out in their docs.
+///
+/// More values may be added in the future, matches on a `CallbackReturn` must have a wildcard
+/// arm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum CallbackReturn {
     /// The default `CallbackReturn`, does nothing when returned.
     Default,
@@ -70,6 +106,13 @@ pub enum CallbackReturn {
     /// Callback specific, check the callback documentation to see if it accepts this return value
     /// and it's effect.
     Char(char),
+    /// Callback specific, check the callback documentation to see if it accepts this return value
+    /// and it's effect.
+    Error,
+    /// Not a value, it only makes matches without a wildcard arm fail to compile. Acts just like
+    /// `Default` when returned.
+    #[doc(hidden)]
+    __Nonexhaustive,
 }
 
 impl CallbackReturn {
@@ -80,11 +123,110 @@ impl CallbackReturn {
             Default => iup_sys::IUP_DEFAULT,
             Ignore => iup_sys::IUP_IGNORE,
             Continue => iup_sys::IUP_CONTINUE,
+            Error => iup_sys::IUP_ERROR,
             Char(c) => c as c_int,
+            __Nonexhaustive => iup_sys::IUP_DEFAULT,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn from_raw(value: c_int) -> CallbackReturn {
+        use self::CallbackReturn::*;
+        match value {
+            iup_sys::IUP_CLOSE => Close,
+            iup_sys::IUP_DEFAULT => Default,
+            iup_sys::IUP_IGNORE => Ignore,
+            iup_sys::IUP_CONTINUE => Continue,
+            iup_sys::IUP_ERROR => Error,
+            c => char::from_u32(c as u32).map(Char).unwrap_or(Default),
         }
     }
 }
 
+/// Declares a return type with only the `CallbackReturn` variants meaningful for some callbacks,
+/// which converts into `CallbackReturn`.
+macro_rules! callback_return {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$var_attr:meta])* $variant:ident $(($field:ident: $ty:ty))*),+,
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        pub enum $name {
+            $($(#[$var_attr])* $variant $(($ty))*),+
+        }
+
+        impl From<$name> for CallbackReturn {
+            fn from(value: $name) -> CallbackReturn {
+                match value {
+                    $($name::$variant $(($field))* => CallbackReturn::$variant $(($field))*),+
+                }
+            }
+        }
+
+        // This allows returning '()' from a callback instead of the type.
+        impl From<()> for $name {
+            fn from(_: ()) -> $name {
+                $name::Default
+            }
+        }
+    }
+}
+
+callback_return! {
+    /// The values accepted from keyboard callbacks such as `KAnyCb`.
+    pub enum KeyReturn {
+        /// The key is processed by the element.
+        Default,
+        /// Closes the dialog of the element.
+        Close,
+        /// The key is ignored, it's not processed by the element.
+        Ignore,
+        /// The key is propagated to the parent of the element.
+        Continue,
+    }
+}
+
+callback_return! {
+    /// The values accepted from action callbacks which can't be refused, e.g. the `Action` of
+    /// buttons and menu items.
+    pub enum ActionReturn {
+        /// Nothing else happens.
+        Default,
+        /// Closes the dialog of the element.
+        Close,
+    }
+}
+
+callback_return! {
+    /// The values accepted from callbacks notifying of something which can be refused, e.g.
+    /// `CloseCb` or `RenameCb`.
+    pub enum RefuseReturn {
+        /// What was notified happens.
+        Default,
+        /// Closes the dialog of the element.
+        Close,
+        /// What was notified doesn't happen, e.g. the dialog is not closed.
+        Ignore,
+    }
+}
+
+callback_return! {
+    /// The values accepted from callbacks filtering the typed text, e.g. `TextAction`.
+    pub enum EditReturn {
+        /// The edit happens.
+        Default,
+        /// Closes the dialog of the element, ignoring the edit.
+        Close,
+        /// The edit is ignored.
+        Ignore,
+        /// The edit happens with this character instead of the typed one.
+        Char(c: char),
+    }
+}
+
 // This allows returning '()' from a callback instead of CallbackReturn.
 impl From<()> for CallbackReturn {
     fn from(_: ()) -> CallbackReturn {
@@ -92,16 +234,25 @@ impl From<()> for CallbackReturn {
     }
 }
 
-pub trait Callback<Args> : 'static {
+/// Closures accepted as callbacks.
+///
+/// Closures are boxed when set, except zero-sized ones (plain `fn`s and closures capturing
+/// nothing), which are set without any allocation. Removing such a callback returns `None`.
+///
+/// `Ret` is the type the closures return, or convert from. Most callbacks accept any
+/// `CallbackReturn`, the others accept only the type with the values they give meaning to (e.g.
+/// `KeyReturn`).
+pub trait Callback<Args, Ret = CallbackReturn> : 'static {
     fn on_callback(&mut self, args: Args) -> c_int; 
 }
 
-impl<Args, Out: Into<CallbackReturn>, F: 'static> Callback<Args> for F where F: FnMut(Args) -> Out {
-    /// Because of the `impl From<()> for CallbackReturn`, closures that return `()` can be
+impl<Args, Ret, Out, F: 'static> Callback<Args, Ret> for F
+                where F: FnMut(Args) -> Out, Out: Into<Ret>, Ret: Into<CallbackReturn> {
+    /// Because of the `impl From<()>` of the return types, closures that return `()` can be
     /// accepted by this impl.
     fn on_callback(&mut self, args: Args) -> c_int {
-        let r = self(args).into();
-        r.to_raw()
+        let r: Ret = self(args).into();
+        r.into().to_raw()
     }
 }
 
@@ -0,0 +1,94 @@
+//! Menus and lists built from the values of an enum.
+//!
+//! An enum implementing `Choice` gives the values and their titles, and the selection is given
+//! back as a value, so there are no indices to keep in sync with the order of the items. The
+//! trait is easily implemented from the `strum` derives:
+//!
+//! ```ignore
+//! #[derive(Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+//! enum Zoom { Fit, Half, Actual, Double }
+//!
+//! impl Choice for Zoom {
+//!     fn choices() -> Vec<Zoom> { Zoom::iter().collect() }
+//!     fn title(&self) -> String { self.to_string() }
+//! }
+//!
+//! let zoom_menu = choice::menu(|zoom: Zoom| set_zoom(zoom));
+//! let zoom_list = choice::fill_list(List::new_dropdown(), |zoom: Zoom| set_zoom(zoom));
+//! ```
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use Element;
+use Handle;
+use control::{List, ListAction, ListItemState};
+use menu::{Menu, Item, RadioGroup};
+use property::Property;
+use callback::Action;
+
+/// A type with a fixed set of values to choose from, usually an enum.
+pub trait Choice: Clone + PartialEq + 'static {
+    /// Gets all the values, in the order they are shown.
+    fn choices() -> Vec<Self>;
+
+    /// Gets the text shown for the value, which may have a mnemonic in menus (e.g. "&Fit").
+    fn title(&self) -> String;
+}
+
+/// Creates a menu with an item per value, calling `on_select` with the value of the selected
+/// item.
+pub fn menu<T: Choice, F: FnMut(T) + 'static>(on_select: F) -> Menu {
+    let on_select = Rc::new(RefCell::new(on_select));
+    let items: Vec<Handle> = T::choices().into_iter().map(|value| {
+        let on_select = on_select.clone();
+        Item::with_title(value.title()).set_action(move |_| {
+            // A nested selection, e.g. from a popup shown by the callback, is ignored.
+            if let Ok(mut on_select) = on_select.try_borrow_mut() {
+                (&mut *on_select)(value.clone());
+            }
+        }).into()
+    }).collect();
+    Menu::new(items)
+}
+
+/// Creates a menu with an item per value, checking the item of the value of `property` and
+/// setting it when an item is selected, see `RadioGroup`.
+pub fn radio_menu<T: Choice>(property: Property<T>) -> Menu {
+    let group = RadioGroup::new(property);
+    let items: Vec<Handle> = T::choices().into_iter().map(|value| {
+        group.item(value.title(), value).into()
+    }).collect();
+    Menu::new(items)
+}
+
+/// Sets the items of `list` to the titles of the values, calling `on_select` with the value of
+/// the item selected by the user.
+///
+/// This sets the ACTION callback of the list. The list shouldn't allow multiple selection.
+pub fn fill_list<T: Choice, F: FnMut(T) + 'static>(mut list: List, mut on_select: F) -> List {
+    let choices = T::choices();
+    list.set_items(choices.iter().map(|value| value.title()).collect::<Vec<_>>());
+    list.set_action(move |(_, _, id, state): (List, String, u32, ListItemState)| {
+        if let ListItemState::Selected = state {
+            let value = (id as usize).checked_sub(1).and_then(|index| choices.get(index));
+            if let Some(value) = value {
+                on_select(value.clone());
+            }
+        }
+    })
+}
+
+/// Gets the value of the item selected in a list filled by `fill_list`.
+pub fn selected<T: Choice>(list: List) -> Option<T> {
+    let id = list.attrib_parse::<usize, _>("VALUE").unwrap_or(0);
+    match id {
+        0 => None,
+        id => T::choices().into_iter().nth(id - 1),
+    }
+}
+
+/// Selects the item of `value` in a list filled by `fill_list`, without calling the callback.
+pub fn select<T: Choice>(mut list: List, value: &T) {
+    let id = T::choices().iter().position(|other| other == value).map_or(0, |pos| pos + 1);
+    list.set_attrib("VALUE", id.to_string());
+}
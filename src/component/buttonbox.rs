@@ -0,0 +1,202 @@
+//! The row of standard buttons at the bottom of a dialog.
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use Element;
+use Handle;
+use element::{Node, Widget};
+use control::Button;
+use dialog::Dialog;
+use layout::{HBox, Fill};
+use callback::{Action, ActionReturn};
+use i18n;
+
+/// A standard button of a `ButtonBox`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StandardButton {
+    Ok,
+    Cancel,
+    Apply,
+    Help,
+}
+
+impl StandardButton {
+    /// Gets the translated title of the button.
+    fn title(&self) -> String {
+        match *self {
+            StandardButton::Ok => i18n::tr("IUPRUST_BUTTON_OK", "OK"),
+            StandardButton::Cancel => i18n::tr("IUPRUST_BUTTON_CANCEL", "Cancel"),
+            StandardButton::Apply => i18n::tr("IUPRUST_BUTTON_APPLY", "&Apply"),
+            StandardButton::Help => i18n::tr("IUPRUST_BUTTON_HELP", "&Help"),
+        }
+    }
+
+    /// Gets the position of the button in the row, following the platform conventions.
+    ///
+    /// Windows puts OK first, while GTK and macOS put it last, the Help button going to the
+    /// left of the dialog.
+    fn order(&self) -> u8 {
+        if cfg!(windows) {
+            match *self {
+                StandardButton::Ok => 0,
+                StandardButton::Cancel => 1,
+                StandardButton::Apply => 2,
+                StandardButton::Help => 3,
+            }
+        } else {
+            match *self {
+                StandardButton::Help => 0,
+                StandardButton::Apply => 1,
+                StandardButton::Cancel => 2,
+                StandardButton::Ok => 3,
+            }
+        }
+    }
+}
+
+struct Inner {
+    root: HBox,
+    buttons: Vec<(StandardButton, Button)>,
+    clicked: Option<StandardButton>,
+    on_click: Option<Box<FnMut(StandardButton) -> ActionReturn>>,
+}
+
+/// A row of standard buttons (OK, Cancel, Apply and Help) in the order of the platform, with
+/// the same width and aligned to the right.
+///
+/// By default OK and Cancel hide the dialog, which ends its `popup`, and the others do nothing,
+/// `on_click` changes that. Either way the last button clicked is remembered, so a modal dialog
+/// knows how it was closed:
+///
+/// ```ignore
+/// let buttons = ButtonBox::ok_cancel();
+/// let mut dialog = Dialog::new(VBox::new(elements![form, buttons.clone()]));
+/// buttons.set_defaults(&mut dialog);
+/// dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent);
+/// if buttons.clicked() == Some(StandardButton::Ok) {
+///     save_settings();
+/// }
+/// ```
+///
+/// The titles are translated by the `i18n` catalog with the "IUPRUST_BUTTON_OK",
+/// "IUPRUST_BUTTON_CANCEL", "IUPRUST_BUTTON_APPLY" and "IUPRUST_BUTTON_HELP" keys.
+///
+/// Cloning a button box gives another handle to the same buttons.
+#[derive(Clone)]
+pub struct ButtonBox {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl ButtonBox {
+    /// Creates a row with the specified buttons, ignoring repeated ones.
+    pub fn new(which: &[StandardButton]) -> ButtonBox {
+        let mut which = which.to_vec();
+        which.sort_by_key(|button| button.order());
+        which.dedup();
+
+        let buttons: Vec<(StandardButton, Button)> = which.iter().map(|&button| {
+            (button, Button::with_title(button.title()).set_attrib("PADDING", "12x2"))
+        }).collect();
+
+        // The Help button stays on the left where it comes first.
+        let mut row: Vec<Handle> = Vec::new();
+        let mut rest = &buttons[..];
+        if let Some(&(StandardButton::Help, help)) = buttons.first() {
+            row.push(help.into());
+            rest = &buttons[1..];
+        }
+        row.push(Fill::new().into());
+        row.extend(rest.iter().map(|&(_, button)| Handle::from(button)));
+        let root = HBox::new(row).set_attrib("NORMALIZESIZE", "HORIZONTAL")
+                                 .set_attrib("GAP", "6");
+
+        let button_box = ButtonBox {
+            inner: Rc::new(RefCell::new(Inner {
+                root: root,
+                buttons: buttons,
+                clicked: None,
+                on_click: None,
+            })),
+        };
+        button_box.bind_buttons();
+        button_box
+    }
+
+    /// Creates a row with the OK and Cancel buttons.
+    pub fn ok_cancel() -> ButtonBox {
+        ButtonBox::new(&[StandardButton::Ok, StandardButton::Cancel])
+    }
+
+    /// Sets the closure called when a button is clicked, replacing the default behavior.
+    ///
+    /// `ActionReturn::Close` closes the dialog.
+    pub fn on_click<F>(self, cb: F) -> ButtonBox
+                        where F: FnMut(StandardButton) -> ActionReturn + 'static {
+        self.inner.borrow_mut().on_click = Some(Box::new(cb));
+        self
+    }
+
+    /// Gets a button of the row, e.g. to disable it.
+    pub fn button(&self, which: StandardButton) -> Option<Button> {
+        self.inner.borrow().buttons.iter().find(|&&(button, _)| button == which)
+                                          .map(|&(_, button)| button)
+    }
+
+    /// Gets the last button clicked, `None` if none was clicked yet.
+    pub fn clicked(&self) -> Option<StandardButton> {
+        self.inner.borrow().clicked
+    }
+
+    /// Makes Enter click the OK button and Esc click the Cancel button of `dialog`, those that
+    /// are in the row.
+    pub fn set_defaults(&self, dialog: &mut Dialog) {
+        if let Some(ok) = self.button(StandardButton::Ok) {
+            dialog.set_default_enter(ok);
+        }
+        if let Some(cancel) = self.button(StandardButton::Cancel) {
+            dialog.set_default_esc(cancel);
+        }
+    }
+
+    /// Returns the element containing the buttons.
+    pub fn element(&self) -> HBox {
+        self.inner.borrow().root
+    }
+
+    fn bind_buttons(&self) {
+        let buttons = self.inner.borrow().buttons.clone();
+        for (which, mut button) in buttons {
+            let button_box = self.clone();
+            button.set_action(move |(button,): (Button,)| {
+                button_box.inner.borrow_mut().clicked = Some(which);
+                // Take the closure out so it's free to access the button box.
+                let cb = button_box.inner.borrow_mut().on_click.take();
+                match cb {
+                    Some(mut cb) => {
+                        let ret = cb(which);
+                        let mut inner = button_box.inner.borrow_mut();
+                        if inner.on_click.is_none() {
+                            inner.on_click = Some(cb);
+                        }
+                        ret
+                    },
+                    None => {
+                        if which == StandardButton::Ok || which == StandardButton::Cancel {
+                            // Hiding also ends the `popup` of a modal dialog.
+                            if let Some(mut dialog) = button.dialog() {
+                                dialog.hide();
+                            }
+                        }
+                        ActionReturn::Default
+                    },
+                }
+            });
+        }
+    }
+}
+
+impl From<ButtonBox> for Handle {
+    fn from(button_box: ButtonBox) -> Handle {
+        Handle::from(button_box.element())
+    }
+}
@@ -0,0 +1,175 @@
+//! An interactive console, e.g. to embed debug commands in a tool.
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use Element;
+use Handle;
+use control::{Label, Text};
+use component::{LogView, LogLevel, LogWriter};
+use element::Widget;
+use layout::{VBox, HBox, Expand};
+use callback::{KeyReturn, KAnyCb};
+
+/// Key codes of Enter, Up and Down.
+const K_CR: i32 = 13;
+const K_UP: i32 = 0xFF52;
+const K_DOWN: i32 = 0xFF54;
+
+struct Inner {
+    root: VBox,
+    prompt: Label,
+    input: Text,
+    scrollback: LogView,
+    history: Vec<String>,
+    /// The history entry shown in the input while browsing it with Up and Down.
+    browsing: Option<usize>,
+    handler: Option<Rc<RefCell<FnMut(String, LogWriter)>>>,
+}
+
+/// A composite with a read-only scrollback and an input line, where the user types commands
+/// run by a handler closure.
+///
+/// Entered commands are echoed to the scrollback after the prompt and kept in a history,
+/// browsed with the Up and Down keys. The handler receives a writer to the scrollback which can
+/// be moved to another thread, so long commands don't block the interface:
+///
+/// ```ignore
+/// let console = Console::new("> ").on_command(|command, mut out| {
+///     thread::spawn(move || {
+///         let result = evaluate(&command);
+///         writeln!(out, "{}", result).unwrap();
+///     });
+/// });
+/// ```
+///
+/// The scrollback is a `LogView`, see `Console::scrollback`. Cloning a console gives another
+/// handle to the same console.
+#[derive(Clone)]
+pub struct Console {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Console {
+    /// Creates a console showing `prompt` before the input line.
+    pub fn new<S: Into<String>>(prompt: S) -> Console {
+        let scrollback = LogView::new();
+        let prompt = Label::with_title(prompt);
+        let input = Text::new().set_expand(Expand::Horizontal);
+        let line = HBox::new(elements![prompt, input]).set_attrib("ALIGNMENT", "ACENTER");
+        let root = VBox::new(elements![scrollback.clone(), line]);
+        let console = Console {
+            inner: Rc::new(RefCell::new(Inner {
+                root: root,
+                prompt: prompt,
+                input: input,
+                scrollback: scrollback,
+                history: Vec::new(),
+                browsing: None,
+                handler: None,
+            })),
+        };
+
+        let this = console.clone();
+        let mut input = input;
+        input.set_k_any(move |(_, c): (Text, i32)| {
+            match c {
+                K_CR => { this.submit(); KeyReturn::Ignore },
+                K_UP => { this.browse(true); KeyReturn::Ignore },
+                K_DOWN => { this.browse(false); KeyReturn::Ignore },
+                _ => KeyReturn::Default,
+            }
+        });
+        console
+    }
+
+    /// Sets the closure running the commands, receiving each command and a writer to the
+    /// scrollback.
+    pub fn on_command<F: FnMut(String, LogWriter) + 'static>(self, handler: F) -> Console {
+        self.inner.borrow_mut().handler = Some(Rc::new(RefCell::new(handler)));
+        self
+    }
+
+    /// Sets the prompt shown before the input line.
+    pub fn set_prompt<S: Into<String>>(&self, prompt: S) -> Console {
+        self.inner.borrow_mut().prompt.set_attrib("TITLE", prompt);
+        self.clone()
+    }
+
+    /// Adds a line to the scrollback.
+    pub fn print<S: Into<String>>(&self, level: LogLevel, line: S) {
+        self.scrollback().append_line(level, line);
+    }
+
+    /// Gets the commands entered so far, the oldest first.
+    pub fn history(&self) -> Vec<String> {
+        self.inner.borrow().history.clone()
+    }
+
+    /// Replaces the history, e.g. with one saved from a previous session.
+    pub fn set_history(&self, history: Vec<String>) {
+        let mut inner = self.inner.borrow_mut();
+        inner.history = history;
+        inner.browsing = None;
+    }
+
+    /// Returns the view of the scrollback.
+    pub fn scrollback(&self) -> LogView {
+        self.inner.borrow().scrollback.clone()
+    }
+
+    /// Returns the input line.
+    pub fn input(&self) -> Text {
+        self.inner.borrow().input
+    }
+
+    /// Returns the element containing the whole console.
+    pub fn element(&self) -> VBox {
+        self.inner.borrow().root
+    }
+
+    /// Runs the command in the input line.
+    fn submit(&self) {
+        let (command, prompt, handler, output) = {
+            let mut inner = self.inner.borrow_mut();
+            let command = inner.input.attrib("VALUE").unwrap_or_default();
+            inner.input.set_attrib("VALUE", "");
+            inner.browsing = None;
+            if !command.trim().is_empty() && inner.history.last() != Some(&command) {
+                inner.history.push(command.clone());
+            }
+            let prompt = inner.prompt.attrib("TITLE").unwrap_or_default();
+            (command, prompt, inner.handler.clone(), inner.scrollback.writer(LogLevel::Info))
+        };
+        self.print(LogLevel::Info, format!("{}{}", prompt, command));
+        // Called without borrowing, so the handler is free to use the console.
+        if let Some(handler) = handler {
+            if let Ok(mut handler) = handler.try_borrow_mut() {
+                (&mut *handler)(command, output);
+            }
+        }
+    }
+
+    /// Shows the previous (`older`) or next history entry in the input line.
+    fn browse(&self, older: bool) {
+        let mut inner = self.inner.borrow_mut();
+        let len = inner.history.len();
+        let browsing = match (inner.browsing, older) {
+            (None, true) if len > 0 => Some(len - 1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            // Back below the newest entry, to an empty input.
+            (Some(_), false) => None,
+            _ => return,
+        };
+        let value = browsing.map_or(String::new(), |i| inner.history[i].clone());
+        inner.browsing = browsing;
+        inner.input.set_attrib("VALUE", &value[..]);
+        inner.input.set_caret_pos(value.chars().count());
+    }
+}
+
+impl From<Console> for Handle {
+    fn from(console: Console) -> Handle {
+        Handle::from(console.element())
+    }
+}
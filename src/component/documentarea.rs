@@ -0,0 +1,342 @@
+//! Tabs of open documents, as in the main window of a multi-document editor.
+use iup_sys;
+use libc::{c_char, c_int};
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::cell::RefCell;
+
+use Element;
+use Handle;
+use element::{Container, Node, Widget, WeakHandle};
+use layout::{FlatTabs, TabCloseCb, Expand};
+use callback::CallbackReturn;
+
+struct Document {
+    elem: Handle,
+    title: String,
+    modified: bool,
+}
+
+struct Inner {
+    tabs: FlatTabs,
+    /// The documents in the order of their tabs.
+    documents: Vec<Document>,
+    confirm_close: Option<Rc<RefCell<FnMut(usize, Handle) -> bool>>>,
+    /// The tab under the mouse when the left button was pressed, and the number of tabs then.
+    dragged: Option<(usize, usize)>,
+}
+
+/// A tabbed area of documents, each tab with a close button.
+///
+/// Besides the close button, a document is closed by clicking its tab with the middle button.
+/// Tabs are reordered by dragging them with the left button, or with `move_document`. Modified
+/// documents have a "*" after their title, see `set_modified`.
+///
+/// Closing can be confirmed, e.g. to save a modified document first:
+///
+/// ```ignore
+/// let area = DocumentArea::new();
+/// let this = area.clone();
+/// area.on_close_request(move |index, _| {
+///     !this.is_modified(index) || ask_save(&this.title(index).unwrap())
+/// });
+/// area.add_document(editor, "main.rs");
+/// ```
+///
+/// Documents are identified by the position of their tab, from 0. Cloning an area gives another
+/// handle to the same area.
+#[derive(Clone)]
+pub struct DocumentArea {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl DocumentArea {
+    /// Creates an area without documents.
+    pub fn new() -> DocumentArea {
+        let tabs = FlatTabs::new(elements![]).set_attrib("SHOWCLOSE", "YES")
+                                              .set_expand(Expand::Yes);
+        let area = DocumentArea {
+            inner: Rc::new(RefCell::new(Inner {
+                tabs: tabs,
+                documents: Vec::new(),
+                confirm_close: None,
+                dragged: None,
+            })),
+        };
+
+        let this = area.clone();
+        let mut tabs = tabs;
+        tabs.set_tabclose_cb(move |(_, pos): (FlatTabs, usize)| {
+            if this.confirm_close(pos) {
+                this.inner.borrow_mut().documents.remove(pos);
+                // IUP removes the tab and destroys the document itself.
+                CallbackReturn::Continue
+            } else {
+                CallbackReturn::Ignore
+            }
+        });
+        watch_buttons(tabs, Rc::downgrade(&area.inner));
+        area
+    }
+
+    /// Sets a closure asked whether the document at an index can be closed, e.g. to save it first.
+    ///
+    /// It's given the index and the element of the document, and returns whether to close it.
+    pub fn on_close_request<F>(&self, confirm: F) -> DocumentArea
+                                                where F: FnMut(usize, Handle) -> bool + 'static {
+        self.inner.borrow_mut().confirm_close = Some(Rc::new(RefCell::new(confirm)));
+        self.clone()
+    }
+
+    /// Adds a document in a new tab and shows it, returning its index.
+    pub fn add_document<E, S>(&self, elem: E, title: S) -> usize
+                                                    where E: Into<Handle>, S: Into<String> {
+        let mut elem = elem.into();
+        let title = title.into();
+        let mut tabs = self.tabs();
+        if tabs.append(elem).is_err() {
+            return self.document_count();
+        }
+        if tabs.attrib("WID").is_some() {
+            let _ = elem.map();
+            tabs.refresh();
+        }
+        let index = {
+            let mut inner = self.inner.borrow_mut();
+            inner.documents.push(Document { elem: elem, title: title, modified: false });
+            inner.documents.len() - 1
+        };
+        self.update_title(index);
+        self.set_current(index);
+        index
+    }
+
+    /// Closes the document at `index` after asking the `on_close_request` closure, returning
+    /// whether it was closed.
+    pub fn close_document(&self, index: usize) -> bool {
+        if !self.confirm_close(index) {
+            return false;
+        }
+        let mut elem = self.inner.borrow_mut().documents.remove(index).elem;
+        // Documents are often closed from one of their own callbacks, e.g. a close button.
+        elem.detach();
+        elem.destroy_later();
+        let mut tabs = self.tabs();
+        if tabs.attrib("WID").is_some() {
+            tabs.refresh();
+        }
+        true
+    }
+
+    /// Moves the document at `from` so its tab ends at the position `to`.
+    pub fn move_document(&self, from: usize, to: usize) -> bool {
+        let mut tabs = self.tabs();
+        let current = tabs.value_pos();
+        if !tabs.move_tab(from, to) {
+            return false;
+        }
+        {
+            let mut inner = self.inner.borrow_mut();
+            let document = inner.documents.remove(from);
+            inner.documents.insert(to, document);
+        }
+        if current == Some(from) {
+            self.set_current(to);
+        }
+        tabs.update();
+        true
+    }
+
+    /// Returns the number of documents.
+    pub fn document_count(&self) -> usize {
+        self.inner.borrow().documents.len()
+    }
+
+    /// Gets the element of the document at `index`.
+    pub fn document(&self, index: usize) -> Option<Handle> {
+        self.inner.borrow().documents.get(index).map(|document| document.elem)
+    }
+
+    /// Gets the index of a document from its element.
+    pub fn index_of<E: Element>(&self, elem: &E) -> Option<usize> {
+        let inner = self.inner.borrow();
+        inner.documents.iter().position(|document| document.elem.raw() == elem.raw())
+    }
+
+    /// Gets the index of the document shown.
+    pub fn current(&self) -> Option<usize> {
+        match self.document_count() {
+            0 => None,
+            _ => self.tabs().value_pos(),
+        }
+    }
+
+    /// Shows the document at `index`.
+    pub fn set_current(&self, index: usize) -> DocumentArea {
+        if index < self.document_count() {
+            self.tabs().set_value_pos(index);
+        }
+        self.clone()
+    }
+
+    /// Gets the title of the document at `index`, without the modified mark.
+    pub fn title(&self, index: usize) -> Option<String> {
+        self.inner.borrow().documents.get(index).map(|document| document.title.clone())
+    }
+
+    /// Sets the title of the document at `index`.
+    pub fn set_title<S: Into<String>>(&self, index: usize, title: S) -> DocumentArea {
+        if let Some(document) = self.inner.borrow_mut().documents.get_mut(index) {
+            document.title = title.into();
+        }
+        self.update_title(index);
+        self.clone()
+    }
+
+    /// Checks whether the document at `index` is marked as modified.
+    pub fn is_modified(&self, index: usize) -> bool {
+        self.inner.borrow().documents.get(index).map_or(false, |document| document.modified)
+    }
+
+    /// Marks the document at `index` as modified or not, shown by a "*" after its title.
+    pub fn set_modified(&self, index: usize, modified: bool) -> DocumentArea {
+        if let Some(document) = self.inner.borrow_mut().documents.get_mut(index) {
+            document.modified = modified;
+        }
+        self.update_title(index);
+        self.clone()
+    }
+
+    /// Gets the indices of the modified documents, e.g. to ask to save them before quitting.
+    pub fn modified_documents(&self) -> Vec<usize> {
+        let inner = self.inner.borrow();
+        inner.documents.iter().enumerate().filter(|&(_, document)| document.modified)
+                                          .map(|(index, _)| index).collect()
+    }
+
+    /// Returns the tabs showing the documents.
+    pub fn tabs(&self) -> FlatTabs {
+        self.inner.borrow().tabs
+    }
+
+    /// Returns the element containing the whole area.
+    pub fn element(&self) -> FlatTabs {
+        self.tabs()
+    }
+
+    /// Asks the `on_close_request` closure whether to close the document at `index`.
+    fn confirm_close(&self, index: usize) -> bool {
+        let (elem, confirm) = {
+            let inner = self.inner.borrow();
+            match inner.documents.get(index) {
+                Some(document) => (document.elem, inner.confirm_close.clone()),
+                None => return false,
+            }
+        };
+        // Called without borrowing, so the closure is free to use the area.
+        match confirm {
+            Some(confirm) => match confirm.try_borrow_mut() {
+                Ok(mut confirm) => (&mut *confirm)(index, elem),
+                Err(_) => false,
+            },
+            None => true,
+        }
+    }
+
+    fn update_title(&self, index: usize) {
+        let inner = self.inner.borrow();
+        if let Some(document) = inner.documents.get(index) {
+            let mut tabs = inner.tabs;
+            let mark = if document.modified { "*" } else { "" };
+            tabs.set_tab_title(index, format!("{}{}", document.title, mark));
+        }
+    }
+}
+
+impl From<DocumentArea> for Handle {
+    fn from(area: DocumentArea) -> Handle {
+        Handle::from(area.element())
+    }
+}
+
+/// The areas whose tabs get the middle clicks and drags, with the BUTTON_CB of FlatTabs itself.
+struct Watched {
+    tabs: WeakHandle<FlatTabs>,
+    area: Weak<RefCell<Inner>>,
+    button_cb: Option<ButtonFn>,
+}
+
+thread_local!(static WATCHED: RefCell<Vec<Watched>> = RefCell::new(Vec::new()));
+
+type ButtonFn = extern fn(*mut iup_sys::Ihandle, c_int, c_int, c_int, c_int, *mut c_char) -> c_int;
+
+/// Replaces the BUTTON_CB of `tabs`, which FlatTabs needs, by one also calling the original.
+fn watch_buttons(tabs: FlatTabs, area: Weak<RefCell<Inner>>) {
+    let button_cb = unsafe { iup_get_callback(tabs.raw(), cstr!("BUTTON_CB")) }
+                        .map(|cb| unsafe { mem::transmute::<iup_sys::Icallback, ButtonFn>(cb) });
+    WATCHED.with(|watched| {
+        let mut watched = watched.borrow_mut();
+        watched.retain(|watched| watched.tabs.is_alive() && watched.area.upgrade().is_some());
+        watched.push(Watched { tabs: tabs.downgrade(), area: area, button_cb: button_cb });
+    });
+    unsafe {
+        let cb = on_button as ButtonFn;
+        iup_sys::IupSetCallback(tabs.raw(), cstr!("BUTTON_CB"), mem::transmute(cb));
+    }
+}
+
+extern fn on_button(ih: *mut iup_sys::Ihandle, button: c_int, pressed: c_int, x: c_int, y: c_int,
+                    status: *mut c_char) -> c_int {
+    let found = WATCHED.with(|watched| {
+        watched.borrow().iter().find(|watched| {
+            watched.tabs.upgrade().map_or(false, |tabs| tabs.raw() == ih)
+        }).map(|watched| (watched.button_cb, watched.area.clone()))
+    });
+    let (button_cb, area) = match found {
+        Some((button_cb, area)) => (button_cb, area),
+        None => return iup_sys::IUP_DEFAULT,
+    };
+    let result = match button_cb {
+        Some(original) => original(ih, button, pressed, x, y, status),
+        None => iup_sys::IUP_DEFAULT,
+    };
+
+    let area = match area.upgrade() {
+        Some(inner) => DocumentArea { inner: inner },
+        None => return result,
+    };
+    // The tab under the mouse, kept up to date by FlatTabs for drawing its highlight. This is
+    // internal to FlatTabs, so it's only trusted when set to a valid tab.
+    let hovered = match unsafe { iup_sys::IupGetAttribute(ih, cstr!("_IUPFTABS_HIGHLIGHTED")) } {
+        value if value.is_null() => None,
+        value => string_from_cstr!(value).parse::<usize>().ok(),
+    };
+    let hovered = hovered.filter(|&index| index < area.document_count());
+    match (button, pressed != 0, hovered) {
+        (iup_sys::IUP_BUTTON2, false, Some(index)) => {
+            area.close_document(index);
+        },
+        (iup_sys::IUP_BUTTON1, true, _) => {
+            let count = area.document_count();
+            area.inner.borrow_mut().dragged = hovered.map(|index| (index, count));
+        },
+        (iup_sys::IUP_BUTTON1, false, _) => {
+            let dragged = area.inner.borrow_mut().dragged.take();
+            if let (Some((from, count)), Some(to)) = (dragged, hovered) {
+                // Unless a tab was closed in between.
+                if from != to && count == area.document_count() {
+                    area.move_document(from, to);
+                }
+            }
+        },
+        _ => (),
+    }
+    result
+}
+
+extern {
+    // Redeclared so a missing callback comes back as `None` instead of a null fn pointer.
+    #[link_name = "IupGetCallback"]
+    fn iup_get_callback(ih: *mut iup_sys::Ihandle, name: *const c_char)
+                                                                -> Option<iup_sys::Icallback>;
+}
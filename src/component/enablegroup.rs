@@ -0,0 +1,203 @@
+//! Enabling sets of widgets from named application states.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use Element;
+use Handle;
+use element::WeakHandle;
+
+/// A widget and the states it requires to be enabled.
+struct Binding {
+    widget: WeakHandle<Handle>,
+    requires: Vec<String>,
+}
+
+struct Inner {
+    active: BTreeSet<String>,
+    bindings: Vec<Binding>,
+}
+
+/// Ties the enabled state (ACTIVE) of widgets to named application states, such as
+/// "document_open" or "has_selection".
+///
+/// Each widget requires some states and is enabled only while all of them are active. A state
+/// prefixed by `!` is required to be inactive. Switching a state updates every widget requiring
+/// it at once, be it a menu item, a toolbar button or a field.
+///
+/// ```ignore
+/// let states = EnableGroup::new();
+/// states.add("document_open", save_item)
+///       .add("document_open", save_button)
+///       .add_requiring(&["document_open", "has_selection"], cut_item)
+///       .add("!document_open", welcome_label);
+/// states.set_state("document_open", true);
+/// ```
+///
+/// Destroyed widgets are forgotten. Cloning gives another handle to the same states.
+#[derive(Clone)]
+pub struct EnableGroup {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl EnableGroup {
+    /// Creates a group with no active state and no widget.
+    pub fn new() -> EnableGroup {
+        EnableGroup {
+            inner: Rc::new(RefCell::new(Inner { active: BTreeSet::new(), bindings: Vec::new() })),
+        }
+    }
+
+    /// Enables `widget` only while `state` is active, see `add_requiring`.
+    pub fn add<E: Into<Handle>>(&self, state: &str, widget: E) -> EnableGroup {
+        self.add_requiring(&[state], widget)
+    }
+
+    /// Enables `widget` only while all the `states` are active, or inactive for those prefixed
+    /// by `!`.
+    ///
+    /// The widget is updated right away. Adding a widget again replaces the states it requires.
+    pub fn add_requiring<E: Into<Handle>>(&self, states: &[&str], widget: E) -> EnableGroup {
+        let widget = widget.into();
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.bindings.retain(|binding| {
+                binding.widget.upgrade().map_or(false, |bound| bound != widget)
+            });
+            inner.bindings.push(Binding {
+                widget: widget.downgrade(),
+                requires: states.iter().map(|state| state.to_string()).collect(),
+            });
+        }
+        self.update(|binding| binding.widget.upgrade() == Some(widget));
+        self.clone()
+    }
+
+    /// Stops updating `widget`, leaving it in its current state.
+    pub fn remove<E: Into<Handle>>(&self, widget: E) {
+        let widget = widget.into();
+        self.inner.borrow_mut().bindings.retain(|binding| {
+            binding.widget.upgrade().map_or(false, |bound| bound != widget)
+        });
+    }
+
+    /// Activates or deactivates `state`, updating the widgets requiring it.
+    pub fn set_state(&self, state: &str, active: bool) {
+        let changed = {
+            let mut inner = self.inner.borrow_mut();
+            if active {
+                inner.active.insert(state.to_string())
+            } else {
+                inner.active.remove(state)
+            }
+        };
+        if changed {
+            self.update(|binding| depends_on(&binding.requires, state));
+        }
+    }
+
+    /// Checks whether `state` is active.
+    pub fn is_active(&self, state: &str) -> bool {
+        self.inner.borrow().active.contains(state)
+    }
+
+    /// Gets the active states, sorted by name.
+    pub fn active_states(&self) -> Vec<String> {
+        self.inner.borrow().active.iter().cloned().collect()
+    }
+
+    /// Updates every widget, e.g. after some were enabled or disabled by other means.
+    pub fn refresh(&self) {
+        self.update(|_| true);
+    }
+
+    /// Sets the ACTIVE attribute of the widgets whose binding matches `filter`.
+    fn update<F: Fn(&Binding) -> bool>(&self, filter: F) {
+        let mut inner = self.inner.borrow_mut();
+        inner.bindings.retain(|binding| binding.widget.is_alive());
+        let inner = &*inner;
+        for binding in inner.bindings.iter().filter(|binding| filter(binding)) {
+            let enabled = is_enabled(&binding.requires, &inner.active);
+            if let Some(mut widget) = binding.widget.upgrade() {
+                widget.set_attrib("ACTIVE", if enabled { "YES" } else { "NO" });
+            }
+        }
+    }
+}
+
+/// Checks whether a widget requiring `requires` is enabled while the `active` states are.
+fn is_enabled(requires: &[String], active: &BTreeSet<String>) -> bool {
+    requires.iter().all(|required| {
+        if required.starts_with('!') {
+            !active.contains(&required[1..])
+        } else {
+            active.contains(required)
+        }
+    })
+}
+
+/// Checks whether a widget requiring `requires` must be updated when `state` changes.
+fn depends_on(requires: &[String], state: &str) -> bool {
+    requires.iter().any(|required| required.trim_left_matches('!') == state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use super::{EnableGroup, is_enabled, depends_on};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn active(states: &[&str]) -> BTreeSet<String> {
+        strings(states).into_iter().collect()
+    }
+
+    #[test]
+    fn enabled_only_while_all_states_are_active() {
+        let requires = strings(&["document_open", "has_selection"]);
+        assert!(!is_enabled(&requires, &active(&[])));
+        assert!(!is_enabled(&requires, &active(&["document_open"])));
+        assert!(is_enabled(&requires, &active(&["document_open", "has_selection", "other"])));
+    }
+
+    #[test]
+    fn negated_states_must_be_inactive() {
+        let requires = strings(&["!document_open"]);
+        assert!(is_enabled(&requires, &active(&[])));
+        assert!(!is_enabled(&requires, &active(&["document_open"])));
+
+        let requires = strings(&["document_open", "!busy"]);
+        assert!(is_enabled(&requires, &active(&["document_open"])));
+        assert!(!is_enabled(&requires, &active(&["document_open", "busy"])));
+    }
+
+    #[test]
+    fn no_requirement_is_always_enabled() {
+        assert!(is_enabled(&[], &active(&[])));
+    }
+
+    #[test]
+    fn updates_only_the_widgets_depending_on_the_state() {
+        let requires = strings(&["document_open", "!busy"]);
+        assert!(depends_on(&requires, "document_open"));
+        assert!(depends_on(&requires, "busy"));
+        assert!(!depends_on(&requires, "has_selection"));
+        assert!(!depends_on(&requires, "!busy"));
+    }
+
+    #[test]
+    fn states_toggle_on_and_off() {
+        let states = EnableGroup::new();
+        states.set_state("document_open", true);
+        states.set_state("has_selection", true);
+        states.set_state("document_open", true);
+        assert!(states.is_active("document_open"));
+        assert_eq!(states.active_states(), strings(&["document_open", "has_selection"]));
+
+        states.clone().set_state("document_open", false);
+        assert!(!states.is_active("document_open"));
+        assert_eq!(states.active_states(), strings(&["has_selection"]));
+    }
+}
@@ -0,0 +1,384 @@
+//! A read-only view of log lines, colored by level.
+use std::io;
+use std::mem;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::mpsc::{channel, Sender, Receiver};
+
+use Element;
+use Handle;
+use element::{Widget, WeakHandle};
+use control::{Text, Toggle, ToggleAction, FormatTag};
+use layout::{VBox, Expand};
+use callback::ActionCb;
+use timer::{self, Timer};
+use i18n;
+
+/// How often, in milliseconds, the lines sent by the writers are shown.
+const WRITER_POLL_INTERVAL: u32 = 100;
+
+/// The default maximum number of lines kept by a `LogView`.
+pub const DEFAULT_MAX_LINES: usize = 10000;
+
+/// The severity of a log line.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+struct Inner {
+    root: VBox,
+    text: Text,
+    follow: Toggle,
+    /// The level and number of characters of each line shown.
+    lines: VecDeque<(LogLevel, usize)>,
+    /// The number of characters of the lines shown, each counted with a line break.
+    length: usize,
+    max_lines: usize,
+    colors: Vec<(LogLevel, (u8, u8, u8))>,
+    sender: Option<Sender<(LogLevel, String)>>,
+    receiver: Option<Receiver<(LogLevel, String)>>,
+}
+
+/// A composite showing log lines in a read-only multiline text, with a toggle to follow the
+/// last line as lines are added.
+///
+/// The text keeps a bounded number of lines (see `set_max_lines`), dropping the oldest ones.
+/// Lines are colored according to their level (see `set_level_color`), which needs the
+/// formatting of the text (Windows and GTK only).
+///
+/// Program output can be redirected into the view from any thread with `writer`:
+///
+/// ```ignore
+/// let log = LogView::new();
+/// let mut out = log.writer(LogLevel::Info);
+/// thread::spawn(move || {
+///     writeln!(out, "Downloading {}", url).unwrap();
+/// });
+/// Dialog::new(log.element()).show()
+/// ```
+///
+/// Cloning a view gives another handle to the same view.
+#[derive(Clone)]
+pub struct LogView {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl LogView {
+    /// Creates an empty view, following the last line.
+    ///
+    /// The toggle title is translated by the `i18n` catalog with the "IUPRUST_LOGVIEW_FOLLOW" key.
+    pub fn new() -> LogView {
+        let text = Text::new_formatted().set_attrib("READONLY", "YES")
+                                        .set_expand(Expand::Yes)
+                                        .set_attrib("APPENDNEWLINE", "YES");
+        let follow = Toggle::with_title(i18n::tr("IUPRUST_LOGVIEW_FOLLOW", "Follow Output"))
+                            .set_attrib("VALUE", "ON");
+        let root = VBox::new(elements![text, follow]);
+        let view = LogView {
+            inner: Rc::new(RefCell::new(Inner {
+                root: root,
+                text: text,
+                follow: follow,
+                lines: VecDeque::new(),
+                length: 0,
+                max_lines: DEFAULT_MAX_LINES,
+                colors: vec![
+                    (LogLevel::Error, (200, 0, 0)),
+                    (LogLevel::Warn, (180, 110, 0)),
+                    (LogLevel::Debug, (110, 110, 110)),
+                    (LogLevel::Trace, (150, 150, 150)),
+                ],
+                sender: None,
+                receiver: None,
+            })),
+        };
+        let mut follow = follow;
+        let this = view.clone();
+        follow.set_action(move |(_, following): (Toggle, bool)| {
+            if following {
+                let mut text = this.inner.borrow().text;
+                text.scroll_to_end();
+            }
+        });
+        view
+    }
+
+    /// Adds a line at the end of the log, scrolling to it when following.
+    ///
+    /// Line breaks in `line` are kept, the pieces are counted as separate lines.
+    pub fn append_line<S: Into<String>>(&self, level: LogLevel, line: S) {
+        let line = line.into();
+        let mut inner = self.inner.borrow_mut();
+        for piece in line.split('\n') {
+            inner.append(level, piece.trim_right_matches('\r'));
+        }
+        inner.trim();
+        if inner.is_following() {
+            inner.text.scroll_to_end();
+        }
+    }
+
+    /// Removes all the lines.
+    pub fn clear(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.lines.clear();
+        inner.length = 0;
+        inner.text.set_attrib("VALUE", "");
+    }
+
+    /// Returns the number of lines shown.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().lines.len()
+    }
+
+    /// Checks whether there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().lines.is_empty()
+    }
+
+    /// Sets the maximum number of lines kept, `DEFAULT_MAX_LINES` by default.
+    pub fn set_max_lines(&self, max_lines: usize) -> LogView {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.max_lines = max_lines.max(1);
+            inner.trim();
+        }
+        self.clone()
+    }
+
+    /// Sets whether the view scrolls to each new line, the same as the follow toggle.
+    pub fn set_follow(&self, follow: bool) -> LogView {
+        let mut inner = self.inner.borrow_mut();
+        inner.follow.set_attrib("VALUE", if follow { "ON" } else { "OFF" });
+        if follow {
+            inner.text.scroll_to_end();
+        }
+        self.clone()
+    }
+
+    /// Checks whether the view scrolls to each new line.
+    pub fn is_following(&self) -> bool {
+        self.inner.borrow().is_following()
+    }
+
+    /// Sets the color of the lines of `level`, for the lines added afterwards.
+    ///
+    /// By default errors are red, warnings are orange, debug and trace lines are grey and info
+    /// lines have the color of the text.
+    pub fn set_level_color(&self, level: LogLevel, rgb: (u8, u8, u8)) -> LogView {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.colors.retain(|&(l, _)| l != level);
+            inner.colors.push((level, rgb));
+        }
+        self.clone()
+    }
+
+    /// Creates a writer adding the text written to it as lines of `level`, from any thread.
+    ///
+    /// Complete lines are shown shortly after they are written, a last line without a line
+    /// break is shown when the writer is flushed or dropped. Writers stop working once the
+    /// view is destroyed.
+    pub fn writer(&self, level: LogLevel) -> LogWriter {
+        let mut inner = self.inner.borrow_mut();
+        if inner.sender.is_none() {
+            let (sender, receiver) = channel();
+            inner.sender = Some(sender);
+            inner.receiver = Some(receiver);
+            self.start_polling(inner.text.downgrade());
+        }
+        LogWriter {
+            level: level,
+            sender: inner.sender.clone().unwrap(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Returns the element containing the whole view.
+    pub fn element(&self) -> VBox {
+        self.inner.borrow().root
+    }
+
+    /// Returns the multiline text showing the lines.
+    pub fn text(&self) -> Text {
+        self.inner.borrow().text
+    }
+
+    /// Shows the lines sent by the writers, until the view is destroyed.
+    fn start_polling(&self, text: WeakHandle<Text>) {
+        let view = self.clone();
+        let mut timer = timer::owned_timer().set_time(WRITER_POLL_INTERVAL);
+        timer.set_action_cb(move |(mut timer,): (Timer,)| {
+            if !text.is_alive() {
+                timer.stop();
+                timer.destroy_later();
+                return;
+            }
+            let received: Vec<(LogLevel, String)> = match view.inner.borrow().receiver {
+                Some(ref receiver) => receiver.try_iter().collect(),
+                None => Vec::new(),
+            };
+            for (level, line) in received {
+                view.append_line(level, line);
+            }
+        }).run();
+    }
+}
+
+impl From<LogView> for Handle {
+    fn from(view: LogView) -> Handle {
+        Handle::from(view.element())
+    }
+}
+
+impl Inner {
+    fn is_following(&self) -> bool {
+        self.follow.attrib("VALUE").map_or(false, |value| value == "ON")
+    }
+
+    fn append(&mut self, level: LogLevel, line: &str) {
+        let len = line.chars().count();
+        // The line break added before the line is counted with the previous line.
+        let start = self.length;
+        self.text.set_attrib("APPEND", line);
+        self.lines.push_back((level, len));
+        self.length += len + 1;
+        let color = self.colors.iter().find(|&&(l, _)| l == level).map(|&(_, rgb)| rgb);
+        if let Some(rgb) = color {
+            if len > 0 {
+                self.text.format_range(FormatTag::new().fg_color(rgb), start, start + len);
+            }
+        }
+    }
+
+    /// Removes the oldest lines beyond `max_lines`.
+    fn trim(&mut self) {
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        if excess == 0 {
+            return;
+        }
+        let removed: usize = self.lines.drain(..excess).map(|(_, len)| len + 1).sum();
+        self.length -= removed;
+        self.text.set_attrib("SELECTIONPOS", format!("0:{}", removed));
+        self.text.set_attrib("SELECTEDTEXT", "");
+    }
+}
+
+/// Writes lines into a `LogView` from any thread, see `LogView::writer`.
+pub struct LogWriter {
+    level: LogLevel,
+    sender: Sender<(LogLevel, String)>,
+    /// The bytes of the last line, not terminated yet.
+    buffer: Vec<u8>,
+}
+
+impl LogWriter {
+    fn send(&self, bytes: &[u8]) -> io::Result<()> {
+        let line = String::from_utf8_lossy(bytes).into_owned();
+        self.sender.send((self.level, line)).map_err(|_| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "the log view was destroyed")
+        })
+    }
+}
+
+impl Clone for LogWriter {
+    /// Clones the writer, without the unterminated line written so far.
+    fn clone(&self) -> LogWriter {
+        LogWriter { level: self.level, sender: self.sender.clone(), buffer: Vec::new() }
+    }
+}
+
+impl io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let rest = self.buffer.split_off(end + 1);
+            let line = mem::replace(&mut self.buffer, rest);
+            try!(self.send(&line[..end]));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let line = mem::replace(&mut self.buffer, Vec::new());
+        self.send(&line)
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        let _ = io::Write::flush(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::mpsc::{channel, Receiver};
+    use super::{LogLevel, LogWriter};
+
+    fn writer() -> (LogWriter, Receiver<(LogLevel, String)>) {
+        let (sender, receiver) = channel();
+        (LogWriter { level: LogLevel::Warn, sender: sender, buffer: Vec::new() }, receiver)
+    }
+
+    fn received(receiver: &Receiver<(LogLevel, String)>) -> Vec<String> {
+        receiver.try_iter().map(|(_, line)| line).collect()
+    }
+
+    #[test]
+    fn splits_complete_lines() {
+        let (mut out, receiver) = writer();
+        out.write_all(b"first\nsecond\n\nlast").unwrap();
+        assert_eq!(received(&receiver), vec!["first", "second", ""]);
+    }
+
+    #[test]
+    fn buffers_the_partial_line_until_flushed() {
+        let (mut out, receiver) = writer();
+        out.write_all(b"hel").unwrap();
+        out.write_all(b"lo wor").unwrap();
+        assert!(received(&receiver).is_empty());
+        out.write_all(b"ld\npartial").unwrap();
+        assert_eq!(received(&receiver), vec!["hello world"]);
+        out.flush().unwrap();
+        assert_eq!(received(&receiver), vec!["partial"]);
+        out.flush().unwrap();
+        assert!(received(&receiver).is_empty());
+    }
+
+    #[test]
+    fn sends_the_partial_line_when_dropped() {
+        let (mut out, receiver) = writer();
+        out.write_all(b"done\nunterminated").unwrap();
+        drop(out);
+        let lines: Vec<_> = receiver.iter().collect();
+        assert_eq!(lines, vec![(LogLevel::Warn, "done".to_string()),
+                               (LogLevel::Warn, "unterminated".to_string())]);
+    }
+
+    #[test]
+    fn clones_without_the_partial_line() {
+        let (mut out, receiver) = writer();
+        out.write_all(b"mine").unwrap();
+        let mut copy = out.clone();
+        copy.write_all(b"theirs\n").unwrap();
+        assert_eq!(received(&receiver), vec!["theirs"]);
+    }
+
+    #[test]
+    fn fails_once_the_view_is_gone() {
+        let (mut out, receiver) = writer();
+        drop(receiver);
+        assert!(out.write_all(b"lost\n").is_err());
+    }
+}
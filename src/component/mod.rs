@@ -0,0 +1,33 @@
+//! High-level components composed of other elements.
+//!
+//! Components are not IUP elements by themselves but are built entirely from the elements
+//! available in this binding. They can be placed in a layout like any element since they
+//! convert into a `Handle`, and expose their root element through an `element` method.
+
+pub mod wizard;
+pub mod undo;
+pub mod titlebar;
+pub mod repeater;
+pub mod logview;
+pub mod console;
+pub mod documentarea;
+pub mod recentfiles;
+pub mod toast;
+pub mod enablegroup;
+pub mod buttonbox;
+#[cfg(feature = "controls")]
+pub mod propertygrid;
+
+pub use self::wizard::{Wizard, WizardData};
+pub use self::undo::UndoStack;
+pub use self::titlebar::TitleBar;
+pub use self::repeater::Repeater;
+pub use self::logview::{LogView, LogLevel, LogWriter};
+pub use self::console::Console;
+pub use self::documentarea::DocumentArea;
+pub use self::recentfiles::RecentFiles;
+pub use self::toast::{Toasts, ToastLevel, Corner};
+pub use self::enablegroup::EnableGroup;
+pub use self::buttonbox::{ButtonBox, StandardButton};
+#[cfg(feature = "controls")]
+pub use self::propertygrid::{PropertyGrid, PropertySource, Property, PropertyValue};
@@ -0,0 +1,503 @@
+//! An editor of named and typed values, e.g. the properties of the object selected in a designer.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::time::Duration;
+#[cfg(feature = "serde_json")]
+use serde_json;
+
+use Element;
+use Handle;
+use element::Widget;
+use layout::Expand;
+use control::{Matrix, MatrixModel, CellType, MatrixClickCb};
+use dialog::{DialogElement, DialogPos, FileDlg, prompt_color};
+use callback::CallbackReturn;
+use callback::button::MouseState;
+use timer;
+use i18n;
+
+/// The value of a property, whose variant chooses how it is edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// A check box.
+    Bool(bool),
+    /// A text which must be a number.
+    Number(f64),
+    /// A free text.
+    Text(String),
+    /// The selected choice and the choices of a drop down.
+    Choice(String, Vec<String>),
+    /// A color as "R G B", also picked from a color dialog with a double click.
+    Color(u8, u8, u8),
+    /// A file path, also picked from a file dialog with a double click.
+    Path(PathBuf),
+}
+
+impl PropertyValue {
+    fn cell_type(&self) -> CellType {
+        match *self {
+            PropertyValue::Bool(_) => CellType::Toggle,
+            PropertyValue::Choice(_, ref choices) => CellType::Dropdown(choices.clone()),
+            _ => CellType::Text,
+        }
+    }
+
+    /// Gets the text of the value in a matrix cell.
+    fn to_cell(&self) -> String {
+        match *self {
+            PropertyValue::Bool(value) => if value { "1" } else { "0" }.to_string(),
+            PropertyValue::Number(value) => value.to_string(),
+            PropertyValue::Text(ref value) => value.clone(),
+            PropertyValue::Choice(ref value, _) => value.clone(),
+            PropertyValue::Color(r, g, b) => format!("{} {} {}", r, g, b),
+            PropertyValue::Path(ref path) => path.to_string_lossy().into_owned(),
+        }
+    }
+
+    /// Parses the text of a matrix cell into a value of the same kind, `None` if it's invalid.
+    fn from_cell(&self, cell: &str) -> Option<PropertyValue> {
+        match *self {
+            PropertyValue::Bool(_) => Some(PropertyValue::Bool(cell == "1")),
+            PropertyValue::Number(_) => cell.trim().parse().ok().map(PropertyValue::Number),
+            PropertyValue::Text(_) => Some(PropertyValue::Text(cell.to_string())),
+            PropertyValue::Choice(_, ref choices) => {
+                if choices.iter().any(|choice| choice == cell) {
+                    Some(PropertyValue::Choice(cell.to_string(), choices.clone()))
+                } else {
+                    None
+                }
+            },
+            PropertyValue::Color(..) => parse_color(cell).map(|(r, g, b)| {
+                PropertyValue::Color(r, g, b)
+            }),
+            PropertyValue::Path(_) => Some(PropertyValue::Path(PathBuf::from(cell))),
+        }
+    }
+}
+
+/// A named value shown by a `PropertyGrid`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub value: PropertyValue,
+    /// Whether the value is only shown, not edited.
+    pub read_only: bool,
+}
+
+impl Property {
+    /// Creates an editable property.
+    pub fn new<S: Into<String>>(name: S, value: PropertyValue) -> Property {
+        Property { name: name.into(), value: value, read_only: false }
+    }
+
+    /// Makes the property read-only.
+    pub fn read_only(mut self) -> Property {
+        self.read_only = true;
+        self
+    }
+}
+
+/// Supplies and stores the properties edited by a `PropertyGrid`.
+pub trait PropertySource {
+    /// Returns the properties, in the order they are shown.
+    fn properties(&self) -> Vec<Property>;
+
+    /// Stores the value the user entered for the property `name`.
+    ///
+    /// Returns whether the value was accepted. The properties are read again afterwards, so a
+    /// change may update other properties or their choices.
+    fn set_property(&mut self, name: &str, value: PropertyValue) -> bool;
+}
+
+impl<P: PropertySource> PropertySource for Rc<RefCell<P>> {
+    fn properties(&self) -> Vec<Property> {
+        self.borrow().properties()
+    }
+    fn set_property(&mut self, name: &str, value: PropertyValue) -> bool {
+        self.borrow_mut().set_property(name, value)
+    }
+}
+
+/// The members of a JSON object are its properties, available with the `serde_json` feature.
+///
+/// Booleans, numbers and strings are edited as such, strings of the form "#rrggbb" as colors.
+/// Other members (null, arrays and objects) are shown as read-only JSON text. Other values
+/// than objects have no properties.
+#[cfg(feature = "serde_json")]
+impl PropertySource for serde_json::Value {
+    fn properties(&self) -> Vec<Property> {
+        let object = match *self {
+            serde_json::Value::Object(ref object) => object,
+            _ => return Vec::new(),
+        };
+        object.iter().map(|(name, value)| {
+            let name = &name[..];
+            match *value {
+                serde_json::Value::Bool(value) => Property::new(name, PropertyValue::Bool(value)),
+                serde_json::Value::Number(ref value) => {
+                    Property::new(name, PropertyValue::Number(value.as_f64().unwrap_or(0.0)))
+                },
+                serde_json::Value::String(ref value) => match parse_hex_color(value) {
+                    Some((r, g, b)) => Property::new(name, PropertyValue::Color(r, g, b)),
+                    None => Property::new(name, PropertyValue::Text(value.clone())),
+                },
+                ref other => {
+                    Property::new(name, PropertyValue::Text(other.to_string())).read_only()
+                },
+            }
+        }).collect()
+    }
+
+    fn set_property(&mut self, name: &str, value: PropertyValue) -> bool {
+        let member = match *self {
+            serde_json::Value::Object(ref mut object) => match object.get_mut(name) {
+                Some(member) => member,
+                None => return false,
+            },
+            _ => return false,
+        };
+        let json = match value {
+            PropertyValue::Bool(value) => serde_json::Value::Bool(value),
+            PropertyValue::Number(value) => {
+                // Integers stay integers, e.g. for counts read by other programs.
+                let integer = member.is_i64() || member.is_u64();
+                if integer && value.fract() == 0.0 && value.abs() < 9007199254740992.0 {
+                    serde_json::Value::from(value as i64)
+                } else {
+                    match serde_json::Number::from_f64(value) {
+                        Some(number) => serde_json::Value::Number(number),
+                        None => return false,
+                    }
+                }
+            },
+            PropertyValue::Text(value) | PropertyValue::Choice(value, _) => {
+                serde_json::Value::String(value)
+            },
+            PropertyValue::Color(r, g, b) => {
+                serde_json::Value::String(format!("#{:02x}{:02x}{:02x}", r, g, b))
+            },
+            PropertyValue::Path(path) => {
+                serde_json::Value::String(path.to_string_lossy().into_owned())
+            },
+        };
+        *member = json;
+        true
+    }
+}
+
+struct Inner {
+    matrix: Matrix,
+    source: Box<PropertySource>,
+    /// The properties shown, as last read from the source.
+    properties: Vec<Property>,
+    handlers: Vec<Rc<RefCell<FnMut(&str, &PropertyValue)>>>,
+}
+
+/// A two column grid of property names and values, edited according to their type: check boxes
+/// for booleans, drop downs for choices, and texts for numbers, strings, colors and paths.
+/// Colors and paths can also be picked from a dialog with a double click.
+///
+/// The properties come from a `PropertySource`, such as a `serde_json::Value` object:
+///
+/// ```ignore
+/// let grid = PropertyGrid::new(settings.clone()).on_change(|name, value| {
+///     println!("{} changed to {:?}", name, value);
+/// });
+/// Dialog::new(grid.element()).show()
+/// ```
+///
+/// Requires the `controls` feature, see `Matrix`. Cloning a grid gives another handle to the
+/// same grid.
+#[derive(Clone)]
+pub struct PropertyGrid {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl PropertyGrid {
+    /// Creates a grid showing the properties of `source`.
+    ///
+    /// The title of the value column is translated by the `i18n` catalog with the
+    /// "IUPRUST_PROPERTYGRID_VALUE" key.
+    pub fn new<P: PropertySource + 'static>(source: P) -> PropertyGrid {
+        let matrix = Matrix::new().set_expand(Expand::Yes)
+                                  .set_attrib("RESIZEMATRIX", "YES")
+                                  .set_attrib("MARKMODE", "CELL")
+                                  .set_attrib("WIDTH0", "80")
+                                  .set_attrib("WIDTH1", "120");
+        let grid = PropertyGrid {
+            inner: Rc::new(RefCell::new(Inner {
+                matrix: matrix,
+                properties: source.properties(),
+                source: Box::new(source),
+                handlers: Vec::new(),
+            })),
+        };
+
+        let mut matrix = matrix;
+        matrix.set_model(GridModel(grid.clone()));
+        let this = grid.clone();
+        matrix.set_click_cb(move |(_, lin, col, state): (Matrix, i32, i32, MouseState)| {
+            if lin > 0 && col == 1 && state.is_double() && this.pick(lin as usize - 1) {
+                CallbackReturn::Ignore
+            } else {
+                CallbackReturn::Default
+            }
+        });
+        grid.show_colors();
+        grid
+    }
+
+    /// Adds a closure called with the name and new value of each property the user changes.
+    pub fn on_change<F: FnMut(&str, &PropertyValue) + 'static>(self, handler: F) -> PropertyGrid {
+        self.inner.borrow_mut().handlers.push(Rc::new(RefCell::new(handler)));
+        self
+    }
+
+    /// Reads the properties from the source again, after it was changed by the application.
+    pub fn refresh(&self) -> PropertyGrid {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.properties = inner.source.properties();
+        }
+        self.show_colors();
+        let mut matrix = self.matrix();
+        matrix.refresh_model();
+        self.clone()
+    }
+
+    /// Gets the properties shown.
+    pub fn properties(&self) -> Vec<Property> {
+        self.inner.borrow().properties.clone()
+    }
+
+    /// Gets the value of the property `name`.
+    pub fn value(&self, name: &str) -> Option<PropertyValue> {
+        let inner = self.inner.borrow();
+        inner.properties.iter().find(|property| property.name == name)
+                               .map(|property| property.value.clone())
+    }
+
+    /// Returns the matrix showing the properties.
+    pub fn matrix(&self) -> Matrix {
+        self.inner.borrow().matrix
+    }
+
+    /// Returns the element containing the whole grid.
+    pub fn element(&self) -> Matrix {
+        self.matrix()
+    }
+
+    /// Stores a value entered for the property at `index`, returning whether it was accepted.
+    ///
+    /// Called from the matrix callbacks, so the handlers and the redraw are left for later.
+    fn edit(&self, index: usize, value: PropertyValue) -> bool {
+        let name = {
+            let mut inner = self.inner.borrow_mut();
+            let name = match inner.properties.get(index) {
+                Some(property) if !property.read_only => property.name.clone(),
+                _ => return false,
+            };
+            if !inner.source.set_property(&name, value.clone()) {
+                return false;
+            }
+            inner.properties = inner.source.properties();
+            name
+        };
+
+        let grid = self.clone();
+        timer::delay(Duration::from_millis(0), move || {
+            grid.refresh();
+            let handlers = grid.inner.borrow().handlers.clone();
+            for handler in handlers {
+                if let Ok(mut handler) = handler.try_borrow_mut() {
+                    (&mut *handler)(&name, &value);
+                }
+            }
+        });
+        true
+    }
+
+    /// Edits the color or path at `index` with a dialog, returning whether it had one.
+    fn pick(&self, index: usize) -> bool {
+        let property = match self.inner.borrow().properties.get(index) {
+            Some(property) if !property.read_only => property.clone(),
+            _ => return false,
+        };
+        let picked = match property.value {
+            PropertyValue::Color(r, g, b) => {
+                prompt_color((r, g, b)).map(|(r, g, b)| PropertyValue::Color(r, g, b))
+            },
+            PropertyValue::Path(ref path) => {
+                let mut dialog = FileDlg::new_open().set_attrib("FILE", path.to_string_lossy());
+                let _ = dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent);
+                let cancelled = dialog.attrib("STATUS").map_or(true, |status| status == "-1");
+                let picked = if cancelled { None } else { dialog.path() };
+                dialog.destroy();
+                picked.map(PropertyValue::Path)
+            },
+            _ => return false,
+        };
+        if let Some(value) = picked {
+            self.edit(index, value);
+        }
+        true
+    }
+
+    /// Paints the background of the color values with their color.
+    fn show_colors(&self) {
+        let inner = self.inner.borrow();
+        let mut matrix = inner.matrix;
+        for (i, property) in inner.properties.iter().enumerate() {
+            let color = match property.value {
+                PropertyValue::Color(r, g, b) => format!("{} {} {}", r, g, b),
+                _ => String::new(),
+            };
+            let name = format!("BGCOLOR{}:1", i + 1);
+            if color.is_empty() {
+                matrix.clear_attrib(name);
+            } else {
+                matrix.set_attrib(name, color);
+            }
+        }
+    }
+}
+
+impl From<PropertyGrid> for Handle {
+    fn from(grid: PropertyGrid) -> Handle {
+        Handle::from(grid.element())
+    }
+}
+
+/// Shows the properties of a grid in its matrix, the names as line titles.
+struct GridModel(PropertyGrid);
+
+impl MatrixModel for GridModel {
+    fn rows(&self) -> usize {
+        self.0.inner.borrow().properties.len()
+    }
+    fn cols(&self) -> usize {
+        1
+    }
+    fn value(&self, row: usize, _col: usize) -> String {
+        self.0.inner.borrow().properties.get(row).map_or(String::new(), |property| {
+            property.value.to_cell()
+        })
+    }
+    fn row_title(&self, row: usize) -> String {
+        self.0.inner.borrow().properties.get(row).map_or(String::new(), |property| {
+            property.name.clone()
+        })
+    }
+    fn col_title(&self, _col: usize) -> String {
+        i18n::tr("IUPRUST_PROPERTYGRID_VALUE", "Value")
+    }
+    fn cell_type(&self, row: usize, _col: usize) -> CellType {
+        self.0.inner.borrow().properties.get(row).map_or(CellType::Text, |property| {
+            property.value.cell_type()
+        })
+    }
+    fn is_editable(&self, row: usize, _col: usize) -> bool {
+        self.0.inner.borrow().properties.get(row).map_or(false, |property| !property.read_only)
+    }
+    fn set_value(&mut self, row: usize, _col: usize, cell: &str) -> bool {
+        let value = self.0.inner.borrow().properties.get(row).and_then(|property| {
+            property.value.from_cell(cell)
+        });
+        match value {
+            Some(value) => self.0.edit(row, value),
+            None => false,
+        }
+    }
+}
+
+/// Parses a color as "R G B" or "#rrggbb".
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let color = color.trim();
+    if color.starts_with('#') {
+        return parse_hex_color(color);
+    }
+    let mut parts = color.split_whitespace().map(|part| part.parse::<u8>().ok());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Some(r)), Some(Some(g)), Some(Some(b)), None) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+/// Parses a color as "#rrggbb".
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    if color.len() != 7 || !color.starts_with('#') || !color.is_char_boundary(1) {
+        return None;
+    }
+    let channel = |i: usize| color.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match (channel(1), channel(3), channel(5)) {
+        (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::{PropertyValue, parse_color};
+
+    #[test]
+    fn parses_rgb_colors() {
+        assert_eq!(parse_color("255 128 0"), Some((255, 128, 0)));
+        assert_eq!(parse_color("  1   2 3 "), Some((1, 2, 3)));
+        assert_eq!(parse_color("1 2"), None);
+        assert_eq!(parse_color("1 2 3 4"), None);
+        assert_eq!(parse_color("1 2 256"), None);
+        assert_eq!(parse_color("red"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#ff8000"), Some((255, 128, 0)));
+        assert_eq!(parse_color(" #FF8000 "), Some((255, 128, 0)));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("#ff80000"), None);
+        assert_eq!(parse_color("#gg8000"), None);
+        assert_eq!(parse_color("#ffé00"), None);
+    }
+
+    #[test]
+    fn cells_keep_the_kind_of_value() {
+        assert_eq!(PropertyValue::Bool(false).from_cell("1"), Some(PropertyValue::Bool(true)));
+        assert_eq!(PropertyValue::Bool(true).from_cell("0"), Some(PropertyValue::Bool(false)));
+        assert_eq!(PropertyValue::Number(0.0).from_cell(" 2.5 "),
+                   Some(PropertyValue::Number(2.5)));
+        assert_eq!(PropertyValue::Number(0.0).from_cell("two"), None);
+        assert_eq!(PropertyValue::Text("a".into()).from_cell(" b "),
+                   Some(PropertyValue::Text(" b ".into())));
+        assert_eq!(PropertyValue::Color(0, 0, 0).from_cell("#010203"),
+                   Some(PropertyValue::Color(1, 2, 3)));
+        assert_eq!(PropertyValue::Color(0, 0, 0).from_cell("1 2"), None);
+        assert_eq!(PropertyValue::Path(PathBuf::new()).from_cell("/tmp/a"),
+                   Some(PropertyValue::Path(PathBuf::from("/tmp/a"))));
+    }
+
+    #[test]
+    fn choices_must_be_listed() {
+        let choices = vec!["Left".to_string(), "Right".to_string()];
+        let value = PropertyValue::Choice("Left".into(), choices.clone());
+        assert_eq!(value.from_cell("Right"), Some(PropertyValue::Choice("Right".into(), choices)));
+        assert_eq!(value.from_cell("Center"), None);
+    }
+
+    #[test]
+    fn cells_round_trip() {
+        let values = vec![
+            PropertyValue::Bool(true),
+            PropertyValue::Number(-1.25),
+            PropertyValue::Text("hello".into()),
+            PropertyValue::Choice("b".into(), vec!["a".into(), "b".into()]),
+            PropertyValue::Color(10, 20, 30),
+            PropertyValue::Path(PathBuf::from("/tmp/file.txt")),
+        ];
+        for value in values {
+            assert_eq!(value.from_cell(&value.to_cell()), Some(value.clone()));
+        }
+    }
+}
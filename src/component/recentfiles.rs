@@ -0,0 +1,190 @@
+//! A submenu of the files opened recently, remembered by a `Config`.
+use iup_sys;
+use libc::{c_char, c_int};
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::ffi::CString;
+
+use Element;
+use Handle;
+use element::Container;
+use config::Config;
+use menu::{Menu, Submenu};
+
+/// The group and key prefix of the variables where IupConfig keeps the recent files.
+const RECENT_GROUP: &'static str = "Recent";
+const RECENT_KEY: &'static str = "File";
+
+struct Inner {
+    config: Config,
+    menu: Menu,
+    submenu: Submenu,
+    max_files: usize,
+    handler: Option<Rc<RefCell<FnMut(PathBuf)>>>,
+}
+
+/// A submenu listing the files opened recently, the most recent first.
+///
+/// The list is kept in the variables of a `Config`, thus it's persisted whenever the config is
+/// saved. Files which don't exist anymore are removed when the list is loaded and by `prune`.
+///
+/// ```ignore
+/// let recent = RecentFiles::new(*config, "&Recent Files", 10).on_select(|path| open(path));
+/// let file_menu = Menu::new(elements![open_item, recent.submenu(), exit_item]);
+/// // After opening or saving a file:
+/// recent.add(&path);
+/// ```
+///
+/// The config must outlive the submenu. Cloning gives another handle to the same list.
+#[derive(Clone)]
+pub struct RecentFiles {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl RecentFiles {
+    /// Creates a submenu named `title` of at most `max_files` files, from the list in `config`.
+    pub fn new<S: Into<String>>(config: Config, title: S, max_files: usize) -> RecentFiles {
+        let menu = Menu::new(elements![]);
+        let recent = RecentFiles {
+            inner: Rc::new(RefCell::new(Inner {
+                config: config,
+                menu: menu,
+                submenu: Submenu::new(title, menu),
+                max_files: max_files.max(1),
+                handler: None,
+            })),
+        };
+        RECENT.with(|recent_lists| {
+            let mut recent_lists = recent_lists.borrow_mut();
+            recent_lists.retain(|&(_, ref inner)| inner.upgrade().is_some());
+            recent_lists.push((config.raw(), Rc::downgrade(&recent.inner)));
+        });
+        let existing = existing(recent.files());
+        recent.store(&existing);
+        let recent_cb = on_recent as extern fn(*mut iup_sys::Ihandle) -> c_int;
+        unsafe {
+            IupConfigRecentInit(config.raw(), menu.raw(), mem::transmute(recent_cb),
+                                max_files.max(1) as c_int);
+        }
+        recent
+    }
+
+    /// Sets the closure called with the path of the file selected in the submenu.
+    pub fn on_select<F: FnMut(PathBuf) + 'static>(self, handler: F) -> RecentFiles {
+        self.inner.borrow_mut().handler = Some(Rc::new(RefCell::new(handler)));
+        self
+    }
+
+    /// Moves `path` to the top of the list, adding it if needed.
+    pub fn add<P: AsRef<Path>>(&self, path: P) {
+        let config = self.inner.borrow().config;
+        let cpath = CString::new(path.as_ref().to_string_lossy().into_owned()).unwrap();
+        unsafe { IupConfigRecentUpdate(config.raw(), cpath.as_ptr()) };
+    }
+
+    /// Gets the files of the list, the most recent first.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let inner = self.inner.borrow();
+        (1..inner.max_files + 1).filter_map(|i| {
+            inner.config.variable(RECENT_GROUP, format!("{}{}", RECENT_KEY, i))
+        }).map(PathBuf::from).collect()
+    }
+
+    /// Removes the files which don't exist anymore, returning how many were removed.
+    pub fn prune(&self) -> usize {
+        let files = self.files();
+        self.set_files(&files);
+        files.len() - self.files().len()
+    }
+
+    /// Removes all the files from the list.
+    pub fn clear(&self) {
+        self.set_files(&[]);
+    }
+
+    /// Returns the submenu listing the files.
+    pub fn submenu(&self) -> Submenu {
+        self.inner.borrow().submenu
+    }
+
+    /// Returns the element containing the whole list, the same as `submenu`.
+    pub fn element(&self) -> Submenu {
+        self.submenu()
+    }
+
+    /// Replaces the list by the `files` which exist and rebuilds the items of the submenu.
+    fn set_files(&self, files: &[PathBuf]) {
+        let files = existing(files.to_vec());
+        // IupConfigRecentUpdate moves its file on top of the others and rebuilds the items.
+        match files.split_first() {
+            Some((first, others)) => {
+                self.store(others);
+                self.add(first);
+            },
+            None => {
+                self.store(&[]);
+                for item in self.inner.borrow().menu.children() {
+                    item.destroy_later();
+                }
+            },
+        }
+    }
+
+    /// Writes `files` into the variables of the config, without updating the submenu.
+    fn store(&self, files: &[PathBuf]) {
+        let inner = self.inner.borrow();
+        let mut config = inner.config;
+        for i in 0..inner.max_files {
+            let key = format!("{}{}", RECENT_KEY, i + 1);
+            match files.get(i) {
+                Some(path) => config.set_variable(RECENT_GROUP, key, path.to_string_lossy()),
+                None => config.remove_variable(RECENT_GROUP, key),
+            };
+        }
+    }
+}
+
+/// Keeps the `files` which exist.
+fn existing(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    files.into_iter().filter(|path| path.exists()).collect()
+}
+
+impl From<RecentFiles> for Handle {
+    fn from(recent: RecentFiles) -> Handle {
+        Handle::from(recent.element())
+    }
+}
+
+// The lists of recent files by the config keeping them.
+thread_local!(static RECENT: RefCell<Vec<(*mut iup_sys::Ihandle, Weak<RefCell<Inner>>)>> =
+              RefCell::new(Vec::new()));
+
+/// Called with the config when a file is selected, the file is in its RECENTFILENAME.
+extern fn on_recent(ih: *mut iup_sys::Ihandle) -> c_int {
+    let path = match unsafe { iup_sys::IupGetAttribute(ih, cstr!("RECENTFILENAME")) } {
+        cpath if cpath.is_null() => return iup_sys::IUP_DEFAULT,
+        cpath => PathBuf::from(string_from_cstr!(cpath)),
+    };
+    let handler = RECENT.with(|recent_lists| {
+        recent_lists.borrow().iter().filter(|&&(config, _)| config == ih)
+                    .filter_map(|&(_, ref inner)| inner.upgrade())
+                    .last()
+                    .and_then(|inner| inner.borrow().handler.clone())
+    });
+    // Called without borrowing, so the handler is free to use the list.
+    if let Some(handler) = handler {
+        if let Ok(mut handler) = handler.try_borrow_mut() {
+            (&mut *handler)(path);
+        }
+    }
+    iup_sys::IUP_DEFAULT
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupConfigRecentInit(ih: *mut iup_sys::Ihandle, menu: *mut iup_sys::Ihandle,
+                           recent_cb: iup_sys::Icallback, max_recent: c_int);
+    fn IupConfigRecentUpdate(ih: *mut iup_sys::Ihandle, filename: *const c_char);
+}
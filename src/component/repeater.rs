@@ -0,0 +1,147 @@
+//! A scrollable list of rows created from a collection.
+use iup_sys;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ptr;
+
+use Element;
+use Handle;
+use element::{Node, Container, Widget};
+use layout::VBox;
+
+/// A vertical list with a row of elements per item of a collection, inside a scroll box.
+///
+/// Each row is created by the template closure and identified by the key of its item. When the
+/// collection changes with `set_items`, only the rows of new items are created, the rows of
+/// removed items are destroyed and the others are reordered and updated, so their state (such
+/// as focus, selections or callbacks) is kept.
+///
+/// ```ignore
+/// let mut contacts = Repeater::new(|contact: &Contact| contact.id, |contact| {
+///     HBox::new(elements![
+///         Label::with_title(&contact.name[..]).set_attrib("NAME", "name"),
+///         Button::with_title("Call"),
+///     ]).into()
+/// }).with_update(|row, contact| {
+///     row.dialog_child("name").unwrap().set_attrib("TITLE", &contact.name[..]);
+/// });
+/// contacts.set_items(&contact_list);
+/// let dialog = Dialog::new(contacts.element());
+/// ```
+pub struct Repeater<K, T> {
+    root: Handle,
+    rows_box: VBox,
+    key: Box<Fn(&T) -> K>,
+    template: Box<Fn(&T) -> Handle>,
+    update: Option<Box<Fn(Handle, &T)>>,
+    rows: HashMap<K, Handle>,
+}
+
+impl<K: Eq + Hash + Clone, T> Repeater<K, T> {
+    /// Creates an empty repeater identifying the items by `key` and creating their rows with
+    /// `template`.
+    pub fn new<F1, F2>(key: F1, template: F2) -> Repeater<K, T>
+                where F1: Fn(&T) -> K + 'static, F2: Fn(&T) -> Handle + 'static {
+        let rows_box = VBox::new(elements![]);
+        let root = Handle::from_raw(unsafe { iup_sys::IupScrollBox(rows_box.raw()) });
+        Repeater {
+            root: root,
+            rows_box: rows_box,
+            key: Box::new(key),
+            template: Box::new(template),
+            update: None,
+            rows: HashMap::new(),
+        }
+    }
+
+    /// Sets a closure called with the row and the item of every item already shown when the
+    /// items are set again, to update the row contents.
+    pub fn with_update<F: Fn(Handle, &T) + 'static>(mut self, update: F) -> Repeater<K, T> {
+        self.update = Some(Box::new(update));
+        self
+    }
+
+    /// Shows a row for each item, in the same order.
+    ///
+    /// Items with the same key are expected to be unique, duplicates after the first are
+    /// skipped.
+    pub fn set_items(&mut self, items: &[T]) {
+        let keys: Vec<K> = items.iter().map(|item| (self.key)(item)).collect();
+
+        // Rows of removed items.
+        let mut removed: Vec<K> = self.rows.keys().cloned().collect();
+        removed.retain(|key| !keys.contains(key));
+        for key in removed {
+            if let Some(mut row) = self.rows.remove(&key) {
+                // Items are often removed from a callback of their own row, e.g. a delete button.
+                row.detach();
+                row.destroy_later();
+            }
+        }
+
+        let mapped = self.rows_box.attrib("WID").is_some();
+        let mut placed = HashSet::new();
+        let mut pos = 0;
+        for (item, key) in items.iter().zip(keys.into_iter()) {
+            if !placed.insert(key.clone()) {
+                continue;
+            }
+            let current = self.rows_box.child(pos);
+            match self.rows.get(&key).cloned() {
+                Some(row) => {
+                    if current.map_or(true, |current| current.raw() != row.raw()) {
+                        // IupReparent appends the row when there's no reference child.
+                        let ref_child = current.map_or(ptr::null_mut(), |current| current.raw());
+                        unsafe { iup_sys::IupReparent(row.raw(), self.rows_box.raw(), ref_child) };
+                    }
+                    if let Some(ref update) = self.update {
+                        update(row, item);
+                    }
+                },
+                None => {
+                    let mut row = (self.template)(item);
+                    // IupInsert places the row first when there's no reference child.
+                    let _ = match current {
+                        Some(current) => self.rows_box.insert(&current, row),
+                        None => self.rows_box.append(row),
+                    };
+                    if mapped {
+                        let _ = row.map();
+                    }
+                    self.rows.insert(key, row);
+                },
+            }
+            pos += 1;
+        }
+
+        if mapped {
+            self.root.refresh();
+        }
+    }
+
+    /// Gets the row shown for the item with `key`.
+    pub fn row(&self, key: &K) -> Option<Handle> {
+        self.rows.get(key).cloned()
+    }
+
+    /// Returns the number of rows shown.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Checks whether no row is shown.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the scroll box containing the rows.
+    pub fn element(&self) -> Handle {
+        self.root
+    }
+}
+
+impl<'a, K, T> From<&'a Repeater<K, T>> for Handle {
+    fn from(repeater: &'a Repeater<K, T>) -> Handle {
+        repeater.root
+    }
+}
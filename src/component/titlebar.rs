@@ -0,0 +1,96 @@
+//! A title bar for dialogs with a custom frame.
+use Element;
+use Handle;
+use element::{Node, Container, Widget};
+use control::{Button, Label};
+use dialog::Dialog;
+use layout::{HBox, Expand};
+use layout::rtl;
+use a11y;
+use i18n;
+use callback::{Action, ActionReturn};
+
+/// A caption with minimize, maximize and close buttons, to be placed at the top of a dialog
+/// with a custom frame (see `Dialog::set_custom_frame`).
+///
+/// The caption label is named CUSTOMFRAMECAPTION, which IUP uses as the area the dialog is
+/// dragged from. Any element can be placed between the caption and the buttons with `insert`.
+///
+/// The buttons are placed on the left in right-to-left layouts, see `layout::rtl`.
+///
+/// ```ignore
+/// Dialog::new(VBox::new(elements![
+///     TitleBar::new("My Application"),
+///     content,
+/// ])).set_custom_frame(CustomFrame::Simulated)
+///    .show()
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct TitleBar {
+    root: HBox,
+    caption: Label,
+}
+
+impl TitleBar {
+    /// Creates a title bar with the specified title.
+    pub fn new<S: Into<String>>(title: S) -> TitleBar {
+        let caption = Label::with_title(title).set_attrib("NAME", "CUSTOMFRAMECAPTION")
+                                              .set_expand(Expand::Horizontal);
+
+        let minimize = Button::with_title("\u{2013}").set_attrib("FLAT", "YES")
+                                                     .set_action(|(button,): (Button,)| {
+            if let Some(mut dialog) = dialog_of(button) {
+                dialog.minimize();
+            }
+        });
+        let maximize = Button::with_title("\u{25A1}").set_attrib("FLAT", "YES")
+                                                     .set_action(|(button,): (Button,)| {
+            if let Some(mut dialog) = dialog_of(button) {
+                if dialog.is_maximized() { dialog.restore(); } else { dialog.maximize(); }
+            }
+        });
+        let close = Button::with_title("\u{2715}").set_attrib("FLAT", "YES")
+                                                  .set_action(|_| ActionReturn::Close);
+
+        a11y::set_name(minimize, i18n::tr("IUPRUST_TITLEBAR_MINIMIZE", "Minimize"));
+        a11y::set_name(maximize, i18n::tr("IUPRUST_TITLEBAR_MAXIMIZE", "Maximize"));
+        a11y::set_name(close, i18n::tr("IUPRUST_TITLEBAR_CLOSE", "Close"));
+
+        let root = HBox::new(elements![caption, minimize, maximize, close])
+                        .set_attrib("ALIGNMENT", "ACENTER")
+                        .set_attrib("NORMALIZESIZE", "VERTICAL");
+        TitleBar { root: rtl::apply(root), caption: caption }
+    }
+
+    /// Sets the title shown in the caption.
+    pub fn set_title<S: Into<String>>(&mut self, title: S) -> TitleBar {
+        self.caption.set_attrib("TITLE", title);
+        *self
+    }
+
+    /// Inserts an element (e.g. a menu button) between the caption and the buttons.
+    pub fn insert<E: Node>(&mut self, elem: E) -> TitleBar {
+        // The caption comes after the buttons in a right-to-left layout.
+        let ref_child = match self.root.child_pos(&self.caption) {
+            Some(0) => self.root.child(1).unwrap(),
+            _ => Handle::from(self.caption),
+        };
+        self.root.insert(&ref_child, elem).ok();
+        *self
+    }
+
+    /// Returns the element containing the whole title bar.
+    pub fn element(&self) -> HBox {
+        self.root
+    }
+}
+
+impl From<TitleBar> for Handle {
+    fn from(titlebar: TitleBar) -> Handle {
+        Handle::from(titlebar.element())
+    }
+}
+
+fn dialog_of(button: Button) -> Option<Dialog> {
+    button.dialog().and_then(|dialog| Dialog::from_handle(dialog).ok())
+}
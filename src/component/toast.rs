@@ -0,0 +1,214 @@
+//! Short messages shown over a corner of a dialog, which go away by themselves.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use Element;
+use element::{Widget, WeakHandle};
+use control::Label;
+use dialog::Dialog;
+use layout::BackgroundBox;
+use callback::button::{ButtonCb, MouseButton, MouseButtonState};
+use timer;
+
+/// The default time a toast is shown.
+const DEFAULT_DURATION_MS: u64 = 4000;
+/// The space between the toasts and the border of the dialog, and between the toasts.
+const MARGIN: i32 = 12;
+
+/// The severity of a toast, which chooses its colors.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ToastLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl ToastLevel {
+    /// Gets the background and text colors.
+    fn colors(self) -> (&'static str, &'static str) {
+        match self {
+            ToastLevel::Info => ("50 50 50", "255 255 255"),
+            ToastLevel::Warn => ("240 180 40", "0 0 0"),
+            ToastLevel::Error => ("190 30 30", "255 255 255"),
+        }
+    }
+}
+
+/// The corner of the dialog where the toasts are stacked.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+struct Inner {
+    parent: WeakHandle<Dialog>,
+    corner: Corner,
+    duration: Duration,
+    /// The toasts shown, the oldest nearest to the corner.
+    toasts: Vec<(u64, WeakHandle<Dialog>)>,
+    next_id: u64,
+}
+
+/// Shows non-blocking messages over a corner of a dialog.
+///
+/// Each toast is a small borderless dialog owned by the parent dialog, placed over its corner
+/// and stacked with the other toasts shown. It's dismissed after a while (see `set_duration`)
+/// or when clicked.
+///
+/// ```ignore
+/// let toasts = Toasts::new(main_dialog);
+/// toasts.show(ToastLevel::Info, "Document saved");
+/// ```
+///
+/// Toasts are placed when shown, they don't follow the parent dialog when it's moved. Cloning
+/// gives another handle to the same stack of toasts.
+///
+/// The toasts are not children of the parent dialog laid out in a Cbox: IUP doesn't define the
+/// stacking order of overlapping native controls, so a toast inside the dialog could be drawn
+/// under the controls it covers, and the dialog contents would have to be wrapped to make room
+/// for it. Owned dialogs always stay above their parent instead, without changing its layout.
+#[derive(Clone)]
+pub struct Toasts {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Toasts {
+    /// Creates toasts over the bottom right corner of `parent`.
+    pub fn new(parent: Dialog) -> Toasts {
+        Toasts {
+            inner: Rc::new(RefCell::new(Inner {
+                parent: parent.downgrade(),
+                corner: Corner::BottomRight,
+                duration: Duration::from_millis(DEFAULT_DURATION_MS),
+                toasts: Vec::new(),
+                next_id: 1,
+            })),
+        }
+    }
+
+    /// Sets the corner of the parent dialog where the toasts are shown.
+    pub fn set_corner(&self, corner: Corner) -> Toasts {
+        self.inner.borrow_mut().corner = corner;
+        self.layout();
+        self.clone()
+    }
+
+    /// Sets how long the next toasts are shown, 4 seconds by default.
+    pub fn set_duration(&self, duration: Duration) -> Toasts {
+        self.inner.borrow_mut().duration = duration;
+        self.clone()
+    }
+
+    /// Shows `message` in a new toast, returning false if the parent dialog was destroyed.
+    pub fn show<S: Into<String>>(&self, level: ToastLevel, message: S) -> bool {
+        let parent = match self.inner.borrow().parent.upgrade() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let (background, foreground) = level.colors();
+        let mut label = Label::with_title(message).set_attrib("FGCOLOR", foreground)
+                                                  .set_attrib("PADDING", "12x8");
+        let mut toast = Dialog::new(BackgroundBox::new(label).set_attrib("BGCOLOR", background))
+                                    .set_attrib("BORDER", "NO")
+                                    .set_attrib("RESIZE", "NO")
+                                    .set_attrib("MENUBOX", "NO")
+                                    .set_attrib("MAXBOX", "NO")
+                                    .set_attrib("MINBOX", "NO")
+                                    .set_attrib("SHOWNOFOCUS", "YES")
+                                    .set_attrib_handle("PARENTDIALOG", parent);
+        if toast.map().is_err() {
+            toast.destroy();
+            return false;
+        }
+
+        let (id, duration) = {
+            let mut inner = self.inner.borrow_mut();
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.toasts.push((id, toast.downgrade()));
+            (id, inner.duration)
+        };
+        let this = self.clone();
+        label.set_button_cb(move |(_, button, state, _, _, _)| {
+            if button == MouseButton::Button1 && state == MouseButtonState::Released {
+                this.dismiss(id);
+            }
+        });
+        let this = self.clone();
+        timer::delay(duration, move || this.dismiss(id));
+        self.layout();
+        true
+    }
+
+    /// Dismisses all the toasts shown.
+    pub fn clear(&self) {
+        let ids: Vec<u64> = self.inner.borrow().toasts.iter().map(|&(id, _)| id).collect();
+        for id in ids {
+            self.dismiss(id);
+        }
+    }
+
+    /// Returns the number of toasts shown.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().toasts.iter().filter(|&&(_, ref toast)| toast.is_alive()).count()
+    }
+
+    /// Checks whether there are no toasts shown.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn dismiss(&self, id: u64) {
+        let toast = {
+            let mut inner = self.inner.borrow_mut();
+            match inner.toasts.iter().position(|&(toast_id, _)| toast_id == id) {
+                Some(pos) => inner.toasts.remove(pos).1,
+                None => return,
+            }
+        };
+        if let Some(mut toast) = toast.upgrade() {
+            toast.hide();
+            // Toasts are dismissed from the callbacks of their own label.
+            toast.destroy_later();
+        }
+        self.layout();
+    }
+
+    /// Places the toasts shown over the corner of the parent dialog.
+    fn layout(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.toasts.retain(|&(_, ref toast)| toast.is_alive());
+        let parent = match inner.parent.upgrade() {
+            Some(parent) => parent.geometry(),
+            None => return,
+        };
+        let mut offset = MARGIN;
+        for &(_, ref toast) in &inner.toasts {
+            let mut toast = match toast.upgrade() {
+                Some(toast) => toast,
+                None => continue,
+            };
+            let size = toast.attrib("RASTERSIZE").unwrap_or_default();
+            let mut parts = size.splitn(2, 'x').map(|part| part.trim().parse::<i32>().unwrap_or(0));
+            let (width, height) = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+            let x = match inner.corner {
+                Corner::TopLeft | Corner::BottomLeft => parent.x + MARGIN,
+                Corner::TopRight | Corner::BottomRight => {
+                    parent.x + parent.width - width - MARGIN
+                },
+            };
+            let y = match inner.corner {
+                Corner::TopLeft | Corner::TopRight => parent.y + offset,
+                Corner::BottomLeft | Corner::BottomRight => {
+                    parent.y + parent.height - height - offset
+                },
+            };
+            toast.move_to(x, y);
+            offset += height + MARGIN;
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Undo and redo of the edits made by the user in a text.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use Element;
+use control::Text;
+use callback::{KeyReturn, ValueChangedCb, KAnyCb};
+
+/// Edits closer than this in time are merged into a single undo step.
+const COALESCE_INTERVAL_MS: u64 = 1000;
+
+/// The default maximum number of undo steps.
+const DEFAULT_LIMIT: usize = 100;
+
+/// IUP key codes of Ctrl+Z and Ctrl+Y.
+const K_CTRL_Z: i32 = 'Z' as i32 | 0x20000000;
+const K_CTRL_Y: i32 = 'Y' as i32 | 0x20000000;
+
+/// Keeps the history of the values of a `Text` (single or multiline) to undo and redo edits.
+///
+/// Edits are recorded from the VALUECHANGED_CB callback. Consecutive small edits typed in a
+/// short interval, such as typing a word, are coalesced into a single undo step.
+///
+/// Ctrl+Z and Ctrl+Y are bound to `undo` and `redo`, replacing the native undo of the control.
+/// The VALUECHANGED_CB and K_ANY callbacks of the text are taken by the undo stack.
+///
+/// ```ignore
+/// let text = Text::new().set_attrib("MULTILINE", "YES");
+/// let mut undo = UndoStack::attach(text);
+/// let undo_button = Button::with_title("Undo").set_action(move |_| { undo.undo(); });
+/// ```
+///
+/// Cloning an undo stack gives another handle to the same history.
+#[derive(Clone)]
+pub struct UndoStack {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    text: Text,
+    current: String,
+    undo: Vec<String>,
+    redo: Vec<String>,
+    last_edit: Option<Instant>,
+    limit: usize,
+}
+
+impl UndoStack {
+    /// Starts recording the edits of `text`.
+    pub fn attach(text: Text) -> UndoStack {
+        let stack = UndoStack {
+            inner: Rc::new(RefCell::new(Inner {
+                text: text,
+                current: text.attrib("VALUE").unwrap_or_default(),
+                undo: Vec::new(),
+                redo: Vec::new(),
+                last_edit: None,
+                limit: DEFAULT_LIMIT,
+            })),
+        };
+
+        let mut text = text;
+
+        let recorder = stack.clone();
+        text.set_valuechanged_cb(move |(text,): (Text,)| {
+            recorder.record(text.attrib("VALUE").unwrap_or_default());
+        });
+
+        let mut shortcuts = stack.clone();
+        text.set_k_any(move |(_, c): (Text, i32)| {
+            match c {
+                K_CTRL_Z => { shortcuts.undo(); KeyReturn::Ignore },
+                K_CTRL_Y => { shortcuts.redo(); KeyReturn::Ignore },
+                _ => KeyReturn::Default,
+            }
+        });
+
+        stack
+    }
+
+    /// Sets the maximum number of undo steps kept, older steps are discarded.
+    pub fn set_limit(&mut self, limit: usize) -> UndoStack {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.limit = limit;
+            inner.trim();
+        }
+        self.clone()
+    }
+
+    /// Checks whether there's an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.inner.borrow().undo.is_empty()
+    }
+
+    /// Checks whether there's an undone edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.inner.borrow().redo.is_empty()
+    }
+
+    /// Reverts the last edit. Returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let restored = {
+            let mut inner = self.inner.borrow_mut();
+            inner.undo.pop().map(|value| {
+                let current = inner.restore(value.clone());
+                inner.redo.push(current);
+                (inner.text, value)
+            })
+        };
+        set_restored(restored)
+    }
+
+    /// Reapplies the last undone edit. Returns whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let restored = {
+            let mut inner = self.inner.borrow_mut();
+            inner.redo.pop().map(|value| {
+                let current = inner.restore(value.clone());
+                inner.undo.push(current);
+                (inner.text, value)
+            })
+        };
+        set_restored(restored)
+    }
+
+    /// Forgets the whole history, the current value of the text becomes the base.
+    pub fn clear(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.current = inner.text.attrib("VALUE").unwrap_or_default();
+        inner.undo.clear();
+        inner.redo.clear();
+        inner.last_edit = None;
+    }
+
+    fn record(&self, value: String) {
+        let mut inner = self.inner.borrow_mut();
+        if value == inner.current {
+            return;
+        }
+
+        let now = Instant::now();
+        let coalesce = match inner.last_edit {
+            Some(last) => now.duration_since(last) < Duration::from_millis(COALESCE_INTERVAL_MS)
+                          && is_small_edit(&inner.current, &value)
+                          && !inner.undo.is_empty(),
+            None => false,
+        };
+
+        if !coalesce {
+            let previous = inner.current.clone();
+            inner.undo.push(previous);
+            inner.trim();
+        }
+        inner.redo.clear();
+        inner.current = value;
+        inner.last_edit = Some(now);
+    }
+}
+
+impl Inner {
+    /// Sets the text VALUE, returning the replaced value.
+    /// Makes `value` the current value, returning the previous one. The caller sets it on the
+    /// text once the stack is released.
+    fn restore(&mut self, value: String) -> String {
+        self.last_edit = None;
+        ::std::mem::replace(&mut self.current, value)
+    }
+
+    fn trim(&mut self) {
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.undo.drain(..excess);
+        }
+    }
+}
+
+/// Sets the value restored by `undo` or `redo` on the text, returns whether there was one.
+///
+/// Called without borrowing the stack, setting VALUE may run the callbacks of the text.
+fn set_restored(restored: Option<(Text, String)>) -> bool {
+    match restored {
+        Some((mut text, value)) => {
+            text.set_attrib("VALUE", value);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Checks whether `new` differs from `old` by a single typed or erased non-whitespace character.
+fn is_small_edit(old: &str, new: &str) -> bool {
+    let (short, long) = if old.len() < new.len() { (old, new) } else { (new, old) };
+    if long.chars().count() != short.chars().count() + 1 {
+        return false;
+    }
+    let prefix = short.chars().zip(long.chars()).take_while(|&(a, b)| a == b).count();
+    long.chars().nth(prefix).map_or(false, |c| !c.is_whitespace())
+}
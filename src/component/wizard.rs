@@ -0,0 +1,267 @@
+//! A wizard guides the user through an ordered sequence of pages.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use Element;
+use Handle;
+use element::{Node, Container, Widget};
+use control::Button;
+use layout::{VBox, HBox, ZBox, Fill, Expand};
+use callback::{Action, ActionReturn};
+use dialog;
+use i18n;
+use a11y;
+
+/// The data collected from the pages, maps the NAME attribute of each element to its VALUE.
+pub type WizardData = HashMap<String, String>;
+
+/// A composite with pages stacked in a `ZBox` and Back/Next/Finish buttons to navigate on them.
+///
+/// Each page may have a validation closure which must succeed before moving to the next page,
+/// the `Err` message is shown to the user. When the user finishes the wizard, the VALUE of
+/// every element with a NAME attribute in all the pages is collected and passed to the
+/// `Wizard::on_finish` callback.
+///
+/// The wizard is embedded in a dialog like any other element:
+///
+/// ```ignore
+/// let wizard = Wizard::new()
+///     .page(VBox::new(elements![
+///         Label::with_title("Name:"),
+///         Text::new().set_attrib("NAME", "name"),
+///     ]))
+///     .page_with_validation(Toggle::with_title("I agree").set_attrib("NAME", "agree"), |page| {
+///         match page.attrib("VALUE") {
+///             Some(ref v) if v == "ON" => Ok(()),
+///             _ => Err("You must agree to continue.".into()),
+///         }
+///     })
+///     .on_finish(|data| {
+///         println!("Hello {}", data["name"]);
+///         ActionReturn::Close
+///     });
+///
+/// Dialog::new(wizard).set_attrib("TITLE", "Setup").show()
+/// ```
+///
+/// Cloning a wizard gives another handle to the same wizard.
+#[derive(Clone)]
+pub struct Wizard {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Page {
+    element: Handle,
+    validate: Option<Box<FnMut(Handle) -> Result<(), String>>>,
+}
+
+struct Inner {
+    root: VBox,
+    zbox: ZBox,
+    back: Button,
+    next: Button,
+    finish: Button,
+    pages: Vec<Page>,
+    current: usize,
+    on_finish: Option<Box<FnMut(WizardData) -> ActionReturn>>,
+}
+
+impl Wizard {
+    /// Creates a wizard with no pages.
+    ///
+    /// The button titles are translated by the `i18n` catalog with the "IUPRUST_WIZARD_BACK",
+    /// "IUPRUST_WIZARD_NEXT" and "IUPRUST_WIZARD_FINISH" keys.
+    pub fn new() -> Wizard {
+        let zbox = ZBox::new(elements![]).set_expand(Expand::Yes);
+        let back = Button::with_title(i18n::tr("IUPRUST_WIZARD_BACK", "< Back"));
+        let next = Button::with_title(i18n::tr("IUPRUST_WIZARD_NEXT", "Next >"));
+        let finish = Button::with_title(i18n::tr("IUPRUST_WIZARD_FINISH", "Finish"));
+        // Screen readers would announce the arrows of the titles.
+        a11y::set_name(back, i18n::tr("IUPRUST_WIZARD_BACK_NAME", "Back"));
+        a11y::set_name(next, i18n::tr("IUPRUST_WIZARD_NEXT_NAME", "Next"));
+
+        let root = VBox::new(elements![
+            zbox,
+            HBox::new(elements![Fill::new(), back, next, finish])
+                .set_attrib("NORMALIZESIZE", "HORIZONTAL")
+                .set_attrib("GAP", "5"),
+        ]).set_attrib("MARGIN", "10x10")
+          .set_attrib("GAP", "10");
+
+        let inner = Rc::new(RefCell::new(Inner {
+            root: root,
+            zbox: zbox,
+            back: back,
+            next: next,
+            finish: finish,
+            pages: Vec::new(),
+            current: 0,
+            on_finish: None,
+        }));
+
+        let wizard = Wizard { inner: inner };
+        wizard.bind_buttons();
+        wizard.update();
+        wizard
+    }
+
+    /// Appends a page to the wizard.
+    pub fn page<E: Node>(self, page: E) -> Wizard {
+        self.push_page(page, None)
+    }
+
+    /// Appends a page to the wizard that must be validated before moving forward.
+    ///
+    /// The `validate` closure receives the page element.
+    pub fn page_with_validation<E, F>(self, page: E, validate: F) -> Wizard
+                        where E: Node, F: FnMut(Handle) -> Result<(), String> + 'static {
+        self.push_page(page, Some(Box::new(validate)))
+    }
+
+    /// Sets the closure called when the user finishes the wizard.
+    ///
+    /// `ActionReturn::Close` closes the dialog.
+    pub fn on_finish<F>(self, cb: F) -> Wizard
+                        where F: FnMut(WizardData) -> ActionReturn + 'static {
+        self.inner.borrow_mut().on_finish = Some(Box::new(cb));
+        self
+    }
+
+    /// Returns the position of the current page, starting from 0.
+    pub fn current_page(&self) -> usize {
+        self.inner.borrow().current
+    }
+
+    /// Shows the page at the specified position without validating the current page.
+    ///
+    /// # Panics
+    /// Panics if there's no page at `pos`.
+    pub fn go_to(&mut self, pos: usize) {
+        assert!(pos < self.inner.borrow().pages.len());
+        self.inner.borrow_mut().current = pos;
+        self.update();
+    }
+
+    /// Collects the data of all the pages, see `WizardData`.
+    pub fn data(&self) -> WizardData {
+        let mut data = WizardData::new();
+        for page in self.inner.borrow().pages.iter() {
+            collect_data(page.element, &mut data);
+        }
+        data
+    }
+
+    /// Returns the element containing the whole wizard.
+    pub fn element(&self) -> VBox {
+        self.inner.borrow().root
+    }
+
+    fn push_page<E: Node>(self, page: E,
+                          validate: Option<Box<FnMut(Handle) -> Result<(), String>>>) -> Wizard {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.zbox.append(page).ok().expect("failed to append page to the wizard");
+            inner.pages.push(Page { element: Handle::from_raw(page.raw()), validate: validate });
+        }
+        self.update();
+        self
+    }
+
+    fn bind_buttons(&self) {
+        let (mut back, mut next, mut finish) = {
+            let inner = self.inner.borrow();
+            (inner.back, inner.next, inner.finish)
+        };
+
+        let mut wizard = self.clone();
+        back.set_action(move |_| {
+            let current = wizard.current_page();
+            if current > 0 {
+                wizard.go_to(current - 1);
+            }
+        });
+
+        let mut wizard = self.clone();
+        next.set_action(move |_| {
+            let current = wizard.current_page();
+            if wizard.validate_current() && current + 1 < wizard.inner.borrow().pages.len() {
+                wizard.go_to(current + 1);
+            }
+        });
+
+        let wizard = self.clone();
+        finish.set_action(move |_| {
+            if !wizard.validate_current() {
+                return ActionReturn::Default;
+            }
+            let data = wizard.data();
+            // Take the closure out so it's free to access the wizard.
+            let cb = wizard.inner.borrow_mut().on_finish.take();
+            match cb {
+                Some(mut cb) => {
+                    let r = cb(data);
+                    wizard.inner.borrow_mut().on_finish = Some(cb);
+                    r
+                },
+                None => ActionReturn::Default,
+            }
+        });
+    }
+
+    /// Runs the validation of the current page, showing the error message on failure.
+    fn validate_current(&self) -> bool {
+        let (pos, element, validate) = {
+            let mut inner = self.inner.borrow_mut();
+            let pos = inner.current;
+            match inner.pages.get_mut(pos) {
+                Some(page) => (pos, page.element, page.validate.take()),
+                None => return true,
+            }
+        };
+        match validate {
+            Some(mut validate) => {
+                let result = validate(element);
+                self.inner.borrow_mut().pages[pos].validate = Some(validate);
+                match result {
+                    Ok(()) => true,
+                    Err(msg) => {
+                        dialog::message(i18n::language_string("IUP_ERROR").unwrap_or("Error".into()), msg);
+                        false
+                    },
+                }
+            },
+            None => true,
+        }
+    }
+
+    /// Shows the current page and enables only the meaningful buttons.
+    fn update(&self) {
+        let mut inner = self.inner.borrow_mut();
+        let len = inner.pages.len();
+        let current = inner.current;
+        let yes_no = |b: bool| if b { "YES" } else { "NO" };
+        if len > 0 {
+            inner.zbox.set_attrib("VALUEPOS", current.to_string());
+        }
+        inner.back.set_attrib("ACTIVE", yes_no(current > 0));
+        inner.next.set_attrib("ACTIVE", yes_no(current + 1 < len));
+        inner.finish.set_attrib("ACTIVE", yes_no(len > 0 && current + 1 == len));
+    }
+}
+
+impl From<Wizard> for Handle {
+    fn from(wizard: Wizard) -> Handle {
+        Handle::from(wizard.element())
+    }
+}
+
+/// Collects the VALUE of every named element under (and including) `elem`.
+fn collect_data(elem: Handle, data: &mut WizardData) {
+    if let (Some(name), Some(value)) = (elem.attrib("NAME"), elem.attrib("VALUE")) {
+        data.insert(name, value);
+    }
+    for child in elem.children() {
+        collect_data(child, data);
+    }
+}
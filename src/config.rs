@@ -0,0 +1,120 @@
+//! Persistent application settings.
+//!
+//! A configuration is a set of variables organized in groups and stored in a text file in the
+//! user home folder, see the [IUP Config Documentation][1].
+//!
+//! ```ignore
+//! let mut config = Config::new("myapp");
+//! config.load().ok();
+//! config.set_variable("Window", "Theme", "dark");
+//! config.save().unwrap();
+//! ```
+//!
+//! [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/func/iupconfig.html
+use iup_sys;
+use libc::{c_char, c_int};
+use std::ffi::CString;
+use std::ptr;
+use std::result::Result;
+
+use Element;
+use Guard;
+use dialog::DialogElement;
+
+/// A configuration file.
+///
+/// # Ownership
+///
+/// The config must be manually destroyed, thus for the user safety it returns a guarded object
+/// on the `new` constructor.
+pub struct Config(*mut iup_sys::Ihandle);
+
+impl Config {
+    /// Creates a configuration stored in a file named after `app_name`.
+    ///
+    /// In UNIX the file is `~/.app_name` and in Windows `app_name.cfg` in the user home folder.
+    pub fn new<S: Into<String>>(app_name: S) -> Guard<Config> {
+        Guard::new(
+            Config::from_raw(unsafe { IupConfig() }).set_attrib("APP_NAME", app_name)
+        )
+    }
+
+    /// Loads the variables from the file, replacing the ones in memory.
+    pub fn load(&mut self) -> Result<(), String> {
+        match unsafe { IupConfigLoad(self.raw()) } {
+            0 => Ok(()),
+            _ => Err("Failed to load the configuration file".into()),
+        }
+    }
+
+    /// Saves all the variables to the file.
+    pub fn save(&mut self) -> Result<(), String> {
+        match unsafe { IupConfigSave(self.raw()) } {
+            0 => Ok(()),
+            _ => Err("Failed to save the configuration file".into()),
+        }
+    }
+
+    /// Sets the `key` variable of `group`.
+    pub fn set_variable<S1, S2, S3>(&mut self, group: S1, key: S2, value: S3) -> Config
+                            where S1: Into<String>, S2: Into<String>, S3: Into<String> {
+        let cgroup = CString::new(group.into()).unwrap();
+        let ckey = CString::new(key.into()).unwrap();
+        let cvalue = CString::new(value.into()).unwrap();
+        unsafe { IupConfigSetVariableStr(self.raw(), cgroup.as_ptr(), ckey.as_ptr(), cvalue.as_ptr()) };
+        *self
+    }
+
+    /// Removes the `key` variable of `group`.
+    pub fn remove_variable<S1, S2>(&mut self, group: S1, key: S2) -> Config
+                                                where S1: Into<String>, S2: Into<String> {
+        let cgroup = CString::new(group.into()).unwrap();
+        let ckey = CString::new(key.into()).unwrap();
+        unsafe { IupConfigSetVariableStr(self.raw(), cgroup.as_ptr(), ckey.as_ptr(), ptr::null()) };
+        *self
+    }
+
+    /// Gets the `key` variable of `group`, if set.
+    pub fn variable<S1, S2>(&self, group: S1, key: S2) -> Option<String>
+                                                where S1: Into<String>, S2: Into<String> {
+        let cgroup = CString::new(group.into()).unwrap();
+        let ckey = CString::new(key.into()).unwrap();
+        match unsafe { IupConfigGetVariableStr(self.raw(), cgroup.as_ptr(), ckey.as_ptr()) } {
+            cvalue if cvalue.is_null() => None,
+            cvalue => Some(string_from_cstr!(cvalue)),
+        }
+    }
+
+    /// Shows `dialog` with the position, size and placement saved under the `name` group.
+    ///
+    /// If nothing was saved the dialog is shown at its current position.
+    pub fn show_dialog<D, S>(&mut self, dialog: D, name: S) where D: DialogElement, S: Into<String> {
+        let cname = CString::new(name.into()).unwrap();
+        unsafe { IupConfigDialogShow(self.raw(), dialog.raw(), cname.as_ptr()) };
+    }
+
+    /// Saves the position, size and placement of `dialog` under the `name` group.
+    ///
+    /// Should be called when the dialog is closed, this does not save the file.
+    pub fn dialog_closed<D, S>(&mut self, dialog: D, name: S) where D: DialogElement, S: Into<String> {
+        let cname = CString::new(name.into()).unwrap();
+        unsafe { IupConfigDialogClosed(self.raw(), dialog.raw(), cname.as_ptr()) };
+    }
+}
+
+impl_element!(Config, "config");
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupConfig() -> *mut iup_sys::Ihandle;
+    fn IupConfigLoad(ih: *mut iup_sys::Ihandle) -> c_int;
+    fn IupConfigSave(ih: *mut iup_sys::Ihandle) -> c_int;
+    fn IupConfigSetVariableStr(ih: *mut iup_sys::Ihandle, group: *const c_char, key: *const c_char,
+                               value: *const c_char);
+    fn IupConfigGetVariableStr(ih: *mut iup_sys::Ihandle, group: *const c_char,
+                               key: *const c_char) -> *const c_char;
+    fn IupConfigDialogShow(ih: *mut iup_sys::Ihandle, dialog: *mut iup_sys::Ihandle,
+                           name: *const c_char);
+    fn IupConfigDialogClosed(ih: *mut iup_sys::Ihandle, dialog: *mut iup_sys::Ihandle,
+                             name: *const c_char);
+}
@@ -31,14 +31,14 @@ impl ::callback::KillFocusCb for Button {}
 impl ::callback::EnterWindowCb for Button {}
 impl ::callback::LeaveWindowCb for Button {}
 impl ::callback::HelpCb for Button {}
-// TODO impl K_ callbacks when it's implemented.
+impl ::callback::KAnyCb for Button {}
 
 /// Action generated when the button 1 (usually left) is selected.
 ///
 /// This callback is called only after the mouse is released and when it is released
 /// inside the button area.
 ///
-/// `CallbackReturn::Close` will be processed.
+/// `ActionReturn::Close` will be processed.
 impl ::callback::Action for Button {}
 
 /// Action generated when any mouse button is pressed and released.
@@ -0,0 +1,212 @@
+use iup_sys;
+use libc::c_float;
+use std::ptr;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use Element;
+use timer::{self, Timer};
+use callback::ActionCb;
+use element::{Node, WeakHandle};
+use dialog::Rect;
+
+/// A working area for the application to draw on.
+///
+/// See the [IUP Canvas Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupcanvas.html
+pub struct Canvas(*mut iup_sys::Ihandle);
+
+impl Canvas {
+    /// Creates a canvas.
+    pub fn new() -> Canvas {
+        unsafe { Canvas::from_raw(iup_sys::IupCanvas(ptr::null_mut())) }
+    }
+
+    /// [Windows Only]: Sets whether the IupDraw functions use Direct2D instead of GDI, which is
+    /// faster and antialiased.
+    ///
+    /// Must be set before the canvas is mapped.
+    pub fn set_direct2d(&mut self, direct2d: bool) -> Canvas {
+        self.set_attrib("DRAWUSEDIRECT2D", if direct2d { "YES" } else { "NO" })
+    }
+
+    /// Marks a region of the canvas as changed and schedules a redraw.
+    ///
+    /// The regions invalidated until the next redraw are merged in a single rectangle, which the
+    /// redraw callback gets with `take_dirty_rect` to repaint only that part of a large canvas.
+    pub fn invalidate_rect(&mut self, x: i32, y: i32, width: i32, height: i32) -> Canvas {
+        if width > 0 && height > 0 {
+            let rect = Rect { x: x, y: y, width: width, height: height };
+            DIRTY_RECTS.with(|dirty| {
+                let mut dirty = dirty.borrow_mut();
+                let merged = match dirty.get(&(self.raw() as usize)) {
+                    Some(&other) => union(rect, other),
+                    None => rect,
+                };
+                dirty.insert(self.raw() as usize, merged);
+            });
+            Node::update(self);
+        }
+        *self
+    }
+
+    /// Gets the region changed since the last `take_dirty_rect`, if any.
+    pub fn dirty_rect(&self) -> Option<Rect> {
+        DIRTY_RECTS.with(|dirty| dirty.borrow().get(&(self.raw() as usize)).cloned())
+    }
+
+    /// Gets and forgets the region changed since the last call, to be called from the
+    /// `CanvasAction` callback.
+    ///
+    /// Returns `None` when nothing was invalidated with `invalidate_rect`, i.e. the redraw comes
+    /// from the system (e.g. the canvas was uncovered or resized) and the whole canvas must be
+    /// repainted.
+    ///
+    /// ```ignore
+    /// let (width, height) = canvas_size(canvas);
+    /// let rect = canvas.take_dirty_rect()
+    ///                  .unwrap_or(Rect { x: 0, y: 0, width: width, height: height });
+    /// draw_shapes_inside(canvas, rect);
+    /// ```
+    pub fn take_dirty_rect(&mut self) -> Option<Rect> {
+        DIRTY_RECTS.with(|dirty| dirty.borrow_mut().remove(&(self.raw() as usize)))
+    }
+
+    /// Calls `f` about `fps` times per second to render a new frame, with the time elapsed since
+    /// the previous frame.
+    ///
+    /// Frames are skipped while the canvas or its dialog is hidden or minimized, and the time
+    /// spent paused is not counted in the elapsed time. The loop replaces any previous loop of
+    /// the canvas and ends when the canvas is destroyed.
+    ///
+    /// ```ignore
+    /// canvas.set_render_loop(60, move |canvas, dt| {
+    ///     world.step(dt);
+    ///     world.draw(canvas);
+    /// });
+    /// ```
+    pub fn set_render_loop<F>(&mut self, fps: u32, f: F) -> Canvas
+                                        where F: FnMut(Canvas, Duration) + 'static {
+        self.stop_render_loop();
+        let mut f = f;
+        let canvas = self.downgrade();
+        let mut last = Instant::now();
+        let mut timer = timer::owned_timer().set_time((1000 / fps.max(1)).max(1));
+        let key = self.raw() as usize;
+        RENDER_LOOPS.with(|loops| loops.borrow_mut().insert(key, timer.downgrade()));
+        timer.set_action_cb(move |(mut timer,): (Timer,)| {
+            let canvas = match canvas.upgrade() {
+                Some(canvas) => canvas,
+                None => {
+                    timer.stop();
+                    timer.destroy_later();
+                    return;
+                },
+            };
+            let now = Instant::now();
+            if is_showing(canvas) {
+                f(canvas, now - last);
+            }
+            last = now;
+        }).run();
+        *self
+    }
+
+    /// Stops the render loop of the canvas, if any.
+    pub fn stop_render_loop(&mut self) -> Canvas {
+        let key = self.raw() as usize;
+        let timer = RENDER_LOOPS.with(|loops| loops.borrow_mut().remove(&key));
+        if let Some(mut timer) = timer.and_then(|timer| timer.upgrade()) {
+            timer.stop();
+            timer.destroy_later();
+        }
+        *self
+    }
+
+    /// Gets the width and height in pixels of `text` written with the DRAWFONT of the canvas, or
+    /// its FONT if not set, see `measure::text_extent`.
+    pub fn text_extent(&self, text: &str) -> (i32, i32) {
+        ::measure::extent(self.raw(), text)
+    }
+}
+
+thread_local!(static DIRTY_RECTS: RefCell<HashMap<usize, Rect>> = RefCell::new(HashMap::new()));
+// The timer of the render loop of each canvas, destroyed with the canvas.
+thread_local!(static RENDER_LOOPS: RefCell<HashMap<usize, WeakHandle<Timer>>> =
+              RefCell::new(HashMap::new()));
+
+/// Forgets the dirty rectangle and destroys the render loop of `ih`, called when the element
+/// is destroyed.
+#[doc(hidden)]
+pub fn element_destroyed(ih: *mut iup_sys::Ihandle) {
+    DIRTY_RECTS.with(|dirty| dirty.borrow_mut().remove(&(ih as usize)));
+    let timer = RENDER_LOOPS.with(|loops| loops.borrow_mut().remove(&(ih as usize)));
+    // The canvas may be destroyed from the callback of the timer itself.
+    if let Some(mut timer) = timer.and_then(|timer| timer.upgrade()) {
+        timer.stop();
+        timer.destroy_later();
+    }
+}
+
+/// The smallest rectangle containing both `a` and `b`.
+fn union(a: Rect, b: Rect) -> Rect {
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    Rect { x: x, y: y, width: right - x, height: bottom - y }
+}
+
+/// Checks whether the canvas can be seen, i.e. it's visible and its dialog isn't minimized.
+fn is_showing(canvas: Canvas) -> bool {
+    match canvas.dialog() {
+        Some(dialog) => {
+            is_visible(canvas) && is_visible(dialog)
+                && dialog.attrib("MINIMIZED").map_or(true, |minimized| minimized != "YES")
+        },
+        None => false,
+    }
+}
+
+fn is_visible<E: Element>(elem: E) -> bool {
+    elem.attrib("VISIBLE").map_or(false, |visible| visible == "YES")
+}
+
+impl_widget!(Canvas, "canvas");
+impl ::callback::MapCb for Canvas {}
+impl ::callback::UnmapCb for Canvas {}
+impl ::callback::GetFocusCb for Canvas {}
+impl ::callback::KillFocusCb for Canvas {}
+impl ::callback::EnterWindowCb for Canvas {}
+impl ::callback::LeaveWindowCb for Canvas {}
+impl ::callback::HelpCb for Canvas {}
+impl ::callback::KAnyCb for Canvas {}
+
+/// Action generated when any mouse button is pressed or released inside the canvas.
+impl ::callback::button::ButtonCb for Canvas {}
+
+/// Action generated when the mouse is moved over the canvas.
+impl ::callback::button::MotionCb for Canvas {}
+
+/// Action generated when the mouse wheel is rotated over the canvas.
+impl ::callback::button::WheelCb for Canvas {}
+
+/// Action generated when one or more files are dropped in the element.
+impl ::callback::DropFilesCb for Canvas {}
+
+/// Action generated when the canvas size is changed.
+impl ::callback::ResizeCb for Canvas {}
+
+/// See the `CanvasAction` documentation.
+impl self::CanvasAction for Canvas {}
+impl_callback! {
+    #[doc="Action generated when the canvas needs to be redrawn."]
+    #[doc=""]
+    #[doc="The `f32` parameters are the scrollbar positions (POSX and POSY)."]
+    pub trait CanvasAction where Self: Element {
+        let name = "ACTION";
+        extern fn listener(ih: *mut iup_sys::Ihandle, posx: c_float, posy: c_float) -> CallbackReturn;
+        fn set_action<F: Callback(Self, f32, f32)>(&mut self, cb: F) -> Self;
+        fn remove_action(&mut self) -> Option<Box<_>>;
+    }
+}
@@ -0,0 +1,188 @@
+//! Import and export of delimited text (CSV, TSV) for lists and matrices.
+//!
+//! Requires the `csv` feature.
+use csv::{ReaderBuilder, WriterBuilder};
+use std::io;
+use std::path::Path;
+use std::result::Result;
+
+use Element;
+use clipboard::Clipboard;
+use control::List;
+#[cfg(feature = "controls")]
+use control::Matrix;
+
+/// How delimited text files are read and written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The field delimiter, `b','` by default.
+    pub delimiter: u8,
+    /// Whether the first record holds the column titles, true by default.
+    pub has_headers: bool,
+}
+
+impl CsvOptions {
+    /// Options for comma separated values with headers.
+    pub fn csv() -> CsvOptions {
+        CsvOptions { delimiter: b',', has_headers: true }
+    }
+
+    /// Options for tab separated values with headers.
+    pub fn tsv() -> CsvOptions {
+        CsvOptions { delimiter: b'\t', has_headers: true }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions::csv()
+    }
+}
+
+/// Reads the records of the file at `path`, calling `progress` with the number of records
+/// read so far.
+fn read_records<P, F>(path: P, options: &CsvOptions, mut progress: F)
+                        -> Result<(Option<Vec<String>>, Vec<Vec<String>>), String>
+                        where P: AsRef<Path>, F: FnMut(usize) {
+    let mut reader = try!(ReaderBuilder::new().delimiter(options.delimiter)
+                                              .has_headers(options.has_headers)
+                                              .flexible(true)
+                                              .from_path(path)
+                                              .map_err(|e| e.to_string()));
+    let headers = if options.has_headers {
+        let headers = try!(reader.headers().map_err(|e| e.to_string()));
+        Some(headers.iter().map(|s| s.to_string()).collect())
+    } else {
+        None
+    };
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = try!(record.map_err(|e| e.to_string()));
+        records.push(record.iter().map(|s| s.to_string()).collect());
+        progress(records.len());
+    }
+    Ok((headers, records))
+}
+
+/// Writes `headers` (if any) and the records to `writer`, calling `progress` with the number
+/// of records written so far.
+fn write_records<W, F>(writer: W, options: &CsvOptions, headers: Option<Vec<String>>,
+                       records: &[Vec<String>], mut progress: F) -> Result<(), String>
+                       where W: io::Write, F: FnMut(usize) {
+    let mut writer = WriterBuilder::new().delimiter(options.delimiter)
+                                         .flexible(true)
+                                         .from_writer(writer);
+    if let Some(headers) = headers {
+        try!(writer.write_record(&headers).map_err(|e| e.to_string()));
+    }
+    for (i, record) in records.iter().enumerate() {
+        try!(writer.write_record(record).map_err(|e| e.to_string()));
+        progress(i + 1);
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Copies the records to the clipboard as tab separated values, which spreadsheets paste
+/// into cells.
+fn copy_records(headers: Option<Vec<String>>, records: &[Vec<String>]) -> Result<(), String> {
+    let mut tsv = Vec::new();
+    try!(write_records(&mut tsv, &CsvOptions::tsv(), headers, records, |_| ()));
+    let text = try!(String::from_utf8(tsv).map_err(|e| e.to_string()));
+    Clipboard::new().set_text(text);
+    Ok(())
+}
+
+impl List {
+    /// Replaces the items by the values of the `column` (starting from 0) of the delimited
+    /// file at `path`.
+    ///
+    /// `progress` is called with the number of records read so far. Returns the number of items.
+    pub fn load_csv<P, F>(&mut self, path: P, options: &CsvOptions, column: usize, progress: F)
+                            -> Result<usize, String> where P: AsRef<Path>, F: FnMut(usize) {
+        let (_, records) = try!(read_records(path, options, progress));
+        let items: Vec<String> = records.into_iter()
+                                        .map(|record| record.into_iter().nth(column)
+                                                            .unwrap_or_default())
+                                        .collect();
+        self.set_items(&items);
+        Ok(items.len())
+    }
+
+    /// Writes the items to the file at `path`, one per record, under the `header` if the
+    /// options have headers.
+    pub fn save_csv<P, F>(&self, path: P, options: &CsvOptions, header: &str, progress: F)
+                            -> Result<(), String> where P: AsRef<Path>, F: FnMut(usize) {
+        let file = try!(::std::fs::File::create(path).map_err(|e| e.to_string()));
+        let headers = if options.has_headers { Some(vec![header.to_string()]) } else { None };
+        write_records(file, options, headers, &self.records(), progress)
+    }
+
+    /// Copies the items to the clipboard, one per line.
+    pub fn copy_tsv(&self) -> Result<(), String> {
+        copy_records(None, &self.records())
+    }
+
+    fn records(&self) -> Vec<Vec<String>> {
+        let count = self.attrib_parse::<u32, _>("COUNT").unwrap_or(0);
+        (1..count + 1).map(|id| vec![self.attrib_at("", id as i32).unwrap_or_default()]).collect()
+    }
+}
+
+#[cfg(feature = "controls")]
+impl Matrix {
+    /// Replaces the cells by the contents of the delimited file at `path`, the headers (if
+    /// any) become the column titles.
+    ///
+    /// `progress` is called with the number of records read so far. Returns the number of
+    /// lines. Not available while a model is set, see `Matrix::set_model`.
+    pub fn load_csv<P, F>(&mut self, path: P, options: &CsvOptions, progress: F)
+                            -> Result<usize, String> where P: AsRef<Path>, F: FnMut(usize) {
+        let (headers, records) = try!(read_records(path, options, progress));
+        let cols = records.iter().map(|record| record.len())
+                          .chain(headers.iter().map(|headers| headers.len()))
+                          .max().unwrap_or(0);
+        self.set_size(records.len(), cols);
+        if let Some(headers) = headers {
+            for (col, title) in headers.into_iter().enumerate() {
+                self.set_cell(0, col as i32 + 1, title);
+            }
+        }
+        for (lin, record) in records.iter().enumerate() {
+            for (col, value) in record.iter().enumerate() {
+                self.set_cell(lin as i32 + 1, col as i32 + 1, &value[..]);
+            }
+        }
+        self.set_attrib("REDRAW", "ALL");
+        Ok(records.len())
+    }
+
+    /// Writes the cells to the file at `path`, with the column titles as headers if the
+    /// options have headers.
+    pub fn save_csv<P, F>(&self, path: P, options: &CsvOptions, progress: F)
+                            -> Result<(), String> where P: AsRef<Path>, F: FnMut(usize) {
+        let file = try!(::std::fs::File::create(path).map_err(|e| e.to_string()));
+        let headers = if options.has_headers { Some(self.titles()) } else { None };
+        write_records(file, options, headers, &self.records(), progress)
+    }
+
+    /// Copies the cells to the clipboard, with the column titles in the first line.
+    pub fn copy_tsv(&self) -> Result<(), String> {
+        copy_records(Some(self.titles()), &self.records())
+    }
+
+    fn size(&self) -> (i32, i32) {
+        (self.attrib_parse("NUMLIN").unwrap_or(0), self.attrib_parse("NUMCOL").unwrap_or(0))
+    }
+
+    fn titles(&self) -> Vec<String> {
+        let (_, cols) = self.size();
+        (1..cols + 1).map(|col| self.cell(0, col).unwrap_or_default()).collect()
+    }
+
+    fn records(&self) -> Vec<Vec<String>> {
+        let (lines, cols) = self.size();
+        (1..lines + 1).map(|lin| {
+            (1..cols + 1).map(|col| self.cell(lin, col).unwrap_or_default()).collect()
+        }).collect()
+    }
+}
@@ -0,0 +1,242 @@
+use iup_sys;
+use libc::c_char;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use Element;
+use callback::CallbackReturn;
+use control::{CheckState, NodeStyle, TreeProvider};
+use control::{BranchOpenCb, BranchCloseCb, ExecuteLeafCb, SelectionCb, ToggleValueCb};
+use control::{MultiSelectionCb, MultiUnselectionCb, ShowRenameCb, RenameCb};
+
+/// See the [IUP FlatTree Documentation][1].
+///
+/// A tree drawn by IUP itself instead of by the native system, so it looks and behaves the same
+/// on every platform and each node can have its own colors and font (see `set_node_style`).
+/// Nodes are identified by their id like the nodes of a `Tree`, and the same callbacks are
+/// supported.
+///
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupflattree.html
+pub struct FlatTree(*mut iup_sys::Ihandle);
+
+impl FlatTree {
+    /// Creates a tree containing nodes of branches or leaves.
+    pub fn new() -> FlatTree {
+        unsafe { FlatTree::from_raw(IupFlatTree()) }
+    }
+
+    /// Adds a leaf after the node `id`, or as the first child of `id` if it's a branch.
+    ///
+    /// Use an `id` of -1 to add the first node of the tree.
+    /// The new node id can be found with `last_added_node`.
+    pub fn add_leaf<S: Into<String>>(&mut self, id: i32, title: S) -> FlatTree {
+        self.set_attrib_at("ADDLEAF", id, title)
+    }
+
+    /// Adds a branch after the node `id`, or as the first child of `id` if it's a branch.
+    ///
+    /// Use an `id` of -1 to add the first node of the tree.
+    /// The new node id can be found with `last_added_node`.
+    pub fn add_branch<S: Into<String>>(&mut self, id: i32, title: S) -> FlatTree {
+        self.set_attrib_at("ADDBRANCH", id, title)
+    }
+
+    /// Inserts a leaf after the node `id` at the same depth, i.e. as its next sibling.
+    ///
+    /// The new node id can be found with `last_added_node`.
+    pub fn insert_leaf<S: Into<String>>(&mut self, id: i32, title: S) -> FlatTree {
+        self.set_attrib_at("INSERTLEAF", id, title)
+    }
+
+    /// Inserts a branch after the node `id` at the same depth, i.e. as its next sibling.
+    ///
+    /// The new node id can be found with `last_added_node`.
+    pub fn insert_branch<S: Into<String>>(&mut self, id: i32, title: S) -> FlatTree {
+        self.set_attrib_at("INSERTBRANCH", id, title)
+    }
+
+    /// Returns the id of the last node added or inserted.
+    pub fn last_added_node(&self) -> Option<i32> {
+        self.attrib_parse("LASTADDNODE")
+    }
+
+    /// Removes the node `id` and all of its children.
+    pub fn remove_node(&mut self, id: i32) -> FlatTree {
+        self.set_attrib_at("DELNODE", id, "SELECTED")
+    }
+
+    /// Removes all the children of the branch `id`.
+    pub fn remove_children(&mut self, id: i32) -> FlatTree {
+        self.set_attrib_at("DELNODE", id, "CHILDREN")
+    }
+
+    /// Removes all the nodes of the tree.
+    pub fn clear(&mut self) -> FlatTree {
+        self.set_attrib("DELNODE", "ALL")
+    }
+
+    /// Returns the total number of nodes in the tree.
+    pub fn node_count(&self) -> usize {
+        self.attrib_parse("COUNT").unwrap_or(0)
+    }
+
+    /// Gets the title of the node `id`.
+    pub fn title(&self, id: i32) -> Option<String> {
+        self.attrib_at("TITLE", id)
+    }
+
+    /// Sets the title of the node `id`.
+    pub fn set_title<S: Into<String>>(&mut self, id: i32, title: S) -> FlatTree {
+        self.set_attrib_at("TITLE", id, title)
+    }
+
+    /// Gets the check state of the node `id`.
+    ///
+    /// Valid only when SHOWTOGGLE=YES, or SHOWTOGGLE=3STATE to allow `CheckState::Indeterminate`.
+    pub fn toggle_value(&self, id: i32) -> Option<CheckState> {
+        self.attrib_at("TOGGLEVALUE", id).and_then(|value| CheckState::from_attrib(&value))
+    }
+
+    /// Sets the check state of the node `id`, see `toggle_value`.
+    pub fn set_toggle_value(&mut self, id: i32, state: CheckState) -> FlatTree {
+        self.set_attrib_at("TOGGLEVALUE", id, state.as_attrib())
+    }
+
+    /// Changes how the node `id` is shown.
+    pub fn set_node_style(&mut self, id: i32, style: &NodeStyle) -> FlatTree {
+        style.apply(self, id);
+        *self
+    }
+
+    /// Fills the tree from `provider`, branch by branch, styling each node with
+    /// `TreeProvider::style`.
+    ///
+    /// The current nodes are removed and the top-level nodes of the provider are added. The
+    /// children of a branch are only fetched when the branch is opened for the first time, all at
+    /// once, so deep hierarchies load quickly but opening a branch with a great many children
+    /// blocks until they are all added.
+    ///
+    /// This sets the BRANCHOPEN_CB callback and the USERDATA of the nodes, so they must not be
+    /// used directly.
+    pub fn set_provider<P: TreeProvider + 'static>(&mut self, provider: P) -> FlatTree {
+        let loader = Rc::new(RefCell::new(FlatTreeLoader {
+            provider: Box::new(provider),
+            nodes: Vec::new(),
+        }));
+
+        self.clear();
+        loader.borrow_mut().add_children(*self, -1, Vec::new());
+
+        self.set_branchopen_cb(move |(mut tree, id): (FlatTree, i32)| {
+            let key = unsafe {
+                iup_sys::IupGetAttributeId(tree.raw(), cstr!("USERDATA"), id)
+            } as usize;
+            let pending = match loader.borrow_mut().nodes.get_mut(key.wrapping_sub(1)) {
+                Some(node) if !node.loaded => {
+                    node.loaded = true;
+                    Some(node.path.clone())
+                },
+                _ => None,
+            };
+            if let Some(path) = pending {
+                // Remove the placeholder which made the branch expandable.
+                tree.remove_children(id);
+                loader.borrow_mut().add_children(tree, id, path);
+            }
+            CallbackReturn::Default
+        })
+    }
+}
+
+impl_widget!(FlatTree, "flattree");
+
+impl ::callback::MapCb for FlatTree {}
+impl ::callback::UnmapCb for FlatTree {}
+impl ::callback::GetFocusCb for FlatTree {}
+impl ::callback::KillFocusCb for FlatTree {}
+impl ::callback::EnterWindowCb for FlatTree {}
+impl ::callback::LeaveWindowCb for FlatTree {}
+impl ::callback::HelpCb for FlatTree {}
+impl ::callback::KAnyCb for FlatTree {}
+
+/// Action generated when any mouse button is pressed or released inside the tree.
+impl ::callback::button::ButtonCb for FlatTree {}
+
+/// Action generated when the mouse is moved over the tree.
+impl ::callback::button::MotionCb for FlatTree {}
+
+/// Action generated when one or more files are dropped in the element.
+impl ::callback::DropFilesCb for FlatTree {}
+
+/// See the `SelectionCb` documentation.
+impl SelectionCb for FlatTree {}
+/// See the `MultiSelectionCb` documentation.
+impl MultiSelectionCb for FlatTree {}
+/// See the `MultiUnselectionCb` documentation.
+impl MultiUnselectionCb for FlatTree {}
+/// See the `BranchOpenCb` documentation.
+impl BranchOpenCb for FlatTree {}
+/// See the `BranchCloseCb` documentation.
+impl BranchCloseCb for FlatTree {}
+/// See the `ExecuteLeafCb` documentation.
+impl ExecuteLeafCb for FlatTree {}
+/// See the `ToggleValueCb` documentation.
+impl ToggleValueCb for FlatTree {}
+/// See the `ShowRenameCb` documentation.
+impl ShowRenameCb for FlatTree {}
+/// See the `RenameCb` documentation.
+impl RenameCb for FlatTree {}
+
+struct FlatTreeLoader {
+    provider: Box<TreeProvider>,
+    /// The nodes added from the provider, the USERDATA of a node is its index plus 1.
+    nodes: Vec<ProviderNode>,
+}
+
+struct ProviderNode {
+    path: Vec<usize>,
+    /// Whether the children of the branch were already added.
+    loaded: bool,
+}
+
+impl FlatTreeLoader {
+    /// Adds the children of the node at `path`, which has the id `parent` (-1 for the top level).
+    fn add_children(&mut self, mut tree: FlatTree, parent: i32, path: Vec<usize>) {
+        let count = self.provider.child_count(&path);
+        let mut previous = None;
+        for i in 0..count {
+            let mut child = path.clone();
+            child.push(i);
+            let title = self.provider.title(&child);
+            let is_branch = self.provider.is_branch(&child);
+
+            match (previous, is_branch) {
+                (None, true) => tree.add_branch(parent, title),
+                (None, false) => tree.add_leaf(parent, title),
+                (Some(prev), true) => tree.insert_branch(prev, title),
+                (Some(prev), false) => tree.insert_leaf(prev, title),
+            };
+
+            let id = match tree.last_added_node() {
+                Some(id) => id,
+                None => break,
+            };
+            tree.set_node_style(id, &self.provider.style(&child));
+            self.nodes.push(ProviderNode { path: child, loaded: false });
+            // USERDATA is stored as a pointer, not copied as a string.
+            let key = self.nodes.len() as *const c_char;
+            unsafe { iup_sys::IupSetAttributeId(tree.raw(), cstr!("USERDATA"), id, key) };
+
+            if is_branch {
+                // A placeholder child allows the branch to be expanded before being loaded.
+                tree.add_leaf(id, "");
+            }
+            previous = Some(id);
+        }
+    }
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupFlatTree() -> *mut iup_sys::Ihandle;
+}
@@ -0,0 +1,160 @@
+use iup_sys;
+use libc::{c_char, c_int};
+use std::ptr;
+use std::result::Result;
+
+use Element;
+use native;
+use dialog::Dialog;
+use element::Widget;
+
+/// A canvas with an OpenGL context, from the `iupgl` library.
+///
+/// Requires the `gl` feature, and `Application::gl` or IupGLCanvasOpen to be called once IUP is
+/// initialized. The OpenGL functions themselves come from any loader (e.g. the `gl` crate),
+/// called between `make_current` and `swap_buffers`.
+///
+/// Sizes such as DRAWSIZE are in the units of the windowing system, which on scaled displays
+/// are not the pixels of the framebuffer. Use `framebuffer_size` for the OpenGL viewport,
+/// otherwise the rendering is stretched and blurry.
+///
+/// See the [IUP GLCanvas Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/ctrl/iupglcanvas.html
+pub struct GLCanvas(*mut iup_sys::Ihandle);
+
+impl GLCanvas {
+    /// Creates a double buffered OpenGL canvas.
+    pub fn new() -> GLCanvas {
+        unsafe { GLCanvas::from_raw(IupGLCanvas(ptr::null())) }
+            .set_attrib("BUFFER", "DOUBLE")
+    }
+
+    /// Makes the context of the canvas the current OpenGL context, the canvas must be mapped.
+    pub fn make_current(&self) {
+        unsafe { IupGLMakeCurrent(self.raw()) };
+    }
+
+    /// Checks whether the context of the canvas is the current OpenGL context.
+    pub fn is_current(&self) -> bool {
+        unsafe { IupGLIsCurrent(self.raw()) != 0 }
+    }
+
+    /// Shows what was rendered, when double buffered.
+    pub fn swap_buffers(&self) {
+        unsafe { IupGLSwapBuffers(self.raw()) };
+    }
+
+    /// Makes the context current, calls `f` with the framebuffer size, then swaps the buffers.
+    pub fn render<R, F: FnOnce(u32, u32) -> R>(&self, f: F) -> R {
+        self.make_current();
+        let (width, height) = self.framebuffer_size();
+        let result = f(width, height);
+        self.swap_buffers();
+        result
+    }
+
+    /// Gets the scale from the units of the windowing system to framebuffer pixels, see
+    /// `native::scale_factor`.
+    pub fn scale_factor(&self) -> f64 {
+        native::scale_factor(self)
+    }
+
+    /// Gets the size of the framebuffer in pixels, e.g. for `glViewport`.
+    ///
+    /// This is the DRAWSIZE scaled by `scale_factor`, or (0, 0) if the canvas is not mapped.
+    pub fn framebuffer_size(&self) -> (u32, u32) {
+        let (width, height) = self.attrib("DRAWSIZE").and_then(|size| {
+            let mut parts = size.splitn(2, 'x').map(|s| s.trim().parse::<u32>().ok());
+            match (parts.next(), parts.next()) {
+                (Some(Some(width)), Some(Some(height))) => Some((width, height)),
+                _ => None,
+            }
+        }).unwrap_or((0, 0));
+        let scale = self.scale_factor();
+        ((width as f64 * scale).round() as u32, (height as f64 * scale).round() as u32)
+    }
+}
+
+impl_widget!(GLCanvas, "glcanvas");
+impl ::callback::MapCb for GLCanvas {}
+impl ::callback::UnmapCb for GLCanvas {}
+impl ::callback::GetFocusCb for GLCanvas {}
+impl ::callback::KillFocusCb for GLCanvas {}
+impl ::callback::EnterWindowCb for GLCanvas {}
+impl ::callback::LeaveWindowCb for GLCanvas {}
+impl ::callback::HelpCb for GLCanvas {}
+impl ::callback::KAnyCb for GLCanvas {}
+
+/// Action generated when any mouse button is pressed or released inside the canvas.
+impl ::callback::button::ButtonCb for GLCanvas {}
+
+/// Action generated when the mouse is moved over the canvas.
+impl ::callback::button::MotionCb for GLCanvas {}
+
+/// Action generated when the mouse wheel is rotated over the canvas.
+impl ::callback::button::WheelCb for GLCanvas {}
+
+/// Action generated when the canvas size is changed.
+impl ::callback::ResizeCb for GLCanvas {}
+
+/// See the `CanvasAction` documentation.
+impl ::control::CanvasAction for GLCanvas {}
+
+/// An OpenGL context which is never shown, to render into framebuffer objects, e.g. for
+/// thumbnails or exporting images.
+///
+/// The context belongs to a canvas in a dialog that is mapped but hidden, destroyed when this is
+/// dropped. The default framebuffer of a hidden window has undefined contents, so rendering must
+/// target a framebuffer object created by the application.
+///
+/// ```ignore
+/// let offscreen = OffscreenGL::new(512, 512).unwrap();
+/// let pixels = offscreen.render(|| {
+///     // Bind a framebuffer object, draw, then glReadPixels.
+/// });
+/// ```
+pub struct OffscreenGL {
+    dialog: Dialog,
+    canvas: GLCanvas,
+}
+
+impl OffscreenGL {
+    /// Creates a hidden OpenGL context of `width` by `height` pixels.
+    pub fn new(width: u32, height: u32) -> Result<OffscreenGL, String> {
+        let canvas = GLCanvas::new().set_attrib("RASTERSIZE", format!("{}x{}", width, height));
+        let mut dialog = Dialog::new(canvas);
+        match dialog.map() {
+            Ok(_) => Ok(OffscreenGL { dialog: dialog, canvas: canvas }),
+            Err(_) => {
+                dialog.destroy();
+                Err("Failed to create the OpenGL context".into())
+            },
+        }
+    }
+
+    /// Gets the hidden canvas owning the context.
+    pub fn canvas(&self) -> GLCanvas {
+        self.canvas
+    }
+
+    /// Makes the context current and calls `f`, returning its result.
+    pub fn render<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        self.canvas.make_current();
+        f()
+    }
+}
+
+impl Drop for OffscreenGL {
+    fn drop(&mut self) {
+        self.dialog.destroy();
+    }
+}
+
+// Not yet bound by iup-sys.
+#[link(name = "iupgl")]
+extern {
+    fn IupGLCanvas(action: *const c_char) -> *mut iup_sys::Ihandle;
+    fn IupGLMakeCurrent(ih: *mut iup_sys::Ihandle);
+    fn IupGLIsCurrent(ih: *mut iup_sys::Ihandle) -> c_int;
+    fn IupGLSwapBuffers(ih: *mut iup_sys::Ihandle);
+}
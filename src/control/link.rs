@@ -0,0 +1,32 @@
+use iup_sys;
+use std::ffi::CString;
+
+use Element;
+
+/// A label showing an hyperlink, which opens its URL in the web browser when clicked.
+///
+/// See the [IUP Link Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iuplink.html
+pub struct Link(*mut iup_sys::Ihandle);
+
+impl Link {
+    /// Creates a link to `url`, showing `title` (or the URL itself when empty).
+    pub fn new<S1, S2>(url: S1, title: S2) -> Link where S1: Into<String>, S2: Into<String> {
+        let title = title.into();
+        let curl = CString::new(url.into()).unwrap();
+        let ctitle = CString::new(title.clone()).unwrap();
+        let title_ptr = if title.is_empty() { curl.as_ptr() } else { ctitle.as_ptr() };
+        unsafe { Link::from_raw(iup_sys::IupLink(curl.as_ptr(), title_ptr)) }
+    }
+
+    /// Gets the URL opened by the link.
+    pub fn url(&self) -> Option<String> {
+        self.attrib("URL")
+    }
+}
+
+impl_widget!(Link, "link");
+impl ::callback::MapCb for Link {}
+impl ::callback::UnmapCb for Link {}
+impl ::callback::EnterWindowCb for Link {}
+impl ::callback::LeaveWindowCb for Link {}
@@ -1,9 +1,13 @@
 use iup_sys;
 use libc::{c_char, c_int};
-use std::ptr;
+use std::{mem, ptr};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
 use Element;
-use callback::IntoRust;
+use WeakHandle;
+use timer::{self, Timer};
+use callback::{IntoRust, ActionCb, CaretCb, EditReturn};
 
 /// See the [IUP List Documentation][1].
 /// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iuplist.html
@@ -31,7 +35,7 @@ impl List {
     pub fn set_items<A>(&mut self, items: A) -> Self where A: AsRef<[String]> {
         self.clear();
         for (i, value) in items.as_ref().iter().enumerate() {
-            self.set_attrib((i+1).to_string(), value.clone());
+            self.set_attrib_at("", (i+1) as i32, value.clone());
         }
         *self
     }
@@ -42,13 +46,239 @@ impl List {
     /// Panics if id is less than 1.
     pub fn item<A>(&self, id: u32) -> Option<String> {
         assert!(id > 0);
-        self.attrib(id.to_string())
+        self.attrib_at("", id as i32)
+    }
+
+    /// Returns the ids (starting from 1) of the selected items, in ascending order.
+    ///
+    /// Valid for lists with MULTIPLE=YES, otherwise it's just the selected item, if any.
+    pub fn selected_items(&self) -> Vec<u32> {
+        match self.attrib("VALUE") {
+            Some(ref value) if self.attrib("MULTIPLE").map_or(false, |m| m == "YES") => {
+                value.bytes().enumerate()
+                     .filter(|&(_, mark)| mark == b'+')
+                     .map(|(i, _)| (i + 1) as u32)
+                     .collect()
+            },
+            Some(value) => value.parse().ok().into_iter().filter(|&id| id > 0).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Selects exactly the items in `ids` (starting from 1). Valid only when MULTIPLE=YES.
+    pub fn set_selected_items(&mut self, ids: &[u32]) -> Self {
+        let count = self.attrib_parse("COUNT").unwrap_or(0);
+        let mut marked: Vec<u8> = (0..count).map(|_| b'-').collect();
+        for &id in ids {
+            if id > 0 {
+                if let Some(mark) = marked.get_mut(id as usize - 1) {
+                    *mark = b'+';
+                }
+            }
+        }
+        self.set_attrib("VALUE", String::from_utf8(marked).unwrap())
+    }
+
+    /// Deselects all the items.
+    pub fn clear_selection(&mut self) -> Self {
+        if self.attrib("MULTIPLE").map_or(false, |m| m == "YES") {
+            self.set_selected_items(&[])
+        } else {
+            self.set_attrib("VALUE", "0")
+        }
     }
 
     /// Clears the list of items. Ignored if called before being mapped.
     pub fn clear(&mut self) -> Self {
         self.set_attrib("REMOVEITEM", "ALL")
     }
+
+    /// Appends an item to the end of the list. Ignored if called before being mapped.
+    pub fn append_item<S: Into<String>>(&mut self, value: S) -> Self {
+        self.set_attrib("APPENDITEM", value)
+    }
+
+    /// Appends several items to the end of the list. Ignored if called before being mapped.
+    pub fn append_items<A>(&mut self, items: A) -> Self where A: AsRef<[String]> {
+        self.update(|list| for value in items.as_ref().iter() {
+            list.append_item(value.clone());
+        })
+    }
+
+    /// Inserts an item before the item `id` (starting from 1). Ignored if called before being mapped.
+    pub fn insert_item<S: Into<String>>(&mut self, id: u32, value: S) -> Self {
+        self.set_attrib_at("INSERTITEM", id as i32, value)
+    }
+
+    /// Shows only the `items` which start with the text typed in the editbox before the caret,
+    /// ignoring case.
+    ///
+    /// The list is filtered `delay` milliseconds after the user stops typing or moving the
+    /// caret, so typing fast doesn't refill the list on every key. Dropdown lists are dropped
+    /// down when there are matches. Valid only when EDITBOX=YES.
+    ///
+    /// This sets the EDIT_CB and CARET_CB callbacks of the list, replacing the ones set before,
+    /// and setting any of them afterwards stops the filtering. Calling it again replaces the
+    /// items and the delay.
+    pub fn set_filter(&mut self, items: Vec<String>, delay: u32) -> Self {
+        let key = self.raw() as usize;
+        let mut timer = FILTER_TIMERS.with(|timers| {
+            let timer = timers.borrow().get(&key).and_then(|timer| timer.upgrade());
+            timer.unwrap_or_else(|| {
+                let timer = Timer::new().unwrap();
+                timers.borrow_mut().insert(key, timer.downgrade());
+                timer
+            })
+        });
+        timer.stop();
+        timer.set_time(delay.max(10));
+
+        let list = *self;
+        timer.set_action_cb(move |(mut timer,): (Timer,)| {
+            timer.stop();
+            let mut list = list;
+            // Read once the edit happened, EDIT_CB is called before the value changes.
+            let caret = list.attrib("CARETPOS").and_then(|pos| pos.parse().ok()).unwrap_or(0);
+            let value = list.attrib("VALUE").unwrap_or_default();
+            let prefix = value.chars().take(caret).collect::<String>().to_lowercase();
+            let matches: Vec<String> = items.iter()
+                                            .filter(|item| item.to_lowercase().starts_with(&prefix))
+                                            .cloned()
+                                            .collect();
+            list.clear();
+            list.append_items(&matches);
+            if !matches.is_empty() && list.attrib("DROPDOWN").map_or(false, |d| d == "YES") {
+                list.set_attrib("SHOWDROPDOWN", "YES");
+            }
+        });
+
+        self.set_edit_cb(move |_: (List, Option<char>, String)| {
+            timer.stop();
+            timer.run();
+        });
+        self.set_caret_cb(move |_: (List, i32, i32, usize)| {
+            timer.stop();
+            timer.run();
+        })
+    }
+
+    /// Fills the list with the items supplied by `provider`.
+    ///
+    /// Lists have no virtual mode, so all the items end up copied into the list. The current
+    /// items are removed and the first items are added right away, the remaining ones are
+    /// appended in batches from a timer, the events being handled between batches. Calling it
+    /// again cancels the previous load.
+    ///
+    /// The list must be mapped since items are appended with APPENDITEM.
+    pub fn set_provider<P: ListProvider + 'static>(&mut self, provider: P) -> Self {
+        self.clear();
+        let list = self.downgrade();
+        PENDING_LISTS.with(|lists| lists.borrow_mut().retain(|pending| pending.list != list));
+
+        let mut pending = PendingList::new(*self, Box::new(provider));
+        if pending.load_batch() {
+            PENDING_LISTS.with(|lists| lists.borrow_mut().push(pending));
+            list_loader().run();
+        }
+        *self
+    }
+}
+
+/// Supplies the items of a `List`, see `List::set_provider`.
+pub trait ListProvider {
+    /// Returns the number of items.
+    fn len(&self) -> usize;
+    /// Returns the text of the item at `index`, starting from 0.
+    fn item(&self, index: usize) -> String;
+}
+
+impl ListProvider for Vec<String> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn item(&self, index: usize) -> String {
+        self[index].clone()
+    }
+}
+
+/// How many items are appended to a list at once.
+const LOAD_BATCH_SIZE: usize = 500;
+
+thread_local!(static PENDING_LISTS: RefCell<Vec<PendingList>> = RefCell::new(Vec::new()));
+thread_local!(static LOADER: Cell<Option<WeakHandle<Timer>>> = Cell::new(None));
+// The timer of `List::set_filter` of each list, destroyed with the list.
+thread_local!(static FILTER_TIMERS: RefCell<HashMap<usize, WeakHandle<Timer>>> =
+              RefCell::new(HashMap::new()));
+
+/// Destroys the filter timer of `ih`, called when the element is destroyed.
+#[doc(hidden)]
+pub fn element_destroyed(ih: *mut iup_sys::Ihandle) {
+    let timer = FILTER_TIMERS.with(|timers| timers.borrow_mut().remove(&(ih as usize)));
+    // The list may be destroyed from the callback of the timer itself.
+    if let Some(mut timer) = timer.and_then(|timer| timer.upgrade()) {
+        timer.stop();
+        timer.destroy_later();
+    }
+}
+
+/// A list being filled from a provider.
+struct PendingList {
+    list: WeakHandle<List>,
+    provider: Box<ListProvider>,
+    next: usize,
+}
+
+impl PendingList {
+    fn new(list: List, provider: Box<ListProvider>) -> PendingList {
+        PendingList { list: list.downgrade(), provider: provider, next: 0 }
+    }
+
+    /// Appends the next batch of items, returns whether there are more items to load.
+    ///
+    /// There is nothing left to load once the list was destroyed.
+    fn load_batch(&mut self) -> bool {
+        let mut list = match self.list.upgrade() {
+            Some(list) => list,
+            None => return false,
+        };
+        let end = self.provider.len().min(self.next + LOAD_BATCH_SIZE);
+        for i in self.next..end {
+            list.set_attrib("APPENDITEM", self.provider.item(i));
+        }
+        self.next = end;
+        end < self.provider.len()
+    }
+}
+
+/// Gets (or lazily creates) the timer which appends the batches of pending lists.
+fn list_loader() -> Timer {
+    match LOADER.with(|loader| loader.get()).and_then(|loader| loader.upgrade()) {
+        Some(timer) => timer,
+        None => {
+            let mut timer = timer::owned_timer().set_time(10);
+            LOADER.with(|loader| loader.set(Some(timer.downgrade())));
+            timer.set_action_cb(|(mut timer,): (Timer,)| {
+                // Taken out so providers are free to call `set_provider` again.
+                let lists = PENDING_LISTS.with(|lists| {
+                    mem::replace(&mut *lists.borrow_mut(), Vec::new())
+                });
+                let mut remaining = Vec::new();
+                for mut pending in lists.into_iter() {
+                    if pending.load_batch() {
+                        remaining.push(pending);
+                    }
+                }
+                PENDING_LISTS.with(|lists| {
+                    let mut lists = lists.borrow_mut();
+                    remaining.extend(lists.drain(..));
+                    *lists = remaining;
+                    if lists.is_empty() {
+                        timer.stop();
+                    }
+                });
+            })
+        },
+    }
 }
 
 impl_widget!(List, "list");
@@ -63,7 +293,7 @@ impl ::callback::KillFocusCb for List {}
 impl ::callback::EnterWindowCb for List {}
 impl ::callback::LeaveWindowCb for List {}
 impl ::callback::HelpCb for List {}
-// TODO impl K_ callbacks when it's implemented.
+impl ::callback::KAnyCb for List {}
 
 // TODO impl future DragSource and DragTarget traits.
 
@@ -98,10 +328,25 @@ impl ::callback::ValueChangedCb for List {}
 // TODO:
 // DBLCLICK_CB
 // MULTISELECT_CB
-// EDIT_CB
 // DROPDOWN_CB
 // DRAGDROP_CB
 
+/// See the `ListEditCb` documentation.
+impl self::ListEditCb for List {}
+impl_callback! {
+    #[doc="Action generated when the text in the editbox is changed, but before its value is"]
+    #[doc="actually changed. Valid only when EDITBOX=YES."]
+    #[doc=""]
+    #[doc="Works just like the `TextAction` callback of `Text`, the `String` parameter is the new"]
+    #[doc="value of the editbox and the callback returns an `EditReturn`."]
+    pub trait ListEditCb where Self: Element {
+        let name = "EDIT_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, c: c_int, newvalue: *const c_char) -> CallbackReturn;
+        fn set_edit_cb<F: Callback(Self, Option<char>, String) -> EditReturn>(&mut self, cb: F) -> Self;
+        fn remove_edit_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
 /// See the `ListAction` documentation.
 impl self::ListAction for List {}
 impl_callback! {
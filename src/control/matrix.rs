@@ -0,0 +1,414 @@
+use iup_sys;
+use libc::{c_char, c_int};
+use std::{mem, ptr};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+
+use Element;
+use element::ConvertXYToPos;
+use control::Mask;
+use callback::button::MouseState;
+
+/// A spreadsheet-like grid of cells, from the `iupcontrols` library.
+///
+/// Requires the `controls` feature, and `Application::controls` or IupControlsOpen to be
+/// called once IUP is initialized.
+///
+/// Line 0 and column 0 of the matrix are the titles, cells start at 1.
+///
+/// See the [IUP Matrix Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/ctrl/iupmatrix.html
+pub struct Matrix(*mut iup_sys::Ihandle);
+
+impl Matrix {
+    /// Creates an empty matrix.
+    pub fn new() -> Matrix {
+        unsafe { Matrix::from_raw(IupMatrix(ptr::null())) }
+    }
+
+    /// Sets the number of lines and columns, not counting the titles.
+    pub fn set_size(&mut self, lines: usize, cols: usize) -> Matrix {
+        self.set_attrib("NUMLIN", lines.to_string());
+        self.set_attrib("NUMCOL", cols.to_string())
+    }
+
+    /// Sets the text of the cell at (`lin`, `col`), not available while a model is set.
+    pub fn set_cell<S: Into<String>>(&mut self, lin: i32, col: i32, value: S) -> Matrix {
+        self.set_attrib_at2("", lin, col, value)
+    }
+
+    /// Gets the text of the cell at (`lin`, `col`).
+    pub fn cell(&self, lin: i32, col: i32) -> Option<String> {
+        self.attrib_at2("", lin, col)
+    }
+
+    /// Restricts the characters the user can type in the cell at (`lin`, `col`), see
+    /// `Text::set_mask`. The mask is checked while the cell is edited.
+    pub fn set_cell_mask(&mut self, lin: i32, col: i32, mask: Mask) -> Matrix {
+        self.set_mask_at(format!("{}:{}", lin, col), mask)
+    }
+
+    /// Removes the mask of the cell at (`lin`, `col`) set with `set_cell_mask`.
+    pub fn remove_cell_mask(&mut self, lin: i32, col: i32) -> Matrix {
+        self.remove_mask_at(format!("{}:{}", lin, col))
+    }
+
+    /// Restricts the characters the user can type in the cells of the column `col`, except the
+    /// cells with their own mask (see `set_cell_mask`).
+    pub fn set_col_mask(&mut self, col: i32, mask: Mask) -> Matrix {
+        self.set_mask_at(format!("*:{}", col), mask)
+    }
+
+    /// Removes the mask of the column `col` set with `set_col_mask`.
+    pub fn remove_col_mask(&mut self, col: i32) -> Matrix {
+        self.remove_mask_at(format!("*:{}", col))
+    }
+
+    /// Shows the data of `model` in callback mode, so the cells are asked to the model when
+    /// drawn instead of being copied into the matrix.
+    ///
+    /// The model rows and columns are indexed from 0 and map to the lines and columns of the
+    /// matrix starting from 1, the titles come from `MatrixModel::row_title` and
+    /// `MatrixModel::col_title`.
+    ///
+    /// To keep changing the data, share it as a `Rc<RefCell<M>>` and call `refresh_model` after
+    /// each change.
+    pub fn set_model<M: MatrixModel + 'static>(&mut self, model: M) -> Matrix {
+        let raw = self.raw();
+        let model: Box<MatrixModel> = Box::new(model);
+        MODELS.with(|models| {
+            models.borrow_mut().insert(raw as usize, BoundModel {
+                model: Rc::new(RefCell::new(model)),
+                buffer: CString::new("").unwrap(),
+            });
+        });
+
+        unsafe {
+            let value_cb = on_value as extern fn(*mut iup_sys::Ihandle, c_int,
+                                                 c_int) -> *mut c_char;
+            let value_edit_cb = on_value_edit as extern fn(*mut iup_sys::Ihandle, c_int, c_int,
+                                                           *mut c_char) -> c_int;
+            let edition_cb = on_edition as extern fn(*mut iup_sys::Ihandle, c_int, c_int, c_int,
+                                                     c_int) -> c_int;
+            let dropcheck_cb = on_dropcheck as extern fn(*mut iup_sys::Ihandle, c_int,
+                                                         c_int) -> c_int;
+            let drop_cb = on_drop as extern fn(*mut iup_sys::Ihandle, *mut iup_sys::Ihandle, c_int,
+                                               c_int) -> c_int;
+            let togglevalue_cb = on_togglevalue as extern fn(*mut iup_sys::Ihandle, c_int, c_int,
+                                                             c_int) -> c_int;
+            iup_sys::IupSetCallback(raw, cstr!("VALUE_CB"), mem::transmute(value_cb));
+            iup_sys::IupSetCallback(raw, cstr!("VALUE_EDIT_CB"), mem::transmute(value_edit_cb));
+            iup_sys::IupSetCallback(raw, cstr!("EDITION_CB"), mem::transmute(edition_cb));
+            iup_sys::IupSetCallback(raw, cstr!("DROPCHECK_CB"), mem::transmute(dropcheck_cb));
+            iup_sys::IupSetCallback(raw, cstr!("DROP_CB"), mem::transmute(drop_cb));
+            iup_sys::IupSetCallback(raw, cstr!("TOGGLEVALUE_CB"), mem::transmute(togglevalue_cb));
+        }
+        self.refresh_model()
+    }
+
+    /// Gets the (lin, col) of the cell at x,y (relative to the element), or `None` if there's
+    /// no cell there. Titles are line or column 0.
+    pub fn cell_at(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let cols = self.attrib_parse::<i32, _>("NUMCOL").unwrap_or(0) + 1;
+        self.convert_xy_to_pos(x, y).map(|pos| (pos / cols, pos % cols))
+    }
+
+    /// Updates the matrix after the data of its model changed, including its size.
+    pub fn refresh_model(&mut self) -> Matrix {
+        let raw = self.raw();
+        let size = with_model(raw, |model| (model.rows(), model.cols()));
+        if let Some((rows, cols)) = size {
+            self.set_size(rows, cols);
+        }
+        self.set_attrib("REDRAW", "ALL")
+    }
+
+    /// Sets the mask attributes of the cells `at`, e.g. "2:3" for a cell or "*:3" for a column.
+    fn set_mask_at(&mut self, at: String, mask: Mask) -> Matrix {
+        self.remove_mask_at(at.clone());
+        if mask.is_case_insensitive() {
+            self.set_attrib(format!("MASKCASEI{}", at), "YES");
+        }
+        let (name, value) = mask.attrib();
+        self.set_attrib(format!("{}{}", name, at), value)
+    }
+
+    fn remove_mask_at(&mut self, at: String) -> Matrix {
+        self.clear_attrib(format!("MASK{}", at));
+        self.set_attrib(format!("MASKCASEI{}", at), "NO")
+    }
+}
+
+impl_widget!(Matrix, "matrix");
+
+impl ::callback::MapCb for Matrix {}
+impl ::callback::UnmapCb for Matrix {}
+impl ::callback::GetFocusCb for Matrix {}
+impl ::callback::KillFocusCb for Matrix {}
+impl ::callback::EnterWindowCb for Matrix {}
+impl ::callback::LeaveWindowCb for Matrix {}
+impl ::callback::HelpCb for Matrix {}
+impl ::callback::KAnyCb for Matrix {}
+
+/// The position is `lin * (NUMCOL + 1) + col`, see `Matrix::cell_at`.
+impl ::element::ConvertXYToPos for Matrix {}
+
+/// See the `MatrixClickCb` documentation.
+impl MatrixClickCb for Matrix {}
+
+impl_callback! {
+    #[doc="Action generated when a cell is clicked, with its line and column (0 for the titles)."]
+    #[doc=""]
+    #[doc="The `MouseState` tells the button used and whether it was a double click."]
+    #[doc="`CallbackReturn::Ignore` prevents the cell from being edited on a double click."]
+    pub trait MatrixClickCb where Self: Element {
+        let name = "CLICK_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int, status: *mut c_char) -> CallbackReturn;
+        fn set_click_cb<F: Callback(Self, i32, i32, MouseState)>(&mut self, cb: F) -> Self;
+        fn remove_click_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// How a cell of a `MatrixModel` is shown and edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellType {
+    /// A text edited in place.
+    Text,
+    /// A check box, whose value is "1" when checked and "0" otherwise.
+    Toggle,
+    /// A text selected from a drop down with the given choices.
+    Dropdown(Vec<String>),
+}
+
+/// Supplies the cells of a `Matrix`, see `Matrix::set_model`.
+///
+/// Rows and columns are indexed from 0.
+pub trait MatrixModel {
+    /// Returns the number of rows.
+    fn rows(&self) -> usize;
+    /// Returns the number of columns.
+    fn cols(&self) -> usize;
+    /// Returns the text of a cell.
+    fn value(&self, row: usize, col: usize) -> String;
+
+    /// Returns the title of a row, empty by default.
+    fn row_title(&self, _row: usize) -> String {
+        String::new()
+    }
+
+    /// Returns the title of a column, empty by default.
+    fn col_title(&self, _col: usize) -> String {
+        String::new()
+    }
+
+    /// Returns how a cell is shown and edited, a text by default.
+    fn cell_type(&self, _row: usize, _col: usize) -> CellType {
+        CellType::Text
+    }
+
+    /// Returns whether the user can change a cell, false by default.
+    fn is_editable(&self, _row: usize, _col: usize) -> bool {
+        false
+    }
+
+    /// Stores the value the user entered in a cell.
+    ///
+    /// Returns whether the value was accepted, which is false by default.
+    fn set_value(&mut self, _row: usize, _col: usize, _value: &str) -> bool {
+        false
+    }
+}
+
+impl MatrixModel for Vec<Vec<String>> {
+    fn rows(&self) -> usize {
+        self.len()
+    }
+    fn cols(&self) -> usize {
+        self.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+    fn value(&self, row: usize, col: usize) -> String {
+        self[row].get(col).cloned().unwrap_or_default()
+    }
+    fn is_editable(&self, _row: usize, _col: usize) -> bool {
+        true
+    }
+    fn set_value(&mut self, row: usize, col: usize, value: &str) -> bool {
+        if self[row].len() <= col {
+            self[row].resize(col + 1, String::new());
+        }
+        self[row][col] = value.to_string();
+        true
+    }
+}
+
+impl<M: MatrixModel> MatrixModel for Rc<RefCell<M>> {
+    fn rows(&self) -> usize {
+        self.borrow().rows()
+    }
+    fn cols(&self) -> usize {
+        self.borrow().cols()
+    }
+    fn value(&self, row: usize, col: usize) -> String {
+        self.borrow().value(row, col)
+    }
+    fn row_title(&self, row: usize) -> String {
+        self.borrow().row_title(row)
+    }
+    fn col_title(&self, col: usize) -> String {
+        self.borrow().col_title(col)
+    }
+    fn cell_type(&self, row: usize, col: usize) -> CellType {
+        self.borrow().cell_type(row, col)
+    }
+    fn is_editable(&self, row: usize, col: usize) -> bool {
+        self.borrow().is_editable(row, col)
+    }
+    fn set_value(&mut self, row: usize, col: usize, value: &str) -> bool {
+        self.borrow_mut().set_value(row, col, value)
+    }
+}
+
+// The model of each matrix, removed when the matrix is destroyed.
+thread_local!(static MODELS: RefCell<HashMap<usize, BoundModel>> = RefCell::new(HashMap::new()));
+
+/// A model shown by a matrix.
+struct BoundModel {
+    model: Rc<RefCell<Box<MatrixModel>>>,
+    /// Holds the last value given to the matrix, which must outlive VALUE_CB.
+    buffer: CString,
+}
+
+/// Forgets the model of `ih`, called when the element is destroyed.
+#[doc(hidden)]
+pub fn element_destroyed(ih: *mut iup_sys::Ihandle) {
+    // Taken out first, dropping the model may use the matrices.
+    let bound = MODELS.with(|models| models.borrow_mut().remove(&(ih as usize)));
+    drop(bound);
+}
+
+/// Calls `f` with the model of the matrix `ih`, if it has one and it's not in use already.
+///
+/// The models are not borrowed meanwhile, so `f` is free to use the matrix or to replace its
+/// model.
+fn with_model<T, F: FnOnce(&mut MatrixModel) -> T>(ih: *mut iup_sys::Ihandle, f: F) -> Option<T> {
+    let model = MODELS.with(|models| {
+        models.borrow().get(&(ih as usize)).map(|bound| bound.model.clone())
+    });
+    let model = match model {
+        Some(model) => model,
+        None => return None,
+    };
+    let result = match model.try_borrow_mut() {
+        Ok(mut model) => Some(f(&mut **model)),
+        Err(_) => None,
+    };
+    result
+}
+
+/// Converts a matrix line and column into a model cell, `None` for titles.
+fn cell(lin: c_int, col: c_int) -> Option<(usize, usize)> {
+    if lin > 0 && col > 0 { Some((lin as usize - 1, col as usize - 1)) } else { None }
+}
+
+extern fn on_value(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int) -> *mut c_char {
+    let value = with_model(ih, |model| match (lin, col) {
+        (0, 0) => String::new(),
+        (0, col) => model.col_title(col as usize - 1),
+        (lin, 0) => model.row_title(lin as usize - 1),
+        (lin, col) => model.value(lin as usize - 1, col as usize - 1),
+    });
+    let value = match value {
+        Some(value) => CString::new(value).unwrap_or_default(),
+        None => return ptr::null_mut(),
+    };
+    MODELS.with(|models| match models.borrow_mut().get_mut(&(ih as usize)) {
+        Some(bound) => {
+            bound.buffer = value;
+            bound.buffer.as_ptr() as *mut c_char
+        },
+        None => ptr::null_mut(),
+    })
+}
+
+extern fn on_value_edit(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int,
+                        newval: *mut c_char) -> c_int {
+    let value = unsafe { CStr::from_ptr(newval) }.to_string_lossy().into_owned();
+    let accepted = cell(lin, col).and_then(|(row, col)| {
+        with_model(ih, |model| model.set_value(row, col, &value))
+    });
+    match accepted {
+        Some(true) => iup_sys::IUP_DEFAULT,
+        _ => iup_sys::IUP_IGNORE,
+    }
+}
+
+extern fn on_edition(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int, mode: c_int,
+                     _update: c_int) -> c_int {
+    // Leaving the edition is always allowed, the value is checked by VALUE_EDIT_CB.
+    if mode != 1 {
+        return iup_sys::IUP_DEFAULT;
+    }
+    let editable = cell(lin, col).and_then(|(row, col)| {
+        with_model(ih, |model| {
+            model.is_editable(row, col) && model.cell_type(row, col) != CellType::Toggle
+        })
+    });
+    match editable {
+        Some(true) => iup_sys::IUP_DEFAULT,
+        _ => iup_sys::IUP_IGNORE,
+    }
+}
+
+extern fn on_dropcheck(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int) -> c_int {
+    let cell_type = cell(lin, col).and_then(|(row, col)| {
+        with_model(ih, |model| model.cell_type(row, col))
+    });
+    match cell_type {
+        Some(CellType::Dropdown(_)) => iup_sys::IUP_DEFAULT,
+        Some(CellType::Toggle) => iup_sys::IUP_CONTINUE,
+        _ => iup_sys::IUP_IGNORE,
+    }
+}
+
+extern fn on_drop(ih: *mut iup_sys::Ihandle, drop: *mut iup_sys::Ihandle, lin: c_int,
+                  col: c_int) -> c_int {
+    let cell = cell(lin, col).and_then(|(row, col)| {
+        with_model(ih, |model| (model.cell_type(row, col), model.value(row, col)))
+    });
+    match cell {
+        Some((CellType::Dropdown(choices), current)) => {
+            for (i, choice) in choices.iter().enumerate() {
+                let cchoice = CString::new(&choice[..]).unwrap_or_default();
+                unsafe { iup_sys::IupSetStrAttributeId(drop, cstr!(""), (i + 1) as c_int,
+                                                       cchoice.as_ptr()) };
+                if *choice == current {
+                    let cvalue = CString::new((i + 1).to_string()).unwrap();
+                    unsafe { iup_sys::IupSetStrAttribute(drop, cstr!("VALUE"), cvalue.as_ptr()) };
+                }
+            }
+            iup_sys::IUP_DEFAULT
+        },
+        _ => iup_sys::IUP_IGNORE,
+    }
+}
+
+extern fn on_togglevalue(ih: *mut iup_sys::Ihandle, lin: c_int, col: c_int,
+                         status: c_int) -> c_int {
+    let value = if status != 0 { "1" } else { "0" };
+    let accepted = cell(lin, col).and_then(|(row, col)| {
+        with_model(ih, |model| model.is_editable(row, col) && model.set_value(row, col, value))
+    });
+    if accepted != Some(true) {
+        // Draw the model value back.
+        unsafe { iup_sys::IupSetAttribute(ih, cstr!("REDRAW"), cstr!("ALL")) };
+    }
+    iup_sys::IUP_DEFAULT
+}
+
+// Not yet bound by iup-sys.
+#[link(name = "iupcontrols")]
+extern {
+    fn IupMatrix(action: *const c_char) -> *mut iup_sys::Ihandle;
+}
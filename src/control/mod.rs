@@ -2,17 +2,47 @@
 //! [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/controls.html
 
 pub mod label;
+pub mod link;
 pub mod text;
 pub mod button;
 pub mod progress;
 pub mod toggle;
 pub mod frame;
 pub mod list;
+pub mod canvas;
+pub mod tree;
+pub mod flattree;
+pub mod proxy;
+pub mod selection;
+pub mod spell;
+pub mod search;
+#[cfg(feature = "controls")]
+pub mod matrix;
+#[cfg(feature = "csv")]
+pub mod delimited;
+#[cfg(feature = "gl")]
+pub mod glcanvas;
 
-pub use self::text::{Text, TextAction};
+pub use self::text::{Text, TextAction, FormatTag, Mask, Alignment, BulletStyle};
 pub use self::label::Label;
+pub use self::link::Link;
 pub use self::button::Button;
 pub use self::progress::ProgressBar;
 pub use self::toggle::{Toggle, ToggleAction};
 pub use self::frame::Frame;
-pub use self::list::{List, ListAction};
+pub use self::list::{List, ListAction, ListEditCb, ListProvider, ListItemState};
+pub use self::canvas::{Canvas, CanvasAction};
+pub use self::tree::{Tree, TreeProvider, SelectionCb, BranchOpenCb, BranchCloseCb, ExecuteLeafCb};
+pub use self::tree::{MultiSelectionCb, MultiUnselectionCb, ShowRenameCb, RenameCb};
+pub use self::tree::{CheckState, ToggleValueCb, TreeData, NodeStyle};
+pub use self::flattree::FlatTree;
+pub use self::proxy::SortFilterProxy;
+pub use self::selection::{SelectionModel, Selectable};
+pub use self::spell::SpellChecker;
+pub use self::search::TextSearch;
+#[cfg(feature = "controls")]
+pub use self::matrix::{Matrix, MatrixModel, CellType, MatrixClickCb};
+#[cfg(feature = "csv")]
+pub use self::delimited::CsvOptions;
+#[cfg(feature = "gl")]
+pub use self::glcanvas::{GLCanvas, OffscreenGL};
@@ -0,0 +1,297 @@
+//! Sorting and filtering of list and matrix models.
+use std::cmp::Ordering;
+
+use i18n;
+use control::ListProvider;
+#[cfg(feature = "controls")]
+use control::{MatrixModel, CellType};
+
+/// A sorted and filtered view of a `ListProvider` or `MatrixModel`, which is itself a model of
+/// the same kind.
+///
+/// The source model is never changed by sorting or filtering, the rows of the view are mapped
+/// back to the rows of the source with `map_to_source`. Sorting is stable, so rows comparing
+/// equal keep their source order.
+///
+/// ```ignore
+/// let mut people = SortFilterProxy::for_matrix(people_table);
+/// people.sort_by_column(1, true);
+/// people.set_filter_text("ana");
+/// matrix.set_model(Rc::new(RefCell::new(people)));
+/// ```
+///
+/// The mapping is updated when the sorting or filtering changes, and must be updated with
+/// `invalidate` after the source model changes.
+pub struct SortFilterProxy<M> {
+    source: M,
+    /// The source row of each row of the view.
+    mapping: Vec<usize>,
+    sort: Option<Box<Fn(&M, usize, usize) -> Ordering>>,
+    filter: Option<Box<Fn(&M, usize) -> bool>>,
+    rows: fn(&M) -> usize,
+    cols: fn(&M) -> usize,
+    text: fn(&M, usize, usize) -> String,
+}
+
+impl<M: ListProvider + 'static> SortFilterProxy<M> {
+    /// Creates an unsorted and unfiltered view of the items of a list.
+    pub fn for_list(source: M) -> SortFilterProxy<M> {
+        SortFilterProxy::new(source, list_rows::<M>, list_cols::<M>, list_text::<M>)
+    }
+}
+
+#[cfg(feature = "controls")]
+impl<M: MatrixModel + 'static> SortFilterProxy<M> {
+    /// Creates an unsorted and unfiltered view of the rows of a matrix.
+    pub fn for_matrix(source: M) -> SortFilterProxy<M> {
+        SortFilterProxy::new(source, matrix_rows::<M>, matrix_cols::<M>, matrix_text::<M>)
+    }
+}
+
+impl<M: 'static> SortFilterProxy<M> {
+    fn new(source: M, rows: fn(&M) -> usize, cols: fn(&M) -> usize,
+           text: fn(&M, usize, usize) -> String) -> SortFilterProxy<M> {
+        let mut proxy = SortFilterProxy {
+            source: source,
+            mapping: Vec::new(),
+            sort: None,
+            filter: None,
+            rows: rows,
+            cols: cols,
+            text: text,
+        };
+        proxy.invalidate();
+        proxy
+    }
+
+    /// Sorts the rows by the text of column `col` (0 for lists), in the order of the current
+    /// language.
+    pub fn sort_by_column(&mut self, col: usize, ascending: bool) {
+        let text = self.text;
+        self.sort_by(move |source, a, b| {
+            let order = i18n::compare(&text(source, a, col), &text(source, b, col), false, true);
+            if ascending { order } else { order.reverse() }
+        });
+    }
+
+    /// Sorts the rows with a comparator of two source rows.
+    pub fn sort_by<F>(&mut self, cmp: F) where F: Fn(&M, usize, usize) -> Ordering + 'static {
+        self.sort = Some(Box::new(cmp));
+        self.invalidate();
+    }
+
+    /// Shows the rows in the source order.
+    pub fn clear_sort(&mut self) {
+        self.sort = None;
+        self.invalidate();
+    }
+
+    /// Shows only the source rows for which `filter` returns true.
+    pub fn set_filter<F>(&mut self, filter: F) where F: Fn(&M, usize) -> bool + 'static {
+        self.filter = Some(Box::new(filter));
+        self.invalidate();
+    }
+
+    /// Shows only the rows with a column containing `text`, ignoring the case.
+    ///
+    /// An empty text shows every row.
+    pub fn set_filter_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return self.clear_filter();
+        }
+        let needle = text.to_lowercase();
+        let (cols, text) = (self.cols, self.text);
+        self.set_filter(move |source, row| {
+            (0..cols(source)).any(|col| text(source, row, col).to_lowercase().contains(&needle))
+        });
+    }
+
+    /// Shows every row.
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+        self.invalidate();
+    }
+
+    /// Updates the view after the source model changed.
+    pub fn invalidate(&mut self) {
+        let source = &self.source;
+        let rows = 0..(self.rows)(source);
+        let mut mapping: Vec<usize> = match self.filter {
+            Some(ref filter) => rows.filter(|&row| filter(source, row)).collect(),
+            None => rows.collect(),
+        };
+        if let Some(ref sort) = self.sort {
+            mapping.sort_by(|&a, &b| sort(source, a, b));
+        }
+        self.mapping = mapping;
+    }
+
+    /// Gets the source row of a row of the view.
+    pub fn map_to_source(&self, row: usize) -> Option<usize> {
+        self.mapping.get(row).cloned()
+    }
+
+    /// Gets the row of the view showing a source row, `None` if it's filtered out.
+    pub fn map_from_source(&self, source_row: usize) -> Option<usize> {
+        self.mapping.iter().position(|&row| row == source_row)
+    }
+
+    /// Returns the number of rows of the view.
+    pub fn len(&self) -> usize {
+        self.mapping.len()
+    }
+
+    /// Checks whether every row is filtered out.
+    pub fn is_empty(&self) -> bool {
+        self.mapping.is_empty()
+    }
+
+    /// Gets the source model.
+    pub fn source(&self) -> &M {
+        &self.source
+    }
+
+    /// Gets the source model to change it, `invalidate` must be called afterwards.
+    pub fn source_mut(&mut self) -> &mut M {
+        &mut self.source
+    }
+
+    /// Returns the source model.
+    pub fn into_source(self) -> M {
+        self.source
+    }
+}
+
+impl<M> ListProvider for SortFilterProxy<M> {
+    fn len(&self) -> usize {
+        self.mapping.len()
+    }
+    fn item(&self, index: usize) -> String {
+        (self.text)(&self.source, self.mapping[index], 0)
+    }
+}
+
+#[cfg(feature = "controls")]
+impl<M: MatrixModel> MatrixModel for SortFilterProxy<M> {
+    fn rows(&self) -> usize {
+        self.mapping.len()
+    }
+    fn cols(&self) -> usize {
+        self.source.cols()
+    }
+    fn value(&self, row: usize, col: usize) -> String {
+        self.source.value(self.mapping[row], col)
+    }
+    fn row_title(&self, row: usize) -> String {
+        self.source.row_title(self.mapping[row])
+    }
+    fn col_title(&self, col: usize) -> String {
+        self.source.col_title(col)
+    }
+    fn cell_type(&self, row: usize, col: usize) -> CellType {
+        self.source.cell_type(self.mapping[row], col)
+    }
+    fn is_editable(&self, row: usize, col: usize) -> bool {
+        self.source.is_editable(self.mapping[row], col)
+    }
+    /// Edits don't update the view, so the edited row stays in place until `invalidate`.
+    fn set_value(&mut self, row: usize, col: usize, value: &str) -> bool {
+        self.source.set_value(self.mapping[row], col, value)
+    }
+}
+
+fn list_rows<M: ListProvider>(source: &M) -> usize {
+    source.len()
+}
+
+fn list_cols<M: ListProvider>(_source: &M) -> usize {
+    1
+}
+
+fn list_text<M: ListProvider>(source: &M, row: usize, _col: usize) -> String {
+    source.item(row)
+}
+
+#[cfg(feature = "controls")]
+fn matrix_rows<M: MatrixModel>(source: &M) -> usize {
+    source.rows()
+}
+
+#[cfg(feature = "controls")]
+fn matrix_cols<M: MatrixModel>(source: &M) -> usize {
+    source.cols()
+}
+
+#[cfg(feature = "controls")]
+fn matrix_text<M: MatrixModel>(source: &M, row: usize, col: usize) -> String {
+    source.value(row, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use control::ListProvider;
+    use super::SortFilterProxy;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn items<P: ListProvider>(proxy: &P) -> Vec<String> {
+        (0..proxy.len()).map(|index| proxy.item(index)).collect()
+    }
+
+    #[test]
+    fn unsorted_view_maps_each_row_to_itself() {
+        let proxy = SortFilterProxy::for_list(strings(&["b", "a", "c"]));
+        assert_eq!(items(&proxy), strings(&["b", "a", "c"]));
+        assert_eq!(proxy.map_to_source(2), Some(2));
+        assert_eq!(proxy.map_to_source(3), None);
+    }
+
+    #[test]
+    fn sorting_is_stable_and_maps_back() {
+        let mut proxy = SortFilterProxy::for_list(strings(&["b2", "a1", "b1", "a2"]));
+        proxy.sort_by(|source, a, b| source[a][..1].cmp(&source[b][..1]));
+        assert_eq!(items(&proxy), strings(&["a1", "a2", "b2", "b1"]));
+        assert_eq!(proxy.map_to_source(0), Some(1));
+        assert_eq!(proxy.map_from_source(0), Some(2));
+        proxy.clear_sort();
+        assert_eq!(items(&proxy), strings(&["b2", "a1", "b1", "a2"]));
+    }
+
+    #[test]
+    fn filtered_out_rows_are_not_mapped() {
+        let mut proxy = SortFilterProxy::for_list(strings(&["Ana", "Bob", "Joana"]));
+        proxy.set_filter_text("ANA");
+        assert_eq!(items(&proxy), strings(&["Ana", "Joana"]));
+        assert_eq!(proxy.map_from_source(1), None);
+        assert_eq!(proxy.map_from_source(2), Some(1));
+        proxy.set_filter_text("");
+        assert_eq!(proxy.len(), 3);
+        proxy.set_filter(|_, row| row > 5);
+        assert!(proxy.is_empty());
+    }
+
+    #[test]
+    fn invalidate_follows_the_source() {
+        let mut proxy = SortFilterProxy::for_list(strings(&["b", "a"]));
+        proxy.sort_by(|source, a, b| source[a].cmp(&source[b]));
+        proxy.source_mut().push("0".to_string());
+        assert_eq!(proxy.len(), 2);
+        proxy.invalidate();
+        assert_eq!(items(&proxy), strings(&["0", "a", "b"]));
+        assert_eq!(proxy.into_source(), strings(&["b", "a", "0"]));
+    }
+
+    #[cfg(feature = "controls")]
+    #[test]
+    fn matrix_filter_text_matches_any_column() {
+        use control::MatrixModel;
+        let rows = vec![strings(&["Ana", "Lisbon"]), strings(&["Bob", "Porto"])];
+        let mut proxy = SortFilterProxy::for_matrix(rows);
+        proxy.set_filter_text("port");
+        assert_eq!(proxy.rows(), 1);
+        assert_eq!(proxy.value(0, 0), "Bob");
+        assert_eq!(proxy.cols(), 2);
+    }
+}
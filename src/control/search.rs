@@ -0,0 +1,265 @@
+//! Incremental search in texts.
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use Element;
+use callback::{KeyReturn, KAnyCb, ValueChangedCb};
+use control::{Text, FormatTag};
+
+/// Key codes of Enter and Shift+Enter.
+const K_CR: i32 = 13;
+const K_SCR: i32 = K_CR | 0x10000000;
+
+/// Background color of the highlighted matches.
+const HIGHLIGHT_COLOR: (u8, u8, u8) = (255, 230, 0);
+
+struct Inner {
+    text: Text,
+    query: String,
+    case_sensitive: bool,
+    whole_words: bool,
+    /// The start and end character positions of the matches.
+    matches: Vec<(usize, usize)>,
+    current: Option<usize>,
+}
+
+/// Finds the occurrences of a query in a `Text` as it's typed, highlighting them and moving
+/// through them with `next` and `previous`.
+///
+/// Clones share the same search. Matches are highlighted only in formatted texts (see
+/// `Text::new_formatted`), otherwise only the current match is shown, as the selection.
+///
+/// ```ignore
+/// let search = TextSearch::new(document);
+/// search.attach(find_text);
+/// next_button.set_action(move |_| { search.next(); });
+/// ```
+#[derive(Clone)]
+pub struct TextSearch(Rc<RefCell<Inner>>);
+
+impl TextSearch {
+    /// Creates a search in `text`, with an empty query.
+    pub fn new(text: Text) -> TextSearch {
+        TextSearch(Rc::new(RefCell::new(Inner {
+            text: text,
+            query: String::new(),
+            case_sensitive: false,
+            whole_words: false,
+            matches: Vec::new(),
+            current: None,
+        })))
+    }
+
+    /// Sets whether the case of the letters must match, false by default.
+    pub fn set_case_sensitive(&self, case_sensitive: bool) {
+        self.0.borrow_mut().case_sensitive = case_sensitive;
+        self.refresh();
+    }
+
+    /// Sets whether only whole words match, false by default.
+    pub fn set_whole_words(&self, whole_words: bool) {
+        self.0.borrow_mut().whole_words = whole_words;
+        self.refresh();
+    }
+
+    /// Searches `query`, selecting the first match at or after the caret.
+    ///
+    /// Returns the number of matches. An empty query clears the search.
+    pub fn search(&self, query: &str) -> usize {
+        self.0.borrow_mut().query = query.to_string();
+        self.refresh()
+    }
+
+    /// Finds the matches of the query again, e.g. after the text changed.
+    ///
+    /// Returns the number of matches.
+    pub fn refresh(&self) -> usize {
+        let mut inner = self.0.borrow_mut();
+        inner.unhighlight();
+        let value = inner.text.attrib("VALUE").unwrap_or_default();
+        inner.matches = find_matches(&value, &inner.query, inner.case_sensitive,
+                                     inner.whole_words);
+        inner.highlight();
+        let caret = inner.text.caret_pos();
+        inner.current = if inner.matches.is_empty() {
+            None
+        } else {
+            Some(inner.matches.iter().position(|&(start, _)| start >= caret).unwrap_or(0))
+        };
+        inner.select_current();
+        inner.matches.len()
+    }
+
+    /// Selects the next match, wrapping around to the first one.
+    ///
+    /// Returns its start and end character positions.
+    pub fn next(&self) -> Option<(usize, usize)> {
+        self.step(|current, len| (current + 1) % len)
+    }
+
+    /// Selects the previous match, wrapping around to the last one.
+    ///
+    /// Returns its start and end character positions.
+    pub fn previous(&self) -> Option<(usize, usize)> {
+        self.step(|current, len| (current + len - 1) % len)
+    }
+
+    /// Gets the start and end character positions of the selected match.
+    pub fn current(&self) -> Option<(usize, usize)> {
+        let inner = self.0.borrow();
+        inner.current.map(|i| inner.matches[i])
+    }
+
+    /// Gets the index of the selected match, starting from 0, e.g. to show "3 of 10".
+    pub fn current_index(&self) -> Option<usize> {
+        self.0.borrow().current
+    }
+
+    /// Gets the start and end character positions of every match.
+    pub fn matches(&self) -> Vec<(usize, usize)> {
+        self.0.borrow().matches.clone()
+    }
+
+    /// Returns the number of matches.
+    pub fn len(&self) -> usize {
+        self.0.borrow().matches.len()
+    }
+
+    /// Checks whether there are no matches.
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().matches.is_empty()
+    }
+
+    /// Removes the highlights and forgets the query.
+    pub fn clear(&self) {
+        self.search("");
+    }
+
+    /// Searches the value of `entry` as it's typed, Enter selecting the next match and
+    /// Shift+Enter the previous one.
+    ///
+    /// This sets the VALUECHANGED_CB and K_ANY callbacks of the entry.
+    pub fn attach(&self, mut entry: Text) {
+        let search = self.clone();
+        entry.set_valuechanged_cb(move |(entry,): (Text,)| {
+            search.search(&entry.attrib("VALUE").unwrap_or_default());
+        });
+        let search = self.clone();
+        entry.set_k_any(move |(_, c): (Text, i32)| {
+            match c {
+                K_CR => { search.next(); KeyReturn::Ignore },
+                K_SCR => { search.previous(); KeyReturn::Ignore },
+                _ => KeyReturn::Default,
+            }
+        });
+    }
+
+    fn step<F: FnOnce(usize, usize) -> usize>(&self, f: F) -> Option<(usize, usize)> {
+        let mut inner = self.0.borrow_mut();
+        let len = inner.matches.len();
+        inner.current = inner.current.map(|current| f(current, len));
+        inner.select_current();
+        inner.current.map(|i| inner.matches[i])
+    }
+}
+
+impl Inner {
+    fn is_formatted(&self) -> bool {
+        self.text.attrib("FORMATTING").map_or(false, |formatting| formatting == "YES")
+    }
+
+    fn highlight(&mut self) {
+        if !self.is_formatted() {
+            return;
+        }
+        for &(start, end) in &self.matches {
+            self.text.format_range(FormatTag::new().bg_color(HIGHLIGHT_COLOR), start, end);
+        }
+    }
+
+    /// Removes the formatting of the matches, keeping the selection.
+    fn unhighlight(&mut self) {
+        if !self.is_formatted() || self.matches.is_empty() {
+            return;
+        }
+        let selection = self.text.attrib("SELECTIONPOS");
+        for &(start, end) in &self.matches {
+            self.text.set_attrib("SELECTIONPOS", format!("{}:{}", start, end));
+            self.text.remove_formatting();
+        }
+        match selection {
+            Some(selection) => self.text.set_attrib("SELECTIONPOS", selection),
+            None => self.text.set_attrib("SELECTION", "NONE"),
+        };
+    }
+
+    fn select_current(&mut self) {
+        if let Some((start, end)) = self.current.map(|i| self.matches[i]) {
+            self.text.set_attrib("SELECTIONPOS", format!("{}:{}", start, end));
+            self.text.set_attrib("SCROLLTOPOS", start.to_string());
+        }
+    }
+}
+
+/// Finds the non overlapping occurrences of `query` in `value`, as character positions.
+fn find_matches(value: &str, query: &str, case_sensitive: bool, whole_words: bool)
+                                                            -> Vec<(usize, usize)> {
+    let fold = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+    let haystack: Vec<char> = value.chars().map(&fold).collect();
+    let needle: Vec<char> = query.chars().map(&fold).collect();
+    let is_word = |pos: usize| haystack.get(pos).map_or(false, |c| c.is_alphanumeric());
+
+    let mut matches = Vec::new();
+    if needle.is_empty() {
+        return matches;
+    }
+    let mut pos = 0;
+    while pos + needle.len() <= haystack.len() {
+        let end = pos + needle.len();
+        let found = haystack[pos..end] == needle[..]
+            && (!whole_words || ((pos == 0 || !is_word(pos - 1)) && !is_word(end)));
+        if found {
+            matches.push((pos, end));
+            pos = end;
+        } else {
+            pos += 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_matches;
+
+    #[test]
+    fn empty_query_finds_nothing() {
+        assert_eq!(find_matches("abc", "", false, false), vec![]);
+        assert_eq!(find_matches("", "a", false, false), vec![]);
+    }
+
+    #[test]
+    fn matches_do_not_overlap() {
+        assert_eq!(find_matches("aaaa", "aa", true, false), vec![(0, 2), (2, 4)]);
+        assert_eq!(find_matches("abab", "ab", true, false), vec![(0, 2), (2, 4)]);
+    }
+
+    #[test]
+    fn case_is_ignored_unless_sensitive() {
+        assert_eq!(find_matches("Foo foo FOO", "foo", false, false),
+                   vec![(0, 3), (4, 7), (8, 11)]);
+        assert_eq!(find_matches("Foo foo FOO", "foo", true, false), vec![(4, 7)]);
+    }
+
+    #[test]
+    fn whole_words_need_boundaries() {
+        assert_eq!(find_matches("cat concat cat.", "cat", true, true), vec![(0, 3), (11, 14)]);
+        assert_eq!(find_matches("cats", "cat", true, true), vec![]);
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(find_matches("été été", "été", true, false), vec![(0, 3), (4, 7)]);
+        assert_eq!(find_matches("ÉTÉ", "été", false, false), vec![(0, 3)]);
+    }
+}
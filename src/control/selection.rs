@@ -0,0 +1,318 @@
+//! Selections shared between lists, trees and matrices.
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::result::Result;
+
+use Element;
+use control::{List, Tree};
+#[cfg(feature = "controls")]
+use control::Matrix;
+
+/// Elements whose selected items can be read and set as indices starting from 0.
+///
+/// The indices are the list items (ids minus 1), the tree node ids and the matrix lines
+/// (minus 1, with MARKMODE=LIN).
+pub trait Selectable: Element {
+    /// Returns the selected indices, in ascending order.
+    fn selection(&self) -> Vec<usize>;
+    /// Selects exactly the `indices`, or only the first one for single selection elements.
+    fn set_selection(&mut self, indices: &[usize]);
+}
+
+impl Selectable for List {
+    fn selection(&self) -> Vec<usize> {
+        self.selected_items().into_iter().map(|id| id as usize - 1).collect()
+    }
+
+    fn set_selection(&mut self, indices: &[usize]) {
+        if self.attrib("MULTIPLE").map_or(false, |m| m == "YES") {
+            let ids: Vec<u32> = indices.iter().map(|&i| i as u32 + 1).collect();
+            self.set_selected_items(&ids);
+        } else {
+            let id = indices.first().map_or(0, |&i| i + 1);
+            self.set_attrib("VALUE", id.to_string());
+        }
+    }
+}
+
+impl Selectable for Tree {
+    fn selection(&self) -> Vec<usize> {
+        self.selected_nodes().into_iter().filter(|&id| id >= 0).map(|id| id as usize).collect()
+    }
+
+    fn set_selection(&mut self, indices: &[usize]) {
+        if self.attrib("MARKMODE").map_or(false, |m| m == "MULTIPLE") {
+            let ids: Vec<i32> = indices.iter().map(|&i| i as i32).collect();
+            self.set_selected_nodes(&ids);
+        } else if let Some(&id) = indices.first() {
+            self.set_attrib("VALUE", id.to_string());
+        }
+    }
+}
+
+#[cfg(feature = "controls")]
+impl Selectable for Matrix {
+    fn selection(&self) -> Vec<usize> {
+        match self.attrib("MARKED") {
+            // With MARKMODE=LIN the marks of the lines come after a 'L'.
+            Some(ref marked) if marked.starts_with('L') => {
+                marked.bytes().skip(1).enumerate()
+                      .filter(|&(_, mark)| mark == b'1')
+                      .map(|(i, _)| i)
+                      .collect()
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn set_selection(&mut self, indices: &[usize]) {
+        let lines = self.attrib_parse("NUMLIN").unwrap_or(0);
+        let mut marked: Vec<u8> = (0..lines).map(|_| b'0').collect();
+        for &i in indices {
+            if let Some(mark) = marked.get_mut(i) {
+                *mark = b'1';
+            }
+        }
+        marked.insert(0, b'L');
+        self.set_attrib("MARKED", String::from_utf8(marked).unwrap());
+        self.set_attrib("REDRAW", "ALL");
+    }
+}
+
+struct Inner {
+    selected: BTreeSet<usize>,
+    listeners: Vec<Rc<RefCell<FnMut(&[usize])>>>,
+}
+
+/// A set of selected indices, independent from the elements showing it.
+///
+/// Clones share the same selection. Elements attached with `attach` show the selection
+/// whenever it changes, and the selection made by the user is brought back with `update_from`,
+/// usually from a selection callback of the element:
+///
+/// ```ignore
+/// let selection = SelectionModel::new();
+/// selection.attach(list);
+/// selection.attach(matrix);
+/// let shared = selection.clone();
+/// list.set_action(move |(list, _, _, _): (List, String, u32, ListItemState)| {
+///     shared.update_from(&list);
+/// });
+/// selection.on_change(|selected| println!("{} selected", selected.len()));
+/// ```
+#[derive(Clone)]
+pub struct SelectionModel(Rc<RefCell<Inner>>);
+
+impl SelectionModel {
+    /// Creates an empty selection.
+    pub fn new() -> SelectionModel {
+        let inner = Inner { selected: BTreeSet::new(), listeners: Vec::new() };
+        SelectionModel(Rc::new(RefCell::new(inner)))
+    }
+
+    /// Returns the selected indices, in ascending order.
+    pub fn selected(&self) -> Vec<usize> {
+        self.0.borrow().selected.iter().cloned().collect()
+    }
+
+    /// Checks whether `index` is selected.
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.0.borrow().selected.contains(&index)
+    }
+
+    /// Returns the number of selected indices.
+    pub fn len(&self) -> usize {
+        self.0.borrow().selected.len()
+    }
+
+    /// Checks whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().selected.is_empty()
+    }
+
+    /// Adds `index` to the selection.
+    pub fn select(&self, index: usize) {
+        self.change(|selected| { selected.insert(index); });
+    }
+
+    /// Removes `index` from the selection.
+    pub fn deselect(&self, index: usize) {
+        self.change(|selected| { selected.remove(&index); });
+    }
+
+    /// Selects `index` if it isn't, deselects it otherwise.
+    pub fn toggle(&self, index: usize) {
+        self.change(|selected| {
+            if !selected.remove(&index) {
+                selected.insert(index);
+            }
+        });
+    }
+
+    /// Adds the indices from `start` to `end` (inclusive) to the selection.
+    pub fn select_range(&self, start: usize, end: usize) {
+        self.change(|selected| selected.extend(start..end + 1));
+    }
+
+    /// Removes the indices from `start` to `end` (inclusive) from the selection.
+    pub fn deselect_range(&self, start: usize, end: usize) {
+        self.change(|selected| selected.retain(|&i| i < start || i > end));
+    }
+
+    /// Selects exactly the `indices`.
+    pub fn set(&self, indices: &[usize]) {
+        self.change(|selected| *selected = indices.iter().cloned().collect());
+    }
+
+    /// Deselects everything.
+    pub fn clear(&self) {
+        self.change(|selected| selected.clear());
+    }
+
+    /// Keeps only the indices below `count`, after items were removed.
+    pub fn truncate(&self, count: usize) {
+        self.change(|selected| selected.retain(|&i| i < count));
+    }
+
+    /// Calls `f` with the selected indices every time the selection changes.
+    pub fn on_change<F: FnMut(&[usize]) + 'static>(&self, f: F) {
+        self.0.borrow_mut().listeners.push(Rc::new(RefCell::new(f)));
+    }
+
+    /// Shows the selection in `elem` now and whenever it changes.
+    pub fn attach<E: Selectable + 'static>(&self, mut elem: E) {
+        elem.set_selection(&self.selected());
+        self.on_change(move |selected| elem.set_selection(selected));
+    }
+
+    /// Replaces the selection by the one of `elem`, e.g. after the user changed it.
+    pub fn update_from<E: Selectable>(&self, elem: &E) {
+        self.set(&elem.selection());
+    }
+
+    /// Saves the selection as text, e.g. "1,3-5", to be restored with `restore`.
+    pub fn save(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for &i in self.0.borrow().selected.iter() {
+            match ranges.last_mut() {
+                Some(&mut (_, ref mut end)) if *end + 1 == i => *end = i,
+                _ => ranges.push((i, i)),
+            }
+        }
+        ranges.iter().map(|&(start, end)| {
+            if start == end { start.to_string() } else { format!("{}-{}", start, end) }
+        }).collect::<Vec<_>>().join(",")
+    }
+
+    /// Restores a selection saved with `save`.
+    pub fn restore(&self, saved: &str) -> Result<(), String> {
+        let mut indices = Vec::new();
+        for range in saved.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let mut bounds = range.splitn(2, '-').map(|s| s.trim().parse::<usize>());
+            let start = try!(bounds.next().unwrap().map_err(|e| e.to_string()));
+            let end = match bounds.next() {
+                Some(end) => try!(end.map_err(|e| e.to_string())),
+                None => start,
+            };
+            indices.extend(start..end + 1);
+        }
+        self.set(&indices);
+        Ok(())
+    }
+
+    /// Changes the selection and notifies the listeners if it changed.
+    fn change<F: FnOnce(&mut BTreeSet<usize>)>(&self, f: F) {
+        let (selected, listeners) = {
+            let mut inner = self.0.borrow_mut();
+            let before = inner.selected.clone();
+            f(&mut inner.selected);
+            if inner.selected == before {
+                return;
+            }
+            (inner.selected.iter().cloned().collect::<Vec<_>>(), inner.listeners.clone())
+        };
+        // Called without borrowing, so listeners are free to read or change the selection.
+        for listener in listeners {
+            if let Ok(mut listener) = listener.try_borrow_mut() {
+                (&mut *listener)(&selected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::SelectionModel;
+
+    #[test]
+    fn ranges_and_toggles_keep_indices_sorted() {
+        let selection = SelectionModel::new();
+        selection.select_range(5, 7);
+        selection.select(1);
+        selection.toggle(6);
+        selection.toggle(9);
+        assert_eq!(selection.selected(), vec![1, 5, 7, 9]);
+        selection.deselect_range(4, 8);
+        assert_eq!(selection.selected(), vec![1, 9]);
+        selection.truncate(5);
+        assert_eq!(selection.selected(), vec![1]);
+        assert!(selection.is_selected(1) && !selection.is_selected(9));
+    }
+
+    #[test]
+    fn clones_share_the_selection() {
+        let selection = SelectionModel::new();
+        let shared = selection.clone();
+        shared.set(&[2, 4]);
+        assert_eq!(selection.selected(), vec![2, 4]);
+        selection.clear();
+        assert!(shared.is_empty());
+    }
+
+    #[test]
+    fn listeners_are_only_called_on_changes() {
+        let selection = SelectionModel::new();
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let seen = calls.clone();
+        selection.on_change(move |selected| seen.borrow_mut().push(selected.to_vec()));
+        selection.select(3);
+        selection.select(3);
+        selection.deselect(8);
+        selection.toggle(3);
+        assert_eq!(*calls.borrow(), vec![vec![3], vec![]]);
+    }
+
+    #[test]
+    fn listeners_can_change_the_selection() {
+        let selection = SelectionModel::new();
+        let inner = selection.clone();
+        selection.on_change(move |selected| {
+            if selected.len() > 2 {
+                inner.truncate(2);
+            }
+        });
+        selection.set(&[0, 1, 2, 3]);
+        assert_eq!(selection.selected(), vec![0, 1]);
+    }
+
+    #[test]
+    fn save_merges_consecutive_indices() {
+        let selection = SelectionModel::new();
+        selection.set(&[1, 3, 4, 5, 8, 9]);
+        assert_eq!(selection.save(), "1,3-5,8-9");
+        selection.clear();
+        assert_eq!(selection.save(), "");
+    }
+
+    #[test]
+    fn restore_reads_saved_selections() {
+        let selection = SelectionModel::new();
+        assert_eq!(selection.restore(" 1, 3 - 5 ,,8"), Ok(()));
+        assert_eq!(selection.selected(), vec![1, 3, 4, 5, 8]);
+        assert!(selection.restore("2,x").is_err());
+        assert_eq!(selection.selected(), vec![1, 3, 4, 5, 8]);
+    }
+}
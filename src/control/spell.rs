@@ -0,0 +1,218 @@
+//! Spell checking of multiline texts.
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use Element;
+use Handle;
+use i18n;
+use timer;
+use callback::{Action, CallbackReturn, ValueChangedCb};
+use callback::button::{ButtonCb, MouseButton, MouseButtonState, MouseState};
+use control::{Text, FormatTag};
+use dialog::DialogPos;
+use element::ConvertXYToPos;
+use menu::{Menu, Item, Separator};
+
+/// How long after the last edit the text is checked again.
+const CHECK_DELAY: u64 = 300;
+/// Maximum number of suggestions shown in the menu of a misspelled word.
+const MAX_SUGGESTIONS: usize = 8;
+/// Color of the misspelled words.
+const MISSPELLED_COLOR: (u8, u8, u8) = (220, 0, 0);
+
+/// Checks the spelling of words, implemented by the application e.g. with hunspell.
+///
+/// See `Text::set_spell_checker`.
+pub trait SpellChecker {
+    /// Checks whether `word` is spelled correctly.
+    fn check(&self, word: &str) -> bool;
+    /// Suggests corrections of a misspelled `word`, the most likely first.
+    fn suggest(&self, word: &str) -> Vec<String>;
+    /// Adds `word` to the user dictionary, from the "Add to Dictionary" item of the menu.
+    ///
+    /// The default implementation does nothing, the word is only ignored while the text is
+    /// being checked by this checker.
+    fn add_word(&mut self, word: &str) {
+        let _ = word;
+    }
+}
+
+struct SpellState<C> {
+    checker: C,
+    /// Words ignored or added to the dictionary from the menu.
+    ignored: HashSet<String>,
+    /// The value checked last, so formatting changes never trigger a new check.
+    checked: Option<String>,
+}
+
+impl<C: SpellChecker> SpellState<C> {
+    fn is_misspelled(&self, word: &str) -> bool {
+        !self.ignored.contains(word) && !self.checker.check(word)
+    }
+}
+
+impl Text {
+    /// Checks the spelling of the text as the user types, showing the misspelled words in red
+    /// and underlined, with a menu of suggestions when right clicked.
+    ///
+    /// The text must have been created with `Text::new_formatted` (or MULTILINE=YES and
+    /// FORMATTING=YES). Every check removes all the formatting of the text before marking the
+    /// misspelled words, so it's meant for plain text. The text is checked again shortly after
+    /// each change.
+    ///
+    /// This sets the VALUECHANGED_CB and BUTTON_CB callbacks of the text.
+    pub fn set_spell_checker<C: SpellChecker + 'static>(&mut self, checker: C) -> Text {
+        let state = Rc::new(RefCell::new(SpellState {
+            checker: checker,
+            ignored: HashSet::new(),
+            checked: None,
+        }));
+        let changed = state.clone();
+        let weak = self.downgrade();
+        self.set_valuechanged_cb(move |(text,): (Text,)| {
+            let state = changed.clone();
+            let key = format!("_IUPRUST_SPELL_{:p}", text.raw());
+            // The text may be destroyed before the check.
+            timer::debounce(&key, Duration::from_millis(CHECK_DELAY), move || {
+                if let Some(text) = weak.upgrade() {
+                    check_spelling(text, &state);
+                }
+            });
+        });
+
+        let clicked = state.clone();
+        self.set_button_cb(move |(text, button, pressed, x, y, _)
+                                    : (Text, MouseButton, MouseButtonState, i32, i32, MouseState)| {
+            if button == MouseButton::Button3 && pressed == MouseButtonState::Pressed {
+                if show_suggestions(text, &clicked, x, y) {
+                    return CallbackReturn::Ignore;
+                }
+            }
+            CallbackReturn::Default
+        });
+
+        check_spelling(*self, &state);
+        *self
+    }
+}
+
+/// Marks the misspelled words of `text`, unless its value was checked already.
+fn check_spelling<C: SpellChecker>(mut text: Text, state: &Rc<RefCell<SpellState<C>>>) {
+    let value = text.attrib("VALUE").unwrap_or_default();
+    let mut state = state.borrow_mut();
+    if state.checked.as_ref() == Some(&value) {
+        return;
+    }
+    text.clear_formatting();
+    for (start, end, word) in words(&value) {
+        if state.is_misspelled(&word) {
+            let tag = FormatTag::new().fg_color(MISSPELLED_COLOR).underline(true);
+            text.format_range(tag, start, end);
+        }
+    }
+    state.checked = Some(value);
+}
+
+/// Shows the menu of the word at (x, y) if it's misspelled, returning whether it was shown.
+fn show_suggestions<C>(text: Text, state: &Rc<RefCell<SpellState<C>>>, x: i32, y: i32) -> bool
+                                                    where C: SpellChecker + 'static {
+    let pos = match text.convert_xy_to_pos(x, y) {
+        Some(pos) => pos as usize,
+        None => return false,
+    };
+    let value = text.attrib("VALUE").unwrap_or_default();
+    let found = words(&value).into_iter().find(|&(start, end, _)| start <= pos && pos < end);
+    let (start, end, word) = match found {
+        Some(found) => found,
+        None => return false,
+    };
+    let suggestions = {
+        let state = state.borrow();
+        if !state.is_misspelled(&word) {
+            return false;
+        }
+        state.checker.suggest(&word)
+    };
+
+    let mut items: Vec<Handle> = Vec::new();
+    for suggestion in suggestions.into_iter().take(MAX_SUGGESTIONS) {
+        let state = state.clone();
+        let mut item = Item::with_title(escape_mnemonic(&suggestion));
+        item.set_action(move |(_,): (Item,)| {
+            let mut text = text;
+            text.set_attrib("SELECTIONPOS", format!("{}:{}", start, end));
+            text.set_attrib("SELECTEDTEXT", &suggestion[..]);
+            check_spelling(text, &state);
+        });
+        items.push(item.into());
+    }
+    if items.is_empty() {
+        let none = i18n::tr("IUPRUST_SPELL_NO_SUGGESTIONS", "(No Suggestions)");
+        items.push(Item::with_title(none).set_attrib("ACTIVE", "NO").into());
+    }
+    items.push(Separator::new().into());
+
+    let (ignore_state, ignore_word) = (state.clone(), word.clone());
+    let mut ignore = Item::with_title(i18n::tr("IUPRUST_SPELL_IGNORE", "&Ignore All"));
+    ignore.set_action(move |(_,): (Item,)| {
+        ignore_state.borrow_mut().ignored.insert(ignore_word.clone());
+        recheck(text, &ignore_state);
+    });
+    items.push(ignore.into());
+
+    let add_state = state.clone();
+    let mut add = Item::with_title(i18n::tr("IUPRUST_SPELL_ADD", "&Add to Dictionary"));
+    add.set_action(move |(_,): (Item,)| {
+        {
+            let mut state = add_state.borrow_mut();
+            state.checker.add_word(&word);
+            state.ignored.insert(word.clone());
+        }
+        recheck(text, &add_state);
+    });
+    items.push(add.into());
+
+    let mut menu = Menu::new(items);
+    let _ = menu.popup(DialogPos::MousePos, DialogPos::MousePos);
+    menu.destroy();
+    true
+}
+
+/// Checks `text` again even if its value didn't change, e.g. after ignoring a word.
+fn recheck<C: SpellChecker>(text: Text, state: &Rc<RefCell<SpellState<C>>>) {
+    state.borrow_mut().checked = None;
+    check_spelling(text, state);
+}
+
+/// Splits `value` in words, with their start and end character positions.
+///
+/// A word is a run of letters, possibly with apostrophes inside (e.g. "don't"). Runs with
+/// digits, such as "mp3", are not words.
+fn words(value: &str) -> Vec<(usize, usize, String)> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    for (pos, c) in value.chars().chain(Some(' ')).enumerate() {
+        if c.is_alphanumeric() || (c == '\'' && !current.is_empty()) {
+            if current.is_empty() {
+                start = pos;
+            }
+            current.push(c);
+        } else if !current.is_empty() {
+            let trailing = current.chars().rev().take_while(|&c| c == '\'').count();
+            let word = current[..current.len() - trailing].to_string();
+            if !word.chars().any(|c| c.is_numeric()) {
+                words.push((start, pos - trailing, word));
+            }
+            current.clear();
+        }
+    }
+    words
+}
+
+/// Doubles the `&` of a menu item title, so they are shown instead of marking a mnemonic.
+fn escape_mnemonic(title: &str) -> String {
+    title.replace("&", "&&")
+}
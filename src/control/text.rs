@@ -1,10 +1,19 @@
 use iup_sys;
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, c_void};
 use std::{mem, ptr};
+use std::ffi::CString;
 
 use Element;
+use callback::EditReturn;
+use element::ConvertXYToPos;
 
 /// See the [IUP Text Documentation][1].
+///
+/// Input methods (e.g. for Chinese, Japanese or Korean) work natively: the text being composed
+/// is shown at the caret and the composed characters are inserted as if typed, calling the
+/// `TextAction` callback. Moving the caret while composing commits the composition, so
+/// `set_caret` and `set_caret_pos` should not be called from the `CaretCb`.
+///
 /// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iuptext.html
 pub struct Text(*mut iup_sys::Ihandle);
 
@@ -21,6 +30,146 @@ impl Text {
         Text::new().set_attrib_data("SPIN", cstr!("YES") as *const _)
     }
 
+    /// Restricts the characters the user can type to the specified mask.
+    ///
+    /// Values set programmatically through VALUE are also checked, if not accepted VALUE is not
+    /// changed. The mask is checked before the ACTION callback is called.
+    pub fn set_mask(&mut self, mask: Mask) -> Text {
+        self.remove_mask();
+        if mask.is_case_insensitive() {
+            self.set_attrib("MASKCASEI", "YES");
+        }
+        let (name, value) = mask.attrib();
+        self.set_attrib(name, value)
+    }
+
+    /// Removes any mask set with `set_mask`.
+    pub fn remove_mask(&mut self) -> Text {
+        self.set_attrib_data("MASK", ptr::null());
+        self.set_attrib("MASKCASEI", "NO")
+    }
+
+    /// Validates every edit with the `validate` closure before it is accepted.
+    ///
+    /// The closure receives what would be the new value of the text and returns whether the edit
+    /// is accepted. Useful for rules that can't be expressed as a `Mask`.
+    ///
+    /// This sets the ACTION callback of the text, replacing any previously set with `set_action`.
+    pub fn set_validator<F>(&mut self, mut validate: F) -> Text
+                                                where F: FnMut(&str) -> bool + 'static {
+        self.set_action(move |(_, _, newvalue): (Text, Option<char>, String)| {
+            if validate(&newvalue) { EditReturn::Default } else { EditReturn::Ignore }
+        })
+    }
+
+    /// Creates a multiline text, with scrollbars shown as needed.
+    pub fn new_multiline() -> Text {
+        Text::new().set_attrib("MULTILINE", "YES")
+    }
+
+    /// Returns the number of lines of a multiline text, 1 for single line texts.
+    pub fn line_count(&self) -> usize {
+        self.attrib_parse("LINECOUNT").unwrap_or(1)
+    }
+
+    /// Scrolls the text so the line `lin` (starting at 1) is visible, without moving the caret.
+    pub fn scroll_to_line(&mut self, lin: i32) -> Text {
+        self.set_attrib("SCROLLTO", format!("{}:1", lin))
+    }
+
+    /// Scrolls the text to its last line, e.g. to follow the output appended to a log.
+    pub fn scroll_to_end(&mut self) -> Text {
+        let last = self.line_count() as i32;
+        self.scroll_to_line(last)
+    }
+
+    /// Gets the first and last lines (starting at 1) currently visible in a multiline text, or
+    /// `None` if the text is not mapped.
+    ///
+    /// The last line may be partially visible.
+    pub fn visible_lines(&self) -> Option<(i32, i32)> {
+        let height = self.attrib("CLIENTSIZE")
+                         .and_then(|size| size.splitn(2, 'x').nth(1).and_then(|h| h.parse().ok()))
+                         .unwrap_or(0);
+        if height <= 0 {
+            return None;
+        }
+        let first = match self.lincol_at(1, 1) {
+            Some((lin, _)) => lin,
+            None => return None,
+        };
+        // When the text ends above the bottom there is no character there.
+        let last = self.lincol_at(1, height - 1).map_or(self.line_count() as i32, |(lin, _)| lin);
+        Some((first, last.max(first)))
+    }
+
+    /// Creates a multiline text which accepts formatting, see `FormatTag`.
+    ///
+    /// Formatting is only available in Windows and GTK.
+    pub fn new_formatted() -> Text {
+        Text::new_multiline().set_attrib("FORMATTING", "YES")
+    }
+
+    /// Applies a format tag to the characters between the `start` and `end` absolute positions.
+    ///
+    /// The text must have been created with `Text::new_formatted` (or MULTILINE=YES and
+    /// FORMATTING=YES), when not mapped yet the tag is applied when it gets mapped.
+    pub fn format_range(&mut self, tag: FormatTag, start: usize, end: usize) -> Text {
+        self.add_format_tag(tag, Some(format!("{}:{}", start, end)))
+    }
+
+    /// Applies a format tag to the current selection, or to the text typed at the caret if there's
+    /// no selection.
+    ///
+    /// See `format_range` for the requirements.
+    pub fn format_selection(&mut self, tag: FormatTag) -> Text {
+        self.add_format_tag(tag, None)
+    }
+
+    /// Removes the formatting of the current selection.
+    pub fn remove_formatting(&mut self) -> Text {
+        self.set_attrib("REMOVEFORMATTING", "YES")
+    }
+
+    /// Removes all the formatting of the text.
+    pub fn clear_formatting(&mut self) -> Text {
+        self.set_attrib("REMOVEFORMATTING", "ALL")
+    }
+
+    fn add_format_tag(&mut self, tag: FormatTag, selection: Option<String>) -> Text {
+        // The tag is a IupUser element which is destroyed by IUP after being applied.
+        let ih = unsafe { iup_sys::IupUser() };
+        if let Some(selection) = selection {
+            set_raw_attrib(ih, "SELECTIONPOS", selection);
+        }
+        for (name, value) in tag.attribs.into_iter() {
+            set_raw_attrib(ih, name, value);
+        }
+        self.set_attrib_data("ADDFORMATTAG_HANDLE", ih as *const c_void)
+    }
+
+    /// Gets the position of the caret as (lin, col), both starting at 1.
+    pub fn caret(&self) -> (i32, i32) {
+        let caret = self.attrib("CARET").unwrap_or_default();
+        let mut parts = caret.splitn(2, ',').map(|s| s.trim().parse().unwrap_or(1));
+        (parts.next().unwrap_or(1), parts.next().unwrap_or(1))
+    }
+
+    /// Moves the caret to (lin, col), both starting at 1.
+    pub fn set_caret(&mut self, lin: i32, col: i32) -> Text {
+        self.set_attrib("CARET", format!("{},{}", lin, col))
+    }
+
+    /// Gets the absolute position of the caret, starting at 0.
+    pub fn caret_pos(&self) -> usize {
+        self.attrib_parse("CARETPOS").unwrap_or(0)
+    }
+
+    /// Moves the caret to an absolute position, starting at 0.
+    pub fn set_caret_pos(&mut self, pos: usize) -> Text {
+        self.set_attrib("CARETPOS", pos.to_string())
+    }
+
     /// Converts a (lin, col) character positioning into an absolute position.
     ///
     /// lin and col starts at 1, pos starts at 0. For single line controls pos is always *col-1*.
@@ -43,10 +192,196 @@ impl Text {
             (lin as i32, col as i32)
         }
     }
+
+    /// Gets the absolute position of the character at x,y (relative to the element), starting
+    /// at 0, or `None` if there's no character there.
+    pub fn pos_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.convert_xy_to_pos(x, y).map(|pos| pos as usize)
+    }
+
+    /// Gets the (lin, col) of the character at x,y (relative to the element), both starting at
+    /// 1, or `None` if there's no character there.
+    pub fn lincol_at(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        self.pos_at(x, y).map(|pos| self.convert_pos_to_lincol(pos))
+    }
 }
 
 impl_widget!(Text, "text");
 
+/// The characters accepted by a `Text`, see `Text::set_mask`, or by the cells of a `Matrix`.
+///
+/// See the [IUP Mask Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/attrib/iup_mask.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mask {
+    /// An integer number, optionally signed.
+    Int,
+    /// An unsigned integer number.
+    UInt,
+    /// A floating point number, optionally signed.
+    Float,
+    /// An unsigned floating point number.
+    UFloat,
+    /// An integer number between the inclusive limits `(min, max)`.
+    IntRange(i32, i32),
+    /// A floating point number between the inclusive limits `(min, max)`.
+    FloatRange(f64, f64),
+    /// A pattern in the IUP mask language, e.g. `"/d/d/d-/d/d/d/d"`.
+    Pattern(String),
+    /// Same as `Pattern` but letters are matched case insensitive.
+    PatternNoCase(String),
+}
+
+impl Mask {
+    /// The attribute setting the mask (MASK, MASKINT or MASKFLOAT) and its value.
+    #[doc(hidden)]
+    pub fn attrib(&self) -> (&'static str, String) {
+        match *self {
+            Mask::Int => ("MASK", "[+/-]?/d+".into()),
+            Mask::UInt => ("MASK", "/d+".into()),
+            Mask::Float => ("MASK", "[+/-]?(/d+/.?/d*|/./d+)".into()),
+            Mask::UFloat => ("MASK", "(/d+/.?/d*|/./d+)".into()),
+            Mask::IntRange(min, max) => ("MASKINT", format!("{}:{}", min, max)),
+            Mask::FloatRange(min, max) => ("MASKFLOAT", format!("{}:{}", min, max)),
+            Mask::Pattern(ref pattern) => ("MASK", pattern.clone()),
+            Mask::PatternNoCase(ref pattern) => ("MASK", pattern.clone()),
+        }
+    }
+
+    /// Checks whether letters are matched case insensitive, i.e. MASKCASEI=YES.
+    #[doc(hidden)]
+    pub fn is_case_insensitive(&self) -> bool {
+        match *self {
+            Mask::PatternNoCase(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Paragraph alignment of a `FormatTag`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+    Justify,
+}
+
+/// Paragraph bullets of a `FormatTag`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BulletStyle {
+    None,
+    Bullet,
+    /// 1, 2, 3, ...
+    Arabic,
+    /// a, b, c, ...
+    LowerAlpha,
+    /// A, B, C, ...
+    UpperAlpha,
+    /// i, ii, iii, ...
+    LowerRoman,
+    /// I, II, III, ...
+    UpperRoman,
+}
+
+/// A set of formatting attributes to be applied to a range of a formatted `Text`.
+///
+/// Only the attributes explicitly set are changed, everything else keeps the current format.
+///
+/// ```ignore
+/// let mut text = Text::new_formatted().set_attrib("VALUE", "Title\nBody");
+/// text.format_range(FormatTag::new().bold(true).font_size(14), 0, 5);
+/// ```
+///
+/// See the [IUP Text Formatting Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/attrib/iup_formatting.html
+#[derive(Debug, Clone, Default)]
+pub struct FormatTag {
+    attribs: Vec<(&'static str, String)>,
+}
+
+impl FormatTag {
+    /// Creates a format tag which changes nothing.
+    pub fn new() -> FormatTag {
+        FormatTag { attribs: Vec::new() }
+    }
+
+    /// Sets the alignment of the paragraphs.
+    pub fn alignment(self, alignment: Alignment) -> FormatTag {
+        self.with("ALIGNMENT", match alignment {
+            Alignment::Left => "LEFT",
+            Alignment::Right => "RIGHT",
+            Alignment::Center => "CENTER",
+            Alignment::Justify => "JUSTIFY",
+        })
+    }
+
+    /// Sets the font face name, e.g. `"Times New Roman"`.
+    pub fn font_face<S: Into<String>>(self, face: S) -> FormatTag {
+        self.with("FONTFACE", face)
+    }
+
+    /// Sets the font size in points.
+    pub fn font_size(self, size: u32) -> FormatTag {
+        self.with("FONTSIZE", size.to_string())
+    }
+
+    /// Sets whether the text is bold.
+    pub fn bold(self, bold: bool) -> FormatTag {
+        self.with("WEIGHT", if bold { "BOLD" } else { "NORMAL" })
+    }
+
+    /// Sets whether the text is italic.
+    pub fn italic(self, italic: bool) -> FormatTag {
+        self.with("ITALIC", if italic { "YES" } else { "NO" })
+    }
+
+    /// Sets whether the text is underlined.
+    pub fn underline(self, underline: bool) -> FormatTag {
+        self.with("UNDERLINE", if underline { "SINGLE" } else { "NONE" })
+    }
+
+    /// Sets the text color.
+    pub fn fg_color(self, rgb: (u8, u8, u8)) -> FormatTag {
+        self.with("FGCOLOR", format!("{} {} {}", rgb.0, rgb.1, rgb.2))
+    }
+
+    /// Sets the text background color.
+    pub fn bg_color(self, rgb: (u8, u8, u8)) -> FormatTag {
+        self.with("BGCOLOR", format!("{} {} {}", rgb.0, rgb.1, rgb.2))
+    }
+
+    /// Sets the bullets of the paragraphs.
+    pub fn bullet(self, style: BulletStyle) -> FormatTag {
+        self.with("NUMBERING", match style {
+            BulletStyle::None => "NONE",
+            BulletStyle::Bullet => "BULLET",
+            BulletStyle::Arabic => "ARABIC",
+            BulletStyle::LowerAlpha => "LCALPHA",
+            BulletStyle::UpperAlpha => "UCALPHA",
+            BulletStyle::LowerRoman => "LCROMAN",
+            BulletStyle::UpperRoman => "UCROMAN",
+        })
+    }
+
+    /// Sets the indentation of the paragraphs in pixels.
+    pub fn indent(self, pixels: u32) -> FormatTag {
+        self.with("INDENT", pixels.to_string())
+    }
+
+    /// Sets any other formatting attribute not covered by the methods above.
+    pub fn with<V: Into<String>>(mut self, name: &'static str, value: V) -> FormatTag {
+        self.attribs.push((name, value.into()));
+        self
+    }
+}
+
+fn set_raw_attrib<V: Into<String>>(ih: *mut iup_sys::Ihandle, name: &str, value: V) {
+    let cname = CString::new(name).unwrap();
+    let cvalue = CString::new(value.into()).unwrap();
+    unsafe { iup_sys::IupSetStrAttribute(ih, cname.as_ptr(), cvalue.as_ptr()) };
+}
+
 /// Returns a position in the string.
 impl ::element::ConvertXYToPos for Text {}
 
@@ -57,7 +392,7 @@ impl ::callback::KillFocusCb for Text {}
 impl ::callback::EnterWindowCb for Text {}
 impl ::callback::LeaveWindowCb for Text {}
 impl ::callback::HelpCb for Text {}
-// TODO impl K_ callbacks when it's implemented.
+impl ::callback::KAnyCb for Text {}
 
 // TODO impl future DragSource and DragTarget traits.
 
@@ -100,15 +435,15 @@ impl_callback! {
     #[doc=""]
     #[doc="Can be generated when using the keyboard, undo system or from the clipboard."]
     #[doc=""]
-    #[doc="The following values can be returned from the callback:"]
-    #[doc=" + `CallbackReturn::Default` or `()` for the default reaction."]
-    #[doc=" + `CallbackReturn::Close` will be processed, but the change will be ignored."]
-    #[doc=" + `CallbackReturn::Ignore` ignores the new value."]
-    #[doc=" + An `CallbackReturn::Char`, if the received `c` is `None` or NUL is returned it'll act"]
-    #[doc="   just like `CallbackReturn::Default` otherwise the returned character will be used"]
+    #[doc="The callback returns an `EditReturn`:"]
+    #[doc=" + `EditReturn::Default` or `()` for the default reaction."]
+    #[doc=" + `EditReturn::Close` will be processed, but the change will be ignored."]
+    #[doc=" + `EditReturn::Ignore` ignores the new value."]
+    #[doc=" + An `EditReturn::Char`, if the received `c` is `None` or NUL is returned it'll act"]
+    #[doc="   just like `EditReturn::Default` otherwise the returned character will be used"]
     #[doc="   instead of the one sent to the callback."]
     #[doc=""]
-    #[doc="The VALUE attribute can be changed only if `CallbackReturn::Ignore` is returned."]
+    #[doc="The VALUE attribute can be changed only if `EditReturn::Ignore` is returned."]
     #[doc=""]
     #[doc="**NOTE:** The **character** received and returned must be in the ASCII range of"]
     #[doc=" UTF-8 (0-127). No restriction on the `newvalue` string, when the value added to the VALUE"]
@@ -117,7 +452,37 @@ impl_callback! {
     pub trait TextAction where Self: Element {
         let name = "ACTION";
         extern fn listener(ih: *mut iup_sys::Ihandle, c: c_int, newvalue: *const c_char) -> CallbackReturn;
-        fn set_action<F: Callback(Self, Option<char>, String)>(&mut self, cb: F) -> Self;
+        fn set_action<F: Callback(Self, Option<char>, String) -> EditReturn>(&mut self, cb: F) -> Self;
         fn remove_action(&mut self) -> Option<Box<_>>;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Mask;
+
+    #[test]
+    fn number_masks_are_the_predefined_ones() {
+        // IUP_MASK_INT, IUP_MASK_UINT, IUP_MASK_FLOAT and IUP_MASK_UFLOAT of iup.h.
+        assert_eq!(Mask::Int.attrib(), ("MASK", "[+/-]?/d+".to_string()));
+        assert_eq!(Mask::UInt.attrib(), ("MASK", "/d+".to_string()));
+        assert_eq!(Mask::Float.attrib(), ("MASK", "[+/-]?(/d+/.?/d*|/./d+)".to_string()));
+        assert_eq!(Mask::UFloat.attrib(), ("MASK", "(/d+/.?/d*|/./d+)".to_string()));
+    }
+
+    #[test]
+    fn ranges_use_their_own_attribute() {
+        assert_eq!(Mask::IntRange(-5, 10).attrib(), ("MASKINT", "-5:10".to_string()));
+        assert_eq!(Mask::FloatRange(0.5, 2.0).attrib(), ("MASKFLOAT", "0.5:2".to_string()));
+    }
+
+    #[test]
+    fn patterns_are_given_as_they_are() {
+        let pattern = "/d/d/d-/d/d/d/d".to_string();
+        assert_eq!(Mask::Pattern(pattern.clone()).attrib(), ("MASK", pattern.clone()));
+        assert_eq!(Mask::PatternNoCase(pattern.clone()).attrib(), ("MASK", pattern.clone()));
+        assert!(Mask::PatternNoCase(pattern.clone()).is_case_insensitive());
+        assert!(!Mask::Pattern(pattern).is_case_insensitive());
+        assert!(!Mask::Int.is_case_insensitive());
+    }
+}
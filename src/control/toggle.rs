@@ -29,7 +29,7 @@ impl ::callback::KillFocusCb for Toggle {}
 impl ::callback::EnterWindowCb for Toggle {}
 impl ::callback::LeaveWindowCb for Toggle {}
 impl ::callback::HelpCb for Toggle {}
-// TODO impl K_ callbacks when it's implemented.
+impl ::callback::KAnyCb for Toggle {}
 
 /// Called after the value was interactively changed by the user.
 ///
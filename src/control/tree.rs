@@ -0,0 +1,806 @@
+use iup_sys;
+use libc::{c_char, c_int, c_void};
+use std::{mem, slice};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::any::Any;
+use std::cell::RefCell;
+
+use Element;
+use element::ConvertXYToPos;
+use callback::{CallbackReturn, RefuseReturn, IntoRust};
+
+/// See the [IUP Tree Documentation][1].
+///
+/// Nodes are identified by their id, a number from 0 to COUNT-1 in the order the nodes are shown
+/// when all branches are expanded. Ids change as nodes are added or removed.
+///
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iuptree.html
+pub struct Tree(*mut iup_sys::Ihandle);
+
+impl Tree {
+    /// Creates a tree containing nodes of branches or leaves.
+    pub fn new() -> Tree {
+        unsafe { Tree::from_raw(iup_sys::IupTree()) }
+    }
+
+    /// Adds a leaf after the node `id`, or as the first child of `id` if it's a branch.
+    ///
+    /// Use an `id` of -1 to add the first node of the tree.
+    /// The new node id can be found with `last_added_node`.
+    pub fn add_leaf<S: Into<String>>(&mut self, id: i32, title: S) -> Tree {
+        self.set_attrib_at("ADDLEAF", id, title)
+    }
+
+    /// Adds a branch after the node `id`, or as the first child of `id` if it's a branch.
+    ///
+    /// Use an `id` of -1 to add the first node of the tree.
+    /// The new node id can be found with `last_added_node`.
+    pub fn add_branch<S: Into<String>>(&mut self, id: i32, title: S) -> Tree {
+        self.set_attrib_at("ADDBRANCH", id, title)
+    }
+
+    /// Inserts a leaf after the node `id` at the same depth, i.e. as its next sibling.
+    ///
+    /// The new node id can be found with `last_added_node`.
+    pub fn insert_leaf<S: Into<String>>(&mut self, id: i32, title: S) -> Tree {
+        self.set_attrib_at("INSERTLEAF", id, title)
+    }
+
+    /// Inserts a branch after the node `id` at the same depth, i.e. as its next sibling.
+    ///
+    /// The new node id can be found with `last_added_node`.
+    pub fn insert_branch<S: Into<String>>(&mut self, id: i32, title: S) -> Tree {
+        self.set_attrib_at("INSERTBRANCH", id, title)
+    }
+
+    /// Returns the id of the last node added or inserted.
+    pub fn last_added_node(&self) -> Option<i32> {
+        self.attrib_parse("LASTADDNODE")
+    }
+
+    /// Removes the node `id` and all of its children.
+    pub fn remove_node(&mut self, id: i32) -> Tree {
+        self.set_attrib_at("DELNODE", id, "SELECTED")
+    }
+
+    /// Removes all the children of the branch `id`.
+    pub fn remove_children(&mut self, id: i32) -> Tree {
+        self.set_attrib_at("DELNODE", id, "CHILDREN")
+    }
+
+    /// Removes all the nodes of the tree.
+    pub fn clear(&mut self) -> Tree {
+        self.set_attrib("DELNODE", "ALL")
+    }
+
+    /// Returns the total number of nodes in the tree.
+    pub fn node_count(&self) -> usize {
+        self.attrib_parse("COUNT").unwrap_or(0)
+    }
+
+    /// Gets the title of the node `id`.
+    pub fn title(&self, id: i32) -> Option<String> {
+        self.attrib_at("TITLE", id)
+    }
+
+    /// Sets the title of the node `id`.
+    pub fn set_title<S: Into<String>>(&mut self, id: i32, title: S) -> Tree {
+        self.set_attrib_at("TITLE", id, title)
+    }
+
+    /// Returns the ids of the selected nodes, in ascending order.
+    ///
+    /// With MARKMODE=SINGLE this is just the focus node.
+    pub fn selected_nodes(&self) -> Vec<i32> {
+        match self.attrib("MARKEDNODES") {
+            Some(marked) => marked.bytes().enumerate()
+                                  .filter(|&(_, mark)| mark == b'+')
+                                  .map(|(id, _)| id as i32)
+                                  .collect(),
+            None => self.attrib_parse("VALUE").into_iter().collect(),
+        }
+    }
+
+    /// Selects exactly the nodes in `ids`. Valid only when MARKMODE=MULTIPLE.
+    pub fn set_selected_nodes(&mut self, ids: &[i32]) -> Tree {
+        let mut marked: Vec<u8> = (0..self.node_count()).map(|_| b'-').collect();
+        for &id in ids {
+            if let Some(mark) = marked.get_mut(id as usize) {
+                *mark = b'+';
+            }
+        }
+        self.set_attrib("MARKEDNODES", String::from_utf8(marked).unwrap())
+    }
+
+    /// Selects the nodes from `start` to `end` (inclusive), keeping the other selected nodes.
+    ///
+    /// Valid only when MARKMODE=MULTIPLE.
+    pub fn select_range(&mut self, start: i32, end: i32) -> Tree {
+        self.set_attrib("MARK", format!("{}-{}", start, end))
+    }
+
+    /// Selects all the nodes. Valid only when MARKMODE=MULTIPLE.
+    pub fn select_all(&mut self) -> Tree {
+        self.set_attrib("MARK", "MARKALL")
+    }
+
+    /// Deselects all the nodes. Valid only when MARKMODE=MULTIPLE.
+    pub fn clear_selection(&mut self) -> Tree {
+        self.set_attrib("MARK", "CLEARALL")
+    }
+
+    /// Gets the check state of the node `id`.
+    ///
+    /// Valid only when SHOWTOGGLE=YES, or SHOWTOGGLE=3STATE to allow `CheckState::Indeterminate`.
+    pub fn toggle_value(&self, id: i32) -> Option<CheckState> {
+        self.attrib_at("TOGGLEVALUE", id).and_then(|value| CheckState::from_attrib(&value))
+    }
+
+    /// Sets the check state of the node `id`, see `toggle_value`.
+    pub fn set_toggle_value(&mut self, id: i32, state: CheckState) -> Tree {
+        self.set_attrib_at("TOGGLEVALUE", id, state.as_attrib())
+    }
+
+    /// Changes how the node `id` is shown.
+    pub fn set_node_style(&mut self, id: i32, style: &NodeStyle) -> Tree {
+        style.apply(self, id);
+        *self
+    }
+
+    /// Returns the ids of the checked nodes, in ascending order.
+    pub fn checked_nodes(&self) -> Vec<i32> {
+        (0..self.node_count() as i32).filter(|&id| {
+            self.toggle_value(id) == Some(CheckState::Checked)
+        }).collect()
+    }
+
+    /// Starts the interactive renaming of the node `id`, as if the user clicked on its title.
+    ///
+    /// Valid only when SHOWRENAME=YES. The node becomes the focus node.
+    pub fn rename_node(&mut self, id: i32) -> Tree {
+        self.set_attrib("VALUE", id.to_string());
+        self.set_attrib("RENAME", "YES")
+    }
+
+    /// Associates a Rust value with the node `id`, replacing any value previously associated.
+    ///
+    /// The value follows the node as other nodes are added or removed, and it's dropped when the
+    /// node is removed or the tree is destroyed.
+    ///
+    /// This uses the node user ids and the NODEREMOVED_CB callback of the tree, so they must not
+    /// be used directly.
+    pub fn set_node_data<T: Any>(&mut self, id: i32, data: T) -> Tree {
+        unsafe {
+            let on_removed = on_node_removed as extern fn(*mut iup_sys::Ihandle, *mut c_void) -> c_int;
+            iup_sys::IupSetCallback(self.raw(), cstr!("NODEREMOVED_CB"), mem::transmute(on_removed));
+        }
+        let old = unsafe { iup_sys::IupTreeGetUserId(self.raw(), id) } as usize;
+        if old != 0 {
+            NODE_DATA.with(|slab| slab.borrow_mut().remove(old));
+        }
+        let key = NODE_DATA.with(|slab| slab.borrow_mut().insert(Box::new(data)));
+        unsafe { iup_sys::IupTreeSetUserId(self.raw(), id, key as *mut c_void) };
+        *self
+    }
+
+    /// Gets a copy of the value associated with the node `id`.
+    ///
+    /// Returns `None` if there's no value associated or it is not a `T`.
+    pub fn node_data<T: Any + Clone>(&self, id: i32) -> Option<T> {
+        self.with_node_data(id, |data: &mut T| data.clone())
+    }
+
+    /// Calls `f` with the value associated with the node `id`, returning its result.
+    ///
+    /// Returns `None` if there's no value associated or it is not a `T`.
+    pub fn with_node_data<T, R, F>(&self, id: i32, f: F) -> Option<R>
+                                            where T: Any, F: FnOnce(&mut T) -> R {
+        let key = unsafe { iup_sys::IupTreeGetUserId(self.raw(), id) } as usize;
+        if key == 0 {
+            return None;
+        }
+        // Taken out while `f` runs so it's free to access the data of other nodes.
+        let mut data = match NODE_DATA.with(|slab| slab.borrow_mut().take(key)) {
+            Some(data) => data,
+            None => return None,
+        };
+        let result = data.downcast_mut::<T>().map(f);
+        NODE_DATA.with(|slab| slab.borrow_mut().put_back(key, data));
+        result
+    }
+
+    /// Fills the tree from `provider`, branch by branch.
+    ///
+    /// The current nodes are removed and the top-level nodes of the provider are added. The
+    /// children of a branch are only fetched when the branch is opened for the first time, all at
+    /// once, so deep hierarchies load quickly but opening a branch with a great many children
+    /// blocks until they are all added.
+    ///
+    /// This sets the BRANCHOPEN_CB callback and the node data of the tree (see `set_node_data`).
+    pub fn set_provider<P: TreeProvider + 'static>(&mut self, provider: P) -> Tree {
+        let loader = Rc::new(RefCell::new(TreeLoader { provider: Box::new(provider) }));
+
+        self.clear();
+        loader.borrow_mut().add_children(*self, -1, Vec::new());
+
+        self.set_branchopen_cb(move |(mut tree, id): (Tree, i32)| {
+            let pending = tree.with_node_data(id, |node: &mut ProviderNode| {
+                if node.loaded {
+                    None
+                } else {
+                    node.loaded = true;
+                    Some(node.path.clone())
+                }
+            });
+            if let Some(Some(path)) = pending {
+                // Remove the placeholder which made the branch expandable.
+                tree.remove_children(id);
+                loader.borrow_mut().add_children(tree, id, path);
+            }
+            CallbackReturn::Default
+        })
+    }
+
+    /// Gets the id of the node at x,y (relative to the element), or `None` if there's no node
+    /// there.
+    pub fn node_at(&self, x: i32, y: i32) -> Option<i32> {
+        self.convert_xy_to_pos(x, y)
+    }
+
+    /// Fills the tree with `root` and all of its descendants.
+    ///
+    /// The current nodes are removed and `root` becomes the first node. All the nodes are added
+    /// at once without redrawing, and branches are added collapsed (ADDEXPANDED=NO) so large
+    /// hierarchies are inserted quickly.
+    ///
+    /// Returns the id of the node of each key, for later updates. The ids stay valid until
+    /// nodes are added or removed.
+    pub fn populate_from<T: TreeData>(&mut self, root: &T) -> HashMap<T::Key, i32> {
+        let mut ids = HashMap::new();
+        let expanded = self.attrib("ADDEXPANDED");
+        self.set_attrib("ADDEXPANDED", "NO");
+        self.clear();
+        self.update(|tree| { populate_node(tree, root, -1, None, &mut ids); });
+        self.set_attrib("ADDEXPANDED", expanded.unwrap_or("YES".into()));
+        ids
+    }
+}
+
+impl_widget!(Tree, "tree");
+
+impl ::callback::MapCb for Tree {}
+impl ::callback::UnmapCb for Tree {}
+impl ::callback::GetFocusCb for Tree {}
+impl ::callback::KillFocusCb for Tree {}
+impl ::callback::EnterWindowCb for Tree {}
+impl ::callback::LeaveWindowCb for Tree {}
+impl ::callback::HelpCb for Tree {}
+impl ::callback::KAnyCb for Tree {}
+
+/// The position is the id of the node.
+impl ::element::ConvertXYToPos for Tree {}
+
+/// Action generated when any mouse button is pressed or released inside the tree.
+impl ::callback::button::ButtonCb for Tree {}
+
+/// Action generated when the mouse is moved over the tree.
+impl ::callback::button::MotionCb for Tree {}
+
+/// Action generated when one or more files are dropped in the element.
+impl ::callback::DropFilesCb for Tree {}
+
+/// See the `SelectionCb` documentation.
+impl self::SelectionCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when a node is selected or deselected."]
+    #[doc=""]
+    #[doc="The `i32` parameter is the node id and the `bool` is whether it was selected."]
+    pub trait SelectionCb where Self: Element {
+        let name = "SELECTION_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int, status: c_int) -> CallbackReturn;
+        fn set_selection_cb<F: Callback(Self, i32, bool)>(&mut self, cb: F) -> Self;
+        fn remove_selection_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// See the `MultiSelectionCb` documentation.
+impl self::MultiSelectionCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when several nodes are selected at once with the mouse and the Shift key."]
+    #[doc=""]
+    #[doc="Valid only when MARKMODE=MULTIPLE. The `Vec<i32>` parameter holds the ids of the newly"]
+    #[doc="selected nodes. SELECTION_CB is not called for those nodes."]
+    pub trait MultiSelectionCb where Self: Element {
+        let name = "MULTISELECTION_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, ids: *mut c_int, n: c_int) -> CallbackReturn;
+        fn set_multiselection_cb<F: Callback(Self, Vec<i32>)>(&mut self, cb: F) -> Self;
+        fn remove_multiselection_cb(&mut self) -> Option<Box<_>>;
+
+        fn resolve_args(elem: Self, ids: *mut c_int, n: c_int) -> (Self, Vec<i32>) {
+            (elem, node_ids(ids, n))
+        }
+    }
+}
+
+/// See the `MultiUnselectionCb` documentation.
+impl self::MultiUnselectionCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when several nodes are deselected at once."]
+    #[doc=""]
+    #[doc="Valid only when MARKMODE=MULTIPLE. The `Vec<i32>` parameter holds the ids of the"]
+    #[doc="deselected nodes. SELECTION_CB is not called for those nodes."]
+    pub trait MultiUnselectionCb where Self: Element {
+        let name = "MULTIUNSELECTION_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, ids: *mut c_int, n: c_int) -> CallbackReturn;
+        fn set_multiunselection_cb<F: Callback(Self, Vec<i32>)>(&mut self, cb: F) -> Self;
+        fn remove_multiunselection_cb(&mut self) -> Option<Box<_>>;
+
+        fn resolve_args(elem: Self, ids: *mut c_int, n: c_int) -> (Self, Vec<i32>) {
+            (elem, node_ids(ids, n))
+        }
+    }
+}
+
+/// Copies an array of node ids received from IUP.
+fn node_ids(ids: *mut c_int, n: c_int) -> Vec<i32> {
+    if ids.is_null() || n <= 0 {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(ids, n as usize) }.to_vec()
+    }
+}
+
+/// See the `BranchOpenCb` documentation.
+impl self::BranchOpenCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when a branch is expanded, with the id of the branch."]
+    #[doc=""]
+    #[doc="`CallbackReturn::Ignore` prevents the branch from being opened."]
+    pub trait BranchOpenCb where Self: Element {
+        let name = "BRANCHOPEN_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int) -> CallbackReturn;
+        fn set_branchopen_cb<F: Callback(Self, i32)>(&mut self, cb: F) -> Self;
+        fn remove_branchopen_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// See the `BranchCloseCb` documentation.
+impl self::BranchCloseCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when a branch is collapsed, with the id of the branch."]
+    #[doc=""]
+    #[doc="`CallbackReturn::Ignore` prevents the branch from being closed."]
+    pub trait BranchCloseCb where Self: Element {
+        let name = "BRANCHCLOSE_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int) -> CallbackReturn;
+        fn set_branchclose_cb<F: Callback(Self, i32)>(&mut self, cb: F) -> Self;
+        fn remove_branchclose_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// See the `ExecuteLeafCb` documentation.
+impl self::ExecuteLeafCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when a leaf is executed (double clicked or Enter pressed on it)."]
+    #[doc=""]
+    #[doc="The `i32` parameter is the id of the leaf."]
+    pub trait ExecuteLeafCb where Self: Element {
+        let name = "EXECUTELEAF_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int) -> CallbackReturn;
+        fn set_executeleaf_cb<F: Callback(Self, i32)>(&mut self, cb: F) -> Self;
+        fn remove_executeleaf_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// The state of the check box of a tree node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CheckState {
+    Checked,
+    Unchecked,
+    /// Only available with SHOWTOGGLE=3STATE.
+    Indeterminate,
+}
+
+impl CheckState {
+    #[doc(hidden)]
+    pub fn as_attrib(self) -> &'static str {
+        match self {
+            CheckState::Checked => "ON",
+            CheckState::Unchecked => "OFF",
+            CheckState::Indeterminate => "NOTDEF",
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn from_attrib(value: &str) -> Option<CheckState> {
+        match value {
+            "ON" => Some(CheckState::Checked),
+            "OFF" => Some(CheckState::Unchecked),
+            "NOTDEF" => Some(CheckState::Indeterminate),
+            _ => None,
+        }
+    }
+}
+
+impl IntoRust<CheckState> for c_int {
+    fn into_rust(self) -> CheckState {
+        match self {
+            0 => CheckState::Unchecked,
+            -1 => CheckState::Indeterminate,
+            _ => CheckState::Checked,
+        }
+    }
+}
+
+/// See the `ToggleValueCb` documentation.
+impl self::ToggleValueCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when the check box of a node is toggled by the user."]
+    #[doc=""]
+    #[doc="Valid only when SHOWTOGGLE=YES or 3STATE. The `i32` parameter is the node id and the"]
+    #[doc="`CheckState` is the new state of the node."]
+    pub trait ToggleValueCb where Self: Element {
+        let name = "TOGGLEVALUE_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int, state: c_int) -> CallbackReturn;
+        fn set_togglevalue_cb<F: Callback(Self, i32, CheckState)>(&mut self, cb: F) -> Self;
+        fn remove_togglevalue_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// See the `ShowRenameCb` documentation.
+impl self::ShowRenameCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when a node is about to be renamed, with the id of the node."]
+    #[doc=""]
+    #[doc="Valid only when SHOWRENAME=YES. `RefuseReturn::Ignore` prevents the node from being"]
+    #[doc="edited."]
+    pub trait ShowRenameCb where Self: Element {
+        let name = "SHOWRENAME_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int) -> CallbackReturn;
+        fn set_showrename_cb<F: Callback(Self, i32) -> RefuseReturn>(&mut self, cb: F) -> Self;
+        fn remove_showrename_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// See the `RenameCb` documentation.
+impl self::RenameCb for Tree {}
+impl_callback! {
+    #[doc="Action generated when the user ends editing a node in place, by pressing Enter or when the"]
+    #[doc="edit box loses the focus, before the new title is set."]
+    #[doc=""]
+    #[doc="The `i32` parameter is the id of the node and the `String` is the new title."]
+    #[doc="Valid only when SHOWRENAME=YES. The title is changed only if `RefuseReturn::Default` is"]
+    #[doc="returned. `RefuseReturn::Ignore` rejects the new title, the node stays in edit mode if Enter"]
+    #[doc="was pressed, otherwise the editing ends."]
+    pub trait RenameCb where Self: Element {
+        let name = "RENAME_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int, title: *const c_char) -> CallbackReturn;
+        fn set_rename_cb<F: Callback(Self, i32, String) -> RefuseReturn>(&mut self, cb: F) -> Self;
+        fn remove_rename_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+/// Supplies the nodes of a `Tree` branch by branch, see `Tree::set_provider`.
+///
+/// Nodes are addressed by their path from the top level, e.g. `[2, 0]` is the first child of the
+/// third top-level node, and `[]` is the invisible parent of the top-level nodes.
+pub trait TreeProvider {
+    /// Returns the number of children of the node at `path`.
+    fn child_count(&self, path: &[usize]) -> usize;
+    /// Returns the title of the node at `path`.
+    fn title(&self, path: &[usize]) -> String;
+    /// Returns whether the node at `path` is a branch, i.e. may have children.
+    fn is_branch(&self, path: &[usize]) -> bool;
+    /// Returns how the node at `path` is shown, by default as any other node.
+    fn style(&self, path: &[usize]) -> NodeStyle {
+        let _ = path;
+        NodeStyle::new()
+    }
+}
+
+/// How a tree node is shown, the unset parts are left as they are.
+///
+/// See `Tree::set_node_style` and `FlatTree::set_node_style`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeStyle {
+    color: Option<(u8, u8, u8)>,
+    bg_color: Option<(u8, u8, u8)>,
+    font: Option<String>,
+    toggle: Option<CheckState>,
+    image: Option<String>,
+}
+
+impl NodeStyle {
+    /// Creates a style that changes nothing.
+    pub fn new() -> NodeStyle {
+        NodeStyle::default()
+    }
+
+    /// Sets the color of the title (COLORid).
+    pub fn color(mut self, rgb: (u8, u8, u8)) -> NodeStyle {
+        self.color = Some(rgb);
+        self
+    }
+
+    /// Sets the background color of the node (BACKCOLORid).
+    pub fn bg_color(mut self, rgb: (u8, u8, u8)) -> NodeStyle {
+        self.bg_color = Some(rgb);
+        self
+    }
+
+    /// Sets the font of the title (TITLEFONTid), e.g. "Helvetica, Bold 10".
+    pub fn font<S: Into<String>>(mut self, font: S) -> NodeStyle {
+        self.font = Some(font.into());
+        self
+    }
+
+    /// Sets the check state of the node (TOGGLEVALUEid), valid only when SHOWTOGGLE is set.
+    pub fn toggle(mut self, state: CheckState) -> NodeStyle {
+        self.toggle = Some(state);
+        self
+    }
+
+    /// Sets the image of the node (IMAGEid) by its handle name.
+    pub fn image<S: Into<String>>(mut self, image: S) -> NodeStyle {
+        self.image = Some(image.into());
+        self
+    }
+
+    /// Applies the style to the node `id` of a `Tree` or `FlatTree`.
+    #[doc(hidden)]
+    pub fn apply<E: Element>(&self, tree: &mut E, id: i32) {
+        let rgb = |(r, g, b): (u8, u8, u8)| format!("{} {} {}", r, g, b);
+        if let Some(color) = self.color {
+            tree.set_attrib_at("COLOR", id, rgb(color));
+        }
+        if let Some(bg_color) = self.bg_color {
+            tree.set_attrib_at("BACKCOLOR", id, rgb(bg_color));
+        }
+        if let Some(ref font) = self.font {
+            tree.set_attrib_at("TITLEFONT", id, &font[..]);
+        }
+        if let Some(state) = self.toggle {
+            tree.set_attrib_at("TOGGLEVALUE", id, state.as_attrib());
+        }
+        if let Some(ref image) = self.image {
+            tree.set_attrib_at("IMAGE", id, &image[..]);
+        }
+    }
+}
+
+/// Data shown as a hierarchy of nodes, see `Tree::populate_from`.
+pub trait TreeData {
+    /// The key identifying a node.
+    type Key: Eq + Hash;
+    /// Returns the key of this node, expected to be unique in the hierarchy.
+    fn key(&self) -> Self::Key;
+    /// Returns the title of this node.
+    fn label(&self) -> String;
+    /// Returns the children of this node.
+    fn children(&self) -> &[Self] where Self: Sized;
+    /// Returns whether this node is a branch, by default when it has children.
+    fn is_branch(&self) -> bool where Self: Sized {
+        !self.children().is_empty()
+    }
+}
+
+/// Adds `data` and its descendants as the first child of `parent`, or after its `previous`
+/// sibling, returning the id of the new node.
+///
+/// Nodes are added in the order of their ids, so the ids of the nodes already added don't change.
+fn populate_node<T: TreeData>(tree: &mut Tree, data: &T, parent: i32, previous: Option<i32>,
+                              ids: &mut HashMap<T::Key, i32>) -> Option<i32> {
+    let is_branch = data.is_branch();
+    match (previous, is_branch) {
+        (None, true) => tree.add_branch(parent, data.label()),
+        (None, false) => tree.add_leaf(parent, data.label()),
+        (Some(prev), true) => tree.insert_branch(prev, data.label()),
+        (Some(prev), false) => tree.insert_leaf(prev, data.label()),
+    };
+    let id = match tree.last_added_node() {
+        Some(id) => id,
+        None => return None,
+    };
+    ids.insert(data.key(), id);
+
+    let mut previous = None;
+    for child in data.children() {
+        previous = populate_node(tree, child, id, previous, ids);
+        if previous.is_none() {
+            break;
+        }
+    }
+    Some(id)
+}
+
+struct TreeLoader {
+    provider: Box<TreeProvider>,
+}
+
+/// The node data of the nodes added from a provider.
+struct ProviderNode {
+    path: Vec<usize>,
+    /// Whether the children of the branch were already added.
+    loaded: bool,
+}
+
+impl TreeLoader {
+    /// Adds the children of the node at `path`, which has the id `parent` (-1 for the top level).
+    fn add_children(&mut self, mut tree: Tree, parent: i32, path: Vec<usize>) {
+        tree.update(|tree| self.add_children_batch(tree, parent, path));
+    }
+
+    fn add_children_batch(&mut self, tree: &mut Tree, parent: i32, path: Vec<usize>) {
+        let count = self.provider.child_count(&path);
+        let mut previous = None;
+        for i in 0..count {
+            let mut child = path.clone();
+            child.push(i);
+            let title = self.provider.title(&child);
+            let is_branch = self.provider.is_branch(&child);
+
+            match (previous, is_branch) {
+                (None, true) => tree.add_branch(parent, title),
+                (None, false) => tree.add_leaf(parent, title),
+                (Some(prev), true) => tree.insert_branch(prev, title),
+                (Some(prev), false) => tree.insert_leaf(prev, title),
+            };
+
+            let id = match tree.last_added_node() {
+                Some(id) => id,
+                None => break,
+            };
+            self.provider.style(&child).apply(tree, id);
+            tree.set_node_data(id, ProviderNode { path: child, loaded: false });
+
+            if is_branch {
+                // A placeholder child allows the branch to be expanded before being loaded.
+                tree.add_leaf(id, "");
+            }
+            previous = Some(id);
+        }
+    }
+}
+
+thread_local!(static NODE_DATA: RefCell<Slab> = RefCell::new(Slab::new()));
+
+/// Bits of a slab key holding the entry index, the others hold the entry generation.
+#[cfg(target_pointer_width = "64")]
+const INDEX_BITS: usize = 32;
+#[cfg(not(target_pointer_width = "64"))]
+const INDEX_BITS: usize = 20;
+
+/// Storage of the tree node data.
+///
+/// Keys are the entry index plus 1, so they are never a null user id, and the generation of the
+/// entry, so the key of removed data doesn't reach the data inserted later in the same entry.
+struct Slab {
+    entries: Vec<SlabEntry>,
+    free: Vec<usize>,
+}
+
+struct SlabEntry {
+    data: Option<Box<Any>>,
+    /// Whether the entry is in use, it can be in use but taken out by `with_node_data`.
+    used: bool,
+    /// Incremented each time the entry is removed.
+    generation: usize,
+}
+
+impl Slab {
+    fn new() -> Slab {
+        Slab { entries: Vec::new(), free: Vec::new() }
+    }
+
+    fn insert(&mut self, data: Box<Any>) -> usize {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.entries[index].data = Some(data);
+                self.entries[index].used = true;
+                index
+            },
+            None => {
+                self.entries.push(SlabEntry { data: Some(data), used: true, generation: 0 });
+                self.entries.len() - 1
+            },
+        };
+        (self.entries[index].generation << INDEX_BITS) | (index + 1)
+    }
+
+    /// Gets the index of the entry of `key`, if it is still in use.
+    fn index(&self, key: usize) -> Option<usize> {
+        let index = (key & ((1 << INDEX_BITS) - 1)).wrapping_sub(1);
+        match self.entries.get(index) {
+            Some(entry) if entry.used && entry.generation == key >> INDEX_BITS => Some(index),
+            _ => None,
+        }
+    }
+
+    fn take(&mut self, key: usize) -> Option<Box<Any>> {
+        self.index(key).and_then(|index| self.entries[index].data.take())
+    }
+
+    /// Puts back a taken entry, unless it was removed in the meantime.
+    fn put_back(&mut self, key: usize, data: Box<Any>) {
+        if let Some(index) = self.index(key) {
+            self.entries[index].data = Some(data);
+        }
+    }
+
+    fn remove(&mut self, key: usize) -> Option<Box<Any>> {
+        self.index(key).and_then(|index| {
+            let entry = &mut self.entries[index];
+            entry.used = false;
+            let max_generation = usize::max_value() >> INDEX_BITS;
+            entry.generation = entry.generation.wrapping_add(1) & max_generation;
+            self.free.push(index);
+            entry.data.take()
+        })
+    }
+}
+
+/// Drops the node data when IUP removes a node, either by DELNODE or by destroying the tree.
+extern fn on_node_removed(_ih: *mut iup_sys::Ihandle, userdata: *mut c_void) -> c_int {
+    let key = userdata as usize;
+    if key != 0 {
+        // Dropped outside the borrow since dropping may access the data of other nodes.
+        let data = NODE_DATA.with(|slab| slab.borrow_mut().remove(key));
+        drop(data);
+    }
+    iup_sys::IUP_DEFAULT
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use super::Slab;
+
+    fn value(data: Option<Box<Any>>) -> Option<i32> {
+        data.and_then(|data| data.downcast_ref::<i32>().cloned())
+    }
+
+    #[test]
+    fn keys_are_never_null() {
+        let mut slab = Slab::new();
+        let key = slab.insert(Box::new(1));
+        assert!(key != 0);
+        assert!(value(slab.take(0)).is_none());
+        assert_eq!(value(slab.take(key)), Some(1));
+    }
+
+    #[test]
+    fn taken_data_is_put_back() {
+        let mut slab = Slab::new();
+        let key = slab.insert(Box::new(1));
+        let data = slab.take(key).unwrap();
+        assert!(slab.take(key).is_none());
+        slab.put_back(key, data);
+        assert_eq!(value(slab.remove(key)), Some(1));
+    }
+
+    #[test]
+    fn removed_keys_dont_reach_reused_entries() {
+        let mut slab = Slab::new();
+        let old = slab.insert(Box::new(1));
+        assert_eq!(value(slab.remove(old)), Some(1));
+        let new = slab.insert(Box::new(2));
+        assert!(new != old);
+        assert!(slab.take(old).is_none());
+        assert!(slab.remove(old).is_none());
+        assert_eq!(value(slab.take(new)), Some(2));
+    }
+
+    #[test]
+    fn data_removed_while_taken_is_not_put_back() {
+        let mut slab = Slab::new();
+        let key = slab.insert(Box::new(1));
+        let data = slab.take(key).unwrap();
+        assert!(slab.remove(key).is_none());
+        slab.put_back(key, data);
+        assert!(slab.take(key).is_none());
+        let other = slab.insert(Box::new(2));
+        assert_eq!(value(slab.take(other)), Some(2));
+    }
+}
@@ -0,0 +1,123 @@
+//! Reporting panics to the user instead of vanishing.
+//!
+//! A panic in a callback can't unwind through IUP, so the process is aborted and a GUI
+//! application simply disappears. `install_panic_dialog` sets a panic hook showing the panic
+//! message and backtrace first, with buttons to copy the report or save it to a file, and then
+//! exits with the code 101 (the code of a Rust panic).
+//!
+//! ```ignore
+//! iup::with_iup(|| {
+//!     crash::install_panic_dialog("My Application");
+//!     ...
+//! }).unwrap();
+//! ```
+use std::io::Write;
+use std::fs::File;
+use std::panic;
+use std::process;
+use std::thread::{self, ThreadId};
+use std::cell::Cell;
+use std::backtrace::Backtrace;
+
+use Element;
+use element::Widget;
+use clipboard::Clipboard;
+use control::{Button, Label, Text};
+use dialog::{Dialog, DialogElement, DialogPos, FileDlg};
+use layout::{VBox, HBox, Fill, Expand};
+use callback::{Action, ActionReturn};
+use mainloop;
+use i18n;
+
+/// Exit code of the process after a panic was reported.
+const PANIC_EXIT_CODE: i32 = 101;
+
+thread_local!(static REPORTING: Cell<bool> = Cell::new(false));
+
+/// Sets a panic hook showing a dialog for the panics of this thread (the UI thread), see the
+/// module documentation.
+///
+/// `app_name` is shown in the dialog and the report. The previous hook is still called first,
+/// thus the panic is also printed to the standard error. Panics on other threads and panics
+/// outside the main loop are only given to the previous hook.
+///
+/// The texts of the dialog are translated by the `i18n` catalog with the "IUPRUST_CRASH_*"
+/// keys.
+pub fn install_panic_dialog<S: Into<String>>(app_name: S) {
+    let app_name = app_name.into();
+    let ui_thread = thread::current().id();
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if !should_report(ui_thread) {
+            return;
+        }
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match info.payload().downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "Box<Any>".to_string(),
+            },
+        };
+        let location = info.location().map_or(String::new(), |location| {
+            format!(" at {}:{}:{}", location.file(), location.line(), location.column())
+        });
+        let report = format!("{} panicked{}:\n{}\n\nBacktrace:\n{}", app_name, location, message,
+                             Backtrace::force_capture());
+        show_report(&app_name, &report);
+        process::exit(PANIC_EXIT_CODE);
+    }));
+}
+
+/// Checks whether a panic should be reported, only once and when a dialog can be shown.
+fn should_report(ui_thread: ThreadId) -> bool {
+    if thread::current().id() != ui_thread || mainloop::level() == 0 {
+        return false;
+    }
+    // A panic while reporting is left to the previous hook.
+    !REPORTING.with(|reporting| reporting.replace(true))
+}
+
+/// Shows the modal dialog with the report.
+fn show_report(app_name: &str, report: &str) {
+    let title = i18n::tr("IUPRUST_CRASH_MESSAGE",
+                         "An unexpected error happened and the application has to close.");
+    let message = Label::with_title(title);
+    let details = Text::new().set_attrib("MULTILINE", "YES")
+                             .set_attrib("READONLY", "YES")
+                             .set_expand(Expand::Yes)
+                             .set_attrib("VISIBLELINES", "12")
+                             .set_attrib("VISIBLECOLUMNS", "60")
+                             .set_attrib("VALUE", report);
+
+    let copy_report = report.to_string();
+    let mut copy = Button::with_title(i18n::tr("IUPRUST_CRASH_COPY", "&Copy Report"));
+    copy.set_action(move |_| {
+        Clipboard::new().set_text(&copy_report[..]);
+    });
+
+    let save_report = report.to_string();
+    let mut save = Button::with_title(i18n::tr("IUPRUST_CRASH_SAVE", "&Save Report..."));
+    save.set_action(move |_| {
+        let mut dialog = FileDlg::new_save().set_attrib("FILE", "crash-report.txt");
+        let _ = dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent);
+        let saved = dialog.attrib("STATUS").map_or(false, |status| status != "-1");
+        if let (true, Some(path)) = (saved, dialog.path()) {
+            if let Ok(mut file) = File::create(path) {
+                let _ = file.write_all(save_report.as_bytes());
+            }
+        }
+        dialog.destroy();
+    });
+
+    let mut exit = Button::with_title(i18n::tr("IUPRUST_CRASH_EXIT", "E&xit"));
+    exit.set_action(|_| ActionReturn::Close);
+
+    let buttons = HBox::new(elements![Fill::new(), copy, save, exit]).set_attrib("GAP", "6");
+    let root = VBox::new(elements![message, details, buttons]).set_attrib("MARGIN", "10x10")
+                                                               .set_attrib("GAP", "8");
+    let mut dialog = Dialog::new(root);
+    dialog.set_attrib("TITLE", app_name);
+    dialog.set_default_esc(exit);
+    let _ = dialog.popup(DialogPos::Center, DialogPos::Center);
+}
@@ -0,0 +1,264 @@
+//! Tools to debug user interfaces at runtime.
+//!
+//! IUP comes with a few builtin dialogs that allow inspecting and editing the hierarchy of a
+//! dialog and the attributes of its elements while the application is running. Those are useful
+//! to find out why a element is not being sized or positioned as expected.
+//!
+//! ```ignore
+//! dialog.set_help_cb(|(dialog,)| { debug::inspect(dialog); });
+//! ```
+//!
+//! The registered element classes can also be queried programmatically, which is useful for
+//! tooling that validates attribute names or generates typed wrappers.
+//!
+//! # Attribute Validation
+//!
+//! IUP silently stores any attribute it doesn't know, so a typo such as `"EXAPND"` simply does
+//! nothing. In debug builds `set_attrib_validation` can be used to check the attribute names set
+//! by `Element::set_attrib` (and friends) against the attributes registered for the element class.
+//!
+//! # Reentrant Callbacks
+//!
+//! A callback may trigger itself, e.g. setting VALUE from a VALUECHANGED_CB or a data binding
+//! updating the element it's listening to, leading to an infinite recursion.
+//! `set_reentrancy_guard` detects a callback called again while it's still running on the same
+//! element, warning about it or suppressing the nested call.
+use iup_sys;
+use libc::{c_char, c_int};
+use std::ptr;
+use std::iter::repeat;
+use std::ffi::CString;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+use Element;
+use dialog::{Dialog, DialogElement};
+use element::Widget;
+
+/// Opens the layout inspector for the specified dialog.
+///
+/// The inspector shows the dialog hierarchy as a tree, allows changing the elements attributes
+/// and even editing the layout interactively. It is destroyed automatically when closed.
+pub fn inspect<D: DialogElement>(dialog: D) -> Dialog {
+    let mut inspector = layout_dialog(Some(dialog)).set_attrib("DESTROYWHENCLOSED", "YES");
+    inspector.show().ok();
+    inspector
+}
+
+/// Creates a layout dialog for the specified dialog, or an empty one with `None`.
+///
+/// The returned dialog is not shown nor destroyed automatically when closed, unless
+/// DESTROYWHENCLOSED=YES is set, see `inspect` for a shortcut.
+///
+/// See the [IUP LayoutDialog Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iuplayoutdialog.html
+pub fn layout_dialog<D: DialogElement>(dialog: Option<D>) -> Dialog {
+    let ih = dialog.map_or(ptr::null_mut(), |dialog| dialog.raw());
+    Dialog::from_raw(unsafe { iup_sys::IupLayoutDialog(ih) })
+}
+
+/// Creates a dialog to inspect and edit the attributes of a single element.
+///
+/// The returned dialog must be shown with `DialogElement::popup`.
+///
+/// See the [IUP ElementPropertiesDialog Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupelementpropdialog.html
+pub fn element_properties_dialog<E: Element>(elem: E) -> Dialog {
+    Dialog::from_raw(unsafe { iup_sys::IupElementPropertiesDialog(elem.raw()) })
+}
+
+/// Creates a dialog to inspect and edit the global attributes.
+///
+/// The returned dialog is not shown nor destroyed automatically when closed.
+///
+/// See the [IUP GlobalsDialog Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupglobalsdialog.html
+pub fn globals_dialog() -> Dialog {
+    Dialog::from_raw(unsafe { IupGlobalsDialog() })
+}
+
+/// Creates a dialog listing the registered classes, their attributes and callbacks.
+///
+/// The returned dialog is not shown nor destroyed automatically when closed.
+///
+/// See the [IUP ClassInfoDialog Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupclassinfodialog.html
+pub fn class_info_dialog<D: DialogElement>(parent: Option<D>) -> Dialog {
+    let ih = parent.map_or(ptr::null_mut(), |parent| parent.raw());
+    Dialog::from_raw(unsafe { IupClassInfoDialog(ih) })
+}
+
+/// Returns the names of all registered classes.
+pub fn all_classes() -> Vec<String> {
+    collect_names(|names, n| unsafe { iup_sys::IupGetAllClasses(names, n) })
+}
+
+/// Returns the names of all registered attributes of a class.
+///
+/// Returns an empty vector if the class does not exist.
+pub fn class_attributes<S: Into<String>>(classname: S) -> Vec<String> {
+    let cname = CString::new(classname.into()).unwrap();
+    collect_names(|names, n| unsafe { iup_sys::IupGetClassAttributes(cname.as_ptr(), names, n) })
+}
+
+/// Returns the names of all registered callbacks of a class.
+///
+/// Returns an empty vector if the class does not exist.
+pub fn class_callbacks<S: Into<String>>(classname: S) -> Vec<String> {
+    let cname = CString::new(classname.into()).unwrap();
+    collect_names(|names, n| unsafe { iup_sys::IupGetClassCallbacks(cname.as_ptr(), names, n) })
+}
+
+/// Calls a IUP names query first to find out the number of names and then to fill them in.
+fn collect_names<F>(query: F) -> Vec<String>
+                        where F: Fn(*mut *mut c_char, c_int) -> c_int {
+    let max = query(ptr::null_mut(), 0);
+    if max <= 0 {
+        return Vec::new();
+    }
+    let mut vec = repeat(ptr::null_mut()).take(max as usize).collect::<Vec<*mut c_char>>();
+    let len = query(vec.as_mut_ptr(), max);
+    vec.into_iter().take(len.max(0) as usize).map(|cstr| string_from_cstr!(cstr)).collect()
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupGlobalsDialog() -> *mut iup_sys::Ihandle;
+    fn IupClassInfoDialog(parent: *mut iup_sys::Ihandle) -> *mut iup_sys::Ihandle;
+}
+
+/// What to do when an unknown attribute name is set on an element.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttribValidation {
+    /// Attribute names are not checked. This is the default.
+    Off,
+    /// A warning is printed to the standard error for each unknown attribute name.
+    Warn,
+    /// Setting an unknown attribute name panics.
+    Panic,
+}
+
+/// What to do when a callback is called again while it's running on the same element.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReentrancyGuard {
+    /// Nested calls are not checked. This is the default.
+    Off,
+    /// A warning is printed to the standard error for each nested call, which still happens.
+    Warn,
+    /// Nested calls are skipped, as if the callback returned `CallbackReturn::Default`.
+    Suppress,
+}
+
+thread_local!(static VALIDATION: Cell<AttribValidation> = Cell::new(AttribValidation::Off));
+thread_local!(static REENTRANCY: Cell<ReentrancyGuard> = Cell::new(ReentrancyGuard::Off));
+// The callbacks running, by element and callback name.
+thread_local!(static RUNNING: RefCell<Vec<(usize, &'static str)>> = RefCell::new(Vec::new()));
+thread_local!(static CLASS_ATTRIBS: RefCell<HashMap<String, HashSet<String>>> = RefCell::new(HashMap::new()));
+
+/// Sets how attribute names are validated, see the module documentation.
+///
+/// Attribute names are only validated in debug builds (i.e. with `debug_assertions`), in release
+/// builds this has no effect.
+///
+/// Names starting with an underscore are reserved for application data and never checked.
+/// Attributes with ids (e.g. `"TITLE2"` or `"1:2"`) are checked without the id part.
+pub fn set_attrib_validation(mode: AttribValidation) {
+    VALIDATION.with(|v| v.set(mode));
+}
+
+/// Sets how nested calls of the same callback are handled, see the module documentation.
+///
+/// Applies to the callbacks set through the binding, in debug and release builds.
+pub fn set_reentrancy_guard(mode: ReentrancyGuard) {
+    REENTRANCY.with(|r| r.set(mode));
+}
+
+/// Marks the callback `name` of `ih` as running until the returned value is dropped.
+///
+/// Returns `None` if the callback must be skipped, see `set_reentrancy_guard`.
+#[doc(hidden)]
+pub fn enter_callback(ih: *mut iup_sys::Ihandle, name: &'static str) -> Option<RunningCallback> {
+    let mode = REENTRANCY.with(|r| r.get());
+    let key = (ih as usize, name);
+    if mode != ReentrancyGuard::Off && RUNNING.with(|running| running.borrow().contains(&key)) {
+        match mode {
+            ReentrancyGuard::Warn => {
+                eprintln!("Warning: {} called again on {:p} while still running", name, ih)
+            },
+            ReentrancyGuard::Suppress => return None,
+            ReentrancyGuard::Off => unreachable!(),
+        }
+    }
+    RUNNING.with(|running| running.borrow_mut().push(key));
+    Some(RunningCallback(key))
+}
+
+/// Gets the number of callbacks running, nested in one another.
+#[doc(hidden)]
+pub fn callback_depth() -> usize {
+    RUNNING.with(|running| running.borrow().len())
+}
+
+/// Checks whether a callback of `ih` is running.
+#[doc(hidden)]
+pub fn is_running(ih: *mut iup_sys::Ihandle) -> bool {
+    RUNNING.with(|running| running.borrow().iter().any(|&(running, _)| running == ih as usize))
+}
+
+/// A callback marked as running by `enter_callback`.
+#[doc(hidden)]
+pub struct RunningCallback((usize, &'static str));
+
+impl Drop for RunningCallback {
+    fn drop(&mut self) {
+        let key = self.0;
+        RUNNING.with(|running| {
+            let mut running = running.borrow_mut();
+            if let Some(pos) = running.iter().rposition(|&running| running == key) {
+                running.remove(pos);
+            }
+        });
+        if callback_depth() == 0 {
+            ::element::destroy_pending();
+        }
+    }
+}
+
+/// Checks whether `name` is a registered attribute of the class of `elem`.
+#[doc(hidden)]
+#[cfg(debug_assertions)]
+pub fn validate_attrib<E: Element>(elem: &E, name: &str) {
+    let mode = VALIDATION.with(|v| v.get());
+    if mode == AttribValidation::Off || name.starts_with("_") {
+        return;
+    }
+
+    let base = name.trim_right_matches(|c: char| c.is_digit(10) || c == ':' || c == '*');
+    if base.is_empty() {
+        return;
+    }
+
+    let classname = unsafe { elem.classname().to_string_lossy().into_owned() };
+    let known = CLASS_ATTRIBS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let attribs = cache.entry(classname.clone())
+                           .or_insert_with(|| class_attributes(classname.clone()).into_iter().collect());
+        // Classes that registered nothing (e.g. user classes) can't be validated.
+        attribs.is_empty() || attribs.contains(name) || attribs.contains(base)
+    });
+
+    if !known {
+        let msg = format!("Unknown attribute '{}' set on element of class '{}'", name, classname);
+        match mode {
+            AttribValidation::Warn => eprintln!("Warning: {}", msg),
+            AttribValidation::Panic => panic!("{}", msg),
+            AttribValidation::Off => unreachable!(),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[cfg(not(debug_assertions))]
+#[inline(always)]
+pub fn validate_attrib<E: Element>(_elem: &E, _name: &str) {
+}
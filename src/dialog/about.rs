@@ -0,0 +1,123 @@
+//! The about box of an application.
+use Element;
+use Handle;
+use dialog::{Dialog, DialogElement, DialogPos};
+use control::{Button, Label, Link};
+use layout::{VBox, HBox, Fill};
+use callback::{Action, ActionReturn};
+use i18n;
+
+/// Makes an `AppInfo` from the package metadata of the calling crate, set by Cargo.
+///
+/// The website is the `homepage` of the package, or its `repository` if it has no homepage.
+///
+/// ```ignore
+/// dialog::about(app_info!().with_icon(logo));
+/// ```
+#[macro_export]
+macro_rules! app_info {
+    () => {
+        $crate::dialog::AppInfo::from_cargo(
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_AUTHORS"),
+            option_env!("CARGO_PKG_LICENSE").unwrap_or(""),
+            option_env!("CARGO_PKG_HOMEPAGE").unwrap_or(""),
+            option_env!("CARGO_PKG_REPOSITORY").unwrap_or(""),
+        )
+    };
+}
+
+/// What the about box tells about the application, see `about`.
+///
+/// Empty fields are not shown.
+#[derive(Debug, Clone, Default)]
+pub struct AppInfo {
+    pub name: String,
+    pub version: String,
+    pub authors: Vec<String>,
+    pub license: String,
+    /// The URL of the website, shown as a link.
+    pub website: String,
+    /// The image of the application, e.g. its logo.
+    pub icon: Option<Handle>,
+}
+
+impl AppInfo {
+    /// Creates the information of the application `name` at `version`.
+    pub fn new<S1, S2>(name: S1, version: S2) -> AppInfo where S1: Into<String>, S2: Into<String> {
+        AppInfo { name: name.into(), version: version.into(), ..AppInfo::default() }
+    }
+
+    /// Makes the information from the Cargo package metadata, see `app_info!`.
+    #[doc(hidden)]
+    pub fn from_cargo(name: &str, version: &str, authors: &str, license: &str, homepage: &str,
+                      repository: &str) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            // Cargo separates the authors with colons.
+            authors: authors.split(':').map(|author| author.trim().to_string())
+                                       .filter(|author| !author.is_empty())
+                                       .collect(),
+            license: license.to_string(),
+            website: if homepage.is_empty() { repository } else { homepage }.to_string(),
+            icon: None,
+        }
+    }
+
+    /// Sets the image of the application.
+    pub fn with_icon<E: Into<Handle>>(mut self, icon: E) -> AppInfo {
+        self.icon = Some(icon.into());
+        self
+    }
+}
+
+/// Shows a modal about box with the name, version, authors, license and website of the
+/// application, next to its image.
+///
+/// The texts around the information are translated by the `i18n` catalog with the
+/// "IUPRUST_ABOUT_*" keys.
+pub fn about(info: AppInfo) {
+    let mut name = Label::with_title(format!("{} {}", info.name, info.version).trim().to_string());
+    name.set_attrib("FONTSTYLE", "Bold");
+    name.set_attrib("FONTSIZE", "14");
+    let mut lines = elements![name];
+    if !info.authors.is_empty() {
+        let by = i18n::tr("IUPRUST_ABOUT_AUTHORS", "By");
+        lines.push(Label::with_title(format!("{} {}", by, info.authors.join(", "))).into());
+    }
+    if !info.license.is_empty() {
+        let license = i18n::tr("IUPRUST_ABOUT_LICENSE", "License:");
+        lines.push(Label::with_title(format!("{} {}", license, info.license)).into());
+    }
+    if !info.website.is_empty() {
+        lines.push(Link::new(&info.website[..], &info.website[..]).into());
+    }
+    let text = VBox::new(lines).set_attrib("GAP", "6");
+
+    let mut contents = Vec::new();
+    if let Some(icon) = info.icon {
+        contents.push(Label::new().set_attrib_handle("IMAGE", icon).into());
+    }
+    contents.push(text.into());
+
+    let mut close = Button::with_title(i18n::tr("IUPRUST_ABOUT_CLOSE", "Close"))
+                           .set_attrib("PADDING", "12x2");
+    close.set_action(|_| ActionReturn::Close);
+    let buttons = HBox::new(elements![Fill::new(), close]);
+    let root = VBox::new(elements![HBox::new(contents).set_attrib("GAP", "16"), buttons])
+                        .set_attrib("MARGIN", "16x16")
+                        .set_attrib("GAP", "16");
+
+    let mut dialog = Dialog::new(root);
+    let title = i18n::tr("IUPRUST_ABOUT_TITLE", "About");
+    dialog.set_attrib("TITLE", format!("{} {}", title, info.name).trim().to_string());
+    dialog.set_attrib("RESIZE", "NO");
+    dialog.set_attrib("MINBOX", "NO");
+    dialog.set_attrib("MAXBOX", "NO");
+    dialog.set_default_enter(close);
+    dialog.set_default_esc(close);
+    let _ = dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent);
+    dialog.destroy();
+}
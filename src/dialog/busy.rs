@@ -0,0 +1,179 @@
+//! Dimming a dialog while a long operation runs.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use Element;
+use Handle;
+use element::{WeakHandle, Container};
+use dialog::{Dialog, Rect};
+use control::{Label, ProgressBar};
+use layout::{VBox, Fill, BackgroundBox};
+use util;
+use i18n;
+
+/// The opacity of the overlay, from 0 to 255.
+const OVERLAY_OPACITY: u8 = 160;
+
+/// A dialog running work in the background.
+struct Busy {
+    dialog: WeakHandle<Dialog>,
+    overlay: WeakHandle<Dialog>,
+    /// The children disabled by the overlay, to be enabled back.
+    disabled: Vec<Handle>,
+    /// The number of operations running, they may overlap.
+    count: usize,
+}
+
+/// Finishes an operation with its result, a boxed `thread::Result`.
+type Finish = Box<FnOnce(Box<Any + Send>)>;
+
+thread_local!(static BUSY: RefCell<Vec<Busy>> = RefCell::new(Vec::new()));
+// The operations running, by the id sent to their thread.
+thread_local!(static RUNNING: RefCell<Vec<(u64, Finish)>> = RefCell::new(Vec::new()));
+thread_local!(static NEXT_ID: Cell<u64> = Cell::new(1));
+
+impl Dialog {
+    /// Runs `work` in another thread while the dialog is dimmed by an overlay showing a progress
+    /// animation, and its children are inactive (ACTIVE=NO).
+    ///
+    /// When the work is done the dialog is restored and `done` is called with its result, in the
+    /// main loop thread. A panic of the work is given to `done` as an error instead.
+    ///
+    /// ```ignore
+    /// dialog.with_busy_overlay(move || index_files(&folder), move |result| match result {
+    ///     Ok(count) => status.set_attrib("TITLE", format!("{} files indexed", count)),
+    ///     Err(_) => status.set_attrib("TITLE", "Indexing failed"),
+    /// });
+    /// ```
+    ///
+    /// The overlay covers the dialog where it is when the work starts, it doesn't follow the dialog
+    /// when moved. Posting is enabled if it wasn't, see `util::post`. The text of the overlay is
+    /// translated by the `i18n` catalog with the "IUPRUST_BUSY" key.
+    pub fn with_busy_overlay<T, F, G>(&mut self, work: F, done: G) -> Dialog
+            where T: Send + 'static, F: FnOnce() -> T + Send + 'static,
+                  G: FnOnce(thread::Result<T>) + 'static {
+        util::enable_posting();
+        self.start_busy();
+        let dialog = self.downgrade();
+        // `done` and the dialog stay in this thread, only the result of the work is sent back.
+        let finish: Finish = Box::new(move |result: Box<Any + Send>| {
+            if let Some(dialog) = dialog.upgrade() {
+                dialog.stop_busy();
+            }
+            done(*result.downcast::<thread::Result<T>>().unwrap());
+        });
+        let id = NEXT_ID.with(|next_id| next_id.replace(next_id.get() + 1));
+        RUNNING.with(|running| running.borrow_mut().push((id, finish)));
+        thread::spawn(move || {
+            let result: Box<Any + Send> = Box::new(panic::catch_unwind(AssertUnwindSafe(work)));
+            let _ = util::post(move || finish_running(id, result));
+        });
+        *self
+    }
+
+    /// Dims the dialog, unless it's already busy.
+    fn start_busy(&self) {
+        let running = BUSY.with(|busy| {
+            let mut busy = busy.borrow_mut();
+            busy.retain(|busy| busy.dialog.is_alive());
+            match busy.iter_mut().find(|busy| busy.dialog.upgrade() == Some(*self)) {
+                Some(busy) => { busy.count += 1; true },
+                None => false,
+            }
+        });
+        if running {
+            return;
+        }
+
+        let disabled: Vec<Handle> = self.children().into_iter().filter(|child| {
+            child.attrib("ACTIVE").map_or(true, |active| active != "NO")
+        }).collect();
+        for mut child in disabled.iter().cloned() {
+            child.set_attrib("ACTIVE", "NO");
+        }
+        let overlay = self.show_overlay();
+        BUSY.with(|busy| busy.borrow_mut().push(Busy {
+            dialog: self.downgrade(),
+            overlay: overlay.downgrade(),
+            disabled: disabled,
+            count: 1,
+        }));
+    }
+
+    /// Restores the dialog when the last operation running is done.
+    fn stop_busy(&self) {
+        let busy = BUSY.with(|busy| {
+            let mut busy = busy.borrow_mut();
+            let pos = match busy.iter().position(|busy| busy.dialog.upgrade() == Some(*self)) {
+                Some(pos) => pos,
+                None => return None,
+            };
+            busy[pos].count -= 1;
+            match busy[pos].count {
+                0 => Some(busy.remove(pos)),
+                _ => None,
+            }
+        });
+        if let Some(busy) = busy {
+            if let Some(overlay) = busy.overlay.upgrade() {
+                overlay.destroy();
+            }
+            for mut child in busy.disabled {
+                child.set_attrib("ACTIVE", "YES");
+            }
+        }
+    }
+
+    /// Shows the overlay over the client area of the dialog.
+    fn show_overlay(&self) -> Dialog {
+        let label = Label::with_title(i18n::tr("IUPRUST_BUSY", "Working..."))
+                          .set_attrib("FGCOLOR", "255 255 255");
+        let progress = ProgressBar::new().set_attrib("MARQUEE", "YES");
+        let content = VBox::new(elements![Fill::new(), label, progress, Fill::new()])
+                          .set_attrib("ALIGNMENT", "ACENTER")
+                          .set_attrib("GAP", "8");
+        let mut overlay = Dialog::new(BackgroundBox::new(content).set_attrib("BGCOLOR", "0 0 0"))
+                                 .set_attrib("BORDER", "NO")
+                                 .set_attrib("RESIZE", "NO")
+                                 .set_attrib("MENUBOX", "NO")
+                                 .set_attrib("MAXBOX", "NO")
+                                 .set_attrib("MINBOX", "NO")
+                                 .set_attrib_handle("PARENTDIALOG", *self);
+        overlay.set_opacity(OVERLAY_OPACITY);
+        overlay.set_geometry(self.client_area());
+        overlay
+    }
+
+    /// Gets the position and size of the client area on the screen, in pixels.
+    fn client_area(&self) -> Rect {
+        let pair = |value: Option<String>, sep: char| value.and_then(|value| {
+            let mut parts = value.splitn(2, sep).map(|part| part.trim().parse::<i32>().ok());
+            match (parts.next(), parts.next()) {
+                (Some(Some(a)), Some(Some(b))) => Some((a, b)),
+                _ => None,
+            }
+        });
+        // The child of the dialog starts at the client area.
+        let position = pair(self.child(0).and_then(|child| child.attrib("SCREENPOSITION")), ',');
+        match (position, pair(self.attrib("CLIENTSIZE"), 'x')) {
+            (Some((x, y)), Some((width, height))) => {
+                Rect { x: x, y: y, width: width, height: height }
+            },
+            _ => self.geometry(),
+        }
+    }
+}
+
+/// Finishes the operation `id`, in the main loop thread.
+fn finish_running(id: u64, result: Box<Any + Send>) {
+    let finish = RUNNING.with(|running| {
+        let mut running = running.borrow_mut();
+        running.iter().position(|&(running_id, _)| running_id == id)
+               .map(|pos| running.remove(pos).1)
+    });
+    if let Some(finish) = finish {
+        finish(result);
+    }
+}
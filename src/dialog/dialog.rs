@@ -1,8 +1,12 @@
 use iup_sys;
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, c_void};
 use std::ptr;
 
 use Element;
+use config::Config;
+use image::ImageElement;
+use control::Button;
+use element::{Widget, Size};
 use callback::IntoRust;
 use callback::button::{MouseButton, MouseButtonState};
 
@@ -24,6 +28,206 @@ impl Dialog {
     pub fn new_empty() -> Dialog {
         unsafe { Dialog::from_raw(iup_sys::IupDialog(ptr::null_mut())) }
     }
+
+    /// Gets the position and size of the dialog on the screen, in pixels.
+    ///
+    /// The position includes the decorations of the dialog and the size is the RASTERSIZE.
+    pub fn geometry(&self) -> Rect {
+        let (x, y) = self.attrib("SCREENPOSITION").and_then(|s| parse_pair(&s, ','))
+                                                  .unwrap_or((0, 0));
+        let (width, height) = self.attrib("RASTERSIZE").and_then(|s| parse_pair(&s, 'x'))
+                                                       .unwrap_or((0, 0));
+        Rect { x: x, y: y, width: width, height: height }
+    }
+
+    /// Sets the position and size of the dialog on the screen, in pixels.
+    ///
+    /// The dialog is shown if hidden, see `Dialog::move_to`.
+    pub fn set_geometry(&mut self, rect: Rect) -> Dialog {
+        self.set_size(Size::px(rect.width, rect.height));
+        self.move_to(rect.x, rect.y)
+    }
+
+    /// Moves the dialog to the specified position of the screen, in pixels.
+    ///
+    /// This is the same as `DialogElement::showxy`, so the dialog is shown if hidden.
+    pub fn move_to(&mut self, x: i32, y: i32) -> Dialog {
+        unsafe { iup_sys::IupShowXY(self.raw(), x, y) };
+        *self
+    }
+
+    /// Maximizes and shows the dialog.
+    pub fn maximize(&mut self) -> Dialog {
+        self.set_placement("MAXIMIZED")
+    }
+
+    /// Minimizes and shows the dialog.
+    pub fn minimize(&mut self) -> Dialog {
+        self.set_placement("MINIMIZED")
+    }
+
+    /// Restores and shows a maximized or minimized dialog.
+    pub fn restore(&mut self) -> Dialog {
+        self.set_placement("NORMAL")
+    }
+
+    /// Checks whether the dialog is maximized.
+    pub fn is_maximized(&self) -> bool {
+        self.attrib_bool("MAXIMIZED").unwrap_or(false)
+    }
+
+    /// Checks whether the dialog is minimized.
+    pub fn is_minimized(&self) -> bool {
+        self.attrib_bool("MINIMIZED").unwrap_or(false)
+    }
+
+    /// Makes the dialog occupy the whole screen, without decorations, or restores it.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) -> Dialog {
+        self.set_attrib("FULLSCREEN", if fullscreen { "YES" } else { "NO" })
+    }
+
+    /// Checks whether the dialog is in fullscreen mode.
+    pub fn is_fullscreen(&self) -> bool {
+        self.attrib_bool("FULLSCREEN").unwrap_or(false)
+    }
+
+    /// Shows the dialog with the geometry saved in `config` under the `key` group, and saves it
+    /// back every time the dialog is hidden or closed.
+    ///
+    /// Use this instead of `Widget::show`. This sets the SHOW_CB callback of the dialog. The
+    /// config is not kept alive by the dialog, the geometry is no longer saved once the config
+    /// is destroyed (e.g. by dropping its `Guard`).
+    pub fn remember_geometry<S: Into<String>>(&mut self, config: Config, key: S) -> Dialog {
+        let key = key.into();
+        let weak_config = config.downgrade();
+        let closed_key = key.clone();
+        self.set_show_cb(move |(dialog, state): (Dialog, ShowState)| {
+            if state != ShowState::Hide {
+                return;
+            }
+            if let Some(mut config) = weak_config.upgrade() {
+                config.dialog_closed(dialog, closed_key.clone());
+                config.save().ok();
+            }
+        });
+        let mut config = config;
+        config.show_dialog(*self, key);
+        *self
+    }
+
+    /// [Windows and GTK Only]: Sets the transparency of the whole dialog, from 0 (invisible) to
+    /// 255 (opaque).
+    pub fn set_opacity(&mut self, opacity: u8) -> Dialog {
+        self.set_attrib("OPACITY", opacity.to_string())
+    }
+
+    /// Gets the transparency of the dialog, see `set_opacity`.
+    pub fn opacity(&self) -> Option<u8> {
+        self.attrib_parse("OPACITY")
+    }
+
+    /// [Windows and GTK Only]: Uses the alpha channel of an RGBA image as the per-pixel
+    /// transparency of the dialog.
+    ///
+    /// The image must have the same size of the dialog.
+    pub fn set_opacity_image<I: ImageElement>(&mut self, image: I) -> Dialog {
+        self.set_attrib_handle("OPACITYIMAGE", image)
+    }
+
+    /// [Windows and GTK Only]: Uses the transparent pixels of an image to define the shape of the
+    /// dialog, the transparent area is not part of the dialog.
+    pub fn set_shape_image<I: ImageElement>(&mut self, image: I) -> Dialog {
+        self.set_attrib_handle("SHAPEIMAGE", image)
+    }
+
+    /// Puts the dialog always on top of all other windows, even of other applications.
+    pub fn set_topmost(&mut self, topmost: bool) -> Dialog {
+        self.set_attrib("TOPMOST", if topmost { "YES" } else { "NO" })
+    }
+
+    /// Shows or hides the border of the dialog. Must be set before the dialog is mapped.
+    pub fn set_border(&mut self, border: bool) -> Dialog {
+        self.set_attrib("BORDER", if border { "YES" } else { "NO" })
+    }
+
+    /// Allows or prevents the user from resizing the dialog. Must be set before the dialog is
+    /// mapped.
+    pub fn set_resizable(&mut self, resizable: bool) -> Dialog {
+        self.set_attrib("RESIZE", if resizable { "YES" } else { "NO" })
+    }
+
+    /// Sets the button clicked when Enter is pressed in the dialog (DEFAULTENTER attribute),
+    /// unless the focus is on another button or on a multiline text.
+    pub fn set_default_enter(&mut self, button: Button) -> Dialog {
+        self.set_attrib_handle("DEFAULTENTER", button)
+    }
+
+    /// Sets the button clicked when Esc is pressed in the dialog (DEFAULTESC attribute),
+    /// usually the one cancelling it.
+    pub fn set_default_esc(&mut self, button: Button) -> Dialog {
+        self.set_attrib_handle("DEFAULTESC", button)
+    }
+
+    /// Replaces the native title bar and borders of the dialog by elements of the application,
+    /// see `component::TitleBar`. Must be set before the dialog is mapped.
+    pub fn set_custom_frame(&mut self, frame: CustomFrame) -> Dialog {
+        match frame {
+            CustomFrame::Native => self.set_attrib("CUSTOMFRAME", "YES"),
+            CustomFrame::NativeEx => self.set_attrib("CUSTOMFRAMEEX", "YES"),
+            CustomFrame::Simulated => self.set_attrib("CUSTOMFRAMESIMULATE", "YES"),
+        }
+    }
+
+    /// Sets the height in pixels of the caption area of a custom frame, the area from which the
+    /// dialog can be dragged.
+    pub fn set_custom_frame_caption_height(&mut self, height: i32) -> Dialog {
+        self.set_attrib("CUSTOMFRAMECAPTIONHEIGHT", height.to_string())
+    }
+
+    /// Sets a native window, not created by IUP, as the parent of the dialog.
+    ///
+    /// The parent is a `HWND` in Windows, a `GtkWidget*` in GTK or a `Widget` in Motif,
+    /// see `native::NativeHandle`. The pointer must be valid while the dialog is mapped.
+    pub fn set_native_parent(&mut self, parent: *mut c_void) -> Dialog {
+        self.set_attrib_data("NATIVEPARENT", parent as *const c_void)
+    }
+
+    fn set_placement(&mut self, placement: &str) -> Dialog {
+        self.set_attrib("PLACEMENT", placement);
+        self.show().ok();
+        *self
+    }
+}
+
+/// How a custom frame is implemented, see `Dialog::set_custom_frame`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CustomFrame {
+    /// [Windows Only]: The native frame is extended over the client area (CUSTOMFRAME).
+    Native,
+    /// [Windows Only]: Same as `Native` but the borders are also removed (CUSTOMFRAMEEX).
+    NativeEx,
+    /// All decorations are removed and IUP simulates the frame behaviour, available in all
+    /// systems (CUSTOMFRAMESIMULATE).
+    Simulated,
+}
+
+/// A rectangle on the screen or inside an element, in pixels.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Parses attribute values such as `"10,20"` or `"640x480"`.
+fn parse_pair(s: &str, sep: char) -> Option<(i32, i32)> {
+    let mut parts = s.splitn(2, sep);
+    match (parts.next().and_then(|a| a.trim().parse().ok()),
+           parts.next().and_then(|b| b.trim().parse().ok())) {
+        (Some(a), Some(b)) => Some((a, b)),
+        _ => None,
+    }
 }
 
 impl_dialog!(Dialog, "dialog");
@@ -34,7 +238,7 @@ impl ::callback::KillFocusCb for Dialog {}
 impl ::callback::EnterWindowCb for Dialog {}
 impl ::callback::LeaveWindowCb for Dialog {}
 impl ::callback::HelpCb for Dialog {}
-// TODO impl K_ callbacks when it's implemented.
+impl ::callback::KAnyCb for Dialog {}
 
 /// Called right before the dialog is closed.
 impl ::callback::CloseCb for Dialog {}
@@ -66,6 +270,9 @@ impl self::ShowCb for Dialog {}
 /// See the `TrayClickCb` documentation.
 impl self::TrayClickCb for Dialog {}
 
+/// See the `CustomFrameActivateCb` documentation.
+impl self::CustomFrameActivateCb for Dialog {}
+
 
 
 impl_callback! {
@@ -113,7 +320,11 @@ impl_callback! {
     pub trait ShowCb where Self: Element {
         let name = "SHOW_CB";
         extern fn listener(ih: *mut iup_sys::Ihandle, state: c_int) -> CallbackReturn;
+        fn set_show_cb<F: Callback(Self, ShowState)>(&mut self, cb: F) -> Self;
+        fn remove_show_cb(&mut self) -> Option<Box<_>>;
+        #[deprecated(note = "renamed to `set_show_cb`, the old name clashes with `MoveCb`")]
         fn set_move_cb<F: Callback(Self, ShowState)>(&mut self, cb: F) -> Self;
+        #[deprecated(note = "renamed to `remove_show_cb`, the old name clashes with `MoveCb`")]
         fn remove_move_cb(&mut self) -> Option<Box<_>>;
     }
 }
@@ -138,6 +349,19 @@ impl_callback! {
     }
 }
 
+impl_callback! {
+    #[doc="Called when a dialog with a custom frame is activated or deactivated, so the custom"]
+    #[doc="title bar can be drawn accordingly."]
+    #[doc=""]
+    #[doc="The `bool` parameter is whether the dialog was activated."]
+    pub trait CustomFrameActivateCb where Self: Element {
+        let name = "CUSTOMFRAMEACTIVATE_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, active: c_int) -> CallbackReturn;
+        fn set_customframeactivate_cb<F: Callback(Self, bool)>(&mut self, cb: F) -> Self;
+        fn remove_customframeactivate_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ShowState {
     Hide,
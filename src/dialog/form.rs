@@ -0,0 +1,266 @@
+//! A typed dialog asking for several values at once.
+use iup_sys;
+use libc::{c_char, c_float, c_int, c_void};
+use std::ffi::{CStr, CString};
+use std::result::Result;
+
+use dialog::message;
+
+/// Maximum number of bytes (including the nul terminator) of the string fields.
+const MAX_TEXT: usize = 4096;
+
+/// The value of a `FormPrompt` field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Str(String),
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+    /// The index of the selected choice.
+    Choice(usize),
+}
+
+impl FieldValue {
+    /// Gets the string of a `Str` field.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self { FieldValue::Str(ref s) => Some(&s[..]), _ => None }
+    }
+
+    /// Gets the number of an `Int` field.
+    pub fn as_int(&self) -> Option<i32> {
+        match *self { FieldValue::Int(i) => Some(i), _ => None }
+    }
+
+    /// Gets the number of a `Float` field.
+    pub fn as_float(&self) -> Option<f32> {
+        match *self { FieldValue::Float(f) => Some(f), _ => None }
+    }
+
+    /// Gets the state of a `Bool` field.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self { FieldValue::Bool(b) => Some(b), _ => None }
+    }
+
+    /// Gets the selected index of a `Choice` field.
+    pub fn as_choice(&self) -> Option<usize> {
+        match *self { FieldValue::Choice(i) => Some(i), _ => None }
+    }
+}
+
+/// Types which can be built from the values of a `FormPrompt`, see `FormPrompt::run_into`.
+pub trait FromForm: Sized {
+    /// Builds the value from the fields, in the order they were declared.
+    fn from_form(values: Vec<FieldValue>) -> Result<Self, String>;
+}
+
+struct Field {
+    label: String,
+    default: FieldValue,
+    choices: Vec<String>,
+    validator: Option<Box<Fn(&FieldValue) -> Result<(), String>>>,
+}
+
+/// A modal dialog with a labeled input for each declared field, built on IupGetParam.
+///
+/// ```ignore
+/// let values = FormPrompt::new("New User")
+///     .string("Name:", "")
+///     .validate(|v| match v.as_str() {
+///         Some("") => Err("The name is required".into()),
+///         _ => Ok(()),
+///     })
+///     .int("Age:", 18)
+///     .choice("Role:", &["User", "Admin"], 0)
+///     .run();
+/// ```
+///
+/// Labels must not contain '%' and choices must not contain '|'.
+pub struct FormPrompt {
+    title: String,
+    fields: Vec<Field>,
+}
+
+impl FormPrompt {
+    /// Creates a form with no fields.
+    pub fn new<S: Into<String>>(title: S) -> FormPrompt {
+        FormPrompt { title: title.into(), fields: Vec::new() }
+    }
+
+    /// Adds a single line string field.
+    pub fn string<S1, S2>(self, label: S1, default: S2) -> FormPrompt
+                                            where S1: Into<String>, S2: Into<String> {
+        self.field(label.into(), FieldValue::Str(default.into()), Vec::new())
+    }
+
+    /// Adds an integer field.
+    pub fn int<S: Into<String>>(self, label: S, default: i32) -> FormPrompt {
+        self.field(label.into(), FieldValue::Int(default), Vec::new())
+    }
+
+    /// Adds a real number field.
+    pub fn float<S: Into<String>>(self, label: S, default: f32) -> FormPrompt {
+        self.field(label.into(), FieldValue::Float(default), Vec::new())
+    }
+
+    /// Adds a check box field.
+    pub fn boolean<S: Into<String>>(self, label: S, default: bool) -> FormPrompt {
+        self.field(label.into(), FieldValue::Bool(default), Vec::new())
+    }
+
+    /// Adds a drop down field selecting one of `choices`.
+    pub fn choice<S: Into<String>>(self, label: S, choices: &[&str], default: usize) -> FormPrompt {
+        let choices = choices.iter().map(|s| s.to_string()).collect();
+        self.field(label.into(), FieldValue::Choice(default), choices)
+    }
+
+    /// Validates the last added field when the user confirms the dialog.
+    ///
+    /// An error message is shown to the user and the dialog is kept open.
+    ///
+    /// # Panics
+    /// Panics if no field was added.
+    pub fn validate<F>(mut self, validator: F) -> FormPrompt
+                                where F: Fn(&FieldValue) -> Result<(), String> + 'static {
+        self.fields.last_mut().expect("no field to validate").validator = Some(Box::new(validator));
+        self
+    }
+
+    fn field(mut self, label: String, default: FieldValue, choices: Vec<String>) -> FormPrompt {
+        self.fields.push(Field {
+            label: label,
+            default: default,
+            choices: choices,
+            validator: None,
+        });
+        self
+    }
+
+    /// Shows the dialog and returns the values of the fields in the order they were declared,
+    /// or `None` if the dialog was cancelled.
+    pub fn run(&self) -> Option<Vec<FieldValue>> {
+        let mut format = String::new();
+        let mut storage: Vec<Storage> = Vec::with_capacity(self.fields.len());
+        for field in self.fields.iter() {
+            format.push_str(&field.label);
+            match field.default {
+                FieldValue::Str(ref s) => {
+                    format.push_str("%s\n");
+                    let mut buf: Vec<c_char> = s.bytes().take(MAX_TEXT - 1)
+                                                .map(|b| b as c_char).collect();
+                    buf.resize(MAX_TEXT, 0);
+                    storage.push(Storage::Str(buf));
+                },
+                FieldValue::Int(i) => {
+                    format.push_str("%i\n");
+                    storage.push(Storage::Int(Box::new(i as c_int)));
+                },
+                FieldValue::Float(f) => {
+                    format.push_str("%r\n");
+                    storage.push(Storage::Float(Box::new(f as c_float)));
+                },
+                FieldValue::Bool(b) => {
+                    format.push_str("%b\n");
+                    storage.push(Storage::Int(Box::new(b as c_int)));
+                },
+                FieldValue::Choice(i) => {
+                    format.push_str("%l|");
+                    for choice in field.choices.iter() {
+                        format.push_str(choice);
+                        format.push('|');
+                    }
+                    format.push('\n');
+                    storage.push(Storage::Int(Box::new(i as c_int)));
+                },
+            }
+        }
+
+        let mut data: Vec<*mut c_void> = storage.iter_mut().map(|s| s.as_ptr()).collect();
+        let ctitle = CString::new(&self.title[..]).unwrap();
+        let cformat = CString::new(format).unwrap();
+        let user_data = self as *const FormPrompt as *mut c_void;
+        let ret = unsafe {
+            iup_sys::IupGetParamv(ctitle.as_ptr(), on_param, user_data, cformat.as_ptr(),
+                                  self.fields.len() as c_int, 0, data.as_mut_ptr())
+        };
+        if ret != 1 {
+            return None;
+        }
+        Some(self.fields.iter().zip(storage.iter()).map(|(field, storage)| {
+            match (&field.default, storage) {
+                (&FieldValue::Str(_), &Storage::Str(ref buf)) => {
+                    let cstr = unsafe { CStr::from_ptr(buf.as_ptr()) };
+                    FieldValue::Str(cstr.to_string_lossy().into_owned())
+                },
+                (&FieldValue::Int(_), &Storage::Int(ref i)) => FieldValue::Int(**i as i32),
+                (&FieldValue::Float(_), &Storage::Float(ref f)) => FieldValue::Float(**f as f32),
+                (&FieldValue::Bool(_), &Storage::Int(ref i)) => FieldValue::Bool(**i != 0),
+                (&FieldValue::Choice(_), &Storage::Int(ref i)) => FieldValue::Choice(**i as usize),
+                _ => unreachable!(),
+            }
+        }).collect())
+    }
+
+    /// Shows the dialog and builds a `T` from the values, or returns `None` if the dialog was
+    /// cancelled.
+    pub fn run_into<T: FromForm>(&self) -> Option<Result<T, String>> {
+        self.run().map(T::from_form)
+    }
+
+    /// Reads the value the user entered for the field at `index`, while the dialog is shown.
+    fn current_value(&self, dialog: *mut iup_sys::Ihandle, index: usize) -> Option<FieldValue> {
+        let cparam = CString::new(format!("PARAM{}", index)).unwrap();
+        // The attribute holds the handle of the parameter element, not a string.
+        let param = unsafe { iup_sys::IupGetAttribute(dialog, cparam.as_ptr()) };
+        let param = param as *mut iup_sys::Ihandle;
+        if param.is_null() {
+            return None;
+        }
+        let cvalue = unsafe { iup_sys::IupGetAttribute(param, cstr!("VALUE")) };
+        let value = if cvalue.is_null() { String::new() } else { string_from_cstr!(cvalue) };
+        match self.fields[index].default {
+            FieldValue::Str(_) => Some(FieldValue::Str(value)),
+            FieldValue::Int(_) => value.trim().parse().ok().map(FieldValue::Int),
+            FieldValue::Float(_) => value.trim().parse().ok().map(FieldValue::Float),
+            FieldValue::Bool(_) => Some(FieldValue::Bool(value.trim() == "1")),
+            FieldValue::Choice(_) => value.trim().parse().ok().map(FieldValue::Choice),
+        }
+    }
+}
+
+enum Storage {
+    Str(Vec<c_char>),
+    Int(Box<c_int>),
+    Float(Box<c_float>),
+}
+
+impl Storage {
+    fn as_ptr(&mut self) -> *mut c_void {
+        match *self {
+            Storage::Str(ref mut buf) => buf.as_mut_ptr() as *mut c_void,
+            Storage::Int(ref mut i) => &mut **i as *mut c_int as *mut c_void,
+            Storage::Float(ref mut f) => &mut **f as *mut c_float as *mut c_void,
+        }
+    }
+}
+
+/// IUP_GETPARAM_BUTTON1, the OK button was pressed.
+const GETPARAM_BUTTON1: c_int = -1;
+
+/// Runs the validators when the user confirms the dialog, keeping it open on errors.
+extern fn on_param(dialog: *mut iup_sys::Ihandle, index: c_int, user_data: *mut c_void) -> c_int {
+    if index != GETPARAM_BUTTON1 {
+        return 1;
+    }
+    let form = unsafe { &*(user_data as *const FormPrompt) };
+    for (i, field) in form.fields.iter().enumerate() {
+        if let Some(ref validator) = field.validator {
+            if let Some(value) = form.current_value(dialog, i) {
+                if let Err(msg) = validator(&value) {
+                    message(form.title.clone(), msg);
+                    return 0;
+                }
+            }
+        }
+    }
+    1
+}
@@ -2,8 +2,14 @@ use iup_sys;
 use std::ffi::CString;
 
 use Element;
+use Handle;
+use dialog::{DialogElement, DialogPos};
+use i18n;
+use util;
 
 /// Shows a modal dialog containing a message. It simply creates and popup a `MessageDlg`.
+///
+/// See `message_dlg` for a message with an icon and other buttons.
 pub fn message<S1, S2>(title: S1, message: S2)
                 where S1: Into<String>, S2: Into<String> {
     let ctitle = CString::new(title.into()).unwrap();
@@ -11,6 +17,66 @@ pub fn message<S1, S2>(title: S1, message: S2)
    	unsafe { iup_sys::IupMessage(ctitle.as_ptr(), cmessage.as_ptr()) };
 }
 
+/// Shows an error message in a modal dialog, formatting its arguments like `format!`.
+///
+/// The dialog is centered on the dialog having the focus, if any, and is shown from the main
+/// loop thread even when called from other threads (see `util::run_in_main_thread`), in which
+/// case it returns right away. Those threads need `util::enable_posting` to have been called,
+/// otherwise the message is written to the standard error instead.
+///
+/// ```ignore
+/// if let Err(err) = fs::write(&path, data) {
+///     message_error!("Failed to save {}: {}", path.display(), err);
+/// }
+/// ```
+///
+/// The title is translated by the `i18n` catalog with the "IUPRUST_MESSAGE_ERROR" key.
+#[macro_export]
+macro_rules! message_error {
+    ($($arg:tt)*) => {
+        $crate::dialog::message::show_message($crate::dialog::DialogType::Error, format!($($arg)*))
+    };
+}
+
+/// Shows an information message in a modal dialog, formatting its arguments like `format!`.
+///
+/// See `message_error!`. The title is translated by the `i18n` catalog with the
+/// "IUPRUST_MESSAGE_INFO" key.
+#[macro_export]
+macro_rules! message_info {
+    ($($arg:tt)*) => {
+        $crate::dialog::message::show_message($crate::dialog::DialogType::Information,
+                                              format!($($arg)*))
+    };
+}
+
+/// Shows `message` for the `message_error!` and `message_info!` macros.
+#[doc(hidden)]
+pub fn show_message(dialog_type: DialogType, message: String) {
+    let shown = message.clone();
+    let result = util::run_in_main_thread(move || {
+        let title = match dialog_type {
+            DialogType::Error => i18n::tr("IUPRUST_MESSAGE_ERROR", "Error"),
+            DialogType::Warning => i18n::tr("IUPRUST_MESSAGE_WARNING", "Warning"),
+            DialogType::Information => i18n::tr("IUPRUST_MESSAGE_INFO", "Information"),
+            DialogType::Question => i18n::tr("IUPRUST_MESSAGE_QUESTION", "Question"),
+        };
+        let mut dialog = MessageDlg::with(dialog_type, Buttons::Ok, title, message);
+        let focus = unsafe { iup_sys::IupGetFocus() };
+        if !focus.is_null() {
+            let parent = unsafe { iup_sys::IupGetDialog(focus) };
+            dialog.set_attrib_handle("PARENTDIALOG", Handle::from_raw(parent));
+        }
+        let _ = dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent);
+        dialog.destroy();
+    });
+    // The message is not lost when it can't be shown, e.g. from a thread while posting is
+    // not enabled.
+    if let Err(err) = result {
+        eprintln!("{} ({})", shown, err);
+    }
+}
+
 /// An predefined dialog for displaying a message.
 ///
 /// This dialog can be shown with the `popup` method only.
@@ -23,7 +89,151 @@ impl MessageDlg {
     pub fn new() -> MessageDlg {
         unsafe { MessageDlg::from_raw(iup_sys::IupMessageDlg()) }
     }
+
+    /// Creates a message dialog with the specified icon, buttons, title and message.
+    pub fn with<S1, S2>(dialog_type: DialogType, buttons: Buttons, title: S1, message: S2)
+                                -> MessageDlg where S1: Into<String>, S2: Into<String> {
+        MessageDlg::new().set_dialog_type(dialog_type)
+                         .set_buttons(buttons)
+                         .set_attrib("TITLE", title)
+                         .set_attrib("VALUE", message)
+    }
+
+    /// Sets the icon of the dialog (DIALOGTYPE attribute).
+    pub fn set_dialog_type(&mut self, dialog_type: DialogType) -> MessageDlg {
+        self.set_attrib("DIALOGTYPE", dialog_type.as_str())
+    }
+
+    /// Sets the buttons of the dialog (BUTTONS attribute).
+    pub fn set_buttons(&mut self, buttons: Buttons) -> MessageDlg {
+        self.set_attrib("BUTTONS", buttons.as_str())
+    }
+
+    /// Sets the button focused when the dialog is shown (BUTTONDEFAULT attribute).
+    ///
+    /// Must be set after the buttons. Ignored if there's no such button.
+    pub fn set_default_response(&mut self, response: MessageResponse) -> MessageDlg {
+        if let Some(number) = self.buttons().responses().iter().position(|&r| r == response) {
+            self.set_attrib("BUTTONDEFAULT", (number + 1).to_string());
+        }
+        self.clone()
+    }
+
+    /// Gets the buttons of the dialog.
+    pub fn buttons(&self) -> Buttons {
+        match self.attrib("BUTTONS") {
+            Some(ref buttons) if buttons == "OKCANCEL" => Buttons::OkCancel,
+            Some(ref buttons) if buttons == "RETRYCANCEL" => Buttons::RetryCancel,
+            Some(ref buttons) if buttons == "YESNO" => Buttons::YesNo,
+            Some(ref buttons) if buttons == "YESNOCANCEL" => Buttons::YesNoCancel,
+            _ => Buttons::Ok,
+        }
+    }
+
+    /// Gets the button pressed to close the dialog, after it's shown by `popup`.
+    ///
+    /// The dialog closed otherwise (e.g. by Esc) gives the response cancelling it.
+    pub fn response(&self) -> MessageResponse {
+        let buttons = self.buttons();
+        self.attrib("BUTTONRESPONSE")
+            .and_then(|number| number.parse::<usize>().ok())
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| buttons.responses().get(index).cloned())
+            .unwrap_or_else(|| buttons.cancel_response())
+    }
 }
 
 impl_dialog!(MessageDlg, "messagedlg");
 impl ::callback::HelpCb for MessageDlg {}
+
+
+/// The icon of a `MessageDlg`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DialogType {
+    Error,
+    Warning,
+    Information,
+    Question,
+}
+
+impl DialogType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            DialogType::Error => "ERROR",
+            DialogType::Warning => "WARNING",
+            DialogType::Information => "INFORMATION",
+            DialogType::Question => "QUESTION",
+        }
+    }
+}
+
+/// The buttons of a `MessageDlg`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Buttons {
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+    RetryCancel,
+}
+
+impl Buttons {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Buttons::Ok => "OK",
+            Buttons::OkCancel => "OKCANCEL",
+            Buttons::YesNo => "YESNO",
+            Buttons::YesNoCancel => "YESNOCANCEL",
+            Buttons::RetryCancel => "RETRYCANCEL",
+        }
+    }
+
+    /// Gets the responses of the buttons, in the order IUP numbers them.
+    pub fn responses(&self) -> &'static [MessageResponse] {
+        match *self {
+            Buttons::Ok => &[MessageResponse::Ok],
+            Buttons::OkCancel => &[MessageResponse::Ok, MessageResponse::Cancel],
+            Buttons::YesNo => &[MessageResponse::Yes, MessageResponse::No],
+            Buttons::YesNoCancel => {
+                &[MessageResponse::Yes, MessageResponse::No, MessageResponse::Cancel]
+            },
+            Buttons::RetryCancel => &[MessageResponse::Retry, MessageResponse::Cancel],
+        }
+    }
+
+    /// Gets the response of the button cancelling the dialog, its last button.
+    pub fn cancel_response(&self) -> MessageResponse {
+        *self.responses().last().unwrap()
+    }
+}
+
+/// The button pressed to close a `MessageDlg`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MessageResponse {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+    Retry,
+}
+
+/// Shows a modal message dialog with an icon and some buttons, returning the button pressed.
+///
+/// ```ignore
+/// match message_dlg(DialogType::Question, Buttons::YesNoCancel, "Quit",
+///                   "Save the changes before quitting?") {
+///     MessageResponse::Yes => { save(); quit(); },
+///     MessageResponse::No => quit(),
+///     _ => {},
+/// }
+/// ```
+pub fn message_dlg<S1, S2>(dialog_type: DialogType, buttons: Buttons, title: S1, message: S2)
+                                -> MessageResponse where S1: Into<String>, S2: Into<String> {
+    let mut dialog = MessageDlg::with(dialog_type, buttons, title, message);
+    let response = match dialog.popup(DialogPos::CenterParent, DialogPos::CenterParent) {
+        Ok(dialog) => dialog.response(),
+        Err(_) => buttons.cancel_response(),
+    };
+    dialog.destroy();
+    response
+}
@@ -18,11 +18,19 @@ pub mod dialog;
 pub mod alarm;
 pub mod message;
 pub mod file;
+pub mod prompt;
+pub mod form;
+pub mod about;
+pub mod busy;
 
-pub use self::dialog::{Dialog, ShowState, CopyDataCb, MdiActivateCb, ShowCb, TrayClickCb};
+pub use self::dialog::{Dialog, Rect, CustomFrame, ShowState};
+pub use self::dialog::{CopyDataCb, MdiActivateCb, ShowCb, TrayClickCb, CustomFrameActivateCb};
 pub use self::alarm::{AlarmButton, alarm};
-pub use self::message::{MessageDlg, message};
+pub use self::message::{MessageDlg, DialogType, Buttons, MessageResponse, message, message_dlg};
 pub use self::file::{FileDlg};
+pub use self::form::{FormPrompt, FieldValue, FromForm};
+pub use self::about::{AppInfo, about};
+pub use self::prompt::{prompt_text, prompt_file, prompt_color, prompt_string, prompt_int};
 
 // An dialog is a top-level container.
 pub trait DialogElement : Element + Widget + Container {
@@ -115,13 +123,15 @@ impl DialogPos {
         }
     }
 
-    fn to_raw_x(&self) -> c_int {
+    #[doc(hidden)]
+    pub fn to_raw_x(&self) -> c_int {
     	use self::DialogPos::*;
     	assert!(*self != Top && *self != Bottom);
     	self.to_raw()
     }
 
-    fn to_raw_y(&self) -> c_int {
+    #[doc(hidden)]
+    pub fn to_raw_y(&self) -> c_int {
     	use self::DialogPos::*;
     	assert!(*self != Right && *self != Left);
     	self.to_raw()
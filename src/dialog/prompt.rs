@@ -0,0 +1,110 @@
+//! Predefined dialogs asking the user for a single value.
+use iup_sys;
+use libc::{c_char, c_int, c_uchar, c_void};
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+use std::ptr;
+
+/// Maximum number of bytes (including the nul terminator) of the prompted strings.
+const MAX_TEXT: usize = 10240;
+/// Maximum number of bytes (including the nul terminator) of the prompted file names.
+const MAX_PATH: usize = 4096;
+
+/// Shows a modal dialog to edit a multiline text, starting with `text`.
+///
+/// Returns the edited text, or `None` if the dialog was cancelled.
+///
+/// See the [IUP GetText Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupgettext.html
+pub fn prompt_text<S1, S2>(title: S1, text: S2) -> Option<String>
+                                        where S1: Into<String>, S2: Into<String> {
+    let ctitle = CString::new(title.into()).unwrap();
+    let mut buf = text_buffer(text.into(), MAX_TEXT);
+    match unsafe { IupGetText(ctitle.as_ptr(), buf.as_mut_ptr(), MAX_TEXT as c_int) } {
+        1 => Some(string_from_buffer(&buf)),
+        _ => None,
+    }
+}
+
+/// Shows a modal dialog to select a file to open or save.
+///
+/// The `filter` selects the initial directory and the listed files, e.g. "../docs/*.txt".
+/// Returns the selected file, which may not exist yet, or `None` if the dialog was cancelled.
+///
+/// See the [IUP GetFile Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupgetfile.html
+pub fn prompt_file<S: Into<String>>(filter: S) -> Option<PathBuf> {
+    let mut buf = text_buffer(filter.into(), MAX_PATH);
+    match unsafe { iup_sys::IupGetFile(buf.as_mut_ptr()) } {
+        0 | 1 => Some(PathBuf::from(string_from_buffer(&buf))),
+        _ => None,
+    }
+}
+
+/// Shows a modal dialog to select a color, starting with `rgb`.
+///
+/// Returns the selected color, or `None` if the dialog was cancelled.
+///
+/// See the [IUP GetColor Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/dlg/iupgetcolor.html
+pub fn prompt_color(rgb: (u8, u8, u8)) -> Option<(u8, u8, u8)> {
+    let (mut r, mut g, mut b) = (rgb.0 as c_uchar, rgb.1 as c_uchar, rgb.2 as c_uchar);
+    match unsafe { iup_sys::IupGetColor(iup_sys::IUP_CENTERPARENT, iup_sys::IUP_CENTERPARENT,
+                                        &mut r, &mut g, &mut b) } {
+        1 => Some((r as u8, g as u8, b as u8)),
+        _ => None,
+    }
+}
+
+/// Shows a modal dialog asking for a single line string, starting with `value`.
+///
+/// The `label` is shown before the input field and must not contain '%'.
+/// Returns the entered string, or `None` if the dialog was cancelled.
+pub fn prompt_string<S1, S2, S3>(title: S1, label: S2, value: S3) -> Option<String>
+                            where S1: Into<String>, S2: Into<String>, S3: Into<String> {
+    let ctitle = CString::new(title.into()).unwrap();
+    let cformat = CString::new(format!("{}%s\n", label.into())).unwrap();
+    let mut buf = text_buffer(value.into(), MAX_TEXT);
+    match unsafe { iup_sys::IupGetParam(ctitle.as_ptr(), accept_param, ptr::null_mut(),
+                                        cformat.as_ptr(), buf.as_mut_ptr()) } {
+        1 => Some(string_from_buffer(&buf)),
+        _ => None,
+    }
+}
+
+/// Shows a modal dialog asking for an integer, starting with `value`.
+///
+/// The `label` is shown before the input field and must not contain '%'.
+/// Returns the entered number, or `None` if the dialog was cancelled.
+pub fn prompt_int<S1, S2>(title: S1, label: S2, value: i32) -> Option<i32>
+                                        where S1: Into<String>, S2: Into<String> {
+    let ctitle = CString::new(title.into()).unwrap();
+    let cformat = CString::new(format!("{}%i\n", label.into())).unwrap();
+    let mut value = value as c_int;
+    match unsafe { iup_sys::IupGetParam(ctitle.as_ptr(), accept_param, ptr::null_mut(),
+                                        cformat.as_ptr(), &mut value as *mut c_int) } {
+        1 => Some(value as i32),
+        _ => None,
+    }
+}
+
+/// GetParam action accepting every change, iup-sys does not allow a null action.
+extern fn accept_param(_dialog: *mut iup_sys::Ihandle, _index: c_int, _data: *mut c_void) -> c_int {
+    1
+}
+
+/// Creates a nul terminated buffer of `size` bytes initialized with `text`, truncated if needed.
+fn text_buffer(text: String, size: usize) -> Vec<c_char> {
+    let mut buf: Vec<c_char> = text.bytes().take(size - 1).map(|b| b as c_char).collect();
+    buf.resize(size, 0);
+    buf
+}
+
+fn string_from_buffer(buf: &[c_char]) -> String {
+    unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned()
+}
+
+// Not yet bound by iup-sys, which binds the older IupGetText without the buffer size.
+extern {
+    fn IupGetText(title: *const c_char, text: *mut c_char, maxsize: c_int) -> c_int;
+}
@@ -0,0 +1,235 @@
+//! Sets of attributes applied together.
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+use std::result::Result;
+use std::iter::FromIterator;
+
+/// An ordered set of attributes, applied to an element with `Element::set_attribs`.
+///
+/// Maps are usually written with the `attribs!` macro, or parsed from the "NAME=VALUE, ..."
+/// strings of `IupSetAttributes` and LED:
+///
+/// ```ignore
+/// let ok = Button::with_title("OK").set_attribs(attribs! {
+///     "EXPAND" => "HORIZONTAL",
+///     "PADDING" => "10x2",
+/// });
+/// let text = Text::new().set_attribs("EXPAND=YES, VALUE=\"Hello, world\""
+///                                        .parse::<AttributeMap>().unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AttributeMap(Vec<(String, String)>);
+
+impl AttributeMap {
+    /// Creates an empty map.
+    pub fn new() -> AttributeMap {
+        AttributeMap(Vec::new())
+    }
+
+    /// Sets an attribute, replacing its previous value but keeping its position.
+    pub fn insert<S1, S2>(&mut self, name: S1, value: S2)
+                                            where S1: Into<String>, S2: Into<String> {
+        let (name, value) = (name.into(), value.into());
+        match self.0.iter().position(|&(ref n, _)| *n == name) {
+            Some(i) => self.0[i].1 = value,
+            None => self.0.push((name, value)),
+        }
+    }
+
+    /// Adds an attribute to the map, in a builder fashion.
+    pub fn with<S1, S2>(mut self, name: S1, value: S2) -> AttributeMap
+                                            where S1: Into<String>, S2: Into<String> {
+        self.insert(name, value);
+        self
+    }
+
+    /// Gets the value of an attribute.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|&&(ref n, _)| n == name).map(|&(_, ref value)| &value[..])
+    }
+
+    /// Removes an attribute, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.0.iter().position(|&(ref n, _)| n == name).map(|i| self.0.remove(i).1)
+    }
+
+    /// Iterates over the names and values of the attributes, in insertion order.
+    pub fn iter(&self) -> slice::Iter<(String, String)> {
+        self.0.iter()
+    }
+
+    /// Returns the number of attributes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks whether there are no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a, 'b, 'c> From<&'a [(&'b str, &'c str)]> for AttributeMap {
+    fn from(attribs: &'a [(&'b str, &'c str)]) -> AttributeMap {
+        attribs.iter().map(|&(name, value)| (name, value)).collect()
+    }
+}
+
+impl<S1: Into<String>, S2: Into<String>> FromIterator<(S1, S2)> for AttributeMap {
+    fn from_iter<I: IntoIterator<Item=(S1, S2)>>(iter: I) -> AttributeMap {
+        let mut map = AttributeMap::new();
+        for (name, value) in iter {
+            map.insert(name, value);
+        }
+        map
+    }
+}
+
+impl<'a> IntoIterator for &'a AttributeMap {
+    type Item = &'a (String, String);
+    type IntoIter = slice::Iter<'a, (String, String)>;
+    fn into_iter(self) -> slice::Iter<'a, (String, String)> {
+        self.0.iter()
+    }
+}
+
+/// Parses the "NAME=VALUE, ..." format of `IupSetAttributes`.
+///
+/// Values containing commas, spaces or equal signs are quoted, e.g. `TITLE="Hello, world"`.
+/// There's no escaping, so a quoted value ends at the next quote.
+///
+/// ```
+/// use iup::element::AttributeMap;
+///
+/// let map: AttributeMap = "EXPAND=YES, TITLE=\"Hello, world\"".parse().unwrap();
+/// assert_eq!(map.get("EXPAND"), Some("YES"));
+/// assert_eq!(map.get("TITLE"), Some("Hello, world"));
+/// assert_eq!(map.to_string(), "EXPAND=YES, TITLE=\"Hello, world\"");
+/// ```
+impl FromStr for AttributeMap {
+    type Err = String;
+    fn from_str(s: &str) -> Result<AttributeMap, String> {
+        let mut map = AttributeMap::new();
+        let mut chars = s.chars().peekable();
+        loop {
+            while chars.peek().map_or(false, |&c| c == ',' || c.is_whitespace()) {
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                return Ok(map);
+            }
+
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '=' || c == ',' || c.is_whitespace() {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            while chars.peek().map_or(false, |c| c.is_whitespace()) {
+                chars.next();
+            }
+            if chars.next() != Some('=') {
+                return Err(format!("missing value of attribute {}", name));
+            }
+            while chars.peek().map_or(false, |c| c.is_whitespace()) {
+                chars.next();
+            }
+
+            let mut value = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(format!("unterminated value of attribute {}", name)),
+                    }
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == ',' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                let len = value.trim_right().len();
+                value.truncate(len);
+            }
+            map.insert(name, value);
+        }
+    }
+}
+
+/// Emits the "NAME=VALUE, ..." format of `IupSetAttributes`, quoting values when needed.
+///
+/// Values containing quotes can't be quoted in this format and are emitted as they are, thus
+/// they only parse back if they don't start with a quote nor contain commas.
+impl fmt::Display for AttributeMap {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(ref name, ref value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                try!(fmt.write_str(", "));
+            }
+            let quoted = !value.contains('"') && (value.is_empty()
+                || value.chars().any(|c| c == ',' || c == '=' || c.is_whitespace()));
+            if quoted {
+                try!(write!(fmt, "{}=\"{}\"", name, value));
+            } else {
+                try!(write!(fmt, "{}={}", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AttributeMap;
+
+    fn round_trip(map: &AttributeMap) -> AttributeMap {
+        map.to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn plain_values_are_not_quoted() {
+        let map = AttributeMap::new().with("EXPAND", "YES").with("SIZE", "50x10");
+        assert_eq!(map.to_string(), "EXPAND=YES, SIZE=50x10");
+        assert_eq!(round_trip(&map), map);
+    }
+
+    #[test]
+    fn values_with_separators_are_quoted() {
+        let map = AttributeMap::new().with("TITLE", "Hello, world")
+                                     .with("TIP", "a=b")
+                                     .with("VALUE", "");
+        assert_eq!(map.to_string(), "TITLE=\"Hello, world\", TIP=\"a=b\", VALUE=\"\"");
+        assert_eq!(round_trip(&map), map);
+    }
+
+    #[test]
+    fn values_with_quotes_are_emitted_as_they_are() {
+        let map = AttributeMap::new().with("TITLE", "a\"b").with("TIP", "say \"hi\"");
+        assert_eq!(map.to_string(), "TITLE=a\"b, TIP=say \"hi\"");
+        assert_eq!(round_trip(&map), map);
+    }
+
+    #[test]
+    fn parse_skips_blanks_and_keeps_the_order() {
+        let map: AttributeMap = "  B = 2 ,A=1,, C=\" x \"".parse().unwrap();
+        let attribs: Vec<_> = map.iter().cloned().collect();
+        assert_eq!(attribs, vec![("B".to_string(), "2".to_string()),
+                                 ("A".to_string(), "1".to_string()),
+                                 ("C".to_string(), " x ".to_string())]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("EXPAND".parse::<AttributeMap>().is_err());
+        assert!("TITLE=\"open".parse::<AttributeMap>().is_err());
+        assert_eq!("".parse::<AttributeMap>(), Ok(AttributeMap::new()));
+    }
+}
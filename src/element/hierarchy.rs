@@ -2,6 +2,7 @@
 use iup_sys;
 use libc::c_int;
 use std::ffi::CString;
+use std::ptr;
 use std::result::Result;
 
 use element::{Element, Handle};
@@ -79,6 +80,17 @@ pub trait Container : Node {
     fn child_count(&self) -> usize {
         unsafe { iup_sys::IupGetChildCount(self.raw()) as usize }
     }
+
+    /// Returns all the children of the element, in the same order as `Container::child`.
+    fn children(&self) -> Vec<Handle> {
+        let mut children = Vec::with_capacity(self.child_count());
+        let mut child = unsafe { iup_sys::IupGetNextChild(self.raw(), ptr::null_mut()) };
+        while !child.is_null() {
+            children.push(Handle::from_raw(child));
+            child = unsafe { iup_sys::IupGetNextChild(self.raw(), child) };
+        }
+        children
+    }
 }
 
 /// Nodes are elements that can be part of a hierarchical structure.
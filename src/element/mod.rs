@@ -9,6 +9,8 @@ use std::ffi::{CStr, CString};
 use std::result::Result;
 use std::iter::repeat;
 use std::str::FromStr;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 
 pub mod guard;
 pub use self::guard::Guard;
@@ -16,9 +18,15 @@ pub use self::guard::Guard;
 pub mod hierarchy;
 pub use self::hierarchy::{Container, Node};
 
+pub mod weak;
+pub use self::weak::WeakHandle;
+
+pub mod attributes;
+pub use self::attributes::AttributeMap;
+
 #[macro_use]
 pub mod widget;
-pub use self::widget::Widget;
+pub use self::widget::{Widget, StateGuard, Expand, Size};
 
 /// Makes a Vec of `Element` trait objects.
 ///
@@ -32,6 +40,20 @@ macro_rules! elements {
     ($($elem:expr),*) => { vec! [ $($crate::element::Handle::from($elem)),* ] };
 }
 
+/// Makes an `AttributeMap` from attribute names and values.
+///
+/// ```ignore
+/// let label = Label::new().set_attribs(attribs! { "TITLE" => "Name:", "PADDING" => "4x0" });
+/// ```
+#[macro_export]
+macro_rules! attribs {
+    () => { $crate::element::AttributeMap::new() };
+    ($($name:expr => $value:expr),+,) => { attribs! { $($name => $value),+ } };
+    ($($name:expr => $value:expr),*) => {
+        $crate::element::AttributeMap::new()$(.with($name, $value))*
+    };
+}
+
 /// This macro should be used for every type binding IUP handles.
 ///
 /// See applicable `$classname`s [here][1]. Some classes aren't on the list and should be
@@ -78,6 +100,20 @@ macro_rules! impl_element_nofrom {
             }
         }
 
+        impl PartialEq for $ty_path {
+            fn eq(&self, other: &$ty_path) -> bool {
+                self.raw() == other.raw()
+            }
+        }
+
+        impl Eq for $ty_path {}
+
+        impl ::std::hash::Hash for $ty_path {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                (self.raw() as usize).hash(state)
+            }
+        }
+
         impl Copy for $ty_path {}
         
         impl Clone for $ty_path {
@@ -149,10 +185,15 @@ impl ::callback::EnterWindowCb for Handle {}
 impl ::callback::LeaveWindowCb for Handle {}
 /// Note: The wrapped element may not support `HelpCb`.
 impl ::callback::HelpCb for Handle {}
-// TODO impl K_ callbacks when it's implemented.
+/// Note: The wrapped element may not support `KAnyCb`.
+impl ::callback::KAnyCb for Handle {}
 
 
 /// Every IUP object is an `Element`.
+///
+/// The element types are equal and hash the same when they wrap the same IUP handle, so they can
+/// be used as keys of a `HashMap`. Their `Debug` output shows only the handle, so formatting a
+/// copy of a destroyed element is harmless, use `describe` for more details.
 pub trait Element : Sized + Copy + Clone {
 
     /// Constructs a specialized Element object from a general Handle if they are compatible.
@@ -197,6 +238,8 @@ pub trait Element : Sized + Copy + Clone {
                 // Note: DESTROY_CB is used here instead of LDESTROY_CB because the DESTROY_CB 
                 // is called later. LDESTROY_CB is used in callbacks.rs, see it for more details.
                 iup_sys::IupSetCallback(ih, cstr!("DESTROY_CB"), on_element_destroy);
+                trace_event!(ih = ?ih, class = ?CStr::from_ptr(iup_sys::IupGetClassName(ih)),
+                             "element bound");
                 Element::from_raw_unchecked(ih)
             }
         }
@@ -224,10 +267,50 @@ pub trait Element : Sized + Copy + Clone {
     ///
     /// Images associated with controls are **NOT** automatically destroyed. The application must
     /// destroy them when they are not used anymore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from a callback of the element or of one of its children, e.g. to close
+    /// a dialog from the action of one of its buttons, since the running callback would be
+    /// freed. Use `destroy_later` instead.
     fn destroy(self) {
+        if ::debug::callback_depth() > 0 && in_callback(self.raw()) {
+            panic!("{} destroyed from its own callback, use destroy_later", self.describe());
+        }
         unsafe { iup_sys::IupDestroy(self.raw()) };
     }
 
+    /// Describes the element with its class, NAME, TITLE and RASTERSIZE, e.g. for debugging.
+    ///
+    /// Like any other operation this must not be used after the element was destroyed.
+    fn describe(&self) -> String {
+        let attrib = |name: &'static str| {
+            match unsafe { iup_sys::IupGetAttribute(self.raw(), intern(name)) } {
+                value if value.is_null() => None,
+                value => Some(string_from_cstr!(value)),
+            }
+        };
+        let classname = unsafe { self.classname().to_string_lossy().into_owned() };
+        format!("{} {{ ptr: {:p}, name: {:?}, title: {:?}, size: {:?} }}", classname, self.raw(),
+                attrib("NAME"), attrib("TITLE"), attrib("RASTERSIZE"))
+    }
+
+    /// Creates a weak handle to the element, which tells whether it was destroyed.
+    fn downgrade(&self) -> WeakHandle<Self> {
+        WeakHandle::new(*self)
+    }
+
+    /// Destroys an interface element and all its children from the main loop, once no callback
+    /// is running. The destruction is posted with `IupPostMessage`, enabling `util::post`.
+    ///
+    /// Does nothing if the element gets destroyed in the meantime.
+    fn destroy_later(self) {
+        defer_destroy(self.raw());
+        if ::debug::callback_depth() == 0 {
+            destroy_pending();
+        }
+    }
+
     /// Checks if a specific attribute exists in the element.
     fn does_attrib_exist(&self, cname: &CString) -> bool {
         let attrib = unsafe { iup_sys::IupGetAttribute(self.raw(), cname.as_ptr()) };
@@ -243,6 +326,31 @@ pub trait Element : Sized + Copy + Clone {
         vec.into_iter().take(len as usize).map(|cstr| string_from_cstr!(cstr)).collect()
     }
 
+    /// Gets a snapshot of the attributes set in the internal hash table of the element (see
+    /// `attribs`), ordered by name.
+    ///
+    /// The attributes holding pointers instead of text are skipped without being read, i.e. the
+    /// data of IUP and of the binding (such as callbacks), whose names start with `_IUP`, and the
+    /// native handles (e.g. `NATIVEPARENT`). Pointers set with `set_attrib_data` under other
+    /// names can't be told apart and must not be snapshotted. Compare snapshots with
+    /// `element::diff`.
+    fn attributes(&self) -> BTreeMap<String, String> {
+        self.attribs().into_iter().filter(|name| is_text_attrib(name)).filter_map(|name| {
+            self.attrib(&name[..]).map(|value| (name, value))
+        }).collect()
+    }
+
+    /// Sets several attributes at once, in the order of the map.
+    ///
+    /// This is the equivalent of `IupSetAttributes`, taking an `AttributeMap` (or a slice of
+    /// name and value pairs) instead of a string.
+    fn set_attribs<A: Into<AttributeMap>>(&mut self, attribs: A) -> Self {
+        for &(ref name, ref value) in &attribs.into() {
+            self.set_attrib(&name[..], &value[..]);
+        }
+        self.clone()
+    }
+
     /// Sets an interface element attribute.
     ///
     /// See also the [IUP Attributes Guide][1].
@@ -250,8 +358,12 @@ pub trait Element : Sized + Copy + Clone {
     fn set_attrib<S1, S2>(&mut self, name: S1, value: S2) -> Self
                                         where S1: Into<String>, S2: Into<String> {
         // The way IupSetAttribute works is infeasible to safety. Use IupSetStrAttribute.
-        let cname = CString::new(name.into()).unwrap();
-        let cvalue = CString::new(value.into()).unwrap();
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        let value = value.into();
+        trace_event!(ih = ?self.raw(), name = &name[..], value = &value[..], "set attribute");
+        let cname = CString::new(name).unwrap();
+        let cvalue = CString::new(value).unwrap();
         unsafe { iup_sys::IupSetStrAttribute(self.raw(), cname.as_ptr(), cvalue.as_ptr()) };
         self.clone()
     }
@@ -271,6 +383,59 @@ pub trait Element : Sized + Copy + Clone {
         }
     }
 
+    /// Sets an indexed interface element attribute, such as the item `id` of a list or the
+    /// node `id` of a tree.
+    ///
+    /// This is the same as setting the attribute `name` followed by `id` (e.g. `"TITLE3"`).
+    fn set_attrib_at<S1, S2>(&mut self, name: S1, id: i32, value: S2) -> Self
+                                        where S1: Into<String>, S2: Into<String> {
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        let value = value.into();
+        trace_event!(ih = ?self.raw(), name = &name[..], id = id, value = &value[..], "set attribute");
+        let cname = CString::new(name).unwrap();
+        let cvalue = CString::new(value).unwrap();
+        unsafe { iup_sys::IupSetStrAttributeId(self.raw(), cname.as_ptr(), id, cvalue.as_ptr()) };
+        self.clone()
+    }
+
+    /// Gets an indexed interface element attribute, see `set_attrib_at`.
+    fn attrib_at<S: Into<String>>(&self, name: S, id: i32) -> Option<String> {
+        let cname = CString::new(name.into()).unwrap();
+        match unsafe { iup_sys::IupGetAttributeId(self.raw(), cname.as_ptr(), id) } {
+            cvalue if cvalue.is_null() => None,
+            cvalue => Some(string_from_cstr!(cvalue)),
+        }
+    }
+
+    /// Sets a two-dimensional indexed interface element attribute, such as the cell at
+    /// (`lin`, `col`) of a matrix.
+    ///
+    /// This is the same as setting the attribute `name` followed by `lin:col` (e.g. `"BGCOLOR1:2"`).
+    fn set_attrib_at2<S1, S2>(&mut self, name: S1, lin: i32, col: i32, value: S2) -> Self
+                                        where S1: Into<String>, S2: Into<String> {
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        let value = value.into();
+        trace_event!(ih = ?self.raw(), name = &name[..], lin = lin, col = col, value = &value[..],
+                     "set attribute");
+        let cname = CString::new(name).unwrap();
+        let cvalue = CString::new(value).unwrap();
+        unsafe {
+            iup_sys::IupSetStrAttributeId2(self.raw(), cname.as_ptr(), lin, col, cvalue.as_ptr())
+        };
+        self.clone()
+    }
+
+    /// Gets a two-dimensional indexed interface element attribute, see `set_attrib_at2`.
+    fn attrib_at2<S: Into<String>>(&self, name: S, lin: i32, col: i32) -> Option<String> {
+        let cname = CString::new(name.into()).unwrap();
+        match unsafe { iup_sys::IupGetAttributeId2(self.raw(), cname.as_ptr(), lin, col) } {
+            cvalue if cvalue.is_null() => None,
+            cvalue => Some(string_from_cstr!(cvalue)),
+        }
+    }
+
     /// Sets a raw interface element attribute.
     ///
     /// # Safety
@@ -279,11 +444,26 @@ pub trait Element : Sized + Copy + Clone {
     /// statically).
     fn set_attrib_data<S1>(&mut self, name: S1, data: *const c_void) -> Self
                                                               where S1: Into<String> {
-        let cname = CString::new(name.into()).unwrap();
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        trace_event!(ih = ?self.raw(), name = &name[..], data = ?data, "set attribute data");
+        let cname = CString::new(name).unwrap();
         unsafe { iup_sys::IupSetAttribute(self.raw(), cname.as_ptr(), data as *const c_char) };
         self.clone()
     }
 
+    /// Sets an interface element attribute without copying it.
+    ///
+    /// This is a fast path to `set_attrib` for code setting attributes in tight loops, such as
+    /// redraw handlers. Both strings are converted to C strings only once per process and the
+    /// value is stored by reference (IupSetAttribute semantics) instead of being duplicated.
+    fn set_attrib_static(&mut self, name: &'static str, value: &'static str) -> Self {
+        ::debug::validate_attrib(self, name);
+        trace_event!(ih = ?self.raw(), name = name, value = value, "set attribute");
+        unsafe { iup_sys::IupSetAttribute(self.raw(), intern(name), intern(value)) };
+        self.clone()
+    }
+
     /// Gets a raw interface element attribute.
     fn attrib_data<S1>(&mut self, name: S1) -> *mut c_void
                                        where S1: Into<String> {
@@ -298,7 +478,10 @@ pub trait Element : Sized + Copy + Clone {
     /// with the attribute.
      fn set_attrib_handle<S1, E>(&mut self, name: S1, elem: E) -> Self
                                                 where S1: Into<String>, E: Element {
-        let cname = CString::new(name.into()).unwrap();
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        trace_event!(ih = ?self.raw(), name = &name[..], handle = ?elem.raw(), "set attribute handle");
+        let cname = CString::new(name).unwrap();
         unsafe { iup_sys::IupSetAttributeHandle(self.raw(), cname.as_ptr(), elem.raw()) };
         self.clone()
     }   
@@ -378,7 +561,10 @@ pub trait Element : Sized + Copy + Clone {
     #[doc(hidden)]
     fn set_attrib_rgb<S1>(&mut self, name: S1, rgb: (u8, u8, u8)) -> Self
                                                               where S1: Into<String> {
-        let cname = CString::new(name.into()).unwrap();
+        let name = name.into();
+        ::debug::validate_attrib(self, &name);
+        trace_event!(ih = ?self.raw(), name = &name[..], rgb = ?rgb, "set attribute");
+        let cname = CString::new(name).unwrap();
         unsafe { iup_sys::IupSetRGB(self.raw(), cname.as_ptr(), rgb.0, rgb.1, rgb.2) };
         self.clone()
     }
@@ -417,6 +603,53 @@ pub trait Element : Sized + Copy + Clone {
         self.attrib(name).and_then(|s| s.parse::<T>().ok())
     }
 
+    /// Groups many attribute changes made by `f` into a single visual update.
+    ///
+    /// Automatic redrawing of trees (AUTOREDRAW) is suspended while `f` runs, and the element is
+    /// updated once at the end, matrices being redrawn completely. Calls on the same element
+    /// may be nested, only the outermost call updates it. The element is restored even if `f`
+    /// panics.
+    ///
+    /// ```ignore
+    /// tree.update(|tree| {
+    ///     for i in 0..10000 {
+    ///         tree.add_leaf(-1, format!("Leaf {}", i));
+    ///     }
+    /// });
+    /// ```
+    fn update<F: FnOnce(&mut Self)>(&mut self, f: F) -> Self {
+        let _guard = UpdateGuard::enter(unsafe { Handle::from_raw_unchecked(self.raw()) });
+        f(self);
+        self.clone()
+    }
+
+    /// Creates a copy of the element and its children with the same attributes.
+    ///
+    /// Only the attributes set on the elements are copied, as in `LayoutSpec::from_element`,
+    /// and callbacks are not copied. The copy is detached, so it must be appended to a container
+    /// or destroyed.
+    ///
+    /// Fails if the element or any of its children can't be created from its class name.
+    fn deep_clone(&self) -> Result<Self, String> {
+        self.deep_clone_with(|_, _| {})
+    }
+
+    /// Same as `deep_clone`, but calls `rebind` with each original element and its copy,
+    /// parents before children, so the callbacks can be set on the copies.
+    ///
+    /// ```ignore
+    /// let row = template_row.deep_clone_with(|original, copy| {
+    ///     if original.attrib("NAME").map_or(false, |name| name == "remove") {
+    ///         Button::from_handle(copy).unwrap().set_action(move |_| remove_row(copy));
+    ///     }
+    /// }).unwrap();
+    /// ```
+    fn deep_clone_with<F: FnMut(Handle, Handle)>(&self, rebind: F) -> Result<Self, String> {
+        let copy = try!(::spec::LayoutSpec::from_element(*self).create());
+        let mut rebind = rebind;
+        rebind_tree(unsafe { Handle::from_raw_unchecked(self.raw()) }, copy, &mut rebind);
+        Ok(unsafe { Self::from_raw_unchecked(copy.raw()) })
+    }
 }
 
 pub trait ConvertXYToPos : Element {
@@ -434,6 +667,142 @@ pub trait ConvertXYToPos : Element {
 }
 
 
+// The elements to destroy once no callback is running, see `Element::destroy_later`.
+thread_local!(static PENDING_DESTROY: RefCell<Vec<WeakHandle<Handle>>> = RefCell::new(Vec::new()));
+
+// How many `Element::update` calls are running on each element.
+thread_local!(static UPDATE_DEPTH: RefCell<HashMap<usize, u32>> = RefCell::new(HashMap::new()));
+
+/// Suspends the redrawing of an element during `Element::update`, updating it when dropped.
+struct UpdateGuard {
+    handle: WeakHandle<Handle>,
+    key: usize,
+    /// The AUTOREDRAW of a tree before the outermost call.
+    redraw: Option<String>,
+}
+
+impl UpdateGuard {
+    fn enter(mut handle: Handle) -> UpdateGuard {
+        let depth = UPDATE_DEPTH.with(|depths| {
+            let mut depths = depths.borrow_mut();
+            let depth = depths.entry(handle.raw() as usize).or_insert(0);
+            *depth += 1;
+            *depth
+        });
+        let is_tree = unsafe { handle.classname().to_bytes() == b"tree" };
+        let redraw = if depth == 1 && is_tree {
+            let redraw = handle.attrib("AUTOREDRAW").unwrap_or("YES".into());
+            handle.set_attrib("AUTOREDRAW", "NO");
+            Some(redraw)
+        } else {
+            None
+        };
+        UpdateGuard { handle: handle.downgrade(), key: handle.raw() as usize, redraw: redraw }
+    }
+}
+
+impl Drop for UpdateGuard {
+    fn drop(&mut self) {
+        let outermost = UPDATE_DEPTH.with(|depths| {
+            let mut depths = depths.borrow_mut();
+            let depth = depths.get(&self.key).cloned().unwrap_or(1) - 1;
+            if depth == 0 {
+                depths.remove(&self.key);
+            } else {
+                depths.insert(self.key, depth);
+            }
+            depth == 0
+        });
+        // The element may have been destroyed by the update.
+        let mut handle = match self.handle.upgrade() {
+            Some(handle) if outermost => handle,
+            _ => return,
+        };
+        if let Some(redraw) = self.redraw.take() {
+            handle.set_attrib("AUTOREDRAW", redraw);
+        }
+        if unsafe { handle.classname().to_bytes().starts_with(b"matrix") } {
+            handle.set_attrib("REDRAW", "ALL");
+        }
+        unsafe { iup_sys::IupUpdate(handle.raw()) };
+    }
+}
+
+thread_local!(static INTERNED: RefCell<HashMap<&'static str, CString>> = RefCell::new(HashMap::new()));
+
+/// Gets a C string with the contents of `s` which lives as long as the program.
+///
+/// The conversion happens only in the first call for each string.
+#[doc(hidden)]
+pub fn intern(s: &'static str) -> *const c_char {
+    INTERNED.with(|interned| {
+        // The heap buffer of a CString does not move when the CString itself moves, so the
+        // pointer stays valid as the map grows.
+        interned.borrow_mut().entry(s).or_insert_with(|| CString::new(s).unwrap()).as_ptr()
+    })
+}
+
+/// The attributes of the internal hash table holding native handles instead of text.
+const HANDLE_ATTRIBS: &'static [&'static str] = &["NATIVEPARENT", "WID", "HWND", "XWINDOW",
+                                                   "XDISPLAY"];
+
+/// Checks whether the attribute `name` holds text, see `Element::attributes`.
+fn is_text_attrib(name: &str) -> bool {
+    !name.starts_with("_IUP") && !HANDLE_ATTRIBS.contains(&name)
+}
+
+/// A difference between two attribute snapshots, see `diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttribChange {
+    /// The attribute is only in the second snapshot, with this value.
+    Added(String),
+    /// The attribute is only in the first snapshot, with this value.
+    Removed(String),
+    /// The attribute changed from the first value to the second one.
+    Changed(String, String),
+}
+
+/// Compares two attribute snapshots taken with `Element::attributes`, returning the attributes
+/// that differ from `a` to `b`.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use iup::element::{diff, AttribChange};
+///
+/// let mut a = BTreeMap::new();
+/// a.insert("TITLE".to_string(), "Ok".to_string());
+/// a.insert("EXPAND".to_string(), "YES".to_string());
+/// let mut b = BTreeMap::new();
+/// b.insert("TITLE".to_string(), "Cancel".to_string());
+/// b.insert("ACTIVE".to_string(), "NO".to_string());
+///
+/// let changes = diff(&a, &b);
+/// assert_eq!(changes["TITLE"], AttribChange::Changed("Ok".into(), "Cancel".into()));
+/// assert_eq!(changes["EXPAND"], AttribChange::Removed("YES".into()));
+/// assert_eq!(changes["ACTIVE"], AttribChange::Added("NO".into()));
+/// ```
+pub fn diff(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>)
+                                                    -> BTreeMap<String, AttribChange> {
+    let mut changes = BTreeMap::new();
+    for (name, old) in a {
+        match b.get(name) {
+            Some(new) if new == old => {},
+            Some(new) => {
+                changes.insert(name.clone(), AttribChange::Changed(old.clone(), new.clone()));
+            },
+            None => {
+                changes.insert(name.clone(), AttribChange::Removed(old.clone()));
+            },
+        }
+    }
+    for (name, new) in b {
+        if !a.contains_key(name) {
+            changes.insert(name.clone(), AttribChange::Added(new.clone()));
+        }
+    }
+    changes
+}
+
 /// Sets an attribute in the global environment.
 ///
 /// If the driver process the attribute then it will not be stored internally.
@@ -477,10 +846,111 @@ pub fn global_data<S1>(name: S1) -> *mut c_void
 }
 
 
+/// Calls `rebind` with each element of the tree of `original` and its counterpart in `copy`.
+fn rebind_tree<F: FnMut(Handle, Handle)>(original: Handle, copy: Handle, rebind: &mut F) {
+    rebind(original, copy);
+    for (original, copy) in original.children().into_iter().zip(copy.children()) {
+        rebind_tree(original, copy, rebind);
+    }
+}
+
+/// Checks whether a callback of `ih` or of one of its children is running.
+fn in_callback(ih: *mut iup_sys::Ihandle) -> bool {
+    let children = unsafe { Handle::from_raw_unchecked(ih) }.children();
+    ::debug::is_running(ih) || children.into_iter().any(|child| in_callback(child.raw()))
+}
+
+/// Destroys `ih` once no callback is running.
+fn defer_destroy(ih: *mut iup_sys::Ihandle) {
+    let elem = WeakHandle::new(unsafe { Handle::from_raw_unchecked(ih) });
+    PENDING_DESTROY.with(|pending| pending.borrow_mut().push(elem));
+}
+
+/// Posts the destruction of the deferred elements to the main loop, called when the outermost
+/// callback returned.
+///
+/// The destruction waits for the main loop since IUP may still use the element after the
+/// callback returned, e.g. to hide a dialog after its CLOSE_CB.
+#[doc(hidden)]
+pub fn destroy_pending() {
+    // Fails once IUP is closed, which destroys the elements anyway.
+    if PENDING_DESTROY.with(|pending| !pending.borrow().is_empty()) {
+        let _ = ::util::post_from_main_thread(destroy_posted);
+    }
+}
+
+/// Destroys the deferred elements, posted by `destroy_pending`.
+fn destroy_posted() {
+    // Posted closures run as a callback, any other one running means a modal dialog or a
+    // nested loop is running from a callback, the elements then wait for it to return.
+    if ::debug::callback_depth() > 1 {
+        return;
+    }
+    let pending = PENDING_DESTROY.with(|pending| mem::replace(&mut *pending.borrow_mut(),
+                                                              Vec::new()));
+    for elem in pending {
+        if let Some(elem) = elem.upgrade() {
+            unsafe { iup_sys::IupDestroy(elem.raw()) };
+        }
+    }
+}
+
 /// Called whenever a Element gets destroyed.
 ///
 /// Use this to perform frees related to the Rust binding that are per-element.
 extern fn on_element_destroy(ih: *mut iup_sys::Ihandle) -> c_int {
+    trace_event!(ih = ?ih, "element destroyed");
     unsafe { ::callback::drop_callbacks(ih); }
+    weak::element_destroyed(ih);
+    ::menu::element_destroyed(ih);
+    ::control::list::element_destroyed(ih);
+    ::control::canvas::element_destroyed(ih);
+    #[cfg(feature = "controls")]
+    ::control::matrix::element_destroyed(ih);
     iup_sys::IUP_DEFAULT
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::{diff, is_text_attrib, AttribChange};
+
+    fn snapshot(attribs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        attribs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn diff_of_equal_snapshots_is_empty() {
+        let a = snapshot(&[("TITLE", "Ok"), ("EXPAND", "YES")]);
+        assert!(diff(&a, &a.clone()).is_empty());
+        assert!(diff(&BTreeMap::new(), &BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_each_kind_of_change() {
+        let a = snapshot(&[("TITLE", "Ok"), ("EXPAND", "YES"), ("ACTIVE", "YES")]);
+        let b = snapshot(&[("TITLE", "Cancel"), ("ACTIVE", "YES"), ("VISIBLE", "NO")]);
+        let changes = diff(&a, &b);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes["TITLE"], AttribChange::Changed("Ok".into(), "Cancel".into()));
+        assert_eq!(changes["EXPAND"], AttribChange::Removed("YES".into()));
+        assert_eq!(changes["VISIBLE"], AttribChange::Added("NO".into()));
+    }
+
+    #[test]
+    fn diff_is_reversed_by_swapping_snapshots() {
+        let a = snapshot(&[("TITLE", "Ok")]);
+        let b = snapshot(&[("SIZE", "50x10")]);
+        assert_eq!(diff(&b, &a)["TITLE"], AttribChange::Added("Ok".into()));
+        assert_eq!(diff(&b, &a)["SIZE"], AttribChange::Removed("50x10".into()));
+    }
+
+    #[test]
+    fn pointer_attributes_are_not_text() {
+        assert!(is_text_attrib("TITLE"));
+        assert!(is_text_attrib("_MY_DATA"));
+        assert!(!is_text_attrib("_IUPRUST_FBOX_ACTION"));
+        assert!(!is_text_attrib("_IUP_XY"));
+        assert!(!is_text_attrib("NATIVEPARENT"));
+    }
+}
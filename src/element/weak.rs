@@ -0,0 +1,101 @@
+//! Weak references to elements, which know when the element was destroyed.
+use iup_sys;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use Element;
+
+/// A reference to an element which can be upgraded back into the element only while the
+/// element is alive.
+///
+/// Elements are plain pointers, so a copy kept after the element was destroyed (e.g. by closing
+/// its dialog) is dangling. Long-lived application structures should keep weak handles instead,
+/// checking with `upgrade` that the element still exists before each use.
+///
+/// ```ignore
+/// let status = WeakHandle::new(status_label);
+/// worker.on_progress(move |progress| {
+///     if let Some(mut label) = status.upgrade() {
+///         label.set_attrib("TITLE", format!("{}%", progress));
+///     }
+/// });
+/// ```
+pub struct WeakHandle<E: Element> {
+    ih: *mut iup_sys::Ihandle,
+    /// Tells the element apart from any later element allocated at the same address.
+    id: u64,
+    marker: PhantomData<E>,
+}
+
+// The id of each element with weak handles, removed when the element is destroyed.
+thread_local!(static WEAK_IDS: RefCell<HashMap<usize, u64>> = RefCell::new(HashMap::new()));
+thread_local!(static NEXT_ID: Cell<u64> = Cell::new(1));
+
+impl<E: Element> WeakHandle<E> {
+    /// Creates a weak handle to `elem`.
+    pub fn new(elem: E) -> WeakHandle<E> {
+        let ih = elem.raw();
+        let id = WEAK_IDS.with(|ids| {
+            *ids.borrow_mut().entry(ih as usize).or_insert_with(|| {
+                NEXT_ID.with(|next| { let id = next.get(); next.set(id + 1); id })
+            })
+        });
+        // Makes sure the destruction is noticed, even if the element never went through
+        // `Element::from_raw`.
+        let _ = E::from_raw(ih);
+        WeakHandle { ih: ih, id: id, marker: PhantomData }
+    }
+
+    /// Gets the element, `None` if it was destroyed.
+    pub fn upgrade(&self) -> Option<E> {
+        if self.is_alive() {
+            Some(unsafe { E::from_raw_unchecked(self.ih) })
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the element was not destroyed yet.
+    pub fn is_alive(&self) -> bool {
+        WEAK_IDS.with(|ids| ids.borrow().get(&(self.ih as usize)) == Some(&self.id))
+    }
+}
+
+/// Forgets the id of `ih`, called when the element is destroyed.
+#[doc(hidden)]
+pub fn element_destroyed(ih: *mut iup_sys::Ihandle) {
+    WEAK_IDS.with(|ids| ids.borrow_mut().remove(&(ih as usize)));
+}
+
+impl<E: Element> Copy for WeakHandle<E> {}
+
+impl<E: Element> Clone for WeakHandle<E> {
+    fn clone(&self) -> WeakHandle<E> {
+        *self
+    }
+}
+
+impl<E: Element> PartialEq for WeakHandle<E> {
+    fn eq(&self, other: &WeakHandle<E>) -> bool {
+        self.ih == other.ih && self.id == other.id
+    }
+}
+
+impl<E: Element> Eq for WeakHandle<E> {}
+
+impl<E: Element> Hash for WeakHandle<E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.ih as usize).hash(state);
+        self.id.hash(state);
+    }
+}
+
+impl<E: Element> fmt::Debug for WeakHandle<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.is_alive() { "alive" } else { "destroyed" };
+        write!(fmt, "WeakHandle({:p}, {})", self.ih, state)
+    }
+}
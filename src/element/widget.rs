@@ -1,8 +1,9 @@
 //! Graphical user interface commons.
 use iup_sys;
 use std::result::Result;
+use std::str::FromStr;
 
-use element::{Element, Node};
+use element::{Element, Node, Container, Handle};
 
 macro_rules! impl_widget {
     ($ty_path:path, $classname:expr) => {
@@ -79,4 +80,330 @@ pub trait Widget : Element + Node {
         unsafe { iup_sys::IupHide(self.raw()) };
         self.clone()
     }
+
+    /// Gets the native objects of the element (window handles, toolkit widgets), or `None` if
+    /// the element is not mapped.
+    ///
+    /// See the `native` module.
+    fn native_handle(&self) -> Option<::native::NativeHandle> {
+        ::native::native_handle(self)
+    }
+
+    /// Gets the position of the top-left corner of the element on the screen, in pixels, or
+    /// `None` if the element is not mapped.
+    ///
+    /// This is the SCREENPOSITION attribute, which for dialogs includes the decorations.
+    fn screen_position(&self) -> Option<(i32, i32)> {
+        if self.native_handle().is_none() {
+            return None;
+        }
+        self.attrib("SCREENPOSITION").and_then(|position| {
+            let mut parts = position.splitn(2, ',').map(|s| s.trim().parse::<i32>().ok());
+            match (parts.next(), parts.next()) {
+                (Some(Some(x)), Some(Some(y))) => Some((x, y)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Converts a position on the screen into a position relative to the top-left corner of
+    /// the element, e.g. for the CURSORPOS global. Returns `None` if the element is not mapped.
+    fn screen_to_client(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        self.screen_position().map(|(left, top)| (x - left, y - top))
+    }
+
+    /// Converts a position relative to the top-left corner of the element into a position on
+    /// the screen, e.g. to show a popup menu next to it. Returns `None` if the element is not
+    /// mapped.
+    fn client_to_screen(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        self.screen_position().map(|(left, top)| (x + left, y + top))
+    }
+
+    /// Shows or hides the element, see `Widget::show` and `Widget::hide`.
+    fn set_visible(&mut self, visible: bool) -> Self {
+        if visible { self.show().ok(); } else { self.hide(); }
+        self.clone()
+    }
+
+    /// Checks whether the element is visible.
+    ///
+    /// An element may be reported visible even if its dialog is hidden.
+    fn is_visible(&self) -> bool {
+        self.attrib_bool("VISIBLE").unwrap_or(false)
+    }
+
+    /// Activates or inactivates the element (ACTIVE attribute).
+    ///
+    /// An inactive element does not respond to the user and is shown greyed out. Since ACTIVE is
+    /// inheritable, the children of an inactive container are also inactive unless they set
+    /// ACTIVE themselves, see `set_enabled_recursive`.
+    fn set_enabled(&mut self, enabled: bool) -> Self {
+        self.set_attrib("ACTIVE", if enabled { "YES" } else { "NO" })
+    }
+
+    /// Checks whether the element is active.
+    fn is_enabled(&self) -> bool {
+        self.attrib_bool("ACTIVE").unwrap_or(true)
+    }
+
+    /// Activates or inactivates the element and all of its descendants.
+    ///
+    /// Overrides any ACTIVE attribute previously set in the descendants.
+    fn set_enabled_recursive(&mut self, enabled: bool) -> Self {
+        fn apply(mut elem: Handle, enabled: bool) {
+            elem.set_enabled(enabled);
+            for child in elem.children() {
+                apply(child, enabled);
+            }
+        }
+        apply(unsafe { Handle::from_raw_unchecked(self.raw()) }, enabled);
+        self.clone()
+    }
+
+    /// Sets how the element grows to occupy the free space of its container (EXPAND attribute).
+    fn set_expand(&mut self, expand: Expand) -> Self {
+        self.set_attrib("EXPAND", expand.as_str())
+    }
+
+    /// Gets how the element grows to occupy the free space of its container.
+    ///
+    /// For containers this is the expansion computed from their children.
+    fn expand(&self) -> Expand {
+        self.attrib_parse("EXPAND").unwrap_or(Expand::No)
+    }
+
+    /// Sets the natural size of the element, the RASTERSIZE attribute for a size in pixels or
+    /// the SIZE attribute for a size in characters.
+    ///
+    /// The size may still grow with EXPAND, and the natural size of containers is at least the
+    /// size of their children.
+    fn set_size(&mut self, size: Size) -> Self {
+        match size {
+            Size::Px(width, height) => {
+                self.set_attrib("RASTERSIZE", format!("{}x{}", width, height))
+            },
+            // SIZE is in quarters of the character width and eighths of its height.
+            Size::Chars(width, height) => {
+                self.set_attrib("SIZE", format!("{}x{}", width * 4, height * 8))
+            },
+        }
+    }
+
+    /// Gets the current size of the element, in pixels.
+    fn size(&self) -> Size {
+        let (width, height) = parse_size(self.attrib("RASTERSIZE")).unwrap_or((0, 0));
+        Size::Px(width, height)
+    }
+
+    /// Sets the minimum size of the element (MINSIZE attribute).
+    ///
+    /// A size in characters is converted to pixels with the current font of the element, it
+    /// doesn't follow later changes of the font. The size of the characters is only known once
+    /// the element is mapped (see `map`), before that a size in characters is an error and
+    /// nothing is set.
+    fn set_min_size(&mut self, size: Size) -> Result<Self, Self> {
+        set_px_size(self, "MINSIZE", size)
+    }
+
+    /// Sets the maximum size of the element (MAXSIZE attribute), see `set_min_size`.
+    fn set_max_size(&mut self, size: Size) -> Result<Self, Self> {
+        set_px_size(self, "MAXSIZE", size)
+    }
+
+    /// Gets the average width and the height of the characters of the font of the element, in
+    /// pixels (CHARSIZE attribute), or (0, 0) if unknown.
+    fn char_size(&self) -> (i32, i32) {
+        char_size(self)
+    }
+}
+
+/// Sets a size attribute in pixels, failing if `size` is in characters of an unknown size.
+fn set_px_size<W: Widget>(elem: &mut W, attrib: &str, size: Size) -> Result<W, W> {
+    if let Size::Chars(..) = size {
+        if char_size(elem) == (0, 0) {
+            return Err(elem.clone());
+        }
+    }
+    let (width, height) = size.to_px(elem);
+    Ok(elem.set_attrib(attrib, format!("{}x{}", width, height)))
+}
+
+/// Parses a "WIDTHxHEIGHT" attribute value.
+fn parse_size(value: Option<String>) -> Option<(i32, i32)> {
+    value.and_then(|value| {
+        let mut parts = value.splitn(2, 'x').map(|s| s.trim().parse::<i32>().ok());
+        match (parts.next(), parts.next()) {
+            (Some(Some(width)), Some(Some(height))) => Some((width, height)),
+            _ => None,
+        }
+    })
+}
+
+/// A size in explicit units, see `Widget::set_size`.
+///
+/// IUP sizes are either in pixels (RASTERSIZE) or in units of the font of the element (SIZE),
+/// which are easily confused. Character sizes follow the font, so they suit text based controls
+/// and scale with the system font settings.
+///
+/// ```ignore
+/// text.set_size(Size::chars(40, 1));
+/// canvas.set_min_size(Size::px(320, 200)).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Size {
+    /// Width and height in pixels.
+    Px(i32, i32),
+    /// Width and height in characters, a character being the average width or the height of the
+    /// characters of the font in each direction.
+    Chars(i32, i32),
+}
+
+impl Size {
+    /// Creates a size in pixels.
+    pub fn px(width: i32, height: i32) -> Size {
+        Size::Px(width, height)
+    }
+
+    /// Creates a size in characters.
+    pub fn chars(width: i32, height: i32) -> Size {
+        Size::Chars(width, height)
+    }
+
+    /// Converts the size to pixels with the font of `elem`.
+    ///
+    /// A size in characters is (0, 0) if the size of the characters is unknown, i.e. before
+    /// `elem` is mapped.
+    pub fn to_px<E: Element>(self, elem: &E) -> (i32, i32) {
+        match self {
+            Size::Px(width, height) => (width, height),
+            Size::Chars(width, height) => {
+                let (char_width, char_height) = char_size(elem);
+                (width * char_width, height * char_height)
+            },
+        }
+    }
+
+    /// Converts the size to characters with the font of `elem`, rounding to the nearest.
+    ///
+    /// Returns (0, 0) if the size of the characters is unknown.
+    pub fn to_chars<E: Element>(self, elem: &E) -> (i32, i32) {
+        match self {
+            Size::Chars(width, height) => (width, height),
+            Size::Px(width, height) => match char_size(elem) {
+                (char_width, char_height) if char_width > 0 && char_height > 0 => {
+                    ((width as f64 / char_width as f64).round() as i32,
+                     (height as f64 / char_height as f64).round() as i32)
+                },
+                _ => (0, 0),
+            },
+        }
+    }
+}
+
+/// Gets the size of the characters of `elem`, see `Widget::char_size`.
+fn char_size<E: Element>(elem: &E) -> (i32, i32) {
+    parse_size(elem.attrib("CHARSIZE")).unwrap_or((0, 0))
+}
+
+/// How an element grows to occupy the free space of its container, see `Widget::set_expand`.
+///
+/// Containers expand only as much as their children allow, unless the expansion is free.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Expand {
+    /// Expands in both directions.
+    Yes,
+    Horizontal,
+    Vertical,
+    /// Expands horizontally, even if the children of the container don't.
+    HorizontalFree,
+    /// Expands vertically, even if the children of the container don't.
+    VerticalFree,
+    /// Keeps the natural size.
+    No,
+}
+
+impl Expand {
+    /// Gets the value of the EXPAND attribute.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Expand::Yes => "YES",
+            Expand::Horizontal => "HORIZONTAL",
+            Expand::Vertical => "VERTICAL",
+            Expand::HorizontalFree => "HORIZONTALFREE",
+            Expand::VerticalFree => "VERTICALFREE",
+            Expand::No => "NO",
+        }
+    }
+}
+
+/// Parses a value of the EXPAND attribute, ignoring case.
+impl FromStr for Expand {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Expand, String> {
+        match &s.trim().to_uppercase()[..] {
+            "YES" => Ok(Expand::Yes),
+            "HORIZONTAL" => Ok(Expand::Horizontal),
+            "VERTICAL" => Ok(Expand::Vertical),
+            "HORIZONTALFREE" => Ok(Expand::HorizontalFree),
+            "VERTICALFREE" => Ok(Expand::VerticalFree),
+            "NO" => Ok(Expand::No),
+            _ => Err(format!("invalid EXPAND value: {}", s)),
+        }
+    }
+}
+
+/// Restores the visibility and activation of an element when dropped.
+///
+/// Useful to disable or hide part of the interface during an operation, making sure it's back to
+/// its previous state however the operation ends.
+///
+/// ```ignore
+/// {
+///     let _guard = StateGuard::disable(form);
+///     save_everything();
+/// } // `form` is active again, unless it was already inactive.
+/// ```
+#[derive(Debug)]
+pub struct StateGuard<W: Widget> {
+    widget: W,
+    visible: Option<String>,
+    active: Option<String>,
+}
+
+impl<W: Widget> StateGuard<W> {
+    /// Remembers the current state of `widget`.
+    pub fn new(widget: W) -> StateGuard<W> {
+        StateGuard {
+            widget: widget,
+            visible: widget.attrib("VISIBLE"),
+            active: widget.attrib("ACTIVE"),
+        }
+    }
+
+    /// Remembers the current state of `widget` and hides it.
+    pub fn hide(widget: W) -> StateGuard<W> {
+        let guard = StateGuard::new(widget);
+        guard.widget.clone().hide();
+        guard
+    }
+
+    /// Remembers the current state of `widget` and inactivates it.
+    pub fn disable(widget: W) -> StateGuard<W> {
+        let guard = StateGuard::new(widget);
+        guard.widget.clone().set_enabled(false);
+        guard
+    }
+}
+
+impl<W: Widget> Drop for StateGuard<W> {
+    fn drop(&mut self) {
+        if let Some(ref active) = self.active {
+            self.widget.set_attrib("ACTIVE", active.clone());
+        }
+        match self.visible {
+            Some(ref visible) if visible == "YES" => { self.widget.show().ok(); },
+            Some(_) => { self.widget.hide(); },
+            None => {},
+        }
+    }
 }
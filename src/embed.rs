@@ -0,0 +1,167 @@
+//! Embedding of native controls created outside IUP.
+//!
+//! A native control (e.g. a web view or a video surface created by another library) can be placed
+//! inside an IUP layout by hosting it in a `Canvas`. The control is reparented into the canvas when
+//! it gets mapped, then kept the size of the canvas and given the keyboard focus along with it.
+//!
+//! ```ignore
+//! let host = embed::host(NativeControl::Win32(hwnd));
+//! let dialog = Dialog::new(VBox::new(elements![toolbar, host]));
+//! ```
+//!
+//! In Windows the control is given by its `HWND`. In GTK and Motif it is given by its X11 window,
+//! a `GtkWidget` can be embedded through the window of its `GtkPlug` or of its `GdkWindow`. The
+//! X11 windows require the `x11` feature.
+use libc::{c_void, c_ulong};
+
+use Element;
+use element::Widget;
+use callback::{CallbackReturn, MapCb, ResizeCb, GetFocusCb};
+use control::Canvas;
+use native::NativeHandle;
+
+/// A native control to be hosted in IUP.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NativeControl {
+    /// A window in the Windows driver.
+    Win32(*mut c_void),
+    /// A X11 window in the GTK or Motif drivers.
+    X11(c_ulong),
+}
+
+/// Creates a canvas hosting the native `control`.
+///
+/// The control is shown and attached once the canvas is mapped, it is not destroyed with the
+/// canvas, that's up to its owner. If the current driver can't host the control, the failure is
+/// printed to the standard error and MAP_CB returns IUP_ERROR.
+pub fn host(control: NativeControl) -> Canvas {
+    Canvas::new()
+        .set_attrib("BORDER", "NO")
+        .set_attrib("SCROLLBAR", "NO")
+        .set_map_cb(move |(canvas,): (Canvas,)| {
+            let parent = match canvas.native_handle() {
+                Some(parent) => parent,
+                None => return CallbackReturn::Default,
+            };
+            match attach(control, parent) {
+                Ok(()) => {
+                    let (w, h) = client_size(canvas);
+                    resize(control, parent, w, h);
+                    CallbackReturn::Default
+                },
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    CallbackReturn::Error
+                },
+            }
+        })
+        .set_resize_cb(move |(canvas, w, h): (Canvas, i32, i32)| {
+            if let Some(parent) = canvas.native_handle() {
+                resize(control, parent, w, h);
+            }
+        })
+        .set_getfocus_cb(move |(canvas,): (Canvas,)| {
+            if let Some(parent) = canvas.native_handle() {
+                focus(control, parent);
+            }
+        })
+}
+
+fn client_size(canvas: Canvas) -> (i32, i32) {
+    canvas.attrib("DRAWSIZE")
+          .and_then(|size| {
+              let mut parts = size.split('x').map(|n| n.parse().ok());
+              match (parts.next(), parts.next()) {
+                  (Some(Some(w)), Some(Some(h))) => Some((w, h)),
+                  _ => None,
+              }
+          })
+          .unwrap_or((0, 0))
+}
+
+fn attach(control: NativeControl, parent: NativeHandle) -> Result<(), String> {
+    match (control, parent) {
+        #[cfg(windows)]
+        (NativeControl::Win32(hwnd), NativeHandle::Win32 { hwnd: parent }) => unsafe {
+            win32::SetParent(hwnd, parent);
+            win32::ShowWindow(hwnd, win32::SW_SHOW);
+            Ok(())
+        },
+        #[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+        (NativeControl::X11(window), NativeHandle::Gtk { xwindow, xdisplay, .. }) |
+        (NativeControl::X11(window), NativeHandle::Motif { xwindow, xdisplay, .. }) => unsafe {
+            x11::XReparentWindow(xdisplay, window, xwindow, 0, 0);
+            x11::XMapWindow(xdisplay, window);
+            Ok(())
+        },
+        _ => Err(format!("{:?} can't be embedded by the current driver", control)),
+    }
+}
+
+// The size is unused when no platform is supported.
+#[allow(unused_variables)]
+fn resize(control: NativeControl, parent: NativeHandle, w: i32, h: i32) {
+    match (control, parent) {
+        #[cfg(windows)]
+        (NativeControl::Win32(hwnd), NativeHandle::Win32 { .. }) => unsafe {
+            win32::MoveWindow(hwnd, 0, 0, w, h, 1);
+        },
+        #[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+        (NativeControl::X11(window), NativeHandle::Gtk { xdisplay, .. }) |
+        (NativeControl::X11(window), NativeHandle::Motif { xdisplay, .. }) => unsafe {
+            // X11 refuses zero sized windows.
+            x11::XMoveResizeWindow(xdisplay, window, 0, 0, w.max(1) as u32, h.max(1) as u32);
+        },
+        _ => {},
+    }
+}
+
+fn focus(control: NativeControl, parent: NativeHandle) {
+    match (control, parent) {
+        #[cfg(windows)]
+        (NativeControl::Win32(hwnd), NativeHandle::Win32 { .. }) => unsafe {
+            win32::SetFocus(hwnd);
+        },
+        #[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+        (NativeControl::X11(window), NativeHandle::Gtk { xdisplay, .. }) |
+        (NativeControl::X11(window), NativeHandle::Motif { xdisplay, .. }) => unsafe {
+            x11::XSetInputFocus(xdisplay, window, x11::REVERT_TO_PARENT, x11::CURRENT_TIME);
+        },
+        _ => {},
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use libc::{c_void, c_int};
+
+    pub const SW_SHOW: c_int = 5;
+
+    #[link(name = "user32")]
+    extern "system" {
+        pub fn SetParent(child: *mut c_void, parent: *mut c_void) -> *mut c_void;
+        pub fn ShowWindow(hwnd: *mut c_void, cmd: c_int) -> c_int;
+        pub fn MoveWindow(hwnd: *mut c_void, x: c_int, y: c_int, w: c_int, h: c_int,
+                          repaint: c_int) -> c_int;
+        pub fn SetFocus(hwnd: *mut c_void) -> *mut c_void;
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+mod x11 {
+    use libc::{c_void, c_int, c_uint, c_ulong};
+
+    pub const REVERT_TO_PARENT: c_int = 2;
+    pub const CURRENT_TIME: c_ulong = 0;
+
+    #[link(name = "X11")]
+    extern {
+        pub fn XReparentWindow(display: *mut c_void, window: c_ulong, parent: c_ulong,
+                               x: c_int, y: c_int) -> c_int;
+        pub fn XMapWindow(display: *mut c_void, window: c_ulong) -> c_int;
+        pub fn XMoveResizeWindow(display: *mut c_void, window: c_ulong, x: c_int, y: c_int,
+                                 w: c_uint, h: c_uint) -> c_int;
+        pub fn XSetInputFocus(display: *mut c_void, window: c_ulong, revert_to: c_int,
+                              time: c_ulong) -> c_int;
+    }
+}
@@ -0,0 +1,145 @@
+//! Preferences of the system, e.g. whether it prefers a dark interface.
+//!
+//! The preferences are read from the IUP globals when the driver gives them, otherwise from the
+//! system. Reading the system may be slow (e.g. running `gsettings`), so it is read once and then
+//! kept up to date by a worker thread, see `watch_system`.
+//!
+//! ```ignore
+//! globals::watch_system(|| {
+//!     println!("dark: {}", globals::prefers_dark());
+//!     true
+//! });
+//! ```
+use std::{mem, thread};
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use element;
+use util;
+
+/// How often, in milliseconds, the worker of `watch_system` checks the system preferences.
+pub const SYSTEM_POLL_INTERVAL: u32 = 2000;
+
+/// Whether the worker thread of `watch_system` is running.
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// The last dark mode preference read from the system.
+thread_local!(static SYSTEM_DARK: Cell<Option<bool>> = Cell::new(None));
+// The callbacks of `watch_system`.
+thread_local!(static WATCH_HOOKS: RefCell<Vec<Box<FnMut() -> bool>>> = RefCell::new(Vec::new()));
+
+/// Checks whether the system prefers a dark interface.
+///
+/// Uses the DARKMODE global of IUP when available, otherwise the "AppsUseLightTheme" registry
+/// value in Windows, and in other systems the GTK_THEME environment variable and the GNOME
+/// color scheme. The system is only read the first time, then the value is updated by the
+/// worker of `watch_system`.
+pub fn prefers_dark() -> bool {
+    if let Some(darkmode) = element::global("DARKMODE") {
+        return darkmode == "YES";
+    }
+    SYSTEM_DARK.with(|cached| {
+        cached.get().unwrap_or_else(|| {
+            let dark = system_prefers_dark();
+            cached.set(Some(dark));
+            dark
+        })
+    })
+}
+
+/// Calls `cb` in the main loop after each check of the system preferences, every
+/// `SYSTEM_POLL_INTERVAL` milliseconds, until it returns `false` or IUP is closed.
+///
+/// The system is checked by a single worker thread shared by all the callbacks, so the main
+/// loop is never blocked. The callback compares `prefers_dark` or the IUP globals with the
+/// values it saw before to detect changes. Must be called from the main thread.
+pub fn watch_system<F>(cb: F) where F: FnMut() -> bool + 'static {
+    WATCH_HOOKS.with(|hooks| hooks.borrow_mut().push(Box::new(cb)));
+    util::enable_posting();
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| {
+        loop {
+            thread::sleep(Duration::from_millis(SYSTEM_POLL_INTERVAL as u64));
+            let dark = system_prefers_dark();
+            if util::post(move || system_checked(dark)).is_err() {
+                break;
+            }
+        }
+        WATCHER_RUNNING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Removes the callbacks of `watch_system`, when IUP is closed.
+#[doc(hidden)]
+pub fn clear_watchers() {
+    WATCH_HOOKS.with(|hooks| hooks.borrow_mut().clear());
+    SYSTEM_DARK.with(|cached| cached.set(None));
+}
+
+fn system_checked(dark: bool) {
+    SYSTEM_DARK.with(|cached| cached.set(Some(dark)));
+    // The hooks are taken out so they are free to add more.
+    let hooks = WATCH_HOOKS.with(|hooks| mem::replace(&mut *hooks.borrow_mut(), Vec::new()));
+    let hooks = hooks.into_iter().filter_map(|mut hook| if hook() { Some(hook) } else { None })
+                     .collect::<Vec<_>>();
+    WATCH_HOOKS.with(|added| {
+        let mut added = added.borrow_mut();
+        let new_hooks = mem::replace(&mut *added, hooks);
+        added.extend(new_hooks);
+    });
+}
+
+#[cfg(windows)]
+fn system_prefers_dark() -> bool {
+    use std::ptr;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use libc::{c_ulong, c_void};
+
+    let wide = |s: &str| OsStr::new(s).encode_wide().chain(Some(0)).collect::<Vec<u16>>();
+    let key = wide(r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize");
+    let value = wide("AppsUseLightTheme");
+    let mut light: u32 = 1;
+    let mut size = mem::size_of::<u32>() as c_ulong;
+    let status = unsafe {
+        advapi32::RegGetValueW(advapi32::HKEY_CURRENT_USER as *mut c_void, key.as_ptr(),
+                               value.as_ptr(), advapi32::RRF_RT_REG_DWORD, ptr::null_mut(),
+                               &mut light as *mut u32 as *mut c_void, &mut size)
+    };
+    status == advapi32::ERROR_SUCCESS && light == 0
+}
+
+#[cfg(not(windows))]
+fn system_prefers_dark() -> bool {
+    use std::process::Command;
+
+    if let Ok(theme) = ::std::env::var("GTK_THEME") {
+        return theme.to_lowercase().contains("dark");
+    }
+    let output = Command::new("gsettings")
+        .args(&["get", "org.gnome.desktop.interface", "color-scheme"])
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains("prefer-dark"),
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+mod advapi32 {
+    use libc::{c_void, c_long, c_ulong};
+
+    pub const HKEY_CURRENT_USER: isize = 0x80000001u32 as i32 as isize;
+    pub const RRF_RT_REG_DWORD: c_ulong = 0x00000010;
+    pub const ERROR_SUCCESS: c_long = 0;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        pub fn RegGetValueW(key: *mut c_void, subkey: *const u16, value: *const u16,
+                            flags: c_ulong, kind: *mut c_ulong, data: *mut c_void,
+                            size: *mut c_ulong) -> c_long;
+    }
+}
@@ -0,0 +1,151 @@
+//! Rendering with wgpu inside IUP layouts, available with the `wgpu` feature.
+//!
+//! A `WgpuSurface` is created from a mapped `Canvas` (or `Dialog`) and keeps its swap chain
+//! configured with the size of the element, so rendering a frame is only a matter of asking
+//! for the next texture:
+//!
+//! ```ignore
+//! // Safe since the surface is moved into a callback of the canvas, dropped right before the
+//! // canvas is destroyed.
+//! let mut surface = unsafe { WgpuSurface::new(&instance, &adapter, &device, canvas) }.unwrap();
+//! canvas.set_resize_cb(move |_| {
+//!     let frame = surface.current_texture(&device).unwrap();
+//!     let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+//!     // Encode the render passes to `view` and submit them to the queue.
+//!     frame.present();
+//! });
+//! ```
+//!
+//! The native window of the element is used, so the same restrictions of the raw window handle
+//! apply (see the `native` module).
+use std::result::Result;
+use raw_window_handle::{HasRawWindowHandle, HasRawDisplayHandle};
+use wgpu;
+
+use Element;
+use native;
+
+/// A wgpu surface drawing into an IUP element, reconfigured as the element is resized.
+///
+/// The element must outlive the surface, see `new`.
+pub struct WgpuSurface<E: Element> {
+    elem: E,
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+}
+
+impl<E> WgpuSurface<E> where E: Element + HasRawWindowHandle + HasRawDisplayHandle {
+    /// Creates a surface on `elem`, which must be mapped, configured for `device`.
+    ///
+    /// The surface uses the first sRGB format supported by `adapter` (or its preferred format)
+    /// and vertical synchronization (`PresentMode::Fifo`).
+    ///
+    /// # Safety
+    /// The surface draws into the native window of `elem`, which wgpu requires to outlive the
+    /// surface. The surface must be dropped before the element (or its dialog) is destroyed,
+    /// which the element handle can't guarantee.
+    pub unsafe fn new(instance: &wgpu::Instance, adapter: &wgpu::Adapter, device: &wgpu::Device,
+               elem: E) -> Result<WgpuSurface<E>, String> {
+        let surface = try!(instance.create_surface(&elem).map_err(|e| e.to_string()));
+        let caps = surface.get_capabilities(adapter);
+        let format = match caps.formats.iter().find(|format| format.is_srgb()) {
+            Some(&format) => format,
+            None => match caps.formats.first() {
+                Some(&format) => format,
+                None => return Err("The surface is not supported by the adapter".into()),
+            },
+        };
+        let (width, height) = pixel_size(elem);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: format,
+            width: width,
+            height: height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes.first().cloned().unwrap_or(wgpu::CompositeAlphaMode::Auto),
+            view_formats: Vec::new(),
+        };
+        surface.configure(device, &config);
+        Ok(WgpuSurface { elem: elem, surface: surface, config: config })
+    }
+}
+
+impl<E: Element> WgpuSurface<E> {
+    /// Gets the element drawn by the surface.
+    pub fn element(&self) -> E {
+        self.elem
+    }
+
+    /// Gets the wgpu surface.
+    pub fn surface(&self) -> &wgpu::Surface {
+        &self.surface
+    }
+
+    /// Gets the current configuration of the surface.
+    pub fn config(&self) -> &wgpu::SurfaceConfiguration {
+        &self.config
+    }
+
+    /// Gets the texture format of the surface, needed by the render pipelines.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    /// Gets the size of the surface in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    /// Changes how frames are presented, e.g. `PresentMode::Immediate` to disable the vertical
+    /// synchronization.
+    pub fn set_present_mode(&mut self, device: &wgpu::Device, mode: wgpu::PresentMode) {
+        self.config.present_mode = mode;
+        self.surface.configure(device, &self.config);
+    }
+
+    /// Gets the texture of the next frame, first reconfiguring the surface if the element was
+    /// resized or the surface became outdated.
+    pub fn current_texture(&mut self, device: &wgpu::Device)
+                                        -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
+        self.resize_if_needed(device);
+        match self.surface.get_current_texture() {
+            Err(wgpu::SurfaceError::Lost) | Err(wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(device, &self.config);
+                self.surface.get_current_texture()
+            },
+            result => result,
+        }
+    }
+
+    /// Reconfigures the surface if the size of the element changed, returning whether it did.
+    ///
+    /// Called by `current_texture`, but may be called from the RESIZE_CB of the element to
+    /// update the projection before rendering.
+    pub fn resize_if_needed(&mut self, device: &wgpu::Device) -> bool {
+        let (width, height) = pixel_size(self.elem);
+        if (width, height) == (self.config.width, self.config.height) {
+            return false;
+        }
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(device, &self.config);
+        true
+    }
+}
+
+/// Gets the drawing size of a canvas or the client size of a dialog in device pixels, at least
+/// 1x1 since surfaces can't be empty.
+fn pixel_size<E: Element>(elem: E) -> (u32, u32) {
+    let size = elem.attrib("DRAWSIZE").or_else(|| elem.attrib("CLIENTSIZE"));
+    let (width, height) = size.and_then(|size| {
+        let mut parts = size.splitn(2, 'x').map(|s| s.trim().parse::<u32>().ok());
+        match (parts.next(), parts.next()) {
+            (Some(Some(width)), Some(Some(height))) => Some((width, height)),
+            _ => None,
+        }
+    }).unwrap_or((1, 1));
+    let scale = native::scale_factor(&elem);
+    let (width, height) = ((width as f64 * scale).round() as u32,
+                           (height as f64 * scale).round() as u32);
+    (width.max(1), height.max(1))
+}
@@ -0,0 +1,181 @@
+//! Internationalization of the interface strings.
+//!
+//! IUP translates the strings of its predefined dialogs (e.g. the buttons of `alarm`) to the
+//! language set by `set_language`, and each of those strings can be replaced with
+//! `set_language_string`.
+//!
+//! The strings of IUP-Rust components (e.g. `Wizard`) and any string of the application can be
+//! translated by a `Catalog`. Once a catalog is set with `set_catalog` it is also used for the
+//! predefined IUP strings, by their names (e.g. "IUP_OK", "IUP_CANCEL").
+//!
+//! ```ignore
+//! let mut catalog = HashMap::new();
+//! catalog.insert("IUP_CANCEL".to_string(), "Abbrechen".to_string());
+//! catalog.insert("greeting".to_string(), "Hallo".to_string());
+//! i18n::set_catalog(catalog);
+//!
+//! let label = Label::new(i18n::tr("greeting", "Hello"));
+//! ```
+//!
+//! With the `fluent-bundle` feature, a [Fluent][1] bundle can be used as a catalog, each message
+//! is looked up by its id.
+//!
+//! [1]: https://projectfluent.org/
+use libc::{c_char, c_int};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::CString;
+
+/// The languages of the predefined IUP strings.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Language {
+    English,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Language::English => "ENGLISH",
+            Language::Portuguese => "PORTUGUESE",
+            Language::Spanish => "SPANISH",
+        }
+    }
+}
+
+/// The names of the predefined IUP strings, which are replaced by the catalog.
+const IUP_STRINGS: &'static [&'static str] = &[
+    "IUP_ERROR", "IUP_ATTENTION", "IUP_YES", "IUP_NO", "IUP_INVALIDDIR", "IUP_FILEISDIR",
+    "IUP_FILENOTEXIST", "IUP_FILEOVERWRITE", "IUP_CREATEFOLDER", "IUP_NAMENEWFOLDER",
+    "IUP_SAVEAS", "IUP_OPEN", "IUP_SELECTDIR", "IUP_OK", "IUP_CANCEL", "IUP_RETRY", "IUP_APPLY",
+    "IUP_RESET", "IUP_GETCOLOR", "IUP_HELP", "IUP_RED", "IUP_GREEN", "IUP_BLUE", "IUP_HUE",
+    "IUP_SATURATION", "IUP_INTENSITY", "IUP_OPACITY", "IUP_PALETTE", "IUP_TRUE", "IUP_FALSE",
+    "IUP_FAMILY", "IUP_STYLE", "IUP_SIZE", "IUP_SAMPLE",
+];
+
+/// A source of translated strings.
+pub trait Catalog {
+    /// Gets the translation of the string identified by `key`, or `None` if not translated.
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+impl Catalog for HashMap<String, String> {
+    fn get(&self, key: &str) -> Option<String> {
+        HashMap::get(self, key).cloned()
+    }
+}
+
+#[cfg(feature = "fluent-bundle")]
+impl<R: ::std::borrow::Borrow<::fluent_bundle::FluentResource>> Catalog for ::fluent_bundle::FluentBundle<R> {
+    fn get(&self, key: &str) -> Option<String> {
+        let pattern = match self.get_message(key).and_then(|message| message.value()) {
+            Some(pattern) => pattern,
+            None => return None,
+        };
+        let mut errors = Vec::new();
+        let value = self.format_pattern(pattern, None, &mut errors).into_owned();
+        if errors.is_empty() { Some(value) } else { None }
+    }
+}
+
+thread_local!(static CATALOG: RefCell<Option<Box<Catalog>>> = RefCell::new(None));
+
+/// Sets the language of the predefined IUP strings, English by default.
+///
+/// Strings previously replaced with `set_language_string` or by a catalog are reset.
+pub fn set_language(language: Language) {
+    let clanguage = CString::new(language.as_str()).unwrap();
+    unsafe { IupSetLanguage(clanguage.as_ptr()) };
+    CATALOG.with(|catalog| {
+        if let Some(ref catalog) = *catalog.borrow() {
+            apply_iup_strings(&**catalog);
+        }
+    });
+}
+
+/// Gets the language of the predefined IUP strings.
+pub fn language() -> Option<Language> {
+    let clanguage = unsafe { IupGetLanguage() };
+    if clanguage.is_null() {
+        return None;
+    }
+    match &string_from_cstr!(clanguage)[..] {
+        "ENGLISH" => Some(Language::English),
+        "PORTUGUESE" => Some(Language::Portuguese),
+        "SPANISH" => Some(Language::Spanish),
+        _ => None,
+    }
+}
+
+/// Replaces the predefined IUP string `name` (e.g. "IUP_OK") in the current language.
+pub fn set_language_string<S1, S2>(name: S1, value: S2) where S1: Into<String>, S2: Into<String> {
+    let cname = CString::new(name.into()).unwrap();
+    let cvalue = CString::new(value.into()).unwrap();
+    unsafe { IupStoreLanguageString(cname.as_ptr(), cvalue.as_ptr()) };
+}
+
+/// Gets the predefined IUP string `name` (e.g. "IUP_OK") in the current language.
+pub fn language_string<S: Into<String>>(name: S) -> Option<String> {
+    let cname = CString::new(name.into()).unwrap();
+    match unsafe { IupGetLanguageString(cname.as_ptr()) } {
+        cvalue if cvalue.is_null() => None,
+        cvalue => Some(string_from_cstr!(cvalue)),
+    }
+}
+
+/// Compares two strings as IUP does to sort the items of a `List`.
+///
+/// The `lexicographic` order takes the current locale into account and sorts numbers in the
+/// middle of the strings by their value (e.g. "item2" before "item10").
+pub fn compare(a: &str, b: &str, case_sensitive: bool, lexicographic: bool) -> Ordering {
+    let ca = CString::new(a).unwrap();
+    let cb = CString::new(b).unwrap();
+    match unsafe { IupStringCompare(ca.as_ptr(), cb.as_ptr(), case_sensitive as c_int,
+                                    lexicographic as c_int) } {
+        n if n < 0 => Ordering::Less,
+        0 => Ordering::Equal,
+        _ => Ordering::Greater,
+    }
+}
+
+/// Sets the catalog translating the interface strings, replacing the previous one.
+///
+/// The predefined IUP strings found in the catalog are replaced right away.
+pub fn set_catalog<C: Catalog + 'static>(catalog: C) {
+    apply_iup_strings(&catalog);
+    CATALOG.with(|cell| *cell.borrow_mut() = Some(Box::new(catalog)));
+}
+
+/// Removes the catalog, the strings are not translated anymore.
+///
+/// Predefined IUP strings replaced by the catalog are kept until `set_language` is called.
+pub fn clear_catalog() {
+    CATALOG.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// Translates the string identified by `key`, or returns `default` if there is no catalog or
+/// the catalog has no translation.
+pub fn tr<S: Into<String>>(key: &str, default: S) -> String {
+    CATALOG.with(|cell| cell.borrow().as_ref().and_then(|catalog| catalog.get(key)))
+           .unwrap_or_else(|| default.into())
+}
+
+fn apply_iup_strings(catalog: &Catalog) {
+    for name in IUP_STRINGS {
+        if let Some(value) = catalog.get(name) {
+            set_language_string(*name, value);
+        }
+    }
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupSetLanguage(lng: *const c_char);
+    fn IupGetLanguage() -> *mut c_char;
+    fn IupStoreLanguageString(name: *const c_char, str: *const c_char);
+    fn IupGetLanguageString(name: *const c_char) -> *mut c_char;
+    fn IupStringCompare(str1: *const c_char, str2: *const c_char, casesensitive: c_int,
+                        lexicographic: c_int) -> c_int;
+}
@@ -137,3 +137,82 @@ impl ImageElement for ImageRgba {
 }
 
 impl_element!(ImageRgba, "imagergba");
+
+
+/// An image of the IUP image library, registered by `Application::image_lib` (or
+/// `IupImageLibOpen`).
+///
+/// See the [IUP Image Library Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/iupimglib.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StockImage {
+    FileNew,
+    FileOpen,
+    FileSave,
+    FileSaveAll,
+    FileClose,
+    FileCloseAll,
+    FileProperties,
+    Print,
+    PrintPreview,
+    EditCut,
+    EditCopy,
+    EditPaste,
+    EditErase,
+    EditFind,
+    EditUndo,
+    EditRedo,
+    ZoomIn,
+    ZoomOut,
+    ZoomActualSize,
+    NavigateHome,
+    NavigateRefresh,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    ArrowDown,
+    ToolsSettings,
+    MessageHelp,
+    MessageInfo,
+    MessageError,
+    /// Any other image of the library, or any image given a name with `add_handle_name`.
+    Named(String),
+}
+
+impl StockImage {
+    /// Gets the name the image is registered with, e.g. "IUP_FileOpen".
+    pub fn name(&self) -> &str {
+        match *self {
+            StockImage::FileNew => "IUP_FileNew",
+            StockImage::FileOpen => "IUP_FileOpen",
+            StockImage::FileSave => "IUP_FileSave",
+            StockImage::FileSaveAll => "IUP_FileSaveAll",
+            StockImage::FileClose => "IUP_FileClose",
+            StockImage::FileCloseAll => "IUP_FileCloseAll",
+            StockImage::FileProperties => "IUP_FileProperties",
+            StockImage::Print => "IUP_Print",
+            StockImage::PrintPreview => "IUP_PrintPreview",
+            StockImage::EditCut => "IUP_EditCut",
+            StockImage::EditCopy => "IUP_EditCopy",
+            StockImage::EditPaste => "IUP_EditPaste",
+            StockImage::EditErase => "IUP_EditErase",
+            StockImage::EditFind => "IUP_EditFind",
+            StockImage::EditUndo => "IUP_EditUndo",
+            StockImage::EditRedo => "IUP_EditRedo",
+            StockImage::ZoomIn => "IUP_ZoomIn",
+            StockImage::ZoomOut => "IUP_ZoomOut",
+            StockImage::ZoomActualSize => "IUP_ZoomActualSize",
+            StockImage::NavigateHome => "IUP_NavigateHome",
+            StockImage::NavigateRefresh => "IUP_NavigateRefresh",
+            StockImage::ArrowLeft => "IUP_ArrowLeft",
+            StockImage::ArrowRight => "IUP_ArrowRight",
+            StockImage::ArrowUp => "IUP_ArrowUp",
+            StockImage::ArrowDown => "IUP_ArrowDown",
+            StockImage::ToolsSettings => "IUP_ToolsSettings",
+            StockImage::MessageHelp => "IUP_MessageHelp",
+            StockImage::MessageInfo => "IUP_MessageInfo",
+            StockImage::MessageError => "IUP_MessageError",
+            StockImage::Named(ref name) => name,
+        }
+    }
+}
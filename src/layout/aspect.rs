@@ -0,0 +1,44 @@
+//! Keeping a dialog or a canvas at a fixed aspect ratio.
+use element::{Node, Widget, Expand};
+use callback::ResizeCb;
+
+/// Keeps the width of `elem` over its height at `ratio` while it's resized, the height
+/// following the width.
+///
+/// For a dialog the client area is resized, so the user can only choose the width. Any other
+/// element (e.g. a `Canvas`) expands only horizontally and gets the height matching its width,
+/// which is still bounded by the size of its container.
+///
+/// ```ignore
+/// layout::lock_aspect_ratio(video_canvas, 16.0 / 9.0);
+/// ```
+///
+/// This sets the RESIZE_CB callback of the element.
+///
+/// # Panics
+/// Panics if `ratio` is not positive.
+pub fn lock_aspect_ratio<E: Widget + ResizeCb>(mut elem: E, ratio: f64) -> E {
+    assert!(ratio > 0.0, "the aspect ratio must be positive");
+    let is_dialog = unsafe { elem.classname().to_bytes() == b"dialog" };
+    if !is_dialog {
+        elem.set_expand(Expand::Horizontal);
+    }
+    elem.set_resize_cb(move |(mut elem, width, height): (E, i32, i32)| {
+        let wanted = (width as f64 / ratio).round() as i32;
+        // The size is adjusted only once, the resize it triggers finds the height right.
+        if (wanted - height).abs() <= 1 {
+            return;
+        }
+        if is_dialog {
+            elem.set_attrib("CLIENTSIZE", format!("{}x{}", width, wanted));
+        } else {
+            // Only the height is set, so the width can still follow the container.
+            elem.set_attrib("RASTERSIZE", format!("x{}", wanted));
+        }
+        match elem.dialog() {
+            Some(mut dialog) => dialog.refresh(),
+            None => elem.refresh(),
+        }
+    });
+    elem
+}
@@ -0,0 +1,21 @@
+use iup_sys;
+
+use Element;
+
+/// A void container painting a background (BGCOLOR or BACKIMAGE) behind its only child.
+///
+/// See the [IUP BackgroundBox Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupbackgroundbox.html
+pub struct BackgroundBox(*mut iup_sys::Ihandle);
+
+impl BackgroundBox {
+    /// Creates a background box around `child`.
+    pub fn new<E: Element>(child: E) -> BackgroundBox {
+        unsafe { BackgroundBox::from_raw(iup_sys::IupBackgroundBox(child.raw())) }
+    }
+}
+
+impl_widget_container!(BackgroundBox, "backgroundbox");
+
+/// Action generated when any mouse button is pressed or released inside the box.
+impl ::callback::button::ButtonCb for BackgroundBox {}
@@ -1,6 +1,7 @@
 use iup_sys;
 
 use Element;
+use element::{Widget, Size};
 
 /// A void element, which dynamically occupies empty spaces always trying to expand itself.
 ///
@@ -13,9 +14,37 @@ use Element;
 pub struct Fill(*mut iup_sys::Ihandle);
 
 impl Fill {
+    /// Creates a fill expanding in the direction of its box.
     pub fn new() -> Fill {
         unsafe { Fill::from_raw(iup_sys::IupFill()) }
     }
 }
 
 impl_widget!(Fill, "fill");
+
+/// Fixed space between the children of an `HBox` or a `VBox`.
+///
+/// A space is a `Fill` with a size, which then doesn't expand. The size is its width inside an
+/// `HBox` and its height inside a `VBox`.
+///
+/// ```ignore
+/// HBox::new(elements![ok, Space::px(8), cancel])
+/// ```
+pub struct Space;
+
+impl Space {
+    /// Creates a space of `size`, only the width or the height of which is used.
+    pub fn new(size: Size) -> Fill {
+        Fill::new().set_size(size)
+    }
+
+    /// Creates a space of `size` pixels.
+    pub fn px(size: i32) -> Fill {
+        Space::new(Size::px(size, size))
+    }
+
+    /// Creates a space of `size` characters of the font of the box, see `Size::chars`.
+    pub fn chars(size: i32) -> Fill {
+        Space::new(Size::chars(size, size))
+    }
+}
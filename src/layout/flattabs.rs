@@ -0,0 +1,113 @@
+use iup_sys;
+use std::ptr;
+
+use Handle;
+use Element;
+use element::Container;
+
+/// A container showing one child at a time, selected by tabs drawn by IUP itself instead of by
+/// the native system, with an optional close button in each tab (SHOWCLOSE=YES).
+///
+/// Tabs are identified by the position of their child, from 0. The title of a tab is its
+/// TABTITLEn attribute, see `set_tab_title`.
+///
+/// See the [IUP FlatTabs Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupflattabs.html
+pub struct FlatTabs(*mut iup_sys::Ihandle);
+
+impl FlatTabs {
+    /// Creates a tabs container with the specified childs, one per tab.
+    pub fn new<A>(elems: A) -> FlatTabs where A: AsRef<[Handle]> {
+        let mut carray = slice_to_ih_array!(elems.as_ref());
+        unsafe { FlatTabs::from_raw(IupFlatTabsv(carray.as_mut_ptr())) }
+    }
+
+    /// Returns the number of tabs.
+    pub fn tab_count(&self) -> usize {
+        self.child_count()
+    }
+
+    /// Gets the position of the visible tab, `None` if there are no tabs.
+    pub fn value_pos(&self) -> Option<usize> {
+        self.attrib_parse("VALUEPOS")
+    }
+
+    /// Shows the tab at `pos`.
+    pub fn set_value_pos(&mut self, pos: usize) -> FlatTabs {
+        self.set_attrib("VALUEPOS", pos.to_string())
+    }
+
+    /// Gets the title of the tab at `pos`.
+    pub fn tab_title(&self, pos: usize) -> Option<String> {
+        self.attrib_at("TABTITLE", pos as i32)
+    }
+
+    /// Sets the title of the tab at `pos`.
+    pub fn set_tab_title<S: Into<String>>(&mut self, pos: usize, title: S) -> FlatTabs {
+        self.set_attrib_at("TABTITLE", pos as i32, title)
+    }
+
+    /// Moves the tab at `from` so it ends at the position `to`, keeping its child mapped.
+    ///
+    /// Returns false if any of the positions is not a tab.
+    pub fn move_tab(&mut self, from: usize, to: usize) -> bool {
+        let count = self.tab_count();
+        let child = match self.child(from) {
+            Some(child) if to < count => child,
+            _ => return false,
+        };
+        if from == to {
+            return true;
+        }
+        // The child is inserted before the reference child, once removed from its position.
+        let reference = if to < from { self.child(to) } else { self.child(to + 1) };
+        let reference = reference.map_or(ptr::null_mut(), |reference| reference.raw());
+        unsafe { iup_sys::IupReparent(child.raw(), self.raw(), reference) == iup_sys::IUP_NOERROR }
+    }
+}
+
+impl_widget_container!(FlatTabs, "flattabs");
+
+impl ::callback::MapCb for FlatTabs {}
+impl ::callback::UnmapCb for FlatTabs {}
+impl ::callback::GetFocusCb for FlatTabs {}
+impl ::callback::KillFocusCb for FlatTabs {}
+impl ::callback::EnterWindowCb for FlatTabs {}
+impl ::callback::LeaveWindowCb for FlatTabs {}
+impl ::callback::HelpCb for FlatTabs {}
+impl ::callback::KAnyCb for FlatTabs {}
+
+/// See the `TabChangePosCb` documentation.
+impl self::TabChangePosCb for FlatTabs {}
+/// See the `TabCloseCb` documentation.
+impl self::TabCloseCb for FlatTabs {}
+
+impl_callback! {
+    #[doc="Action generated when the user selects another tab."]
+    #[doc=""]
+    #[doc="The `usize` parameters are the positions of the new and the previous tab."]
+    pub trait TabChangePosCb where Self: Element {
+        let name = "TABCHANGEPOS_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, new_pos: c_int, old_pos: c_int) -> CallbackReturn;
+        fn set_tabchangepos_cb<F: Callback(Self, usize, usize)>(&mut self, cb: F) -> Self;
+        fn remove_tabchangepos_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+impl_callback! {
+    #[doc="Action generated when the close button of a tab is clicked, with the tab position."]
+    #[doc=""]
+    #[doc="`CallbackReturn::Continue` removes the tab and destroys its child,"]
+    #[doc="`CallbackReturn::Ignore` keeps the tab, and `CallbackReturn::Default` hides the tab."]
+    pub trait TabCloseCb where Self: Element {
+        let name = "TABCLOSE_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, pos: c_int) -> CallbackReturn;
+        fn set_tabclose_cb<F: Callback(Self, usize)>(&mut self, cb: F) -> Self;
+        fn remove_tabclose_cb(&mut self) -> Option<Box<_>>;
+    }
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupFlatTabsv(children: *mut *mut iup_sys::Ihandle) -> *mut iup_sys::Ihandle;
+}
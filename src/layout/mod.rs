@@ -4,10 +4,20 @@
 
 pub mod vbox;
 pub mod hbox;
+pub mod zbox;
 pub mod radio;
 pub mod fill;
+pub mod flattabs;
+pub mod backgroundbox;
+pub mod rtl;
+pub mod aspect;
 
 pub use self::vbox::VBox;
 pub use self::hbox::HBox;
+pub use self::zbox::ZBox;
 pub use self::radio::Radio;
-pub use self::fill::Fill;
+pub use self::fill::{Fill, Space};
+pub use element::{Expand, Size};
+pub use self::aspect::lock_aspect_ratio;
+pub use self::backgroundbox::BackgroundBox;
+pub use self::flattabs::{FlatTabs, TabChangePosCb, TabCloseCb};
@@ -0,0 +1,103 @@
+//! Right-to-left layouts, for languages such as Arabic and Hebrew.
+//!
+//! IUP lays out elements from left to right. In right-to-left mode `mirror` flips a layout
+//! horizontally, reversing the order of the horizontal boxes and swapping the left and right
+//! alignments, so the same layout code serves both directions.
+//!
+//! ```ignore
+//! rtl::set_direction(rtl::direction_from_locale());
+//! let dialog = Dialog::new(rtl::apply(VBox::new(elements![...])));
+//! ```
+//!
+//! The direction of the native controls themselves (e.g. the text cursor of a `Text`) follows the
+//! system settings.
+use iup_sys;
+use std::cell::Cell;
+use std::env;
+
+use Element;
+use Handle;
+use element::{Node, Container};
+
+/// The direction the interface is laid out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+thread_local!(static DIRECTION: Cell<Direction> = Cell::new(Direction::LeftToRight));
+
+/// Languages (ISO 639-1 codes) written from right to left.
+const RTL_LANGUAGES: &'static [&'static str] = &["ar", "dv", "fa", "he", "iw", "ps", "ur", "yi"];
+
+/// Sets the direction used by `apply` and the IUP-Rust components (e.g. `TitleBar`).
+pub fn set_direction(direction: Direction) {
+    DIRECTION.with(|cell| cell.set(direction));
+}
+
+/// Gets the direction set with `set_direction`, left to right by default.
+pub fn direction() -> Direction {
+    DIRECTION.with(|cell| cell.get())
+}
+
+/// Checks whether the direction is right to left.
+pub fn is_rtl() -> bool {
+    direction() == Direction::RightToLeft
+}
+
+/// Gets the direction of the user language, from the `LC_ALL`, `LC_MESSAGES` or `LANG`
+/// environment variables.
+pub fn direction_from_locale() -> Direction {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+                    .filter_map(|name| env::var(name).ok())
+                    .find(|value| !value.is_empty())
+                    .unwrap_or_default();
+    let language = locale.split(|c| c == '_' || c == '-' || c == '.').next().unwrap_or("");
+    if RTL_LANGUAGES.contains(&&language.to_lowercase()[..]) {
+        Direction::RightToLeft
+    } else {
+        Direction::LeftToRight
+    }
+}
+
+/// Mirrors `elem` if the direction is right to left, see `mirror`.
+pub fn apply<E: Node>(elem: E) -> E {
+    if is_rtl() { mirror(elem) } else { elem }
+}
+
+/// Flips the layout of `elem` and its children horizontally.
+///
+/// The children of horizontal boxes are reversed and the ALIGNMENT attributes have left and
+/// right swapped. Mirroring twice restores the original layout. Should be done before the
+/// dialog is shown, otherwise `Node::refresh` must be called afterwards.
+pub fn mirror<E: Node>(elem: E) -> E {
+    let mut handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+    mirror_handle(&mut handle);
+    elem
+}
+
+fn mirror_handle(handle: &mut Handle) {
+    if let Some(alignment) = handle.attrib("ALIGNMENT") {
+        let swapped: Vec<&str> = alignment.split(':').map(|part| match part {
+            "ALEFT" => "ARIGHT",
+            "ARIGHT" => "ALEFT",
+            part => part,
+        }).collect();
+        handle.set_attrib("ALIGNMENT", swapped.join(":"));
+    }
+
+    let children = handle.children();
+    if unsafe { handle.classname() }.to_bytes() == b"hbox" {
+        // Moving each child to the front, in order, reverses them.
+        for child in children.iter() {
+            let first = unsafe { iup_sys::IupGetChild(handle.raw(), 0) };
+            if first != child.raw() {
+                unsafe { iup_sys::IupReparent(child.raw(), handle.raw(), first) };
+            }
+        }
+    }
+    for mut child in children {
+        mirror_handle(&mut child);
+    }
+}
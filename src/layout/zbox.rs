@@ -0,0 +1,23 @@
+use iup_sys;
+
+use Handle;
+use Element;
+
+/// A void container for composing elements in hidden layers with only one layer visible.
+///
+/// The visible child is selected with the VALUE (by handle name) or VALUEPOS (by position)
+/// attributes.
+///
+/// See the [IUP ZBox Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupzbox.html
+pub struct ZBox(*mut iup_sys::Ihandle);
+
+impl ZBox {
+    /// Creates a layered container box with the specified childs.
+    pub fn new<A>(elems: A) -> ZBox where A: AsRef<[Handle]>  {
+        let mut carray = slice_to_ih_array!(elems.as_ref());
+        unsafe { ZBox::from_raw(iup_sys::IupZboxv(carray.as_mut_ptr())) }
+    }
+}
+
+impl_widget_container!(ZBox, "zbox");
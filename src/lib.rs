@@ -81,6 +81,10 @@
 //!
 //! The user is also able to destroy elements manually by calling `Element::destroy`, one should
 //! make sure such element does not have other *references* to it wandering in the code.
+//! An element can't be destroyed from its own callbacks or those of its children, which would
+//! free the running callback, `Element::destroy_later` destroys it once the callbacks returned.
+//! Copies of an element kept after it's destroyed are dangling, long-lived references should be
+//! a `WeakHandle`, which can only be turned back into the element while it's alive.
 //!
 //! From looking on the above auto-destroy rules, the following are the cases of elements that must
 //! be destroyed manually:
@@ -99,13 +103,77 @@
 //! IUP to conform with Rust string standards, thus both `UTF8MODE` and `UTF8MODE_FILE` attributes
 //! are enabled by default in IUP-Rust.
 //!
+//! ## Tracing
+//!
+//! When the `tracing` feature is enabled, attribute changes, callback invocations and the
+//! creation and destruction of elements are reported as [tracing][2] events and spans under the
+//! `iup` target, at the TRACE level. This is helpful to understand the order of events when
+//! debugging focus or layout issues.
+//!
+//! ## Raw Window Handle
+//!
+//! When the `raw-window-handle` feature is enabled, `Dialog` and `Canvas` implement the
+//! [raw-window-handle][3] traits, so they can be given to renderers such as wgpu or softbuffer.
+//! See the `native` module.
+//!
+//! ## wgpu
+//!
+//! When the `wgpu` feature is enabled, a `Canvas` can be rendered with wgpu through a
+//! `WgpuSurface`, which follows the size of the canvas, see the `gpu` module.
+//!
+//! ## Multi-touch
+//!
+//! When the `tuio` feature is enabled, canvases receive touch events and the `TuioClient` of
+//! the `iuptuio` library is available, see the `touch` module.
+//!
+//! ## OpenGL
+//!
+//! When the `gl` feature is enabled, the `GLCanvas` of the `iupgl` library is available, see
+//! `Application::gl`.
+//!
+//! ## Hot-reload
+//!
+//! When the `dev` feature is enabled, dialogs can be rebuilt from a LED or JSON layout file every
+//! time it's saved, see the `reload` module.
+//!
+//! ## Notifications
+//!
+//! When the `notifications` feature is enabled, native desktop notifications can be shown, see
+//! the `notification` module. On Linux it links to libnotify.
+//!
+//! ## X11
+//!
+//! When the `x11` feature is enabled, X11 windows can be embedded in the GTK and Motif drivers,
+//! see the `embed` module. It links to libX11.
+//!
 //! [1]: http://www.tecgraf.puc-rio.br/iup/
+//! [2]: https://docs.rs/tracing
+//! [3]: https://docs.rs/raw-window-handle
 //!
 
 extern crate libc;
 extern crate iup_sys;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "raw-window-handle")]
+extern crate raw_window_handle;
+#[cfg(feature = "fluent-bundle")]
+extern crate fluent_bundle;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "ctrlc")]
+extern crate ctrlc;
+#[cfg(feature = "notify")]
+extern crate notify;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+#[cfg(feature = "csv")]
+extern crate csv;
+#[cfg(feature = "wgpu")]
+extern crate wgpu;
 
 use std::result::Result;
+use std::ffi::CString;
 use std::ptr;
 
 #[macro_use]
@@ -113,7 +181,8 @@ mod macros;
 
 #[macro_use]
 pub mod element;
-pub use element::{Element, Handle, Guard};
+pub use element::{Element, Handle, Guard, WeakHandle, AttributeMap};
+pub use app::Application;
 
 #[macro_use]
 pub mod callback;
@@ -121,11 +190,45 @@ pub mod callback;
 pub mod dialog;
 pub mod layout;
 pub mod control;
+pub mod menu;
+pub mod component;
 
 pub mod led;
 pub mod image;
 pub mod timer;
 pub mod clipboard;
+pub mod config;
+pub mod mainloop;
+pub mod app;
+pub mod observe;
+pub mod property;
+pub mod choice;
+pub mod util;
+pub mod measure;
+pub mod i18n;
+pub mod a11y;
+pub mod globals;
+pub mod theme;
+pub mod style;
+pub mod spec;
+pub mod state;
+pub mod session;
+pub mod crash;
+#[cfg(feature = "notifications")]
+pub mod notification;
+pub mod validation;
+#[cfg(feature = "dev")]
+pub mod reload;
+pub mod native;
+#[cfg(feature = "wgpu")]
+pub mod gpu;
+pub mod embed;
+pub mod platform;
+#[cfg(feature = "tuio")]
+pub mod touch;
+pub mod automation;
+pub mod testing;
+pub mod debug;
 
 pub mod prelude;
 
@@ -170,14 +273,21 @@ pub enum InitError {
 ///
 /// ## Blocking
 /// This functin will not return until until a callback returns `CallbackReturn::Close`,
-/// IupExitLoop (TODO) is called, or there are no visible dialogs. 
+/// `mainloop::exit_loop` or `app::exit` is called, or there are no visible dialogs.
 ///
 /// If the `f` closure returns successfully without any visible dialogs and no active timers,
 /// the application will hang and will not be possible to close the main loop. The process will
 /// have to be interrupted by the system.
 ///
-/// When the last visible dialog is hidden the IupExitLoop (TODO) function is automatically called,
-/// causing this function to return. To avoid that set LOCKLOOP=YES before hiding the last dialog.
+/// When the last visible dialog is hidden the `mainloop::exit_loop` function is automatically
+/// called, causing this function to return. To avoid that call
+/// `mainloop::set_exit_on_last_window_closed(false)` or keep a `mainloop::LoopGuard` alive
+/// before hiding the last dialog.
+///
+/// ## Command Line
+/// IUP is not given the command line arguments, so driver specific switches are ignored. Use
+/// `Application` or `app::AppBuilder` to give them, or to control the initialization and the
+/// main loop further.
 ///
 /// ## Enviroment Variables
 ///
@@ -191,12 +301,57 @@ pub enum InitError {
 ///
 pub fn with_iup<F: FnOnce() -> Result<(), String>>(f: F) -> Result<(), InitError> {
 
-    match unsafe { iup_sys::IupOpen(ptr::null(), ptr::null()) } {
+    try!(open());
+
+    let user_result = f();
+    if user_result.is_ok() {
+        // IupMainLoop always returns IUP_NOERROR.
+        unsafe { iup_sys::IupMainLoop(); }
+    }
+
+    close();
+
+    user_result.map_err(|e| InitError::UserError(e))
+}
+
+/// Initializes IUP and the binding specific global settings.
+fn open() -> Result<(), InitError> {
+    open_with_args(&[]).map(|_| ())
+}
+
+/// Initializes IUP with the command line `args` (including the program name), so the driver
+/// can handle its own switches such as `--display` in GTK.
+///
+/// Returns the arguments left after the driver removed the ones it handled.
+fn open_with_args(args: &[String]) -> Result<Vec<String>, InitError> {
+
+    let cargs: Vec<CString> = args.iter()
+                                  .map(|arg| CString::new(&arg[..]).unwrap_or_default())
+                                  .collect();
+    let mut argv: Vec<*const libc::c_char> = cargs.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(ptr::null());
+    let mut argc = cargs.len() as libc::c_int;
+    let mut argv_ptr = argv.as_ptr();
+
+    // Without arguments IUP is given null pointers, as it always was.
+    let (pargc, pargv) = if args.is_empty() {
+        (ptr::null(), ptr::null())
+    } else {
+        (&mut argc as *mut libc::c_int as *const _, &mut argv_ptr as *mut _ as *const _)
+    };
+    match unsafe { iup_sys::IupOpen(pargc, pargv) } {
         iup_sys::IUP_NOERROR => {},
         iup_sys::IUP_OPENED => return Err(InitError::AlreadyOpen),
         iup_sys::IUP_ERROR => return Err(InitError::Error),
         _ => unreachable!(),
     };
+    util::set_main_thread();
+    timer::open_timers();
+
+    // The driver may have removed arguments, updating argc and argv.
+    let remaining = (0..argc as usize).map(|i| {
+        string_from_cstr!(unsafe { *argv_ptr.offset(i as isize) })
+    }).collect();
 
     // Turn UTF-8 mode ON since Rust uses UTF-8 on strings.
     match element::global("DRIVER").unwrap().as_ref() {
@@ -207,19 +362,21 @@ pub fn with_iup<F: FnOnce() -> Result<(), String>>(f: F) -> Result<(), InitError
         _ => println!("Warning: This IUP driver does not seem to support UTF-8!"),
     }
 
-    let user_result = f();
-    if user_result.is_ok() {
-        // IupMainLoop always returns IUP_NOERROR.
-        unsafe { iup_sys::IupMainLoop(); }
-    }
+    Ok(remaining)
+}
 
+/// Frees the binding global data and finishes IUP.
+fn close() {
+    // the helper timers are forgotten first, so the callbacks below can't arm them again.
+    timer::close_timers();
     // perform manual drop_callback! on the global callbacks.
     // also calls our iup-rust specific close callback.
+    app::run_exit_hooks();
     callback::remove_idle();
+    globals::clear_watchers();
     callback::remove_close_cb().map( |mut fbox| fbox.on_callback(()) );
+    util::disable_posting();
     unsafe { iup_sys::IupClose(); }
-
-    user_result.map_err(|e| InitError::UserError(e))
 }
 
 /// Returns a string with the IUP version number.
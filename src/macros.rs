@@ -25,3 +25,26 @@ macro_rules! slice_to_ih_array {
         v
     }};    
 }
+
+/// Emits a `tracing` event under the "iup" target, does nothing without the tracing feature.
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => { ::tracing::trace!(target: "iup", $($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => { () };
+}
+
+/// Enters a `tracing` span under the "iup" target, the span is exited when the returned guard
+/// gets dropped. Does nothing without the tracing feature.
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($($arg:tt)*) => { ::tracing::trace_span!(target: "iup", $($arg)*).entered() };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($($arg:tt)*) => { () };
+}
@@ -0,0 +1,89 @@
+//! Control of the IUP main loop and its nested levels.
+//!
+//! Every `DialogElement::popup` and every call to `nested_loop` runs a new level of the main
+//! loop, which ends when a callback returns `CallbackReturn::Close` or `exit_loop` is called.
+//!
+//! By default IUP also ends the main loop when the last visible dialog is hidden. That is
+//! surprising when a nested level hides the only dialog shown, e.g. a main window hidden while a
+//! popup is running, because the whole application then quits once the popup closes. This can be
+//! prevented for the whole application with `set_exit_on_last_window_closed(false)`, or while a
+//! `LoopGuard` is alive.
+use iup_sys;
+
+use element;
+use dialog::{DialogElement, DialogPos};
+
+/// Gets the current level of the main loop, zero when it isn't running.
+pub fn level() -> i32 {
+    unsafe { iup_sys::IupMainLoopLevel() as i32 }
+}
+
+/// Ends the current level of the main loop, once the current callback returns.
+pub fn exit_loop() {
+    unsafe { iup_sys::IupExitLoop() };
+}
+
+/// Sets whether the main loop ends when the last visible dialog is hidden, true by default.
+pub fn set_exit_on_last_window_closed(exit: bool) {
+    element::set_global("LOCKLOOP", if exit { "NO" } else { "YES" });
+}
+
+/// Gets whether the main loop ends when the last visible dialog is hidden.
+pub fn exit_on_last_window_closed() -> bool {
+    element::global("LOCKLOOP").map_or(true, |lock| lock != "YES")
+}
+
+/// Keeps the main loop from ending when the last visible dialog is hidden while alive, and
+/// remembers the loop level it was created at.
+///
+/// ```ignore
+/// let guard = LoopGuard::new();
+/// main_dialog.hide();
+/// settings_dialog.popup(DialogPos::Center, DialogPos::Center);
+/// main_dialog.show();
+/// drop(guard);
+/// ```
+#[derive(Debug)]
+pub struct LoopGuard {
+    level: i32,
+    exit_on_close: bool,
+}
+
+impl LoopGuard {
+    /// Creates a guard at the current loop level.
+    pub fn new() -> LoopGuard {
+        let exit_on_close = exit_on_last_window_closed();
+        set_exit_on_last_window_closed(false);
+        LoopGuard { level: level(), exit_on_close: exit_on_close }
+    }
+
+    /// Gets the loop level the guard was created at.
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Checks whether the code is running in a level nested inside the guard level.
+    pub fn is_nested(&self) -> bool {
+        level() > self.level
+    }
+}
+
+impl Drop for LoopGuard {
+    fn drop(&mut self) {
+        set_exit_on_last_window_closed(self.exit_on_close);
+    }
+}
+
+/// Runs a nested level of the main loop until a callback returns `CallbackReturn::Close` or
+/// `exit_loop` is called, which then only ends this level.
+pub fn nested_loop() {
+    let _guard = LoopGuard::new();
+    unsafe { iup_sys::IupMainLoop() };
+}
+
+/// Shows `dialog` as a modal popup under a `LoopGuard`, so closing it never ends the
+/// application, even when no other dialog is visible.
+pub fn popup_guarded<D: DialogElement>(mut dialog: D, x: DialogPos, y: DialogPos) -> Result<D, D> {
+    let _guard = LoopGuard::new();
+    dialog.popup(x, y)
+}
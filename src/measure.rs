@@ -0,0 +1,111 @@
+//! Measurement of text, to lay out the drawing of custom canvas widgets.
+//!
+//! ```ignore
+//! let (width, height) = measure::text_extent("Helvetica, 10", "Hello");
+//! let title = measure::elide("Helvetica, 10", &path, rect.width);
+//! ```
+//!
+//! The sizes are in pixels, as the driver draws the text with the font, the same as
+//! `Canvas::text_extent` while drawing. The text is measured on a hidden canvas, mapped in a
+//! dialog which is never shown, since the driver only measures inside a draw cycle of a mapped
+//! canvas.
+use iup_sys;
+use libc::{c_char, c_int};
+use std::cell::Cell;
+use std::ffi::CString;
+
+use Element;
+use Handle;
+use WeakHandle;
+use control::Canvas;
+use dialog::Dialog;
+
+// The canvas measuring the text, its dialog is destroyed by IUP on close.
+thread_local!(static MEASURER: Cell<Option<WeakHandle<Canvas>>> = Cell::new(None));
+
+/// The ellipsis appended to truncated text.
+const ELLIPSIS: &'static str = "\u{2026}";
+
+/// Gets the width and height in pixels of `text` written with `font` (in the format of the
+/// FONT attribute, empty for the default font of the application).
+///
+/// Multiline text gets the width of its longest line and the height of all the lines.
+pub fn text_extent(font: &str, text: &str) -> (i32, i32) {
+    with_measurer(font, |ih| extent(ih, text))
+}
+
+/// Shortens `text` written with `font` to fit in `max_width` pixels, replacing its end with an
+/// ellipsis ("…").
+///
+/// The text is returned unchanged if it fits. If not even the ellipsis fits an empty string is
+/// returned.
+///
+/// This is meant for text drawn on a canvas, labels shorten their title by themselves with
+/// their ELLIPSIS attribute.
+pub fn elide(font: &str, text: &str, max_width: i32) -> String {
+    with_measurer(font, |ih| elide_with(ih, text, max_width))
+}
+
+fn elide_with(ih: *mut iup_sys::Ihandle, text: &str, max_width: i32) -> String {
+    if extent(ih, text).0 <= max_width {
+        return text.to_string();
+    }
+    // Finds the longest prefix fitting with the ellipsis, by a binary search on characters.
+    let ends: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let (mut fits, mut too_long) = (0, ends.len());
+    while too_long - fits > 1 {
+        let middle = (fits + too_long) / 2;
+        let candidate = format!("{}{}", &text[..ends[middle]], ELLIPSIS);
+        if extent(ih, &candidate).0 <= max_width {
+            fits = middle;
+        } else {
+            too_long = middle;
+        }
+    }
+    if fits == 0 && extent(ih, ELLIPSIS).0 > max_width {
+        return String::new();
+    }
+    format!("{}{}", text[..ends[fits]].trim_right(), ELLIPSIS)
+}
+
+/// Measures `text` with the DRAWFONT (or FONT) of `ih`.
+#[doc(hidden)]
+pub fn extent(ih: *mut iup_sys::Ihandle, text: &str) -> (i32, i32) {
+    let ctext = CString::new(text).unwrap();
+    let (mut width, mut height) = (0, 0);
+    // The length is the number of bytes of the UTF-8 text, not of characters.
+    unsafe {
+        IupDrawGetTextSize(ih, ctext.as_ptr(), text.len() as c_int, &mut width, &mut height)
+    };
+    (width, height)
+}
+
+/// Calls `f` with the hidden canvas used to measure the text, inside a draw cycle with `font`.
+fn with_measurer<R, F: FnOnce(*mut iup_sys::Ihandle) -> R>(font: &str, f: F) -> R {
+    let mut measurer = measurer();
+    measurer.set_attrib("DRAWFONT", font);
+    unsafe { IupDrawBegin(measurer.raw()) };
+    let result = f(measurer.raw());
+    unsafe { IupDrawEnd(measurer.raw()) };
+    result
+}
+
+/// Gets the hidden canvas used to measure the text, creating and mapping it the first time.
+fn measurer() -> Handle {
+    let measurer = MEASURER.with(|measurer| measurer.get()).and_then(|canvas| canvas.upgrade());
+    Handle::from(measurer.unwrap_or_else(|| {
+        let canvas = Canvas::new();
+        let dialog = Dialog::new(canvas);
+        unsafe { iup_sys::IupMap(dialog.raw()) };
+        MEASURER.with(|measurer| measurer.set(Some(canvas.downgrade())));
+        canvas
+    }))
+}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupDrawBegin(ih: *mut iup_sys::Ihandle);
+    fn IupDrawEnd(ih: *mut iup_sys::Ihandle);
+    fn IupDrawGetTextSize(ih: *mut iup_sys::Ihandle, text: *const c_char, len: c_int,
+                          w: *mut c_int, h: *mut c_int);
+}
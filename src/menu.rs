@@ -0,0 +1,296 @@
+//! See [IUP Menus][1].
+//! [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/menus.html
+use iup_sys;
+use std::ptr;
+use std::result::Result;
+use std::ffi::CString;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use Element;
+use Handle;
+use element::WeakHandle;
+use dialog::DialogPos;
+use property::Property;
+use callback::Action;
+use image::{ImageElement, StockImage};
+
+/// A menu, shown as the menu bar of a dialog (MENU attribute) or as a popup menu.
+///
+/// See the [IUP Menu Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupmenu.html
+pub struct Menu(*mut iup_sys::Ihandle);
+
+impl Menu {
+    /// Creates a menu with the specified items, submenus and separators.
+    pub fn new<A>(elems: A) -> Menu where A: AsRef<[Handle]> {
+        let mut carray = slice_to_ih_array!(elems.as_ref());
+        unsafe { Menu::from_raw(iup_sys::IupMenuv(carray.as_mut_ptr())) }
+    }
+
+    /// Shows the menu as a popup at the given position, returning after the user selects an
+    /// item (whose callback was already called) or dismisses the menu.
+    ///
+    /// Popup menus are not destroyed with any dialog, so they must be destroyed by the
+    /// application once not needed anymore.
+    ///
+    /// # Panics
+    /// Panics if `x` is either `Bottom` or `Top` or if `y` is either `Left` or `Right`.
+    pub fn popup(&mut self, x: DialogPos, y: DialogPos) -> Result<Menu, Menu> {
+        match unsafe { iup_sys::IupPopup(self.raw(), x.to_raw_x(), y.to_raw_y()) } {
+            iup_sys::IUP_NOERROR => Ok(*self),
+            _ => Err(*self),
+        }
+    }
+}
+
+impl_widget_container!(Menu, "menu");
+impl ::callback::MapCb for Menu {}
+impl ::callback::UnmapCb for Menu {}
+
+/// An item of a menu.
+///
+/// See the [IUP Item Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupitem.html
+pub struct Item(*mut iup_sys::Ihandle);
+
+impl Item {
+    /// Creates an item with the specified text, which may have a mnemonic, e.g. "&Open".
+    pub fn with_title<S: Into<String>>(title: S) -> Item {
+        let ctitle = CString::new(title.into()).unwrap();
+        unsafe { Item::from_raw(iup_sys::IupItem(ctitle.as_ptr(), ptr::null_mut())) }
+    }
+
+    /// Makes the item toggle its check mark by itself when selected (AUTOTOGGLE attribute).
+    pub fn set_auto_toggle(&mut self, auto_toggle: bool) -> Item {
+        self.set_attrib("AUTOTOGGLE", if auto_toggle { "YES" } else { "NO" })
+    }
+
+    /// Shows or hides the check mark of the item.
+    pub fn set_checked(&mut self, checked: bool) -> Item {
+        self.set_attrib("VALUE", if checked { "ON" } else { "OFF" })
+    }
+
+    /// Checks whether the item shows a check mark.
+    pub fn is_checked(&self) -> bool {
+        self.attrib("VALUE").map_or(false, |value| value.eq_ignore_ascii_case("ON"))
+    }
+
+    /// Shows the check mark of the item while `property` is true, and toggles the property when
+    /// the item is selected.
+    ///
+    /// This sets the ACTION callback of the item, observe the property instead. The binding
+    /// ends when the item is destroyed.
+    pub fn bind_checked(&mut self, property: Property<bool>) -> Item {
+        self.set_auto_toggle(false);
+        self.set_checked(property.get());
+        let item = self.downgrade();
+        property.watch(move |&checked| match item.upgrade() {
+            Some(mut item) => { item.set_checked(checked); true },
+            None => false,
+        });
+        self.set_action(move |_| property.set(!property.get()))
+    }
+
+    /// Shows `image` next to the title (IMAGE attribute).
+    ///
+    /// The image is destroyed with the last item showing it, so it shouldn't be used by other
+    /// elements. Images shared with them are better named and given by `set_stock_image`.
+    pub fn set_image<I: ImageElement>(&mut self, image: I) -> Item {
+        set_owned_image(self, "IMAGE", Handle::from_raw(image.raw()));
+        self.clone()
+    }
+
+    /// Shows `image` instead of the check mark while the item is checked (IMPRESS attribute).
+    ///
+    /// The image is owned by the item, see `set_image`.
+    pub fn set_checked_image<I: ImageElement>(&mut self, image: I) -> Item {
+        set_owned_image(self, "IMPRESS", Handle::from_raw(image.raw()));
+        self.clone()
+    }
+
+    /// Shows a named image next to the title, such as an image of the IUP image library.
+    ///
+    /// The image is not destroyed with the item.
+    pub fn set_stock_image(&mut self, image: StockImage) -> Item {
+        release_owned_image(self, "IMAGE");
+        self.set_attrib("IMAGE", image.name())
+    }
+
+    /// Shows a named image instead of the check mark while the item is checked.
+    ///
+    /// The image is not destroyed with the item.
+    pub fn set_stock_checked_image(&mut self, image: StockImage) -> Item {
+        release_owned_image(self, "IMPRESS");
+        self.set_attrib("IMPRESS", image.name())
+    }
+}
+
+thread_local!(
+    // The images owned by the items, by item and attribute.
+    static ITEM_IMAGES: RefCell<HashMap<(usize, &'static str), WeakHandle<Handle>>> =
+        RefCell::new(HashMap::new())
+);
+
+/// Sets the image attribute `attrib` of `item` to `image`, owned from now on by the item.
+fn set_owned_image(item: &mut Item, attrib: &'static str, image: Handle) {
+    item.set_attrib_handle(attrib, image);
+    let previous = ITEM_IMAGES.with(|images| {
+        images.borrow_mut().insert((item.raw() as usize, attrib), image.downgrade())
+    });
+    if let Some(previous) = previous {
+        release_image(previous);
+    }
+}
+
+/// Forgets the image owned by `item` for `attrib`, destroying it if no other item shows it.
+fn release_owned_image(item: &Item, attrib: &'static str) {
+    let key = (item.raw() as usize, attrib);
+    let image = ITEM_IMAGES.with(|images| images.borrow_mut().remove(&key));
+    if let Some(image) = image {
+        release_image(image);
+    }
+}
+
+/// Destroys `image` if no item owns it anymore.
+fn release_image(image: WeakHandle<Handle>) {
+    let owned = ITEM_IMAGES.with(|images| images.borrow().values().any(|&other| other == image));
+    if owned {
+        return;
+    }
+    // The image may have been destroyed by the application, or by IUP on close.
+    if let Some(image) = image.upgrade() {
+        image.destroy();
+    }
+}
+
+/// Destroys the images owned by `ih` no other item shows, called when the element is destroyed.
+#[doc(hidden)]
+pub fn element_destroyed(ih: *mut iup_sys::Ihandle) {
+    let released: Vec<WeakHandle<Handle>> = ITEM_IMAGES.with(|images| {
+        let mut images = images.borrow_mut();
+        let keys: Vec<_> = images.keys().filter(|&&(item, _)| item == ih as usize).cloned()
+                                 .collect();
+        keys.iter().filter_map(|key| images.remove(key)).collect()
+    });
+    for image in released {
+        release_image(image);
+    }
+}
+
+impl_widget!(Item, "item");
+impl ::callback::MapCb for Item {}
+impl ::callback::UnmapCb for Item {}
+impl ::callback::HelpCb for Item {}
+
+/// Action generated when the item is selected.
+///
+/// `ActionReturn::Close` will be processed.
+impl ::callback::Action for Item {}
+
+/// A line separating groups of items in a menu.
+///
+/// See the [IUP Separator Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupseparator.html
+pub struct Separator(*mut iup_sys::Ihandle);
+
+impl Separator {
+    /// Creates a separator.
+    pub fn new() -> Separator {
+        unsafe { Separator::from_raw(iup_sys::IupSeparator()) }
+    }
+}
+
+impl_widget!(Separator, "separator");
+
+/// Items of which only the one standing for the value of a property is checked, like the radio
+/// buttons of a `Radio`.
+///
+/// Selecting an item sets the property to its value, and the check marks follow the property
+/// however it's changed. The items may be anywhere in a menu, mixed with other items.
+///
+/// ```ignore
+/// let view = Property::new(ViewMode::List);
+/// let group = RadioGroup::new(view.clone());
+/// let menu = Menu::new(elements![
+///     group.item("&List", ViewMode::List),
+///     group.item("&Icons", ViewMode::Icons),
+///     Separator::new(),
+///     Item::with_title("&Refresh"),
+/// ]);
+/// ```
+///
+/// For a menu made only of exclusive items, the RADIO attribute of the menu does the same
+/// without a property.
+pub struct RadioGroup<T> {
+    property: Property<T>,
+    items: Rc<RefCell<Vec<(WeakHandle<Item>, T)>>>,
+}
+
+impl<T: Clone + PartialEq + 'static> RadioGroup<T> {
+    /// Creates an empty group bound to `property`.
+    pub fn new(property: Property<T>) -> RadioGroup<T> {
+        let items: Rc<RefCell<Vec<(WeakHandle<Item>, T)>>> = Rc::new(RefCell::new(Vec::new()));
+        let observed = items.clone();
+        property.on_change(move |current| check_current(&mut observed.borrow_mut(), current));
+        RadioGroup { property: property, items: items }
+    }
+
+    /// Creates an item of the group standing for `value`.
+    pub fn item<S: Into<String>>(&self, title: S, value: T) -> Item {
+        self.add(Item::with_title(title), value)
+    }
+
+    /// Adds an item to the group, standing for `value`.
+    ///
+    /// This sets the ACTION callback of the item.
+    pub fn add(&self, item: Item, value: T) -> Item {
+        let mut item = item;
+        item.set_auto_toggle(false);
+        item.set_checked(self.property.get() == value);
+        self.items.borrow_mut().push((item.downgrade(), value.clone()));
+        let property = self.property.clone();
+        item.set_action(move |_| property.set(value.clone()))
+    }
+
+    /// Gets the items of the group not destroyed yet.
+    pub fn items(&self) -> Vec<Item> {
+        self.items.borrow().iter().filter_map(|&(ref item, _)| item.upgrade()).collect()
+    }
+
+    /// Gets the property the group is bound to.
+    pub fn property(&self) -> Property<T> {
+        self.property.clone()
+    }
+}
+
+/// Checks the items standing for `current` and unchecks the others.
+fn check_current<T: PartialEq>(items: &mut Vec<(WeakHandle<Item>, T)>, current: &T) {
+    items.retain(|&(ref item, _)| item.is_alive());
+    for &(ref item, ref value) in items.iter() {
+        if let Some(mut item) = item.upgrade() {
+            item.set_checked(value == current);
+        }
+    }
+}
+
+/// An item opening a nested menu.
+///
+/// See the [IUP Submenu Documentation][1].
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/elem/iupsubmenu.html
+pub struct Submenu(*mut iup_sys::Ihandle);
+
+impl Submenu {
+    /// Creates a submenu with the specified text showing `menu`.
+    pub fn new<S: Into<String>>(title: S, menu: Menu) -> Submenu {
+        let ctitle = CString::new(title.into()).unwrap();
+        unsafe { Submenu::from_raw(iup_sys::IupSubmenu(ctitle.as_ptr(), menu.raw())) }
+    }
+}
+
+impl_widget_container!(Submenu, "submenu");
+impl ::callback::MapCb for Submenu {}
+impl ::callback::UnmapCb for Submenu {}
+impl ::callback::HelpCb for Submenu {}
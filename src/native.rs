@@ -0,0 +1,169 @@
+//! Access to the native objects behind IUP elements.
+//!
+//! Those allow integrating IUP with libraries that need to know about the native window of an
+//! element, such as rendering libraries (e.g. to draw on a `Canvas` with wgpu) or to embed
+//! native components created outside IUP.
+//!
+//! The element must be mapped (see `Widget::map`) for its native objects to exist.
+//!
+//! With the `raw-window-handle` feature, `Dialog` and `Canvas` also implement
+//! `HasRawWindowHandle` and `HasRawDisplayHandle`. Those panic if the element is not mapped or
+//! the driver has no X11 window (e.g. GTK on Wayland), since the traits can't report failures.
+use libc::{c_void, c_ulong};
+#[cfg(all(unix, not(target_os = "macos")))]
+use libc::{c_char, c_int};
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::mem;
+
+use Element;
+use element;
+#[cfg(feature = "raw-window-handle")]
+use raw_window_handle::{HasRawWindowHandle, HasRawDisplayHandle, RawWindowHandle, RawDisplayHandle,
+                        Win32WindowHandle, WindowsDisplayHandle, XlibWindowHandle, XlibDisplayHandle};
+#[cfg(feature = "raw-window-handle")]
+use dialog::Dialog;
+#[cfg(feature = "raw-window-handle")]
+use control::Canvas;
+
+/// The native objects of a mapped element, depending on the IUP driver in use.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NativeHandle {
+    /// Windows driver.
+    Win32 {
+        /// The `HWND` of the element.
+        hwnd: *mut c_void,
+    },
+    /// GTK driver.
+    Gtk {
+        /// The `GtkWidget*` of the element.
+        widget: *mut c_void,
+        /// The X11 `Window` of the element, zero when GTK is not running on X11.
+        xwindow: c_ulong,
+        /// The X11 `Display*`, null when GTK is not running on X11.
+        xdisplay: *mut c_void,
+    },
+    /// Motif driver.
+    Motif {
+        /// The Xt `Widget` of the element.
+        widget: *mut c_void,
+        /// The X11 `Window` of the element.
+        xwindow: c_ulong,
+        /// The X11 `Display*`.
+        xdisplay: *mut c_void,
+    },
+}
+
+/// Gets the native objects of `elem`, or `None` if it is not mapped.
+pub fn native_handle<E: Element>(elem: &E) -> Option<NativeHandle> {
+    let mut elem = *elem;
+    let wid = elem.attrib_data("WID");
+    if wid.is_null() {
+        return None;
+    }
+    match element::global("DRIVER").as_ref().map(|s| &s[..]) {
+        Some("Win32") => Some(NativeHandle::Win32 { hwnd: wid }),
+        Some("GTK") => Some(NativeHandle::Gtk {
+            widget: wid,
+            xwindow: elem.attrib_data("XWINDOW") as c_ulong,
+            xdisplay: elem.attrib_data("XDISPLAY"),
+        }),
+        Some("Motif") => Some(NativeHandle::Motif {
+            widget: wid,
+            xwindow: elem.attrib_data("XWINDOW") as c_ulong,
+            xdisplay: elem.attrib_data("XDISPLAY"),
+        }),
+        _ => None,
+    }
+}
+
+/// Gets the scale from the units of the windowing system to device pixels, for the monitor
+/// showing `elem`, which must be mapped.
+///
+/// This is 1 except with GTK 3 on a scaled display, where IUP reports sizes such as DRAWSIZE
+/// before the scaling. Windows applications see real pixels when they are DPI aware.
+pub fn scale_factor<E: Element>(elem: &E) -> f64 {
+    match native_handle(elem) {
+        Some(NativeHandle::Gtk { widget, .. }) => gtk_scale_factor(widget),
+        _ => 1.0,
+    }
+}
+
+/// Asks GTK for the scale factor of `widget`, 1 with GTK 2 which has no scaling.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn gtk_scale_factor(widget: *mut c_void) -> f64 {
+    // Looked up at run time since the function is missing from GTK 2, which IUP may use.
+    let symbol = unsafe { dlsym(RTLD_DEFAULT, cstr!("gtk_widget_get_scale_factor")) };
+    if symbol.is_null() {
+        return 1.0;
+    }
+    let get_scale_factor: extern fn(*mut c_void) -> c_int = unsafe { mem::transmute(symbol) };
+    get_scale_factor(widget).max(1) as f64
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn gtk_scale_factor(_widget: *mut c_void) -> f64 {
+    1.0
+}
+
+#[cfg(all(unix, not(target_os = "macos"), any(target_os = "linux", target_os = "android")))]
+const RTLD_DEFAULT: *mut c_void = 0 as *mut c_void;
+#[cfg(all(unix, not(target_os = "macos"), not(any(target_os = "linux", target_os = "android"))))]
+const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+// Not yet bound by libc 0.1.
+#[cfg(all(unix, not(target_os = "macos")))]
+extern {
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+#[cfg(feature = "raw-window-handle")]
+fn mapped_handle<E: Element>(elem: &E) -> NativeHandle {
+    match native_handle(elem) {
+        Some(NativeHandle::Gtk { xwindow: 0, .. }) => panic!("GTK is not running on X11"),
+        Some(handle) => handle,
+        None => panic!("the element must be mapped to get its raw window handle"),
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+macro_rules! impl_raw_window_handle {
+    ($ty_path:path) => {
+        unsafe impl HasRawWindowHandle for $ty_path {
+            fn raw_window_handle(&self) -> RawWindowHandle {
+                match mapped_handle(self) {
+                    NativeHandle::Win32 { hwnd } => {
+                        let mut handle = Win32WindowHandle::empty();
+                        handle.hwnd = hwnd as *mut _;
+                        handle.hinstance = element::global_data("HINSTANCE") as *mut _;
+                        RawWindowHandle::Win32(handle)
+                    },
+                    NativeHandle::Gtk { xwindow, .. } | NativeHandle::Motif { xwindow, .. } => {
+                        let mut handle = XlibWindowHandle::empty();
+                        handle.window = xwindow;
+                        RawWindowHandle::Xlib(handle)
+                    },
+                }
+            }
+        }
+
+        unsafe impl HasRawDisplayHandle for $ty_path {
+            fn raw_display_handle(&self) -> RawDisplayHandle {
+                match mapped_handle(self) {
+                    NativeHandle::Win32 { .. } => RawDisplayHandle::Windows(WindowsDisplayHandle::empty()),
+                    NativeHandle::Gtk { xdisplay, .. } | NativeHandle::Motif { xdisplay, .. } => {
+                        let mut handle = XlibDisplayHandle::empty();
+                        handle.display = xdisplay as *mut _;
+                        handle.screen = element::global("XSCREEN")
+                                                .and_then(|s| s.parse().ok()).unwrap_or(0);
+                        RawDisplayHandle::Xlib(handle)
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl_raw_window_handle!(Dialog);
+#[cfg(feature = "raw-window-handle")]
+impl_raw_window_handle!(Canvas);
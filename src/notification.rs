@@ -0,0 +1,281 @@
+//! Native desktop notifications.
+//!
+//! On Windows the notification is a balloon over a tray icon, on Linux it's sent to the
+//! notification server through libnotify. When no native notification can be shown (other
+//! platforms, or no notification server running) it falls back to a toast, see `set_fallback`.
+//!
+//! ```ignore
+//! notification::set_fallback(Toasts::new(main_dialog));
+//! notification::send_with_action("Download finished", "report.pdf", "", move || {
+//!     main_dialog.show();
+//! }).unwrap();
+//! ```
+//!
+//! The functions must be called from the main loop thread (use `util::post` from other threads)
+//! and the activation callbacks are called there as well. On Linux the callbacks need the GTK
+//! driver, since libnotify delivers them through the GLib main loop.
+use std::cell::RefCell;
+
+use component::{Toasts, ToastLevel};
+
+thread_local!(static FALLBACK: RefCell<Option<Toasts>> = RefCell::new(None));
+
+/// Sets the toasts showing the notifications which can't be shown natively.
+///
+/// Without a fallback `send` fails in that case.
+pub fn set_fallback(toasts: Toasts) {
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = Some(toasts));
+}
+
+/// Shows a notification with `title` and `body`.
+///
+/// `icon` is an optional (empty for none) icon: on Windows the name of an IUP image (see
+/// `Element::add_handle_name`), on Linux an icon name of the desktop theme or the path of an
+/// image file.
+pub fn send<S1, S2, S3>(title: S1, body: S2, icon: S3) -> Result<(), String>
+        where S1: Into<String>, S2: Into<String>, S3: Into<String> {
+    send_with_action(title, body, icon, || ())
+}
+
+/// Shows a notification like `send`, calling `on_activate` when the user clicks it.
+///
+/// `on_activate` is never called when the notification is shown as a toast.
+pub fn send_with_action<S1, S2, S3, F>(title: S1, body: S2, icon: S3, on_activate: F)
+                                                            -> Result<(), String>
+        where S1: Into<String>, S2: Into<String>, S3: Into<String>, F: FnOnce() + 'static {
+    let (title, body, icon) = (title.into(), body.into(), icon.into());
+    match native::send(&title, &body, &icon, Box::new(on_activate)) {
+        Ok(()) => Ok(()),
+        Err(err) => FALLBACK.with(|fallback| match *fallback.borrow() {
+            Some(ref toasts) if toasts.show(ToastLevel::Info, format!("{}\n{}", title, body)) => {
+                Ok(())
+            },
+            _ => Err(err),
+        }),
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    use Element;
+    use element::{Widget, WeakHandle};
+    use dialog::{Dialog, TrayClickCb};
+    use callback::button::{MouseButton, MouseButtonState};
+    use timer;
+
+    /// How long the tray icon is kept after the balloon is shown.
+    const TRAY_TIMEOUT_MS: u64 = 10000;
+
+    struct Tray {
+        dialog: WeakHandle<Dialog>,
+        /// The activation callback of the last notification, with its id.
+        pending: Option<(u64, Box<FnOnce()>)>,
+        next_id: u64,
+    }
+
+    thread_local!(static TRAY: RefCell<Option<Tray>> = RefCell::new(None));
+
+    pub fn send(title: &str, body: &str, icon: &str, on_activate: Box<FnOnce()>)
+                                                            -> Result<(), String> {
+        let (mut dialog, id) = try!(TRAY.with(|tray| -> Result<_, String> {
+            let mut tray = tray.borrow_mut();
+            let dialog = match tray.as_ref().and_then(|tray| tray.dialog.upgrade()) {
+                Some(dialog) => dialog,
+                None => {
+                    let dialog = try!(tray_dialog());
+                    *tray = Some(Tray { dialog: dialog.downgrade(), pending: None, next_id: 1 });
+                    dialog
+                },
+            };
+            let tray = tray.as_mut().unwrap();
+            let id = tray.next_id;
+            tray.next_id += 1;
+            tray.pending = Some((id, on_activate));
+            Ok((dialog, id))
+        }));
+        dialog.set_attrib("TRAYIMAGE", icon);
+        dialog.set_attrib("TRAY", "YES");
+        // The balloon is shown when its tip is set, so the title goes first.
+        dialog.set_attrib("TRAYTIPBALLOONTITLE", title);
+        dialog.set_attrib("TRAYTIPBALLOONTITLEICON", "1");
+        dialog.set_attrib("TRAYTIP", body);
+        timer::delay(Duration::from_millis(TRAY_TIMEOUT_MS), move || expire(id));
+        Ok(())
+    }
+
+    /// Creates the hidden dialog owning the tray icon.
+    fn tray_dialog() -> Result<Dialog, String> {
+        let mut dialog = Dialog::new_empty().set_attrib("TRAYTIPBALLOON", "YES");
+        if dialog.map().is_err() {
+            dialog.destroy();
+            return Err("Failed to map the tray dialog".into());
+        }
+        // The balloon and the icon are reported the same way.
+        dialog.set_trayclick_cb(|(_, button, state, _)| {
+            if button == MouseButton::Button1 && state == MouseButtonState::Pressed {
+                let pending = TRAY.with(|tray| {
+                    tray.borrow_mut().as_mut().and_then(|tray| tray.pending.take())
+                });
+                if let Some((id, on_activate)) = pending {
+                    on_activate();
+                    hide(id);
+                }
+            }
+        });
+        Ok(dialog)
+    }
+
+    /// Forgets the notification `id` if it's still the last one, and hides its icon.
+    fn expire(id: u64) {
+        TRAY.with(|tray| {
+            if let Some(ref mut tray) = *tray.borrow_mut() {
+                if tray.pending.as_ref().map_or(false, |&(pending, _)| pending == id) {
+                    tray.pending = None;
+                }
+            }
+        });
+        hide(id);
+    }
+
+    /// Hides the tray icon unless a notification was sent after `id`.
+    fn hide(id: u64) {
+        TRAY.with(|tray| {
+            if let Some(ref mut tray) = *tray.borrow_mut() {
+                if let (true, Some(mut dialog)) = (tray.next_id == id + 1, tray.dialog.upgrade()) {
+                    dialog.set_attrib("TRAY", "NO");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod native {
+    use libc::{c_char, c_int, c_uint, c_ulong, c_void};
+    use std::ffi::{CStr, CString};
+    use std::path::Path;
+    use std::env;
+    use std::mem;
+    use std::ptr;
+
+    use i18n;
+
+    /// The part of `GError` read here.
+    #[repr(C)]
+    struct GError {
+        domain: c_uint,
+        code: c_int,
+        message: *mut c_char,
+    }
+
+    // Not yet bound by any crate.
+    #[link(name = "notify")]
+    extern {
+        fn notify_is_initted() -> c_int;
+        fn notify_init(app_name: *const c_char) -> c_int;
+        fn notify_notification_new(summary: *const c_char, body: *const c_char,
+                                   icon: *const c_char) -> *mut c_void;
+        fn notify_notification_add_action(notification: *mut c_void, action: *const c_char,
+                                          label: *const c_char,
+                                          callback: extern fn(*mut c_void, *mut c_char,
+                                                              *mut c_void),
+                                          user_data: *mut c_void,
+                                          free_func: extern fn(*mut c_void));
+        fn notify_notification_show(notification: *mut c_void, error: *mut *mut GError) -> c_int;
+    }
+
+    #[link(name = "gobject-2.0")]
+    extern {
+        fn g_object_unref(object: *mut c_void);
+        fn g_signal_connect_data(instance: *mut c_void, signal: *const c_char,
+                                 handler: *const c_void, data: *mut c_void,
+                                 destroy_data: *const c_void, flags: c_int) -> c_ulong;
+    }
+
+    #[link(name = "glib-2.0")]
+    extern {
+        fn g_error_free(error: *mut GError);
+    }
+
+    type Activation = Option<Box<FnOnce()>>;
+
+    pub fn send(title: &str, body: &str, icon: &str, on_activate: Box<FnOnce()>)
+                                                            -> Result<(), String> {
+        try!(init());
+        let ctitle = CString::new(title).unwrap();
+        let cbody = CString::new(body).unwrap();
+        let cicon = CString::new(icon).unwrap();
+        let clabel = CString::new(i18n::tr("IUPRUST_NOTIFICATION_OPEN", "Open")).unwrap();
+        unsafe {
+            let icon_ptr = if icon.is_empty() { ptr::null() } else { cicon.as_ptr() };
+            let notification = notify_notification_new(ctitle.as_ptr(), cbody.as_ptr(), icon_ptr);
+            if notification.is_null() {
+                return Err("Failed to create the notification".into());
+            }
+            // The "default" action is the one of a click on the notification itself.
+            let activation: Box<Activation> = Box::new(Some(on_activate));
+            notify_notification_add_action(notification, cstr!("default"), clabel.as_ptr(),
+                                           on_action, Box::into_raw(activation) as *mut c_void,
+                                           free_activation);
+            // The notification must live until closed to receive the action.
+            let on_closed = on_closed as extern fn(*mut c_void, *mut c_void);
+            g_signal_connect_data(notification, cstr!("closed"), on_closed as *const c_void,
+                                  ptr::null_mut(), ptr::null(), 0);
+
+            let mut error: *mut GError = ptr::null_mut();
+            if notify_notification_show(notification, &mut error) == 0 {
+                let message = if error.is_null() {
+                    "Failed to show the notification".to_string()
+                } else {
+                    let message = CStr::from_ptr((*error).message).to_string_lossy().into_owned();
+                    g_error_free(error);
+                    message
+                };
+                g_object_unref(notification);
+                return Err(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes libnotify once, with the name of the executable as the application name.
+    fn init() -> Result<(), String> {
+        if unsafe { notify_is_initted() } != 0 {
+            return Ok(());
+        }
+        let app_name = env::args().next().as_ref().and_then(|arg| Path::new(arg).file_stem())
+                                  .map_or("iup".to_string(), |name| name.to_string_lossy()
+                                                                        .into_owned());
+        let capp_name = CString::new(app_name).unwrap();
+        match unsafe { notify_init(capp_name.as_ptr()) } {
+            0 => Err("Failed to initialize libnotify".into()),
+            _ => Ok(()),
+        }
+    }
+
+    extern fn on_action(_notification: *mut c_void, _action: *mut c_char, data: *mut c_void) {
+        let activation = unsafe { &mut *(data as *mut Activation) };
+        if let Some(on_activate) = mem::replace(activation, None) {
+            on_activate();
+        }
+    }
+
+    extern fn free_activation(data: *mut c_void) {
+        drop(unsafe { Box::from_raw(data as *mut Activation) });
+    }
+
+    extern fn on_closed(notification: *mut c_void, _data: *mut c_void) {
+        unsafe { g_object_unref(notification) };
+    }
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+mod native {
+    pub fn send(_title: &str, _body: &str, _icon: &str, _on_activate: Box<FnOnce()>)
+                                                            -> Result<(), String> {
+        Err("Native notifications are not supported on this platform".into())
+    }
+}
@@ -0,0 +1,121 @@
+//! Observation of attribute changes.
+//!
+//! IUP does not notify when an attribute changes, so observed attributes are polled from a timer
+//! and the observers are called whenever the value differs from the last one seen. This catches
+//! changes made both by the user (e.g. typing in a text) and by code (e.g. `set_attrib`),
+//! with a delay of at most `POLL_INTERVAL` milliseconds.
+//!
+//! ```ignore
+//! let observer = observe::observe(text, "VALUE", |text, value| {
+//!     println!("{:?} is now {:?}", text, value);
+//! });
+//! // ...
+//! observe::unobserve(observer);
+//! ```
+use std::mem;
+use std::cell::{Cell, RefCell};
+
+use Element;
+use Handle;
+use element::WeakHandle;
+use timer::{self, Timer};
+use callback::ActionCb;
+
+/// How often, in milliseconds, the observed attributes are checked.
+pub const POLL_INTERVAL: u32 = 100;
+
+/// Identifies an observer to stop it with `unobserve`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ObserverId(usize);
+
+struct Observer {
+    id: usize,
+    handle: WeakHandle<Handle>,
+    name: String,
+    last: Option<String>,
+    cb: Box<FnMut(Option<String>)>,
+}
+
+thread_local!(static OBSERVERS: RefCell<Vec<Observer>> = RefCell::new(Vec::new()));
+thread_local!(static NEXT_ID: Cell<usize> = Cell::new(1));
+thread_local!(static POLLER: Cell<Option<WeakHandle<Timer>>> = Cell::new(None));
+// Observers removed while `check` is running, thus not in `OBSERVERS`.
+thread_local!(static REMOVED: RefCell<Vec<usize>> = RefCell::new(Vec::new()));
+
+/// Calls `cb` with the element and the new value every time the attribute `name` of `elem`
+/// changes.
+///
+/// The observer is removed automatically when the element is destroyed.
+pub fn observe<E, S, F>(elem: E, name: S, mut cb: F) -> ObserverId
+                    where E: Element + 'static, S: Into<String>, F: FnMut(E, Option<String>) + 'static {
+    let name = name.into();
+    let id = NEXT_ID.with(|next| { let id = next.get(); next.set(id + 1); id });
+    let observer = Observer {
+        id: id,
+        handle: Handle::from_raw(elem.raw()).downgrade(),
+        last: elem.attrib(&name[..]),
+        name: name,
+        cb: Box::new(move |value| cb(elem, value)),
+    };
+    OBSERVERS.with(|observers| observers.borrow_mut().push(observer));
+    poller().run();
+    ObserverId(id)
+}
+
+/// Stops an observer. Does nothing if it was already removed.
+pub fn unobserve(id: ObserverId) {
+    let removed = OBSERVERS.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        let pos = observers.iter().position(|observer| observer.id == id.0);
+        pos.map(|pos| observers.remove(pos))
+    });
+    if removed.is_none() {
+        REMOVED.with(|removed| removed.borrow_mut().push(id.0));
+    }
+}
+
+/// Checks all the observed attributes right away, calling the observers of those which changed.
+///
+/// Useful in tests, where the main loop (and thus the polling timer) does not run.
+pub fn check() {
+    // Taken out so observers are free to observe or unobserve.
+    let observers = OBSERVERS.with(|observers| mem::replace(&mut *observers.borrow_mut(), Vec::new()));
+    let mut remaining = Vec::with_capacity(observers.len());
+    for mut observer in observers.into_iter() {
+        let handle = match observer.handle.upgrade() {
+            Some(handle) => handle,
+            None => continue,
+        };
+        let value = handle.attrib(&observer.name[..]);
+        if value != observer.last {
+            observer.last = value.clone();
+            (observer.cb)(value);
+        }
+        remaining.push(observer);
+    }
+    OBSERVERS.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        // Observers removed by a callback in the meantime must not come back.
+        let removed = REMOVED.with(|removed| mem::replace(&mut *removed.borrow_mut(), Vec::new()));
+        remaining.retain(|observer| !removed.contains(&observer.id));
+        remaining.extend(observers.drain(..));
+        *observers = remaining;
+    });
+}
+
+/// Gets (or lazily creates) the timer which polls the observed attributes.
+fn poller() -> Timer {
+    match POLLER.with(|poller| poller.get()).and_then(|poller| poller.upgrade()) {
+        Some(timer) => timer,
+        None => {
+            let mut timer = timer::owned_timer().set_time(POLL_INTERVAL);
+            POLLER.with(|poller| poller.set(Some(timer.downgrade())));
+            timer.set_action_cb(|(mut timer,): (Timer,)| {
+                check();
+                if OBSERVERS.with(|observers| observers.borrow().is_empty()) {
+                    timer.stop();
+                }
+            })
+        },
+    }
+}
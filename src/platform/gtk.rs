@@ -0,0 +1,41 @@
+//! Features of the GTK driver.
+use libc::c_void;
+use std::env;
+
+use element::{self, Widget};
+
+/// Gets the version of GTK in use at runtime, e.g. "3.24.5".
+pub fn version() -> Option<String> {
+    element::global("GTKVERSION")
+}
+
+/// Gets the version of GTK IUP was compiled against.
+pub fn dev_version() -> Option<String> {
+    element::global("GTKDEVVERSION")
+}
+
+/// Selects the GTK theme used by the application, instead of the one of the desktop.
+///
+/// Must be called before IUP is initialized, since GTK reads the theme on its initialization.
+/// The theme may include a variant, e.g. "Adwaita:dark".
+pub fn set_theme<S: AsRef<str>>(theme: S) {
+    env::set_var("GTK_THEME", theme.as_ref());
+}
+
+/// Checks whether GTK is running on X11, thus X11 objects (e.g. `XWINDOW`) are available.
+pub fn is_x11() -> bool {
+    !element::global_data("XDISPLAY").is_null()
+}
+
+/// GTK specific methods of every widget.
+pub trait WidgetExt : Widget {
+    /// Gets the `GtkWidget*` of the widget, null if it is not mapped.
+    ///
+    /// Useful to apply styles (e.g. with a `GtkCssProvider`) to a single widget.
+    fn gtk_widget(&self) -> *mut c_void {
+        let mut elem = *self;
+        elem.attrib_data("WID")
+    }
+}
+
+impl<W: Widget> WidgetExt for W {}
@@ -0,0 +1,18 @@
+//! Driver specific features.
+//!
+//! Each driver module is only available when compiling for a platform its driver runs on, and
+//! exposes the attributes IUP supports only in that driver as typed methods, usually through
+//! extension traits implemented by the portable elements.
+//!
+//! ```ignore
+//! #[cfg(windows)]
+//! {
+//!     use iup::platform::windows::DialogExt;
+//!     dialog.set_toolbox(true);
+//! }
+//! ```
+
+#[cfg(windows)]
+pub mod windows;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod gtk;
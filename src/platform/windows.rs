@@ -0,0 +1,163 @@
+//! Features of the Windows driver.
+use libc::c_void;
+
+use Element;
+use element::{self, Widget};
+use dialog::Dialog;
+use control::Canvas;
+
+/// The state of the progress shown in the taskbar button, see `DialogExt::set_taskbar_progress`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TaskbarState {
+    /// The progress is going on, shown in green.
+    Normal,
+    /// The progress is paused, shown in yellow.
+    Paused,
+    /// The progress failed, shown in red.
+    Error,
+}
+
+impl TaskbarState {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            TaskbarState::Normal => "NORMAL",
+            TaskbarState::Paused => "PAUSED",
+            TaskbarState::Error => "ERROR",
+        }
+    }
+}
+
+/// Gets the `HINSTANCE` of the application.
+pub fn hinstance() -> *mut c_void {
+    element::global_data("HINSTANCE")
+}
+
+/// Windows specific methods of every widget.
+pub trait WidgetExt : Widget {
+    /// Gets the `HWND` of the widget, null if it is not mapped.
+    fn hwnd(&self) -> *mut c_void {
+        let mut elem = *self;
+        elem.attrib_data("HWND")
+    }
+}
+
+impl<W: Widget> WidgetExt for W {}
+
+#[repr(C)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[repr(C)]
+struct Rect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+#[repr(C)]
+struct CompositionForm {
+    style: u32,
+    current_pos: Point,
+    area: Rect,
+}
+
+/// `COMPOSITIONFORM` style placing the composition window at `current_pos`.
+const CFS_POINT: u32 = 0x0002;
+
+#[link(name = "imm32")]
+extern "system" {
+    fn ImmGetContext(hwnd: *mut c_void) -> *mut c_void;
+    fn ImmReleaseContext(hwnd: *mut c_void, himc: *mut c_void) -> i32;
+    fn ImmSetCompositionWindow(himc: *mut c_void, form: *const CompositionForm) -> i32;
+}
+
+/// Windows specific methods of the canvas.
+pub trait CanvasExt : WidgetExt {
+    /// Places the window of the input method (IME) composing text at (x, y), in pixels relative
+    /// to the canvas, usually at the caret drawn by the application.
+    ///
+    /// Canvases implementing text entry should call it whenever their caret moves, otherwise
+    /// the composition window is shown at the corner of the canvas. The composed characters are
+    /// received by the `KAnyCb` as if typed. Does nothing if the canvas is not mapped.
+    fn set_ime_position(&mut self, x: i32, y: i32) -> Self {
+        let hwnd = self.hwnd();
+        if !hwnd.is_null() {
+            let form = CompositionForm {
+                style: CFS_POINT,
+                current_pos: Point { x: x, y: y },
+                area: Rect { left: 0, top: 0, right: 0, bottom: 0 },
+            };
+            unsafe {
+                let himc = ImmGetContext(hwnd);
+                if !himc.is_null() {
+                    ImmSetCompositionWindow(himc, &form);
+                    ImmReleaseContext(hwnd, himc);
+                }
+            }
+        }
+        *self
+    }
+}
+
+impl CanvasExt for Canvas {}
+
+/// Windows specific methods of the dialog.
+pub trait DialogExt : Element {
+    /// Uses double buffering for the whole dialog, reducing flickering in complex layouts.
+    ///
+    /// Must be set before the dialog is mapped. Canvases inside the dialog won't be able to
+    /// draw with OpenGL or other drawing libraries that require their own buffer.
+    fn set_composited(&mut self, composited: bool) -> Self {
+        self.set_attrib("COMPOSITED", if composited { "YES" } else { "NO" })
+    }
+
+    /// Makes the dialog a tool window, with a smaller title bar and no taskbar button.
+    ///
+    /// Must be set before the dialog is mapped.
+    fn set_toolbox(&mut self, toolbox: bool) -> Self {
+        self.set_attrib("TOOLBOX", if toolbox { "YES" } else { "NO" })
+    }
+
+    /// Shows a help button (a question mark) in the title bar, which calls the `HelpCb` of the
+    /// clicked element.
+    ///
+    /// Must be set before the dialog is mapped, and requires MAXBOX=NO and MINBOX=NO.
+    fn set_help_button(&mut self, help_button: bool) -> Self {
+        self.set_attrib("HELPBUTTON", if help_button { "YES" } else { "NO" })
+    }
+
+    /// Places the dialog inside another application window, given as a `HWND`, as a child
+    /// control instead of a top level window.
+    ///
+    /// Must be set before the dialog is mapped.
+    fn set_control_parent(&mut self, parent: *mut c_void) -> Self {
+        self.set_attrib("CONTROL", "YES");
+        self.set_attrib_data("NATIVEPARENT", parent as *const c_void)
+    }
+
+    /// Shows the progress of a long task in the taskbar button of the dialog.
+    ///
+    /// The `progress` goes from `0.0` to `1.0`, or is `None` when unknown, then the progress is
+    /// shown as indeterminate (the `state` is ignored). The dialog must be mapped.
+    fn set_taskbar_progress(&mut self, progress: Option<f32>, state: TaskbarState) -> Self {
+        self.set_attrib("TASKBARPROGRESS", "YES");
+        match progress {
+            Some(progress) => {
+                let value = (progress.max(0.0).min(1.0) * 100.0).round() as u32;
+                self.set_attrib("TASKBARPROGRESSSTATE", state.as_str());
+                self.set_attrib("TASKBARPROGRESSVALUE", value.to_string())
+            },
+            None => self.set_attrib("TASKBARPROGRESSSTATE", "INDETERMINATE"),
+        }
+    }
+
+    /// Removes the progress from the taskbar button of the dialog.
+    fn clear_taskbar_progress(&mut self) -> Self {
+        self.set_attrib("TASKBARPROGRESSSTATE", "NOPROGRESS")
+    }
+}
+
+impl DialogExt for Dialog {}
@@ -15,6 +15,7 @@
 // Common Types
 pub use ::Orientation;
 pub use dialog::{Dialog, DialogPos};
+pub use control::{Alignment, BulletStyle};
 
 // Common Traits
 pub use element::{Element, Widget, Container, Node, ConvertXYToPos};
@@ -23,9 +24,16 @@ pub use dialog::DialogElement;
 
 // Callbacks
 pub use callback::{CallbackReturn, Action, ActionCb, DestroyCb};
+pub use callback::{KeyReturn, ActionReturn, RefuseReturn, EditReturn};
 pub use callback::{MapCb, UnmapCb, GetFocusCb, KillFocusCb, EnterWindowCb, LeaveWindowCb, HelpCb};
+pub use callback::KAnyCb;
 pub use callback::{CaretCb, SpinCb, ValueChangedCb, DropFilesCb};
 pub use callback::{CloseCb, MoveCb, ResizeCb};
-pub use callback::button::{ButtonCb, MotionCb};
-pub use control::{TextAction, ToggleAction, ListAction};
-pub use dialog::{CopyDataCb, MdiActivateCb, ShowCb, TrayClickCb};
+pub use callback::button::{ButtonCb, MotionCb, WheelCb};
+pub use control::{TextAction, ToggleAction, ListAction, ListEditCb, CanvasAction};
+pub use control::{SelectionCb, BranchOpenCb, BranchCloseCb, ExecuteLeafCb};
+pub use control::{MultiSelectionCb, MultiUnselectionCb, ShowRenameCb, RenameCb, ToggleValueCb};
+pub use layout::{TabChangePosCb, TabCloseCb};
+pub use dialog::{CopyDataCb, MdiActivateCb, ShowCb, TrayClickCb, CustomFrameActivateCb};
+#[cfg(feature = "tuio")]
+pub use touch::{TouchCb, MultiTouchCb};
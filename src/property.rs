@@ -0,0 +1,143 @@
+//! Values of the application state that the interface follows.
+//!
+//! A `Property` holds a value and calls its observers whenever it changes, so elements bound to
+//! it (e.g. a checked menu item, see `Item::bind_checked`) stay consistent with the state
+//! however it's changed.
+//!
+//! ```ignore
+//! let word_wrap = Property::new(false);
+//! Item::with_title("&Word Wrap").bind_checked(word_wrap.clone());
+//! word_wrap.on_change(move |&wrap| {
+//!     text.set_attrib("WORDWRAP", if wrap { "YES" } else { "NO" });
+//! });
+//! ```
+use std::rc::Rc;
+use std::cell::RefCell;
+
+struct Inner<T> {
+    value: T,
+    observers: Vec<Rc<RefCell<FnMut(&T) -> bool>>>,
+}
+
+/// A shared value calling its observers when changed.
+///
+/// Cloning gives another handle to the same value.
+pub struct Property<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> Clone for Property<T> {
+    fn clone(&self) -> Property<T> {
+        Property { inner: self.inner.clone() }
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> Property<T> {
+    /// Creates a property with an initial value.
+    pub fn new(value: T) -> Property<T> {
+        Property { inner: Rc::new(RefCell::new(Inner { value: value, observers: Vec::new() })) }
+    }
+
+    /// Gets the current value.
+    pub fn get(&self) -> T {
+        self.inner.borrow().value.clone()
+    }
+
+    /// Changes the value, calling the observers if it's different from the current one.
+    ///
+    /// Observers may set the value again, the other observers are then called with the newest
+    /// value.
+    pub fn set(&self, value: T) {
+        let observers = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.value == value {
+                return;
+            }
+            inner.value = value;
+            inner.observers.clone()
+        };
+        for observer in observers {
+            // An observer setting the value is not called again for it.
+            let keep = match observer.try_borrow_mut() {
+                Ok(mut observer) => (&mut *observer)(&self.get()),
+                Err(_) => true,
+            };
+            if !keep {
+                self.inner.borrow_mut().observers.retain(|other| !Rc::ptr_eq(other, &observer));
+            }
+        }
+    }
+
+    /// Calls `cb` with the new value every time the value changes.
+    pub fn on_change<F: FnMut(&T) + 'static>(&self, mut cb: F) {
+        self.watch(move |value| { cb(value); true });
+    }
+
+    /// Calls `cb` with the new value every time the value changes, until it returns false.
+    ///
+    /// Useful for observers tied to the life of an element.
+    pub fn watch<F: FnMut(&T) -> bool + 'static>(&self, cb: F) {
+        self.inner.borrow_mut().observers.push(Rc::new(RefCell::new(cb)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::Property;
+
+    /// Records the values an observer of `property` is called with.
+    fn record(property: &Property<i32>) -> Rc<RefCell<Vec<i32>>> {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let observed = seen.clone();
+        property.on_change(move |&value| observed.borrow_mut().push(value));
+        seen
+    }
+
+    #[test]
+    fn clones_share_the_value() {
+        let property = Property::new(1);
+        let other = property.clone();
+        other.set(2);
+        assert_eq!(property.get(), 2);
+    }
+
+    #[test]
+    fn notifies_only_changes() {
+        let property = Property::new(1);
+        let seen = record(&property);
+        property.set(1);
+        property.set(2);
+        property.set(2);
+        property.set(3);
+        assert_eq!(*seen.borrow(), vec![2, 3]);
+    }
+
+    #[test]
+    fn watch_stops_once_false_is_returned() {
+        let property = Property::new(0);
+        let calls = Rc::new(RefCell::new(0));
+        let counted = calls.clone();
+        property.watch(move |&value| {
+            *counted.borrow_mut() += 1;
+            value < 2
+        });
+        for value in 1..5 {
+            property.set(value);
+        }
+        assert_eq!(*calls.borrow(), 2);
+    }
+
+    #[test]
+    fn observers_see_the_newest_value() {
+        let property = Property::new(0);
+        let clamped = property.clone();
+        property.on_change(move |&value| if value > 10 { clamped.set(10) });
+        let seen = record(&property);
+        property.set(50);
+        assert_eq!(property.get(), 10);
+        // Called for the nested set, then with the newest value for the outer one.
+        assert_eq!(*seen.borrow(), vec![10, 10]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Hot-reload of layout files during development.
+//!
+//! With the `dev` feature a layout file (LED or a JSON `LayoutSpec`) can be watched, and the
+//! contents of a dialog rebuilt every time the file is saved, keeping the dialog size and
+//! position. Since the rebuilt elements are new, their callbacks must be set again by the
+//! `bind` closure, which is called after every build. The input of the named widgets is kept,
+//! see the `state` module:
+//!
+//! ```ignore
+//! let dialog = Dialog::new_empty().set_attrib("TITLE", "Preview");
+//! let _reload = HotReload::watch("ui/main.led", LayoutFormat::Led("main_box".into()), dialog,
+//!     |root| {
+//!         Button::from_handle(Handle::from_named("ok").unwrap()).unwrap()
+//!             .set_action(|_| println!("Ok!"));
+//!     }).unwrap();
+//! dialog.show()
+//! ```
+//!
+//! The file is watched from another thread, so posting is enabled, see `util::post`.
+use iup_sys;
+use notify::{self, Watcher, RecommendedWatcher, RecursiveMode};
+use serde_json;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::time::Duration;
+
+use Element;
+use Handle;
+use led;
+use state;
+use timer;
+use util::{self, LogLevel};
+use dialog::Dialog;
+use element::{Container, Node, Widget};
+use spec::LayoutSpec;
+
+/// Time to wait for the file to settle, editors often write it in several steps.
+const SETTLE_TIME: u64 = 100;
+
+/// The format of a watched layout file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutFormat {
+    /// A LED file, whose root is the element with the given name.
+    Led(String),
+    /// A `LayoutSpec` serialized as JSON.
+    Json,
+}
+
+struct Reloader {
+    format: LayoutFormat,
+    dialog: Dialog,
+    bind: Box<FnMut(Handle)>,
+}
+
+thread_local!(static RELOADERS: RefCell<HashMap<PathBuf, Reloader>> = RefCell::new(HashMap::new()));
+
+/// Watches a layout file and rebuilds the contents of a dialog when it changes.
+///
+/// The dialog stops being updated when this object is dropped.
+pub struct HotReload {
+    path: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl HotReload {
+    /// Builds the contents of `dialog` from the layout at `path` and rebuilds them each time the
+    /// file changes, calling `bind` with the root of the new contents.
+    ///
+    /// The dialog must have no children or a single one, which gets replaced. Build errors
+    /// after the first are written to the system log and keep the previous contents.
+    ///
+    /// Must be called from the main loop thread.
+    pub fn watch<P, F>(path: P, format: LayoutFormat, dialog: Dialog, bind: F)
+                                    -> Result<HotReload, String>
+                                    where P: AsRef<Path>, F: FnMut(Handle) + 'static {
+        let path = try!(path.as_ref().canonicalize().map_err(|e| e.to_string()));
+        RELOADERS.with(|reloaders| reloaders.borrow_mut().insert(path.clone(), Reloader {
+            format: format,
+            dialog: dialog,
+            bind: Box::new(bind),
+        }));
+        if let Err(err) = reload(&path) {
+            RELOADERS.with(|reloaders| reloaders.borrow_mut().remove(&path));
+            return Err(err);
+        }
+
+        util::enable_posting();
+        let changed = path.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                // Only the watched file, the other files of the directory are also notified.
+                let name = changed.file_name();
+                let is_file = event.paths.iter().any(|path| path.file_name() == name);
+                if is_file && (event.kind.is_modify() || event.kind.is_create()) {
+                    let path = changed.clone();
+                    let _ = util::post(move || schedule(path));
+                }
+            }
+        });
+        let mut watcher = try!(watcher.map_err(|e| e.to_string()));
+        // Editors may replace the file, so the directory is watched instead of the file itself.
+        let dir = path.parent().unwrap_or(&path).to_path_buf();
+        if let Err(err) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            RELOADERS.with(|reloaders| reloaders.borrow_mut().remove(&path));
+            return Err(err.to_string());
+        }
+        Ok(HotReload { path: path, _watcher: watcher })
+    }
+
+    /// Rebuilds the contents of the dialog now.
+    pub fn reload(&self) -> Result<(), String> {
+        reload(&self.path)
+    }
+}
+
+impl Drop for HotReload {
+    fn drop(&mut self) {
+        RELOADERS.with(|reloaders| reloaders.borrow_mut().remove(&self.path));
+    }
+}
+
+/// Reloads the file at `path` once it settles, in the main loop thread.
+fn schedule(path: PathBuf) {
+    let key = format!("_IUPRUST_RELOAD_{}", path.display());
+    timer::debounce(&key, Duration::from_millis(SETTLE_TIME), move || {
+        if let Err(err) = reload(&path) {
+            util::log(LogLevel::Error, format!("Failed to reload {}: {}", path.display(), err));
+        }
+    });
+}
+
+/// Rebuilds the dialog contents from the file at `path`, if it's still watched.
+fn reload(path: &Path) -> Result<(), String> {
+    // Taken out so `bind` is free to watch or drop reloaders.
+    let reloader = RELOADERS.with(|reloaders| reloaders.borrow_mut().remove(path));
+    let mut reloader = match reloader {
+        Some(reloader) => reloader,
+        None => return Ok(()),
+    };
+    let state = state::capture(reloader.dialog);
+    let result = build(path, &reloader.format).and_then(|root| {
+        replace_contents(reloader.dialog, root).map(|_| (reloader.bind)(root))
+    });
+    if result.is_ok() {
+        state.restore(reloader.dialog);
+    }
+    RELOADERS.with(|reloaders| {
+        reloaders.borrow_mut().entry(path.to_path_buf()).or_insert(reloader);
+    });
+    result
+}
+
+/// Creates the elements described by the file at `path`.
+fn build(path: &Path, format: &LayoutFormat) -> Result<Handle, String> {
+    match *format {
+        LayoutFormat::Led(ref name) => {
+            try!(led::load(path));
+            Handle::from_named(&name[..])
+                   .ok_or_else(|| format!("The layout does not define '{}'", name))
+        },
+        LayoutFormat::Json => {
+            let mut json = String::new();
+            try!(File::open(path).and_then(|mut f| f.read_to_string(&mut json))
+                                 .map_err(|e| e.to_string()));
+            let spec: LayoutSpec = try!(serde_json::from_str(&json).map_err(|e| e.to_string()));
+            spec.create()
+        },
+    }
+}
+
+/// Replaces the children of `dialog` by `root`, keeping the dialog size.
+fn replace_contents(mut dialog: Dialog, mut root: Handle) -> Result<(), String> {
+    let size = dialog.attrib("RASTERSIZE");
+    // Dialogs accept a single child, so the old one is detached first and restored on failure.
+    let mut old_children = dialog.children();
+    for child in old_children.iter_mut() {
+        child.detach();
+    }
+    let appended = !unsafe { iup_sys::IupAppend(dialog.raw(), root.raw()) }.is_null();
+    let mapped = appended && (dialog.attrib("WID").is_none() || root.map().is_ok());
+    if !mapped {
+        if appended {
+            root.detach();
+        }
+        root.destroy();
+        for child in old_children.iter_mut() {
+            unsafe { iup_sys::IupAppend(dialog.raw(), child.raw()) };
+            if dialog.attrib("WID").is_some() {
+                let _ = child.map();
+            }
+        }
+        dialog.refresh();
+        return Err("The dialog does not accept the new contents".into());
+    }
+    for child in old_children {
+        child.destroy();
+    }
+    if let Some(size) = size {
+        dialog.set_attrib("RASTERSIZE", size);
+    }
+    dialog.refresh();
+    Ok(())
+}
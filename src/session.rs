@@ -0,0 +1,373 @@
+//! Saving the workspace when the application exits and restoring it at startup.
+//!
+//! A `Session` holds the open documents, the geometry of the windows, the positions of split
+//! bars and the selected tabs. It's captured from the elements before exiting and applied back
+//! to the new elements at startup, by name:
+//!
+//! ```ignore
+//! // On exit.
+//! let mut session = Session::new();
+//! session.capture_window("main", main_dialog);
+//! session.capture_tabs("sidebar", sidebar_tabs);
+//! session.capture_documents(&documents, |_, elem| editors.path_of(elem));
+//! session.save_to_config(*config, "Session");
+//! config.save().ok();
+//!
+//! // At startup.
+//! let session = Session::load_from_config(*config, "Session");
+//! session.restore_documents(&documents, |path| editors.open(path));
+//! session.restore_tabs("sidebar", sidebar_tabs);
+//! session.restore_window("main", main_dialog);
+//! ```
+//!
+//! With the `serde` feature a session can also be (de)serialized in any format supported by
+//! serde, such as JSON.
+use std::collections::BTreeMap;
+
+use Element;
+use Handle;
+use element::Widget;
+use config::Config;
+use dialog::{Dialog, Rect};
+use layout::FlatTabs;
+use component::DocumentArea;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// The position, size and placement of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool,
+}
+
+/// The state of the workspace, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Session {
+    /// The open documents as described by the application (e.g. their paths), in tab order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub documents: Vec<String>,
+    /// The index of the document shown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub current_document: Option<usize>,
+    /// The windows by name.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub windows: BTreeMap<String, WindowState>,
+    /// The VALUE of split bars by name.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub splits: BTreeMap<String, String>,
+    /// The position of the selected tab of tabs containers by name.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tabs: BTreeMap<String, usize>,
+    /// Any other values of the application by name.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub values: BTreeMap<String, String>,
+}
+
+impl Session {
+    /// Creates an empty session.
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Remembers the geometry of `dialog` under `name`.
+    ///
+    /// The geometry before maximizing isn't known, so a maximized dialog keeps the geometry
+    /// captured before, if any.
+    pub fn capture_window<S: Into<String>>(&mut self, name: S, dialog: Dialog) {
+        let state = self.windows.entry(name.into()).or_insert_with(WindowState::default);
+        if dialog.is_maximized() {
+            state.maximized = true;
+        } else {
+            let rect = dialog.geometry();
+            *state = WindowState {
+                x: rect.x, y: rect.y, width: rect.width, height: rect.height, maximized: false,
+            };
+        }
+    }
+
+    /// Shows `dialog` with the geometry remembered under `name`, maximized if it was, returning
+    /// false if there is none (the dialog is left hidden then, to be shown as usual).
+    pub fn restore_window(&self, name: &str, mut dialog: Dialog) -> bool {
+        let state = match self.windows.get(name) {
+            Some(state) => *state,
+            None => return false,
+        };
+        if state.width > 0 && state.height > 0 {
+            dialog.set_geometry(Rect {
+                x: state.x, y: state.y, width: state.width, height: state.height,
+            });
+        }
+        // Setting the geometry shows the dialog already, but there may be no geometry.
+        if state.maximized {
+            dialog.maximize();
+        } else {
+            dialog.show().ok();
+        }
+        true
+    }
+
+    /// Remembers the position of a split bar, its VALUE, under `name`.
+    pub fn capture_split<S: Into<String>, E: Element>(&mut self, name: S, split: E) {
+        if let Some(value) = split.attrib("VALUE") {
+            self.splits.insert(name.into(), value);
+        }
+    }
+
+    /// Moves a split bar to the position remembered under `name`, returning whether there was
+    /// one.
+    pub fn restore_split<E: Element>(&self, name: &str, mut split: E) -> bool {
+        match self.splits.get(name) {
+            Some(value) => { split.set_attrib("VALUE", &value[..]); true },
+            None => false,
+        }
+    }
+
+    /// Remembers the selected tab of `tabs` under `name`.
+    pub fn capture_tabs<S: Into<String>>(&mut self, name: S, tabs: FlatTabs) {
+        if let Some(pos) = tabs.value_pos() {
+            self.tabs.insert(name.into(), pos);
+        }
+    }
+
+    /// Selects the tab remembered under `name`, returning whether it still exists.
+    pub fn restore_tabs(&self, name: &str, mut tabs: FlatTabs) -> bool {
+        match self.tabs.get(name) {
+            Some(&pos) if pos < tabs.tab_count() => { tabs.set_value_pos(pos); true },
+            _ => false,
+        }
+    }
+
+    /// Remembers the documents of `area`, as described by `describe` (e.g. their paths).
+    ///
+    /// `describe` is given the index and the element of each document, and returns `None` for
+    /// the documents which can't be reopened, such as unsaved new documents.
+    pub fn capture_documents<F>(&mut self, area: &DocumentArea, mut describe: F)
+                                            where F: FnMut(usize, Handle) -> Option<String> {
+        let current = area.current();
+        self.documents.clear();
+        self.current_document = None;
+        for index in 0..area.document_count() {
+            let description = area.document(index).and_then(|elem| describe(index, elem));
+            if let Some(description) = description {
+                if current == Some(index) {
+                    self.current_document = Some(self.documents.len());
+                }
+                self.documents.push(description);
+            }
+        }
+    }
+
+    /// Reopens the remembered documents in `area` with `open`, and shows the one shown before.
+    ///
+    /// `open` is given the description of each document, and returns its element and title, or
+    /// `None` if it can't be opened anymore (e.g. the file was deleted).
+    pub fn restore_documents<F>(&self, area: &DocumentArea, mut open: F)
+                                        where F: FnMut(&str) -> Option<(Handle, String)> {
+        let mut current = None;
+        for (i, description) in self.documents.iter().enumerate() {
+            if let Some((elem, title)) = open(description) {
+                let index = area.add_document(elem, title);
+                if self.current_document == Some(i) {
+                    current = Some(index);
+                }
+            }
+        }
+        if let Some(index) = current {
+            area.set_current(index);
+        }
+    }
+
+    /// Stores the session in the `group` of `config`, replacing the session stored there.
+    ///
+    /// This does not save the file, see `Config::save`.
+    pub fn save_to_config<S: Into<String>>(&self, config: Config, group: S) {
+        let mut config = config;
+        self.save_to(&mut config, &group.into());
+    }
+
+    /// Loads the session stored in the `group` of `config`, empty if there is none.
+    pub fn load_from_config(config: Config, group: &str) -> Session {
+        Session::load_from(&config, group)
+    }
+
+    fn save_to<V: Variables>(&self, vars: &mut V, group: &str) {
+        let documents = self.documents.iter().map(|document| (String::new(), document.clone()));
+        save_list(vars, group, "Document", documents);
+        match self.current_document {
+            Some(index) => vars.set(group, "CurrentDocument", index.to_string()),
+            None => vars.remove(group, "CurrentDocument"),
+        };
+        let windows = self.windows.iter().map(|(name, state)| {
+            let value = format!("{} {} {} {} {}", state.x, state.y, state.width, state.height,
+                                if state.maximized { 1 } else { 0 });
+            (name.clone(), value)
+        });
+        save_list(vars, group, "Window", windows);
+        let splits = self.splits.iter().map(|(name, value)| (name.clone(), value.clone()));
+        save_list(vars, group, "Split", splits);
+        let tabs = self.tabs.iter().map(|(name, pos)| (name.clone(), pos.to_string()));
+        save_list(vars, group, "Tabs", tabs);
+        let values = self.values.iter().map(|(name, value)| (name.clone(), value.clone()));
+        save_list(vars, group, "Value", values);
+    }
+
+    fn load_from<V: Variables>(vars: &V, group: &str) -> Session {
+        let documents = load_list(vars, group, "Document").into_iter()
+                                                          .map(|(_, document)| document)
+                                                          .collect();
+        let windows = load_list(vars, group, "Window").into_iter().filter_map(|(name, value)| {
+            let parts: Vec<i32> = value.split_whitespace().filter_map(|part| part.parse().ok())
+                                                          .collect();
+            if parts.len() != 5 {
+                return None;
+            }
+            let state = WindowState {
+                x: parts[0], y: parts[1], width: parts[2], height: parts[3],
+                maximized: parts[4] != 0,
+            };
+            Some((name, state))
+        }).collect();
+        let tabs = load_list(vars, group, "Tabs").into_iter().filter_map(|(name, value)| {
+            value.parse().ok().map(|pos| (name, pos))
+        }).collect();
+        Session {
+            documents: documents,
+            current_document: vars.get(group, "CurrentDocument")
+                                  .and_then(|index| index.parse().ok()),
+            windows: windows,
+            splits: load_list(vars, group, "Split").into_iter().collect(),
+            tabs: tabs,
+            values: load_list(vars, group, "Value").into_iter().collect(),
+        }
+    }
+}
+
+/// The variables of a configuration, where sessions are stored.
+trait Variables {
+    fn get(&self, group: &str, key: &str) -> Option<String>;
+    fn set(&mut self, group: &str, key: &str, value: String);
+    fn remove(&mut self, group: &str, key: &str);
+}
+
+impl Variables for Config {
+    fn get(&self, group: &str, key: &str) -> Option<String> {
+        self.variable(group, key)
+    }
+
+    fn set(&mut self, group: &str, key: &str, value: String) {
+        self.set_variable(group, key, value);
+    }
+
+    fn remove(&mut self, group: &str, key: &str) {
+        self.remove_variable(group, key);
+    }
+}
+
+/// Stores named values as the variables "{prefix}Count", "{prefix}Name{i}" and "{prefix}{i}",
+/// from 1, removing the ones left from a longer list.
+fn save_list<V, I>(vars: &mut V, group: &str, prefix: &str, items: I)
+                                where V: Variables, I: Iterator<Item=(String, String)> {
+    let previous = count(vars, group, prefix);
+    let mut len = 0;
+    for (name, value) in items {
+        len += 1;
+        if !name.is_empty() {
+            vars.set(group, &format!("{}Name{}", prefix, len), name);
+        }
+        vars.set(group, &format!("{}{}", prefix, len), value);
+    }
+    for i in len + 1..previous + 1 {
+        vars.remove(group, &format!("{}Name{}", prefix, i));
+        vars.remove(group, &format!("{}{}", prefix, i));
+    }
+    vars.set(group, &format!("{}Count", prefix), len.to_string());
+}
+
+/// Loads the named values stored by `save_list`, with empty names if they have none.
+fn load_list<V: Variables>(vars: &V, group: &str, prefix: &str) -> Vec<(String, String)> {
+    (1..count(vars, group, prefix) + 1).filter_map(|i| {
+        let name = vars.get(group, &format!("{}Name{}", prefix, i)).unwrap_or_default();
+        vars.get(group, &format!("{}{}", prefix, i)).map(|value| (name, value))
+    }).collect()
+}
+
+fn count<V: Variables>(vars: &V, group: &str, prefix: &str) -> usize {
+    vars.get(group, &format!("{}Count", prefix)).and_then(|count| count.parse().ok())
+                                                .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::{Session, WindowState, Variables};
+
+    impl Variables for BTreeMap<(String, String), String> {
+        fn get(&self, group: &str, key: &str) -> Option<String> {
+            self.get(&(group.to_string(), key.to_string())).cloned()
+        }
+
+        fn set(&mut self, group: &str, key: &str, value: String) {
+            self.insert((group.to_string(), key.to_string()), value);
+        }
+
+        fn remove(&mut self, group: &str, key: &str) {
+            BTreeMap::remove(self, &(group.to_string(), key.to_string()));
+        }
+    }
+
+    fn session() -> Session {
+        let mut session = Session::new();
+        session.documents = vec!["/tmp/a.txt".into(), "/tmp/b c.txt".into()];
+        session.current_document = Some(1);
+        session.windows.insert("main".into(), WindowState {
+            x: -8, y: 20, width: 800, height: 600, maximized: true,
+        });
+        session.splits.insert("sidebar".into(), "300".into());
+        session.tabs.insert("panels".into(), 2);
+        session.values.insert("theme".into(), "dark".into());
+        session
+    }
+
+    #[test]
+    fn round_trips_through_the_variables() {
+        let mut vars = BTreeMap::new();
+        session().save_to(&mut vars, "Session");
+        assert_eq!(Session::load_from(&vars, "Session"), session());
+        assert_eq!(Session::load_from(&vars, "Other"), Session::new());
+    }
+
+    #[test]
+    fn saving_a_shorter_session_removes_the_extra_values() {
+        let mut vars = BTreeMap::new();
+        session().save_to(&mut vars, "Session");
+        let mut shorter = session();
+        shorter.documents.truncate(1);
+        shorter.current_document = None;
+        shorter.values.clear();
+        shorter.save_to(&mut vars, "Session");
+        assert_eq!(Session::load_from(&vars, "Session"), shorter);
+        assert!(!vars.keys().any(|&(_, ref key)| key == "Document2" || key == "ValueName1"));
+    }
+
+    #[test]
+    fn malformed_windows_are_skipped() {
+        let mut vars = BTreeMap::new();
+        session().save_to(&mut vars, "Session");
+        vars.insert(("Session".into(), "Window1".into()), "1 2 three 4 0".into());
+        assert!(Session::load_from(&vars, "Session").windows.is_empty());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn round_trips_through_serde() {
+        let json = ::serde_json::to_string(&session()).unwrap();
+        assert_eq!(::serde_json::from_str::<Session>(&json).unwrap(), session());
+        assert_eq!(::serde_json::from_str::<Session>("{}").unwrap(), Session::new());
+    }
+}
@@ -0,0 +1,117 @@
+//! Layouts described as data.
+//!
+//! A `LayoutSpec` describes an element tree by the class names, attributes and children of its
+//! elements. It can be instantiated into real elements with `LayoutSpec::create`, and an
+//! existing tree can be exported back with `LayoutSpec::from_element`, e.g. for design tools.
+//!
+//! With the `serde` feature the spec can be (de)serialized in any format supported by serde:
+//!
+//! ```ignore
+//! let spec: LayoutSpec = serde_json::from_str(r#"{
+//!     "class": "dialog",
+//!     "attribs": { "TITLE": "Hello" },
+//!     "children": [
+//!         { "class": "vbox", "children": [
+//!             { "class": "label", "attribs": { "TITLE": "Name:" } },
+//!             { "class": "text", "attribs": { "NAME": "name", "EXPAND": "HORIZONTAL" } }
+//!         ] }
+//!     ]
+//! }"#).unwrap();
+//! let dialog = Dialog::from_handle(spec.create().unwrap()).unwrap();
+//! ```
+use iup_sys;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::result::Result;
+
+use Element;
+use Handle;
+use element::Container;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Description of an element and its children.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayoutSpec {
+    /// The IUP class name, e.g. "button" or "vbox".
+    pub class: String,
+    /// Attributes set on the element after it's created.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub attribs: BTreeMap<String, String>,
+    /// Children, for containers.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<LayoutSpec>,
+}
+
+impl LayoutSpec {
+    /// Creates a spec of an element of `class` with no attributes or children.
+    pub fn new<S: Into<String>>(class: S) -> LayoutSpec {
+        LayoutSpec { class: class.into(), attribs: BTreeMap::new(), children: Vec::new() }
+    }
+
+    /// Adds an attribute to the spec.
+    pub fn with_attrib<S1, S2>(mut self, name: S1, value: S2) -> LayoutSpec
+                                            where S1: Into<String>, S2: Into<String> {
+        self.attribs.insert(name.into(), value.into());
+        self
+    }
+
+    /// Adds a child to the spec.
+    pub fn with_child(mut self, child: LayoutSpec) -> LayoutSpec {
+        self.children.push(child);
+        self
+    }
+
+    /// Creates the elements described by the spec.
+    ///
+    /// Fails on unknown classes or children the class does not accept, in which case the
+    /// elements created so far are destroyed.
+    pub fn create(&self) -> Result<Handle, String> {
+        let cclass = CString::new(&self.class[..]).unwrap();
+        let ih = unsafe { iup_sys::IupCreate(cclass.as_ptr()) };
+        if ih.is_null() {
+            return Err(format!("Unknown class '{}'", self.class));
+        }
+        let mut handle = Handle::from_raw(ih);
+        for (name, value) in self.attribs.iter() {
+            handle.set_attrib(&name[..], &value[..]);
+        }
+        for child in self.children.iter() {
+            let result = child.create().and_then(|child_handle| {
+                match unsafe { iup_sys::IupAppend(ih, child_handle.raw()) } {
+                    parent if parent.is_null() => {
+                        child_handle.destroy();
+                        Err(format!("'{}' does not accept the child '{}'", self.class, child.class))
+                    },
+                    _ => Ok(()),
+                }
+            });
+            if let Err(err) = result {
+                handle.destroy();
+                return Err(err);
+            }
+        }
+        Ok(handle)
+    }
+
+    /// Describes an existing element and its children.
+    ///
+    /// Only the attributes set on the elements are included, not inherited or default ones.
+    /// Internal attributes (starting with an underscore) and non-textual attributes are skipped.
+    pub fn from_element<E: Element>(elem: E) -> LayoutSpec {
+        let handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+        let class = unsafe { handle.classname() }.to_string_lossy().into_owned();
+        let mut attribs = BTreeMap::new();
+        for (name, value) in handle.attributes() {
+            if !name.starts_with('_') && !value.is_empty() {
+                attribs.insert(name, value);
+            }
+        }
+        LayoutSpec {
+            class: class,
+            attribs: attribs,
+            children: handle.children().into_iter().map(LayoutSpec::from_element).collect(),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Preservation of the user input across rebuilds of an element tree.
+//!
+//! `capture` collects the state of the named widgets of a tree (those with a NAME attribute,
+//! as found by `IupGetDialogChild`), and `WidgetState::restore` sets it back on a new tree
+//! using the same names:
+//!
+//! ```ignore
+//! let state = state::capture(dialog);
+//! rebuild_contents(dialog);
+//! state.restore(dialog);
+//! ```
+//!
+//! The state of a widget is its VALUE and CARETPOS, plus the user state set with
+//! `set_user_state`, which is free form text for any value that is not an attribute.
+use std::collections::BTreeMap;
+
+use Element;
+use Handle;
+use element::Container;
+
+/// Attributes holding the user input, in the order they are restored (setting VALUE moves the
+/// caret).
+const STATE_ATTRIBS: &'static [&'static str] = &["VALUE", "CARETPOS"];
+/// Prefix of the attributes holding the user state. Not "_IUP", IupGetAllAttributes skips those.
+const USER_STATE_PREFIX: &'static str = "IUPRUST_STATE_";
+
+/// The state of the named widgets of a tree, by widget name and then attribute name.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WidgetState {
+    widgets: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl WidgetState {
+    /// Creates an empty state.
+    pub fn new() -> WidgetState {
+        WidgetState::default()
+    }
+
+    /// Gets the captured value of an attribute of the widget named `name`.
+    pub fn get(&self, name: &str, attrib: &str) -> Option<&str> {
+        self.widgets.get(name).and_then(|attribs| attribs.get(attrib)).map(|v| &v[..])
+    }
+
+    /// Sets the value an attribute of the widget named `name` gets when restored.
+    pub fn insert<S1, S2, S3>(&mut self, name: S1, attrib: S2, value: S3)
+                                where S1: Into<String>, S2: Into<String>, S3: Into<String> {
+        self.widgets.entry(name.into()).or_insert_with(BTreeMap::new)
+                    .insert(attrib.into(), value.into());
+    }
+
+    /// Gets the names of the widgets with some state.
+    pub fn names(&self) -> Vec<&str> {
+        self.widgets.keys().map(|name| &name[..]).collect()
+    }
+
+    /// Checks whether no state was captured.
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Sets the state back on the widgets of the tree of `root` with the same names.
+    ///
+    /// Names not found in the tree are ignored. VALUE is set before the other attributes.
+    pub fn restore<E: Element>(&self, root: E) {
+        let handle = unsafe { Handle::from_raw_unchecked(root.raw()) };
+        let mut named = BTreeMap::new();
+        collect_named(handle, &mut named);
+        for (name, attribs) in self.widgets.iter() {
+            if let Some(widget) = named.get_mut(name) {
+                let input = STATE_ATTRIBS.iter().filter_map(|attrib| {
+                    attribs.get(*attrib).map(|value| (*attrib, value))
+                });
+                let user = attribs.iter().filter(|&(attrib, _)| {
+                    !STATE_ATTRIBS.contains(&&attrib[..])
+                }).map(|(attrib, value)| (&attrib[..], value));
+                for (attrib, value) in input.chain(user) {
+                    widget.set_attrib(attrib, &value[..]);
+                }
+            }
+        }
+    }
+}
+
+/// Captures the state of the named widgets of the tree of `root`, including `root` itself.
+pub fn capture<E: Element>(root: E) -> WidgetState {
+    let handle = unsafe { Handle::from_raw_unchecked(root.raw()) };
+    let mut named = BTreeMap::new();
+    collect_named(handle, &mut named);
+
+    let mut state = WidgetState::new();
+    for (name, widget) in named {
+        for attrib in STATE_ATTRIBS {
+            if let Some(value) = widget.attrib(*attrib) {
+                state.insert(&name[..], *attrib, value);
+            }
+        }
+        for attrib in widget.attribs() {
+            if attrib.starts_with(USER_STATE_PREFIX) {
+                if let Some(value) = widget.attrib(&attrib[..]) {
+                    state.insert(&name[..], attrib, value);
+                }
+            }
+        }
+    }
+    state
+}
+
+/// Sets a free form user state of a widget, kept by `capture` and `WidgetState::restore`.
+pub fn set_user_state<E: Element, S: Into<String>>(mut elem: E, key: &str, value: S) {
+    elem.set_attrib(format!("{}{}", USER_STATE_PREFIX, key), value.into());
+}
+
+/// Gets a user state of a widget set with `set_user_state`.
+pub fn user_state<E: Element>(elem: E, key: &str) -> Option<String> {
+    elem.attrib(format!("{}{}", USER_STATE_PREFIX, key))
+}
+
+fn collect_named(handle: Handle, named: &mut BTreeMap<String, Handle>) {
+    if let Some(name) = handle.attrib("NAME") {
+        named.insert(name, handle);
+    }
+    for child in handle.children() {
+        collect_named(child, named);
+    }
+}
@@ -0,0 +1,186 @@
+//! Stylesheets, separating the visual design from the layout code.
+//!
+//! A stylesheet is a list of rules setting attributes on the elements matched by selectors:
+//!
+//! ```text
+//! /* every button */
+//! button { padding: 10x4; }
+//! /* buttons with the "primary" style class */
+//! button.primary { bgcolor: 0 120 215; fgcolor: 255 255 255; font: Helvetica, Bold 10; }
+//! /* the element named "status" (see the NAME attribute) */
+//! #status { fgcolor: 128 128 128; }
+//! label, text { font: Courier, 10; }
+//! ```
+//!
+//! A selector is an IUP class name (e.g. "button", or `*` for any class), optionally followed by
+//! `.class` to match a style class (see `set_style_class`) and `#name` to match the NAME of the
+//! element. When several rules set the same attribute, the most specific selector wins, then
+//! the last rule.
+//!
+//! ```ignore
+//! style::set_stylesheet(Stylesheet::parse(include_str!("app.style")).unwrap());
+//! let ok = style::styled(style::set_style_class(Button::with_title("OK"), "primary"));
+//! // or style a whole tree at once
+//! style::apply(dialog);
+//! ```
+use std::cell::RefCell;
+use std::result::Result;
+
+use Element;
+use Handle;
+use element::Container;
+
+/// A parsed stylesheet.
+#[derive(Debug, Clone, Default)]
+pub struct Stylesheet {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    selector: Selector,
+    attribs: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Selector {
+    class: Option<String>,
+    style_class: Option<String>,
+    name: Option<String>,
+}
+
+thread_local!(static STYLESHEET: RefCell<Stylesheet> = RefCell::new(Stylesheet::default()));
+
+impl Stylesheet {
+    /// Parses a stylesheet, see the module documentation for the syntax.
+    pub fn parse(source: &str) -> Result<Stylesheet, String> {
+        let source = strip_comments(source);
+        let mut rules = Vec::new();
+        let mut rest = &source[..];
+        while let Some(open) = rest.find('{') {
+            let close = match rest[open..].find('}') {
+                Some(close) => open + close,
+                None => return Err(format!("Missing '}}' after '{}'", rest[..open].trim())),
+            };
+            let mut attribs = Vec::new();
+            for decl in rest[open+1..close].split(';').map(|d| d.trim()).filter(|d| !d.is_empty()) {
+                match decl.find(':') {
+                    Some(colon) => attribs.push((decl[..colon].trim().to_uppercase(),
+                                                 decl[colon+1..].trim().to_string())),
+                    None => return Err(format!("Invalid declaration '{}'", decl)),
+                }
+            }
+            for selector in rest[..open].split(',') {
+                let selector = try!(Selector::parse(selector.trim()));
+                rules.push(Rule { selector: selector, attribs: attribs.clone() });
+            }
+            rest = &rest[close+1..];
+        }
+        if !rest.trim().is_empty() {
+            return Err(format!("Unexpected '{}'", rest.trim()));
+        }
+        Ok(Stylesheet { rules: rules })
+    }
+
+    /// Appends the rules of `other`, which then take precedence over the current ones.
+    pub fn extend(&mut self, other: Stylesheet) {
+        self.rules.extend(other.rules);
+    }
+
+    /// Applies the stylesheet to `elem` and all its children.
+    pub fn apply<E: Element>(&self, elem: E) {
+        let handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+        self.apply_element(handle);
+        for child in handle.children() {
+            self.apply(child);
+        }
+    }
+
+    /// Applies the stylesheet to `elem` only.
+    pub fn apply_element<E: Element>(&self, mut elem: E) {
+        let classname = unsafe { elem.classname() }.to_string_lossy().into_owned();
+        let style_classes = elem.attrib("STYLECLASS").unwrap_or_default();
+        let name = elem.attrib("NAME");
+
+        let mut matched: Vec<(usize, usize, &Rule)> = self.rules.iter().enumerate()
+            .filter(|&(_, rule)| rule.selector.matches(&classname, &style_classes, name.as_ref()))
+            .map(|(order, rule)| (rule.selector.specificity(), order, rule))
+            .collect();
+        matched.sort_by_key(|&(specificity, order, _)| (specificity, order));
+        for (_, _, rule) in matched {
+            for &(ref key, ref value) in rule.attribs.iter() {
+                elem.set_attrib(&key[..], &value[..]);
+            }
+        }
+    }
+}
+
+impl Selector {
+    fn parse(s: &str) -> Result<Selector, String> {
+        if s.is_empty() {
+            return Err("Empty selector".into());
+        }
+        let (s, name) = match s.find('#') {
+            Some(pos) => (&s[..pos], Some(s[pos+1..].to_string())),
+            None => (s, None),
+        };
+        let (class, style_class) = match s.find('.') {
+            Some(pos) => (&s[..pos], Some(s[pos+1..].to_string())),
+            None => (s, None),
+        };
+        let class = match class {
+            "" | "*" => None,
+            class => Some(class.to_lowercase()),
+        };
+        Ok(Selector { class: class, style_class: style_class, name: name })
+    }
+
+    fn matches(&self, classname: &str, style_classes: &str, name: Option<&String>) -> bool {
+        self.class.as_ref().map_or(true, |class| class == classname)
+            && self.style_class.as_ref().map_or(true, |sc| style_classes.split_whitespace().any(|c| c == sc))
+            && self.name.as_ref().map_or(true, |n| Some(n) == name)
+    }
+
+    fn specificity(&self) -> usize {
+        self.class.is_some() as usize + 10 * self.style_class.is_some() as usize
+            + 100 * self.name.is_some() as usize
+    }
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start+2..].find("*/") {
+            Some(end) => &rest[start+2+end+2..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Sets the stylesheet used by `styled` and `apply`.
+pub fn set_stylesheet(stylesheet: Stylesheet) {
+    STYLESHEET.with(|current| *current.borrow_mut() = stylesheet);
+}
+
+/// Sets the style classes of `elem`, separated by spaces, to be matched by `.class` selectors.
+pub fn set_style_class<E: Element, S: Into<String>>(mut elem: E, classes: S) -> E {
+    elem.set_attrib("STYLECLASS", classes);
+    elem
+}
+
+/// Styles a newly created element with the current stylesheet, returning it.
+///
+/// Its children, if any, are styled too.
+pub fn styled<E: Element>(elem: E) -> E {
+    apply(elem);
+    elem
+}
+
+/// Applies the current stylesheet to `elem` and all its children.
+pub fn apply<E: Element>(elem: E) {
+    STYLESHEET.with(|current| current.borrow().apply(elem));
+}
@@ -0,0 +1,150 @@
+//! Helpers for testing user interfaces without user interaction.
+//!
+//! The `run` function initializes IUP just like `with_iup` but does **not** enter the main loop,
+//! so elements and dialogs can be constructed, mapped (`Widget::map`) without being shown,
+//! have their callbacks fired programmatically and their attributes checked.
+//!
+//! ```ignore
+//! #[test]
+//! fn counter_increments() {
+//!     iup::testing::run(|| {
+//!         let mut text = Text::new().set_attrib("VALUE", "0");
+//!         let button = Button::new().set_action(move |_| {
+//!             let count: i32 = text.attrib_parse("VALUE").unwrap();
+//!             text.set_attrib("VALUE", (count + 1).to_string());
+//!         });
+//!         Dialog::new(HBox::new(elements![text, button])).map().unwrap();
+//!
+//!         testing::simulate_click(button);
+//!         testing::assert_attrib(text, "VALUE", "1");
+//!     }).unwrap();
+//! }
+//! ```
+//!
+//! # Continuous Integration
+//!
+//! The native drivers still need a graphical environment, in a headless UNIX machine run the
+//! tests under a virtual X server such as `xvfb-run cargo test`.
+//!
+//! IUP is not thread safe, `run` serializes its callers but tests should still preferably run
+//! with `--test-threads=1` since some native toolkits must be used from a single thread.
+use iup_sys;
+use libc::{c_char, c_int};
+use std::sync::Mutex;
+use std::result::Result;
+
+use Element;
+use InitError;
+use callback::CallbackReturn;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Initializes IUP, calls `f` and finishes IUP without running the main loop.
+///
+/// All IUP-Rust functions, objects and methods must be used within the bounds of the `f` closure.
+/// Calls from several threads are serialized. IUP is finished even if `f` panics (e.g. on a
+/// failed assertion), so the following tests can still open it.
+pub fn run<F: FnOnce()>(f: F) -> Result<(), InitError> {
+    let _lock = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    try!(::open());
+    let _close = CloseOnDrop;
+    f();
+    Ok(())
+}
+
+/// Finishes IUP when dropped, including while unwinding.
+struct CloseOnDrop;
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        ::close();
+    }
+}
+
+/// Processes at most `n` pending events of the main loop, without blocking.
+///
+/// Returns `CallbackReturn::Close` as soon as any callback asks the loop to close,
+/// `CallbackReturn::Default` otherwise.
+pub fn run_iterations(n: usize) -> CallbackReturn {
+    for _ in 0..n {
+        if unsafe { iup_sys::IupLoopStep() } == iup_sys::IUP_CLOSE {
+            return CallbackReturn::Close;
+        }
+    }
+    CallbackReturn::Default
+}
+
+/// Processes all pending events and updates the native elements.
+pub fn flush() {
+    unsafe { iup_sys::IupFlush() };
+}
+
+/// Fires the callbacks of an element as if the user clicked on it.
+///
+/// On toggles the VALUE is switched and both ACTION and VALUECHANGED_CB are called, on every
+/// other element only ACTION is called.
+///
+/// Returns what the ACTION callback returned, or `CallbackReturn::Default` if none is set.
+pub fn simulate_click<E: Element>(elem: E) -> CallbackReturn {
+    let ih = elem.raw();
+    if unsafe { elem.classname().to_bytes() } == b"toggle" {
+        let mut elem = elem;
+        let state = elem.attrib("VALUE").map_or(false, |value| value == "ON");
+        elem.set_attrib("VALUE", if state { "OFF" } else { "ON" });
+        let r = unsafe {
+            call_raw(ih, cstr!("ACTION"), |cb: ToggleActionFn| cb(ih, !state as c_int))
+        };
+        unsafe { call_raw(ih, cstr!("VALUECHANGED_CB"), |cb: SimpleFn| cb(ih)) };
+        r
+    } else {
+        unsafe { call_raw(ih, cstr!("ACTION"), |cb: SimpleFn| cb(ih)) }
+    }
+}
+
+/// Sets the VALUE of an element and fires its VALUECHANGED_CB as if the user typed it.
+///
+/// Returns what the callback returned, or `CallbackReturn::Default` if none is set.
+pub fn set_text_and_notify<E, S>(elem: E, value: S) -> CallbackReturn
+                                                    where E: Element, S: Into<String> {
+    let mut elem = elem;
+    let ih = elem.raw();
+    elem.set_attrib("VALUE", value);
+    unsafe { call_raw(ih, cstr!("VALUECHANGED_CB"), |cb: SimpleFn| cb(ih)) }
+}
+
+/// Asserts an attribute of an element has the expected value.
+///
+/// # Panics
+/// Panics with a descriptive message if the value differs or the attribute is not set.
+pub fn assert_attrib<E, S>(elem: E, name: &str, expected: S)
+                                                    where E: Element, S: Into<String> {
+    let expected = expected.into();
+    match elem.attrib(name) {
+        Some(ref value) if *value == expected => {},
+        value => panic!("assertion failed: {:p}.{} is {:?}, expected {:?}",
+                        elem.raw(), name, value, expected),
+    }
+}
+
+
+type SimpleFn = extern fn(*mut iup_sys::Ihandle) -> c_int;
+type ToggleActionFn = extern fn(*mut iup_sys::Ihandle, c_int) -> c_int;
+
+extern {
+    // Redeclared so a missing callback comes back as `None` instead of a null fn pointer.
+    #[link_name = "IupGetCallback"]
+    fn iup_get_callback(ih: *mut iup_sys::Ihandle, name: *const c_char)
+                                                                -> Option<iup_sys::Icallback>;
+}
+
+/// Calls the callback `name` of `ih` through `call` after casting it to the signature `T`.
+///
+/// It's undefined behaviour if `T` does not match the actual callback signature.
+unsafe fn call_raw<T, F>(ih: *mut iup_sys::Ihandle, name: *const c_char, call: F) -> CallbackReturn
+                                                                where T: Copy, F: FnOnce(T) -> c_int {
+    use std::mem::transmute_copy;
+    match iup_get_callback(ih, name) {
+        Some(cb) => CallbackReturn::from_raw(call(transmute_copy(&cb))),
+        None => CallbackReturn::Default,
+    }
+}
@@ -0,0 +1,352 @@
+//! Themes changing the colors, fonts and spacing of the interface.
+//!
+//! A `Theme` is applied recursively to an element tree with `Theme::apply`, setting the
+//! attributes appropriate to each class. The flat controls (e.g. `flatbutton`) draw themselves
+//! and follow the theme completely, while the native controls only take the colors the system
+//! allows to be changed.
+//!
+//! ```ignore
+//! let dialog = Dialog::new(layout);
+//! Theme::light().apply(dialog);
+//! // ... later, e.g. from a menu
+//! theme::switch_theme(Theme::dark());
+//! ```
+//!
+//! The application can also follow the dark mode preference of the system (see
+//! `globals::prefers_dark`) with `follow_system`, or use its colors with `Theme::system` and
+//! `SystemColors`.
+use iup_sys;
+use std::cell::{Cell, RefCell};
+
+use Element;
+use Handle;
+use WeakHandle;
+use state;
+use globals::{self, prefers_dark};
+use element::{self, Container};
+
+/// Colors, font and spacing of the interface.
+///
+/// Colors are in RGB, `None` fields are left unchanged when applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Background of dialogs and containers.
+    pub background: (u8, u8, u8),
+    /// Foreground (text) of dialogs and controls.
+    pub foreground: (u8, u8, u8),
+    /// Background of editable controls (e.g. `Text`, `List`).
+    pub text_background: (u8, u8, u8),
+    /// Foreground of editable controls.
+    pub text_foreground: (u8, u8, u8),
+    /// Background of the flat controls when the mouse is over them, and of selected text.
+    pub highlight: (u8, u8, u8),
+    /// Background of the flat controls when pressed.
+    pub pressed: (u8, u8, u8),
+    /// Border of the flat controls.
+    pub border: (u8, u8, u8),
+    /// Foreground of links.
+    pub link: (u8, u8, u8),
+    /// Font, e.g. "Segoe UI, 10".
+    pub font: Option<String>,
+    /// Horizontal and vertical padding of buttons and texts, in pixels.
+    pub padding: Option<(u32, u32)>,
+}
+
+/// The colors of the system, as given by the driver through the IUP globals.
+///
+/// Themes overwrite those globals (see `Theme::apply_globals`), so the driver values are kept
+/// aside the first time a theme does.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SystemColors {
+    /// Background of dialogs (DLGBGCOLOR).
+    pub background: (u8, u8, u8),
+    /// Foreground of dialogs (DLGFGCOLOR).
+    pub foreground: (u8, u8, u8),
+    /// Background of editable controls (TXTBGCOLOR).
+    pub text_background: (u8, u8, u8),
+    /// Foreground of editable controls (TXTFGCOLOR).
+    pub text_foreground: (u8, u8, u8),
+    /// Background of selected text (TXTHLCOLOR).
+    pub highlight: (u8, u8, u8),
+    /// Foreground of links (LINKFGCOLOR).
+    pub link: (u8, u8, u8),
+    /// Whether the system prefers a dark interface, see `globals::prefers_dark`.
+    pub dark: bool,
+}
+
+impl SystemColors {
+    /// Gets the colors of the system.
+    pub fn current() -> SystemColors {
+        SYSTEM_COLORS.with(|colors| *colors.borrow()).unwrap_or_else(read_system_colors)
+    }
+}
+
+thread_local!(static CURRENT: RefCell<Option<Theme>> = RefCell::new(None));
+// Counts the calls to `follow_system` and `unfollow_system`, so only the last one follows.
+thread_local!(static FOLLOW_GENERATION: Cell<u64> = Cell::new(0));
+// The driver colors, once the globals were overwritten by a theme.
+thread_local!(static SYSTEM_COLORS: RefCell<Option<SystemColors>> = RefCell::new(None));
+// Roots of the trees themed with `apply`.
+thread_local!(static THEMED: RefCell<Vec<WeakHandle<Handle>>> = RefCell::new(Vec::new()));
+
+impl Theme {
+    /// A light theme.
+    pub fn light() -> Theme {
+        Theme {
+            background: (240, 240, 240),
+            foreground: (0, 0, 0),
+            text_background: (255, 255, 255),
+            text_foreground: (0, 0, 0),
+            highlight: (200, 225, 245),
+            pressed: (160, 200, 235),
+            border: (170, 170, 170),
+            link: (0, 0, 238),
+            font: None,
+            padding: None,
+        }
+    }
+
+    /// A dark theme.
+    pub fn dark() -> Theme {
+        Theme {
+            background: (32, 32, 32),
+            foreground: (230, 230, 230),
+            text_background: (45, 45, 45),
+            text_foreground: (230, 230, 230),
+            highlight: (62, 62, 64),
+            pressed: (0, 90, 158),
+            border: (85, 85, 85),
+            link: (100, 170, 255),
+            font: None,
+            padding: None,
+        }
+    }
+
+    /// A theme with the colors of the system, see `SystemColors`.
+    ///
+    /// The colors the system doesn't give (pressed and border) are mixed from the others.
+    pub fn system() -> Theme {
+        let colors = SystemColors::current();
+        Theme {
+            background: colors.background,
+            foreground: colors.foreground,
+            text_background: colors.text_background,
+            text_foreground: colors.text_foreground,
+            highlight: colors.highlight,
+            pressed: mix(colors.highlight, colors.foreground, 0.25),
+            border: mix(colors.background, colors.foreground, 0.35),
+            link: colors.link,
+            font: None,
+            padding: None,
+        }
+    }
+
+    /// Applies the theme to `elem` and all its children.
+    ///
+    /// The tree is remembered so `switch_theme` updates it later, until it is destroyed.
+    pub fn apply<E: Element>(&self, elem: E) {
+        let handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+        self.apply_tree(handle);
+        remember(handle);
+    }
+
+    /// Sets the IUP default colors, used by the elements created afterwards.
+    pub fn apply_globals(&self) {
+        SYSTEM_COLORS.with(|colors| {
+            let mut colors = colors.borrow_mut();
+            if colors.is_none() {
+                *colors = Some(read_system_colors());
+            }
+        });
+        element::set_global("DLGBGCOLOR", color(self.background));
+        element::set_global("DLGFGCOLOR", color(self.foreground));
+        element::set_global("TXTBGCOLOR", color(self.text_background));
+        element::set_global("TXTFGCOLOR", color(self.text_foreground));
+        element::set_global("TXTHLCOLOR", color(self.highlight));
+        element::set_global("LINKFGCOLOR", color(self.link));
+        if let Some(ref font) = self.font {
+            element::set_global("DEFAULTFONT", &font[..]);
+        }
+    }
+
+    fn apply_tree(&self, mut handle: Handle) {
+        let classname = unsafe { handle.classname() }.to_string_lossy().into_owned();
+        match &classname[..] {
+            "dialog" => {
+                handle.set_attrib("BGCOLOR", color(self.background));
+                handle.set_attrib("FGCOLOR", color(self.foreground));
+                if let Some(ref font) = self.font {
+                    handle.set_attrib("FONT", &font[..]);
+                }
+            },
+            "text" | "multiline" | "list" | "tree" => {
+                handle.set_attrib("BGCOLOR", color(self.text_background));
+                handle.set_attrib("FGCOLOR", color(self.text_foreground));
+                if classname == "text" {
+                    self.apply_padding(&mut handle);
+                }
+            },
+            "button" | "toggle" => {
+                handle.set_attrib("BGCOLOR", color(self.background));
+                handle.set_attrib("FGCOLOR", color(self.foreground));
+                self.apply_padding(&mut handle);
+            },
+            "link" => {
+                handle.set_attrib("BGCOLOR", color(self.background));
+                handle.set_attrib("FGCOLOR", color(self.link));
+            },
+            "label" | "frame" | "progressbar" | "val" => {
+                handle.set_attrib("BGCOLOR", color(self.background));
+                handle.set_attrib("FGCOLOR", color(self.foreground));
+            },
+            class if class.starts_with("flat") => {
+                handle.set_attrib("BGCOLOR", color(self.background));
+                handle.set_attrib("FGCOLOR", color(self.foreground));
+                handle.set_attrib("HLCOLOR", color(self.highlight));
+                handle.set_attrib("PSCOLOR", color(self.pressed));
+                handle.set_attrib("BORDERCOLOR", color(self.border));
+                self.apply_padding(&mut handle);
+            },
+            _ => {},
+        }
+        for child in handle.children() {
+            self.apply_tree(child);
+        }
+        if handle.attrib("WID").is_some() {
+            unsafe { iup_sys::IupRedraw(handle.raw(), 0) };
+        }
+    }
+
+    fn apply_padding(&self, handle: &mut Handle) {
+        if let Some((h, v)) = self.padding {
+            handle.set_attrib("PADDING", format!("{}x{}", h, v));
+        }
+    }
+}
+
+/// Gets the theme set with `switch_theme`, if any.
+pub fn current_theme() -> Option<Theme> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Changes the theme of the whole application in place.
+///
+/// The theme is applied to the IUP defaults (see `Theme::apply_globals`) and again to every tree
+/// it was applied before with `Theme::apply`, including mapped ones.
+pub fn switch_theme(theme: Theme) {
+    theme.apply_globals();
+    let roots = THEMED.with(|themed| {
+        let mut themed = themed.borrow_mut();
+        themed.retain(|root| root.is_alive());
+        themed.iter().filter_map(|root| root.upgrade()).collect::<Vec<_>>()
+    });
+    for handle in roots {
+        // Some drivers recreate the native controls when their colors or font change.
+        let state = state::capture(handle);
+        theme.apply_tree(handle);
+        state.restore(handle);
+    }
+    CURRENT.with(|current| *current.borrow_mut() = Some(theme));
+}
+
+/// Switches between the light and the dark themes following the system preference, now and
+/// whenever it changes, calling `cb` after each change with whether the theme is dark.
+///
+/// Changes are detected with `globals::watch_system`. Calling this again replaces the themes
+/// and the callback.
+pub fn follow_system<F>(light: Theme, dark: Theme, mut cb: F) where F: FnMut(bool) + 'static {
+    let generation = FOLLOW_GENERATION.with(|g| { g.set(g.get() + 1); g.get() });
+    let mut is_dark = prefers_dark();
+    switch_theme(if is_dark { dark.clone() } else { light.clone() });
+    cb(is_dark);
+
+    globals::watch_system(move || {
+        if FOLLOW_GENERATION.with(|g| g.get()) != generation {
+            return false;
+        }
+        let now_dark = prefers_dark();
+        if now_dark != is_dark {
+            is_dark = now_dark;
+            switch_theme(if is_dark { dark.clone() } else { light.clone() });
+            cb(is_dark);
+        }
+        true
+    });
+}
+
+/// Stops following the system preference, the current theme is kept.
+pub fn unfollow_system() {
+    FOLLOW_GENERATION.with(|g| g.set(g.get() + 1));
+}
+
+/// Calls `cb` with the new colors whenever the colors of the system change, including the
+/// dark mode preference.
+///
+/// Changes are detected with `globals::watch_system`. Some drivers only read the colors when
+/// IUP is initialized, thus a change of the color scheme may only be seen in
+/// `SystemColors::dark`, e.g. to switch between `Theme::light` and `Theme::dark`.
+pub fn on_system_colors_changed<F>(mut cb: F) where F: FnMut(&SystemColors) + 'static {
+    let mut last = SystemColors::current();
+    globals::watch_system(move || {
+        // The globals are only the driver colors while no theme overwrote them.
+        let overwritten = SYSTEM_COLORS.with(|colors| *colors.borrow());
+        let now = match overwritten {
+            Some(colors) => SystemColors { dark: prefers_dark(), ..colors },
+            None => read_system_colors(),
+        };
+        if now != last {
+            last = now;
+            if overwritten.is_some() {
+                SYSTEM_COLORS.with(|colors| *colors.borrow_mut() = Some(now));
+            }
+            cb(&now);
+        }
+        true
+    });
+}
+
+/// Reads the colors of the system from the IUP globals.
+fn read_system_colors() -> SystemColors {
+    let light = Theme::light();
+    let global_color = |name: &str, default: (u8, u8, u8)| {
+        element::global(name).and_then(|value| parse_color(&value)).unwrap_or(default)
+    };
+    SystemColors {
+        background: global_color("DLGBGCOLOR", light.background),
+        foreground: global_color("DLGFGCOLOR", light.foreground),
+        text_background: global_color("TXTBGCOLOR", light.text_background),
+        text_foreground: global_color("TXTFGCOLOR", light.text_foreground),
+        highlight: global_color("TXTHLCOLOR", light.highlight),
+        link: global_color("LINKFGCOLOR", light.link),
+        dark: prefers_dark(),
+    }
+}
+
+/// Remembers a themed tree, until it is destroyed.
+fn remember(root: Handle) {
+    let root = root.downgrade();
+    THEMED.with(|themed| {
+        let mut themed = themed.borrow_mut();
+        if !themed.contains(&root) {
+            themed.push(root);
+        }
+    });
+}
+
+fn color(rgb: (u8, u8, u8)) -> String {
+    format!("{} {} {}", rgb.0, rgb.1, rgb.2)
+}
+
+fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = value.split_whitespace().map(|part| part.parse::<u8>().ok());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Some(r)), Some(Some(g)), Some(Some(b))) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+/// Mixes `amount` (from 0 to 1) of `other` into `base`.
+fn mix(base: (u8, u8, u8), other: (u8, u8, u8), amount: f64) -> (u8, u8, u8) {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * amount).round() as u8;
+    (channel(base.0, other.0), channel(base.1, other.1), channel(base.2, other.2))
+}
@@ -1,8 +1,26 @@
 //! Timer to periodically execute an action.
+//!
+//! Besides the `Timer` element, this module has helpers to run closures later on the main loop:
+//! `delay` runs a closure once after some time, while `debounce` and `throttle` coalesce rapid
+//! events (e.g. text changes or resizes) identified by a key.
+//!
+//! ```ignore
+//! text.set_action(|(text, _, _): (Text, i32, String)| {
+//!     let query = text.attrib("VALUE").unwrap_or_default();
+//!     timer::debounce("search", Duration::from_millis(300), move || search(&query));
+//!     CallbackReturn::Default
+//! });
+//! ```
 use iup_sys;
+use std::mem;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::Duration;
 
 use Element;
 use Guard;
+use element::WeakHandle;
+use callback::ActionCb;
 
 /// A timer which periodically invokes a callback when the time is up.
 ///
@@ -63,3 +81,167 @@ impl_element!(Timer, "timer");
 ///
 /// `CallbackReturn::Close` will be processed.
 impl ::callback::ActionCb for Timer {}
+
+thread_local!(static IDLE_TIMERS: RefCell<Vec<Timer>> = RefCell::new(Vec::new()));
+thread_local!(static DEBOUNCED: RefCell<HashMap<String, Timer>> = RefCell::new(HashMap::new()));
+thread_local!(static THROTTLED: RefCell<HashMap<String, Throttled>> = RefCell::new(HashMap::new()));
+// Whether IUP is being closed, the helpers are then ignored.
+thread_local!(static CLOSED: Cell<bool> = Cell::new(false));
+// The timers of `owned_timer`, destroyed by `close_timers`.
+thread_local!(static OWNED_TIMERS: RefCell<Vec<WeakHandle<Timer>>> = RefCell::new(Vec::new()));
+
+struct Throttled {
+    timer: Timer,
+    pending: Option<Box<FnOnce()>>,
+}
+
+/// Calls `f` once on the main loop after `duration`.
+pub fn delay<F: FnOnce() + 'static>(duration: Duration, f: F) {
+    if is_closed() {
+        return;
+    }
+    let mut f = Some(f);
+    let mut timer = idle_timer().set_time(millis(duration));
+    timer.set_action_cb(move |(mut timer,): (Timer,)| {
+        timer.stop();
+        if let Some(f) = f.take() {
+            f();
+        }
+        // Timers can't be destroyed from their own callback, so they are reused. Only after
+        // `f`, otherwise it could replace this very callback with a nested `delay`.
+        if !is_closed() {
+            IDLE_TIMERS.with(|idle| idle.borrow_mut().push(timer));
+        }
+    }).run();
+}
+
+/// Calls `f` on the main loop once no other call with the same `key` happened for `duration`.
+///
+/// Each call replaces the closure of the previous one with the same key, thus only the last
+/// closure of a burst of calls runs.
+pub fn debounce<F: FnOnce() + 'static>(key: &str, duration: Duration, f: F) {
+    if is_closed() {
+        return;
+    }
+    let mut timer = DEBOUNCED.with(|debounced| {
+        let timer = debounced.borrow().get(key).cloned();
+        timer.unwrap_or_else(|| {
+            let timer = idle_timer();
+            debounced.borrow_mut().insert(key.to_string(), timer);
+            timer
+        })
+    });
+    let mut f = Some(f);
+    // Stopping and running again restarts the interval.
+    timer.stop();
+    timer.set_time(millis(duration)).set_action_cb(move |(mut timer,): (Timer,)| {
+        timer.stop();
+        if let Some(f) = f.take() {
+            f();
+        }
+    }).run();
+}
+
+/// Calls `f` on the main loop at most once every `duration` for the same `key`.
+///
+/// The first call runs right away. Calls made in the following `duration` are coalesced, the
+/// last one runs when the `duration` ends.
+pub fn throttle<F: FnOnce() + 'static>(key: &str, duration: Duration, f: F) {
+    if is_closed() {
+        return;
+    }
+    let cooling = THROTTLED.with(|throttled| {
+        match throttled.borrow_mut().get_mut(key) {
+            Some(ref mut throttled) if throttled.timer.is_running() => {
+                throttled.pending = Some(Box::new(f));
+                None
+            },
+            _ => Some(f),
+        }
+    });
+    let f = match cooling {
+        Some(f) => f,
+        None => return,
+    };
+
+    let key = key.to_string();
+    let mut timer = THROTTLED.with(|throttled| {
+        let mut throttled = throttled.borrow_mut();
+        let entry = throttled.entry(key.clone()).or_insert_with(|| {
+            Throttled { timer: idle_timer(), pending: None }
+        });
+        entry.timer
+    });
+    timer.set_time(millis(duration)).set_action_cb(move |(mut timer,): (Timer,)| {
+        let pending = THROTTLED.with(|throttled| {
+            throttled.borrow_mut().get_mut(&key).and_then(|throttled| throttled.pending.take())
+        });
+        match pending {
+            // Keeps cooling down after the trailing call.
+            Some(pending) => pending(),
+            None => { timer.stop(); },
+        }
+    }).run();
+    f();
+}
+
+/// Stops and forgets the timers of `delay`, `debounce` and `throttle`, when IUP is closed.
+///
+/// The helpers do nothing from now on until `open_timers`, so a closure running during the
+/// teardown (e.g. a debounced one calling `debounce` again) can't arm a timer IUP destroys.
+#[doc(hidden)]
+pub fn close_timers() {
+    CLOSED.with(|closed| closed.set(true));
+    // Taken out first, dropping the pending closures may call the helpers.
+    let idle = IDLE_TIMERS.with(|idle| mem::replace(&mut *idle.borrow_mut(), Vec::new()));
+    let debounced = DEBOUNCED.with(|debounced| {
+        mem::replace(&mut *debounced.borrow_mut(), HashMap::new())
+    });
+    let throttled = THROTTLED.with(|throttled| {
+        mem::replace(&mut *throttled.borrow_mut(), HashMap::new())
+    });
+    let timers = idle.into_iter()
+                     .chain(debounced.into_iter().map(|(_, timer)| timer))
+                     .chain(throttled.into_iter().map(|(_, throttled)| throttled.timer));
+    for mut timer in timers {
+        timer.stop();
+    }
+    let owned = OWNED_TIMERS.with(|owned| mem::replace(&mut *owned.borrow_mut(), Vec::new()));
+    for timer in owned.into_iter().filter_map(|timer| timer.upgrade()) {
+        timer.destroy();
+    }
+}
+
+/// Enables the helpers again, when IUP is opened.
+#[doc(hidden)]
+pub fn open_timers() {
+    CLOSED.with(|closed| closed.set(false));
+}
+
+fn is_closed() -> bool {
+    CLOSED.with(|closed| closed.get())
+}
+
+/// Creates a timer destroyed when IUP is closed, for the timers of the crate which don't
+/// belong to any element (otherwise they would leak, see the crate ownership docs).
+#[doc(hidden)]
+pub fn owned_timer() -> Timer {
+    let timer = Timer::new().unwrap();
+    OWNED_TIMERS.with(|owned| {
+        let mut owned = owned.borrow_mut();
+        owned.retain(|timer| timer.is_alive());
+        owned.push(timer.downgrade());
+    });
+    timer
+}
+
+/// Gets a stopped timer, reusing one from `delay` if possible.
+fn idle_timer() -> Timer {
+    IDLE_TIMERS.with(|idle| idle.borrow_mut().pop()).unwrap_or_else(owned_timer)
+}
+
+fn millis(duration: Duration) -> u32 {
+    let ms = duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64;
+    // IUP ignores a zero TIME.
+    ms.max(1).min(u32::max_value() as u64) as u32
+}
@@ -0,0 +1,343 @@
+//! Multi-touch input, available with the `tuio` feature.
+//!
+//! Canvases receive touch events through `TouchCb` and `MultiTouchCb`. Those come natively from
+//! the system in Windows 7 or later (see `Canvas::set_touch`), or from a [TUIO][1] tracker in any
+//! platform through a `TuioClient`.
+//!
+//! ```ignore
+//! let mut client = TuioClient::new(3333);
+//! client.set_target_canvas(canvas).connect();
+//! // The client is destroyed with its guard, which is kept until the main loop ends.
+//! app::on_exit(move || drop(client));
+//! canvas.set_touch_cb(|(canvas, point): (Canvas, TouchPoint)| {
+//!     println!("{:?} touched at {}x{}", point.state, point.x, point.y);
+//! });
+//! ```
+//!
+//! [1]: http://www.tuio.org/
+use iup_sys;
+use libc::{c_char, c_int};
+use std::slice;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use Element;
+use Guard;
+use control::Canvas;
+
+/// The state of a touch point.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TouchState {
+    /// The point started touching.
+    Down,
+    /// The point moved while touching.
+    Move,
+    /// The point stopped touching.
+    Up,
+    /// Any other state reported by the system.
+    Other,
+}
+
+/// A point touching a canvas.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TouchPoint {
+    /// Identifies the point while it is touching, from `Down` to `Up`.
+    pub id: i32,
+    /// Horizontal position in the canvas, in pixels.
+    pub x: i32,
+    /// Vertical position in the canvas, in pixels.
+    pub y: i32,
+    pub state: TouchState,
+    /// Whether this is the primary point of a multi-touch interaction, only known in `TouchCb`.
+    pub primary: bool,
+}
+
+impl Canvas {
+    /// [Windows Only]: Enables the native touch events of the system, Windows 7 or later.
+    ///
+    /// Must be set before the canvas is mapped.
+    pub fn set_touch(&mut self, touch: bool) -> Canvas {
+        self.set_attrib("TOUCH", if touch { "YES" } else { "NO" })
+    }
+}
+
+/// See the `TouchCb` documentation.
+impl self::TouchCb for Canvas {}
+impl_callback! {
+    #[doc="Action generated when a point touches, moves or leaves the canvas."]
+    #[doc=""]
+    #[doc="Called for each point, after the `MultiTouchCb` of the points changed at once."]
+    pub trait TouchCb where Self: Element {
+        let name = "TOUCH_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, id: c_int, x: c_int, y: c_int,
+                           state: *mut c_char) -> CallbackReturn;
+        fn set_touch_cb<F: Callback(Self, TouchPoint)>(&mut self, cb: F) -> Self;
+        fn remove_touch_cb(&mut self) -> Option<Box<_>>;
+
+        fn resolve_args(elem: Self, id: c_int, x: c_int, y: c_int, state: *mut c_char) -> (Self, TouchPoint) {
+            let state = string_from_cstr!(state);
+            let point = TouchPoint {
+                id: id,
+                x: x,
+                y: y,
+                state: match state.split('-').next() {
+                    Some("DOWN") => TouchState::Down,
+                    Some("MOVE") => TouchState::Move,
+                    Some("UP") => TouchState::Up,
+                    _ => TouchState::Other,
+                },
+                primary: state.ends_with("-PRIMARY"),
+            };
+            (elem, point)
+        }
+    }
+}
+
+/// See the `MultiTouchCb` documentation.
+impl self::MultiTouchCb for Canvas {}
+impl_callback! {
+    #[doc="Action generated when several points touch, move or leave the canvas at once."]
+    pub trait MultiTouchCb where Self: Element {
+        let name = "MULTITOUCH_CB";
+        extern fn listener(ih: *mut iup_sys::Ihandle, count: c_int, pid: *mut c_int, px: *mut c_int,
+                           py: *mut c_int, pstate: *mut c_int) -> CallbackReturn;
+        fn set_multitouch_cb<F: Callback(Self, Vec<TouchPoint>)>(&mut self, cb: F) -> Self;
+        fn remove_multitouch_cb(&mut self) -> Option<Box<_>>;
+
+        fn resolve_args(elem: Self, count: c_int, pid: *mut c_int, px: *mut c_int, py: *mut c_int,
+                        pstate: *mut c_int) -> (Self, Vec<TouchPoint>) {
+            (elem, touch_points(count, pid, px, py, pstate))
+        }
+    }
+}
+
+fn touch_points(count: c_int, pid: *mut c_int, px: *mut c_int, py: *mut c_int,
+                pstate: *mut c_int) -> Vec<TouchPoint> {
+    if count <= 0 {
+        return Vec::new();
+    }
+    let n = count as usize;
+    let (ids, xs, ys, states) = unsafe {
+        (slice::from_raw_parts(pid, n), slice::from_raw_parts(px, n),
+         slice::from_raw_parts(py, n), slice::from_raw_parts(pstate, n))
+    };
+    (0..n).map(|i| TouchPoint {
+        id: ids[i],
+        x: xs[i],
+        y: ys[i],
+        state: match states[i] as u8 {
+            b'D' => TouchState::Down,
+            b'M' => TouchState::Move,
+            b'U' => TouchState::Up,
+            _ => TouchState::Other,
+        },
+        primary: false,
+    }).collect()
+}
+
+/// A high-level gesture, recognized from the touch points by `Gestures`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Gesture {
+    /// Two points moved together, by `dx` and `dy` pixels.
+    Pan { dx: f32, dy: f32 },
+    /// Two points moved apart (`scale` greater than one) or closer (`scale` less than one),
+    /// centered at `x` and `y`.
+    Pinch { scale: f32, x: f32, y: f32 },
+}
+
+/// Recognizes two-finger pan and pinch zoom gestures from the touch points of a canvas.
+///
+/// Gestures are computed between consecutive events with exactly two points touching, so each
+/// gesture is relative to the previous one (e.g. the zoom is the product of the pinch scales).
+///
+/// IUP has no gesture or pen events of its own, those are all synthesized from `MultiTouchCb`.
+#[derive(Debug, Default)]
+pub struct Gestures {
+    points: HashMap<i32, (f32, f32)>,
+}
+
+impl Gestures {
+    /// Creates a recognizer with no points touching.
+    pub fn new() -> Gestures {
+        Gestures { points: HashMap::new() }
+    }
+
+    /// Calls `cb` with the gestures made on `canvas`, replacing its `MultiTouchCb`.
+    pub fn attach<F>(mut canvas: Canvas, mut cb: F) -> Canvas
+                                            where F: FnMut(Canvas, Gesture) + 'static {
+        let gestures = Rc::new(RefCell::new(Gestures::new()));
+        canvas.set_multitouch_cb(move |(canvas, points): (Canvas, Vec<TouchPoint>)| {
+            let recognized = gestures.borrow_mut().update(&points);
+            for gesture in recognized {
+                cb(canvas, gesture);
+            }
+        })
+    }
+
+    /// Updates the touching points and returns the gestures they made since the last update.
+    pub fn update(&mut self, points: &[TouchPoint]) -> Vec<Gesture> {
+        let before = self.two_points();
+        for point in points {
+            match point.state {
+                TouchState::Up => { self.points.remove(&point.id); },
+                _ => { self.points.insert(point.id, (point.x as f32, point.y as f32)); },
+            }
+        }
+        let mut gestures = Vec::new();
+        if let (Some((ids0, a0, b0)), Some((ids1, a1, b1))) = (before, self.two_points()) {
+            // A finger was replaced by another, that's not a movement.
+            if ids0 != ids1 {
+                return gestures;
+            }
+            let (c0, c1) = (midpoint(a0, b0), midpoint(a1, b1));
+            if c0 != c1 {
+                gestures.push(Gesture::Pan { dx: c1.0 - c0.0, dy: c1.1 - c0.1 });
+            }
+            let (d0, d1) = (distance(a0, b0), distance(a1, b1));
+            if d0 > 0.0 && d1 != d0 {
+                gestures.push(Gesture::Pinch { scale: d1 / d0, x: c1.0, y: c1.1 });
+            }
+        }
+        gestures
+    }
+
+    /// Gets the ids of two touching points and their positions, if exactly two are touching.
+    fn two_points(&self) -> Option<((i32, i32), (f32, f32), (f32, f32))> {
+        if self.points.len() != 2 {
+            return None;
+        }
+        let mut ids: Vec<i32> = self.points.keys().cloned().collect();
+        ids.sort();
+        Some(((ids[0], ids[1]), self.points[&ids[0]], self.points[&ids[1]]))
+    }
+}
+
+fn midpoint(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// A client receiving touch events from a TUIO tracker (e.g. a multi-touch table) over UDP.
+///
+/// See the [IUP TuioClient Documentation][1].
+///
+/// # Ownership
+///
+/// The client must be manually destroyed, thus for the user safety it returns a guarded object
+/// on the `new` constructor.
+///
+/// [1]: http://webserver2.tecgraf.puc-rio.br/iup/en/ctrl/iuptuio.html
+pub struct TuioClient(*mut iup_sys::Ihandle);
+
+impl TuioClient {
+    /// Creates a client listening on the UDP `port`, 3333 by default in most trackers.
+    pub fn new(port: u16) -> Guard<TuioClient> {
+        unsafe { IupTuioOpen() };
+        Guard::new(
+            TuioClient::from_raw(unsafe { IupTuioClient(port as c_int) })
+        )
+    }
+
+    /// Starts receiving events.
+    pub fn connect(&mut self) -> TuioClient {
+        self.set_attrib("CONNECT", "YES")
+    }
+
+    /// Stops receiving events.
+    pub fn disconnect(&mut self) -> TuioClient {
+        self.set_attrib("CONNECT", "NO")
+    }
+
+    /// Sets the canvas receiving the touch events, as `TouchCb` and `MultiTouchCb`.
+    ///
+    /// The tracker positions are converted to the canvas coordinates.
+    pub fn set_target_canvas(&mut self, canvas: Canvas) -> TuioClient {
+        self.set_attrib_handle("TARGETCANVAS", canvas)
+    }
+
+    /// Prints the received events to the standard output.
+    pub fn set_debug(&mut self, debug: bool) -> TuioClient {
+        self.set_attrib("DEBUG", if debug { "YES" } else { "NO" })
+    }
+}
+
+impl_element!(TuioClient, "tuioclient");
+
+// Not yet bound by iup-sys.
+#[link(name = "iuptuio")]
+extern {
+    fn IupTuioOpen() -> c_int;
+    fn IupTuioClient(port: c_int) -> *mut iup_sys::Ihandle;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gestures, Gesture, TouchPoint, TouchState};
+
+    fn point(id: i32, x: i32, y: i32, state: TouchState) -> TouchPoint {
+        TouchPoint { id: id, x: x, y: y, state: state, primary: false }
+    }
+
+    #[test]
+    fn single_point_makes_no_gesture() {
+        let mut gestures = Gestures::new();
+        assert!(gestures.update(&[point(1, 0, 0, TouchState::Down)]).is_empty());
+        assert!(gestures.update(&[point(1, 50, 0, TouchState::Move)]).is_empty());
+    }
+
+    #[test]
+    fn points_touching_make_no_gesture() {
+        let mut gestures = Gestures::new();
+        let down = [point(1, 0, 0, TouchState::Down), point(2, 100, 0, TouchState::Down)];
+        assert!(gestures.update(&down).is_empty());
+    }
+
+    #[test]
+    fn moving_together_pans() {
+        let mut gestures = Gestures::new();
+        gestures.update(&[point(1, 0, 0, TouchState::Down), point(2, 100, 0, TouchState::Down)]);
+        let moved = [point(1, 10, 20, TouchState::Move), point(2, 110, 20, TouchState::Move)];
+        assert_eq!(gestures.update(&moved), vec![Gesture::Pan { dx: 10.0, dy: 20.0 }]);
+    }
+
+    #[test]
+    fn moving_apart_pinches() {
+        let mut gestures = Gestures::new();
+        gestures.update(&[point(1, 50, 0, TouchState::Down), point(2, 150, 0, TouchState::Down)]);
+        let moved = [point(1, 0, 0, TouchState::Move), point(2, 200, 0, TouchState::Move)];
+        assert_eq!(gestures.update(&moved), vec![Gesture::Pinch { scale: 2.0, x: 100.0, y: 0.0 }]);
+        let moved = [point(1, 50, 0, TouchState::Move), point(2, 150, 0, TouchState::Move)];
+        assert_eq!(gestures.update(&moved), vec![Gesture::Pinch { scale: 0.5, x: 100.0, y: 0.0 }]);
+    }
+
+    #[test]
+    fn moving_one_point_pans_and_pinches() {
+        let mut gestures = Gestures::new();
+        gestures.update(&[point(1, 0, 0, TouchState::Down), point(2, 100, 0, TouchState::Down)]);
+        let moved = gestures.update(&[point(2, 200, 0, TouchState::Move)]);
+        assert_eq!(moved, vec![Gesture::Pan { dx: 50.0, dy: 0.0 },
+                               Gesture::Pinch { scale: 2.0, x: 100.0, y: 0.0 }]);
+    }
+
+    #[test]
+    fn replaced_point_is_not_a_movement() {
+        let mut gestures = Gestures::new();
+        gestures.update(&[point(1, 0, 0, TouchState::Down), point(2, 100, 0, TouchState::Down)]);
+        let replaced = [point(2, 100, 0, TouchState::Up), point(3, 300, 0, TouchState::Down)];
+        assert!(gestures.update(&replaced).is_empty());
+        let moved = [point(3, 310, 0, TouchState::Move), point(1, 10, 0, TouchState::Move)];
+        assert_eq!(gestures.update(&moved), vec![Gesture::Pan { dx: 10.0, dy: 0.0 }]);
+    }
+
+    #[test]
+    fn lifting_a_point_ends_the_gesture() {
+        let mut gestures = Gestures::new();
+        gestures.update(&[point(1, 0, 0, TouchState::Down), point(2, 100, 0, TouchState::Down)]);
+        assert!(gestures.update(&[point(2, 100, 0, TouchState::Up)]).is_empty());
+        assert!(gestures.update(&[point(1, 40, 0, TouchState::Move)]).is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Utilities to run external programs, log messages and post work from other threads.
+//!
+//! IUP is not thread safe, elements must only be used from the thread running the main loop.
+//! Other threads can still update the interface by posting closures with `post`, which are then
+//! called in the main loop.
+//!
+//! ```ignore
+//! util::enable_posting();
+//! thread::spawn(move || {
+//!     let result = compute();
+//!     util::post(move || { label.set_attrib("TITLE", result); }).unwrap();
+//! });
+//! ```
+use iup_sys;
+use libc::{c_char, c_int, c_double, c_void};
+use std::{mem, ptr, thread};
+use std::ffi::CString;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::result::Result;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use Element;
+use WeakHandle;
+use control::Text;
+
+/// Opens a file or runs a program, without waiting for it to finish.
+///
+/// Files are opened with the application the system associates them with, e.g. a web page
+/// URL is opened in the default browser.
+pub fn execute<S1, S2>(file: S1, params: S2) -> Result<(), String>
+                                        where S1: Into<String>, S2: Into<String> {
+    let cfile = CString::new(file.into()).unwrap();
+    let cparams = CString::new(params.into()).unwrap();
+    execute_result(unsafe { IupExecute(cfile.as_ptr(), cparams.as_ptr()) })
+}
+
+/// Same as `execute` but waits for the program to finish.
+pub fn execute_wait<S1, S2>(file: S1, params: S2) -> Result<(), String>
+                                        where S1: Into<String>, S2: Into<String> {
+    let cfile = CString::new(file.into()).unwrap();
+    let cparams = CString::new(params.into()).unwrap();
+    execute_result(unsafe { IupExecuteWait(cfile.as_ptr(), cparams.as_ptr()) })
+}
+
+fn execute_result(ret: c_int) -> Result<(), String> {
+    match ret {
+        1 => Ok(()),
+        -2 => Err("File not found".into()),
+        _ => Err("Failed to execute the file".into()),
+    }
+}
+
+/// The priority of a log message, from the least to the most important.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LogLevel {
+    fn as_cstr(&self) -> *const c_char {
+        match *self {
+            LogLevel::Debug => cstr!("DEBUG"),
+            LogLevel::Info => cstr!("INFO"),
+            LogLevel::Notice => cstr!("NOTICE"),
+            LogLevel::Warning => cstr!("WARNING"),
+            LogLevel::Error => cstr!("ERROR"),
+            LogLevel::Critical => cstr!("CRITICAL"),
+            LogLevel::Alert => cstr!("ALERT"),
+            LogLevel::Emergency => cstr!("EMERGENCY"),
+        }
+    }
+}
+
+/// Writes a message to the system log (syslog in UNIX and the Event Log in Windows).
+pub fn log<S: Into<String>>(level: LogLevel, message: S) {
+    let cmessage = CString::new(message.into()).unwrap();
+    unsafe { IupLog(level.as_cstr(), cstr!("%s"), cmessage.as_ptr()) };
+}
+
+/// The receiver of the posted closures, destroyed by `disable_posting`.
+static RECEIVER: AtomicPtr<iup_sys::Ihandle> = AtomicPtr::new(ptr::null_mut());
+static POSTED: Mutex<Vec<Box<FnOnce() + Send>>> = Mutex::new(Vec::new());
+static MAIN_THREAD: Mutex<Option<ThreadId>> = Mutex::new(None);
+/// The commands of `stream_output` still running, killed by `disable_posting`.
+static STREAMED: Mutex<Vec<Arc<Mutex<Option<Child>>>>> = Mutex::new(Vec::new());
+
+/// Enables `post`, must be called from the main loop thread after IUP is initialized.
+///
+/// Does nothing if already enabled.
+pub fn enable_posting() {
+    if !RECEIVER.load(Ordering::SeqCst).is_null() {
+        return;
+    }
+    unsafe {
+        let ih = iup_sys::IupUser();
+        let on_posted = on_posted as extern fn(*mut iup_sys::Ihandle, *mut c_char, c_int,
+                                               c_double, *mut c_void) -> c_int;
+        iup_sys::IupSetCallback(ih, cstr!("POSTMESSAGE_CB"), mem::transmute(on_posted));
+        RECEIVER.store(ih, Ordering::SeqCst);
+    }
+}
+
+/// Calls `f` in the main loop thread, can be called from any thread.
+///
+/// Fails if `enable_posting` was not called, or if IUP was closed since. Closures posted but
+/// not called yet when IUP is closed are dropped.
+pub fn post<F: FnOnce() + Send + 'static>(f: F) -> Result<(), String> {
+    // The lock is held while posting so the receiver can't be destroyed meanwhile, see
+    // `disable_posting`.
+    let mut posted = POSTED.lock().unwrap();
+    let ih = RECEIVER.load(Ordering::SeqCst);
+    if ih.is_null() {
+        return Err("Posting is not enabled, see enable_posting".into());
+    }
+    posted.push(Box::new(f));
+    unsafe { IupPostMessage(ih, ptr::null(), 0, 0.0, ptr::null_mut()) };
+    Ok(())
+}
+
+/// Posts `f` from the main loop thread, enabling posting if it wasn't.
+///
+/// Fails if IUP is not open or called from another thread.
+#[doc(hidden)]
+pub fn post_from_main_thread<F: FnOnce() + Send + 'static>(f: F) -> Result<(), String> {
+    match *MAIN_THREAD.lock().unwrap() {
+        Some(id) if id == thread::current().id() => (),
+        _ => return Err("Not called from the main loop thread".into()),
+    }
+    enable_posting();
+    post(f)
+}
+
+/// Stops posting before IUP is closed, dropping the closures not called yet and killing the
+/// commands of `stream_output` still running.
+#[doc(hidden)]
+pub fn disable_posting() {
+    let streamed = mem::replace(&mut *STREAMED.lock().unwrap(), Vec::new());
+    for child in streamed {
+        if let Some(ref mut child) = *child.lock().unwrap() {
+            child.kill().ok();
+        }
+    }
+    let (receiver, dropped) = {
+        let mut posted = POSTED.lock().unwrap();
+        let receiver = RECEIVER.swap(ptr::null_mut(), Ordering::SeqCst);
+        (receiver, mem::replace(&mut *posted, Vec::new()))
+    };
+    *MAIN_THREAD.lock().unwrap() = None;
+    drop(dropped);
+    if !receiver.is_null() {
+        unsafe { iup_sys::IupDestroy(receiver) };
+    }
+}
+
+/// Calls `f` right away in the main loop thread, or posts it from other threads.
+///
+/// Fails if IUP is not open, or if called from another thread while posting is not enabled
+/// (see `post`), `f` is then dropped.
+pub fn run_in_main_thread<F: FnOnce() + Send + 'static>(f: F) -> Result<(), String> {
+    let main_thread = *MAIN_THREAD.lock().unwrap();
+    match main_thread {
+        Some(id) if id == thread::current().id() => { f(); Ok(()) },
+        Some(_) => post(f),
+        None => Err("IUP is not open".into()),
+    }
+}
+
+/// Records the calling thread as the main loop thread, called when IUP is opened.
+#[doc(hidden)]
+pub fn set_main_thread() {
+    *MAIN_THREAD.lock().unwrap() = Some(thread::current().id());
+}
+
+/// Calls the posted closures, in the main loop thread.
+///
+/// The closures run as a callback of the receiver, see `debug::enter_callback`.
+extern fn on_posted(ih: *mut iup_sys::Ihandle, _s: *mut c_char, _i: c_int, _d: c_double,
+                    _p: *mut c_void) -> c_int {
+    // A nested call skipped by the reentrancy guard leaves its closures to this loop.
+    let _running = match ::debug::enter_callback(ih, "POSTMESSAGE_CB") {
+        Some(running) => running,
+        None => return iup_sys::IUP_DEFAULT,
+    };
+    loop {
+        // The closures are taken out so they are free to post more.
+        let posted = mem::replace(&mut *POSTED.lock().unwrap(), Vec::new());
+        if posted.is_empty() {
+            break;
+        }
+        for f in posted {
+            f();
+        }
+    }
+    iup_sys::IUP_DEFAULT
+}
+
+/// Runs `command` and appends the lines it writes to the standard output to `text`, as they
+/// are written.
+///
+/// The lines are read in another thread and posted to the main loop, so posting is enabled if
+/// it wasn't. The text should be multiline. The command is killed once the text is destroyed
+/// (noticed on its next line) or when IUP is closed, which also ends the thread.
+pub fn stream_output(mut command: Command, text: Text) -> io::Result<()> {
+    enable_posting();
+    let mut child = try!(command.stdout(Stdio::piped()).spawn());
+    let stdout = child.stdout.take().unwrap();
+    let child = Arc::new(Mutex::new(Some(child)));
+    STREAMED.lock().unwrap().push(child.clone());
+    let text = SendWeak(text.downgrade());
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            let text = text.clone();
+            let child = child.clone();
+            let posted = post(move || match text.0.upgrade() {
+                Some(mut text) => { text.set_attrib("APPEND", line); },
+                None => {
+                    if let Some(ref mut child) = *child.lock().unwrap() {
+                        child.kill().ok();
+                    }
+                },
+            });
+            if posted.is_err() {
+                break;
+            }
+        }
+        STREAMED.lock().unwrap().retain(|streamed| !Arc::ptr_eq(streamed, &child));
+        // Taken out to wait without holding the lock, which would block the main loop thread.
+        let finished = child.lock().unwrap().take();
+        if let Some(mut finished) = finished {
+            finished.wait().ok();
+        }
+    });
+    Ok(())
+}
+
+/// A weak handle moved to another thread, only to be posted back to the main loop thread.
+#[derive(Clone)]
+struct SendWeak(WeakHandle<Text>);
+
+unsafe impl Send for SendWeak {}
+
+// Not yet bound by iup-sys.
+extern {
+    fn IupExecute(filename: *const c_char, parameters: *const c_char) -> c_int;
+    fn IupExecuteWait(filename: *const c_char, parameters: *const c_char) -> c_int;
+    fn IupLog(kind: *const c_char, format: *const c_char, ...);
+    fn IupPostMessage(ih: *mut iup_sys::Ihandle, s: *const c_char, i: c_int, d: c_double,
+                      p: *mut c_void);
+}
@@ -0,0 +1,247 @@
+//! Validation of the user input in forms.
+//!
+//! Validators are closures registered on the input elements, checking their value and giving
+//! an error message when it's invalid. `validate_all` runs the validators of every element of a
+//! dialog (or any container), showing the invalid elements with an error background and their
+//! message as tooltip:
+//!
+//! ```ignore
+//! validation::required(name_text, "The name is required");
+//! validation::add_validator(age_text, |text: Text| {
+//!     match text.attrib_parse::<u32, _>("VALUE") {
+//!         Some(age) if age < 150 => Ok(()),
+//!         _ => Err("The age must be a number below 150".into()),
+//!     }
+//! });
+//! validation::block_until_valid(ok_button, dialog);
+//!
+//! ok_button.set_action(move |_| {
+//!     if validation::validate_all(dialog).is_empty() {
+//!         save(dialog);
+//!     }
+//! });
+//! ```
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::result::Result;
+
+use Element;
+use Handle;
+use observe;
+use element::{Node, Container, Widget, WeakHandle};
+
+/// Background color of the invalid elements.
+pub const ERROR_BGCOLOR: &'static str = "255 210 210";
+
+/// Attribute holding the error message of an invalid element.
+const ERROR_ATTRIB: &'static str = "VALIDATION_ERROR";
+/// Attributes keeping the BGCOLOR and TIP of an element while it's shown as invalid.
+const SAVED_BGCOLOR: &'static str = "_IUPRUST_VALIDATION_BGCOLOR";
+const SAVED_TIP: &'static str = "_IUPRUST_VALIDATION_TIP";
+
+struct Validator {
+    handle: WeakHandle<Handle>,
+    check: Rc<Fn() -> Result<(), String>>,
+}
+
+thread_local!(static VALIDATORS: RefCell<Vec<Validator>> = RefCell::new(Vec::new()));
+
+/// Registers a validator of `elem`, which returns an error message when the element is invalid.
+///
+/// An element may have several validators, run in the order they were added until one fails.
+/// The validators are removed when the element is destroyed.
+pub fn add_validator<E, F>(elem: E, validator: F)
+                        where E: Element + 'static, F: Fn(E) -> Result<(), String> + 'static {
+    let validator = Validator {
+        handle: Handle::from_raw(elem.raw()).downgrade(),
+        check: Rc::new(move || validator(elem)),
+    };
+    VALIDATORS.with(|validators| validators.borrow_mut().push(validator));
+}
+
+/// Registers a validator failing with `message` when the VALUE of `elem` is empty or only
+/// whitespace.
+pub fn required<E, S>(elem: E, message: S) where E: Element + 'static, S: Into<String> {
+    let message = message.into();
+    add_validator(elem, move |elem: E| {
+        if is_blank(elem.attrib("VALUE").as_ref().map(|value| &value[..])) {
+            Err(message.clone())
+        } else {
+            Ok(())
+        }
+    });
+}
+
+/// Removes all the validators of `elem`, and its error indication if any.
+pub fn remove_validators<E: Element>(elem: E) {
+    let handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+    VALIDATORS.with(|validators| validators.borrow_mut().retain(|v| !v.validates(handle)));
+    clear_error(unsafe { Handle::from_raw_unchecked(elem.raw()) });
+}
+
+/// Runs the validators of `elem`, showing or clearing its error indication.
+///
+/// Returns the message of the first failing validator.
+pub fn validate<E: Element>(elem: E) -> Result<(), String> {
+    let handle = unsafe { Handle::from_raw_unchecked(elem.raw()) };
+    let result = check(handle);
+    match result {
+        Ok(()) => clear_error(handle),
+        Err(ref message) => show_error(handle, message),
+    }
+    result
+}
+
+/// Runs the validators of every element inside `root` (itself included), showing or clearing
+/// their error indication.
+///
+/// Returns the invalid elements with their error message, in dialog order, thus an empty vector
+/// when the whole form is valid. Hidden elements are validated as well.
+pub fn validate_all<E: Node>(root: E) -> Vec<(Handle, String)> {
+    validated_elements(root).into_iter().filter_map(|elem| {
+        validate(elem).err().map(|message| (elem, message))
+    }).collect()
+}
+
+/// Checks whether every element inside `root` is valid, without changing their error
+/// indication.
+pub fn is_valid<E: Node>(root: E) -> bool {
+    validated_elements(root).into_iter().all(|elem| check(elem).is_ok())
+}
+
+/// Gets the error message shown by `elem`, if it was found invalid by the last validation.
+pub fn error<E: Element>(elem: E) -> Option<String> {
+    elem.attrib(ERROR_ATTRIB)
+}
+
+/// Keeps `button` inactive while any element inside `root` is invalid.
+///
+/// The VALUE of the validated elements is observed (see `observe::observe`), so the button is
+/// updated shortly after each change. The error indication of an element is updated too, once
+/// it was shown by `validate` or `validate_all`. Only the validators registered before this
+/// call are observed.
+pub fn block_until_valid<B, E>(button: B, root: E) where B: Widget + 'static, E: Node + 'static {
+    let mut button = button;
+    button.set_enabled(is_valid(root));
+    for elem in validated_elements(root) {
+        observe::observe(elem, "VALUE", move |elem, _| {
+            if error(elem).is_some() {
+                let _ = validate(elem);
+            }
+            button.set_enabled(is_valid(root));
+        });
+    }
+}
+
+/// Runs the validators of `handle` without showing the result.
+fn check(handle: Handle) -> Result<(), String> {
+    // Cloned out so validators are free to add or remove validators.
+    let checks: Vec<Rc<Fn() -> Result<(), String>>> = VALIDATORS.with(|validators| {
+        let mut validators = validators.borrow_mut();
+        validators.retain(|v| v.handle.is_alive());
+        validators.iter().filter(|v| v.validates(handle)).map(|v| v.check.clone()).collect()
+    });
+    run_checks(&checks)
+}
+
+/// Runs `checks` in order until one fails.
+fn run_checks(checks: &[Rc<Fn() -> Result<(), String>>]) -> Result<(), String> {
+    for check in checks {
+        try!(check());
+    }
+    Ok(())
+}
+
+/// Checks whether a VALUE is missing, empty or only whitespace.
+fn is_blank(value: Option<&str>) -> bool {
+    value.map_or(true, |value| value.trim().is_empty())
+}
+
+/// Finds the elements inside `root` with validators, in dialog order.
+fn validated_elements<E: Node>(root: E) -> Vec<Handle> {
+    let mut found = Vec::new();
+    find_validated(unsafe { Handle::from_raw_unchecked(root.raw()) }, &mut found);
+    found
+}
+
+fn find_validated(handle: Handle, found: &mut Vec<Handle>) {
+    let validated = VALIDATORS.with(|validators| {
+        validators.borrow().iter().any(|v| v.validates(handle))
+    });
+    if validated {
+        found.push(handle);
+    }
+    for child in handle.children() {
+        find_validated(child, found);
+    }
+}
+
+fn show_error(mut handle: Handle, message: &str) {
+    if handle.attrib(ERROR_ATTRIB).is_none() {
+        let bgcolor = handle.attrib("BGCOLOR").unwrap_or_default();
+        let tip = handle.attrib("TIP").unwrap_or_default();
+        handle.set_attrib(SAVED_BGCOLOR, bgcolor);
+        handle.set_attrib(SAVED_TIP, tip);
+    }
+    handle.set_attrib(ERROR_ATTRIB, message);
+    handle.set_attrib("BGCOLOR", ERROR_BGCOLOR);
+    handle.set_attrib("TIP", message);
+}
+
+fn clear_error(mut handle: Handle) {
+    if handle.attrib(ERROR_ATTRIB).is_none() {
+        return;
+    }
+    let bgcolor = handle.attrib(SAVED_BGCOLOR).unwrap_or_default();
+    let tip = handle.attrib(SAVED_TIP).unwrap_or_default();
+    handle.clear_attrib(ERROR_ATTRIB);
+    handle.clear_attrib(SAVED_BGCOLOR);
+    handle.clear_attrib(SAVED_TIP);
+    for &(name, ref value) in &[("BGCOLOR", bgcolor), ("TIP", tip)] {
+        if value.is_empty() {
+            handle.clear_attrib(name);
+        } else {
+            handle.set_attrib(name, &value[..]);
+        }
+    }
+}
+
+impl Validator {
+    /// Checks whether this validates `handle`, which is false once its element was destroyed.
+    fn validates(&self, handle: Handle) -> bool {
+        self.handle.upgrade().map_or(false, |validated| validated.raw() == handle.raw())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::result::Result;
+    use super::{is_blank, run_checks};
+
+    #[test]
+    fn blank_values() {
+        assert!(is_blank(None));
+        assert!(is_blank(Some("")));
+        assert!(is_blank(Some(" \t\n")));
+        assert!(!is_blank(Some(" a ")));
+    }
+
+    #[test]
+    fn checks_run_in_order_until_one_fails() {
+        let runs = Rc::new(Cell::new(0));
+        let check = |result: Result<(), &'static str>| -> Rc<Fn() -> Result<(), String>> {
+            let runs = runs.clone();
+            Rc::new(move || {
+                runs.set(runs.get() + 1);
+                result.map_err(|message| message.to_string())
+            })
+        };
+        let checks = vec![check(Ok(())), check(Err("first")), check(Err("second"))];
+        assert_eq!(run_checks(&checks), Err("first".to_string()));
+        assert_eq!(runs.get(), 2);
+        assert_eq!(run_checks(&checks[..1]), Ok(()));
+        assert_eq!(run_checks(&[]), Ok(()));
+    }
+}
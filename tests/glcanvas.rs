@@ -0,0 +1,59 @@
+//! Integration tests of the `gl` feature, creating hidden OpenGL canvases.
+//!
+//! They need a graphical environment and the `iupgl` library, run them with
+//! `xvfb-run cargo test --features gl -- --test-threads=1` on a headless machine.
+#![cfg(feature = "gl")]
+
+extern crate iup;
+
+use iup::prelude::*;
+use iup::control::{GLCanvas, OffscreenGL};
+use iup::testing;
+
+fn open_gl() {
+    unsafe { IupGLCanvasOpen() };
+}
+
+#[link(name = "iupgl")]
+extern {
+    fn IupGLCanvasOpen();
+}
+
+#[test]
+fn framebuffer_size_is_zero_before_mapping() {
+    testing::run(|| {
+        open_gl();
+        let canvas = GLCanvas::new();
+        assert_eq!(canvas.framebuffer_size(), (0, 0));
+        canvas.destroy();
+    }).unwrap();
+}
+
+#[test]
+fn framebuffer_size_follows_the_scale_factor() {
+    testing::run(|| {
+        open_gl();
+        let canvas = GLCanvas::new().set_attrib("RASTERSIZE", "200x100");
+        let mut dialog = Dialog::new(canvas);
+        dialog.map().unwrap();
+        testing::flush();
+
+        let scale = canvas.scale_factor();
+        assert!(scale >= 1.0);
+        let (width, height) = canvas.framebuffer_size();
+        assert_eq!((width, height), ((200.0 * scale).round() as u32,
+                                     (100.0 * scale).round() as u32));
+        dialog.destroy();
+    }).unwrap();
+}
+
+#[test]
+fn offscreen_context_is_hidden_and_current_while_rendering() {
+    testing::run(|| {
+        open_gl();
+        let offscreen = OffscreenGL::new(64, 32).unwrap();
+        let canvas = offscreen.canvas();
+        testing::assert_attrib(canvas.dialog().unwrap(), "VISIBLE", "NO");
+        assert!(offscreen.render(|| canvas.is_current()));
+    }).unwrap();
+}